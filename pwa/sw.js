@@ -0,0 +1,46 @@
+// Keeps the game playable offline once it has been loaded. Trunk
+// fingerprints the wasm and JS file names on every build, so rather than a
+// fixed list this caches whatever the page fetches: the network copy when
+// online, the cached one when not. Bump CACHE to drop everything cached by
+// older builds.
+const CACHE = "tetris-html-v1";
+const SHELL = ["./", "./index.html", "./manifest.webmanifest"];
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(SHELL)));
+  self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+      .then(() => self.clients.claim()),
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) {
+    return;
+  }
+  event.respondWith(
+    fetch(request)
+      .then((response) => {
+        if (response.ok) {
+          const copy = response.clone();
+          event.waitUntil(caches.open(CACHE).then((cache) => cache.put(request, copy)));
+        }
+        return response;
+      })
+      .catch(async () => {
+        const cached = await caches.match(request);
+        if (cached) {
+          return cached;
+        }
+        // Offline navigation to any page of the app gets the app.
+        return request.mode === "navigate" ? caches.match("./index.html") : Response.error();
+      }),
+  );
+});
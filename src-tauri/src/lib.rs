@@ -1,6 +1,106 @@
+/// Where the sync file `path` lives: under the app's data folder, so the
+/// webview can't reach any other file. Only the main window may ask, and
+/// `path` has to be relative without `..`, e.g. `sync.json`.
+fn sync_file(window: &tauri::WebviewWindow, path: &str) -> Result<std::path::PathBuf, String> {
+    use std::path::{Component, Path};
+    use tauri::Manager;
+
+    if window.label() != "main" {
+        return Err("only the main window can sync".to_string());
+    }
+    let relative = Path::new(path);
+    if path.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("sync file {path:?} must be a relative path inside the app's data folder"));
+    }
+    let dir = window.app_handle().path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(relative))
+}
+
+/// Contents of the sync file at `path`, or `None` if it doesn't exist yet.
+/// Used by the frontend's `TauriFileBackend`.
+#[tauri::command]
+fn read_sync_file(window: tauri::WebviewWindow, path: String) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(sync_file(&window, &path)?) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+fn write_sync_file(window: tauri::WebviewWindow, path: String, contents: String) -> Result<(), String> {
+    let file = sync_file(&window, &path)?;
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(file, contents).map_err(|e| e.to_string())
+}
+
+/// Opens one of the frontend's separate views (see `ui::views`) in a window
+/// of its own, or focuses it if it is already open.
+#[tauri::command]
+async fn open_view(app: tauri::AppHandle, view: String) -> Result<(), String> {
+    use tauri::Manager;
+
+    let (title, width, height) = match view.as_str() {
+        "stats" => ("Tetris - Stats", 240.0, 160.0),
+        "next" => ("Tetris - Next", 160.0, 420.0),
+        "spectator" => ("Tetris - Spectator", 320.0, 800.0),
+        _ => return Err(format!("unknown view {view}")),
+    };
+    let label = format!("view-{view}");
+    if let Some(window) = app.get_webview_window(&label) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let url = tauri::WebviewUrl::App(format!("index.html#view={view}").into());
+    tauri::WebviewWindowBuilder::new(&app, label, url)
+        .title(title)
+        .inner_size(width, height)
+        .build()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Logical size of the window in mini mode: the frontend's 10 × 25 board
+/// and the margin it is dragged by.
+const MINI_WIDTH: f64 = 172.0;
+const MINI_HEIGHT: f64 = 412.0;
+
+/// The window's size and position before mini mode, to go back to.
+#[derive(Default)]
+struct MiniMode(std::sync::Mutex<Option<(tauri::PhysicalSize<u32>, tauri::PhysicalPosition<i32>)>>);
+
+/// Shrinks `window` to a small borderless board kept above other windows,
+/// or with `on` false puts it back the way it was.
+#[tauri::command]
+fn set_mini_mode(window: tauri::WebviewWindow, state: tauri::State<MiniMode>, on: bool) -> Result<(), String> {
+    let result = (|| -> tauri::Result<()> {
+        let mut saved = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        if on {
+            if saved.is_none() {
+                *saved = Some((window.inner_size()?, window.outer_position()?));
+            }
+            window.set_decorations(false)?;
+            window.set_always_on_top(true)?;
+            window.set_size(tauri::LogicalSize::new(MINI_WIDTH, MINI_HEIGHT))?;
+        } else {
+            window.set_decorations(true)?;
+            window.set_always_on_top(false)?;
+            if let Some((size, position)) = saved.take() {
+                window.set_size(size)?;
+                window.set_position(position)?;
+            }
+        }
+        Ok(())
+    })();
+    result.map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(MiniMode::default())
+        .invoke_handler(tauri::generate_handler![read_sync_file, write_sync_file, open_view, set_mini_mode])
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -0,0 +1,123 @@
+//! One-off unlocks earned from game events and running totals. Like
+//! objectives, only ids (plus the lifetime line count) need persisting.
+
+use crate::GameEvent;
+
+#[derive(Debug)]
+pub struct Achievement {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+pub const FIRST_TETRIS: Achievement = Achievement {
+    id: "first-tetris",
+    title: "Tetris!",
+    description: "Clear 4 lines with one piece",
+};
+pub const CENTURY: Achievement = Achievement {
+    id: "century",
+    title: "Century",
+    description: "Clear 100 lines in total",
+};
+pub const BACK_TO_BACK_3: Achievement = Achievement {
+    id: "b2b-3",
+    title: "Back-to-Back ×3",
+    description: "Chain three Tetrises or T-spin clears without a plain clear in between",
+};
+pub const SPRINT_2M: Achievement = Achievement {
+    id: "sprint-2m",
+    title: "Sprinter",
+    description: "Clear 40 lines in under 2 minutes",
+};
+
+pub const ACHIEVEMENTS: [&Achievement; 4] = [&FIRST_TETRIS, &CENTURY, &BACK_TO_BACK_3, &SPRINT_2M];
+
+const CENTURY_LINES: u32 = 100;
+const SPRINT_LINES: u32 = 40;
+const SPRINT_MS: f64 = 120_000.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct AchievementTracker {
+    unlocked: Vec<&'static str>,
+    total_lines: u32,
+    /// Consecutive "difficult" clears (Tetrises and T-spin clears) this game.
+    back_to_back: u32,
+    /// The piece that just locked was a T-spin with lines.
+    t_spin_clear: bool,
+}
+
+impl AchievementTracker {
+    /// Restores persisted progress, skipping ids no longer defined.
+    pub fn from_saved<'a>(ids: impl IntoIterator<Item = &'a str>, total_lines: u32) -> Self {
+        let unlocked = ids
+            .into_iter()
+            .filter_map(|id| ACHIEVEMENTS.iter().find(|a| a.id == id).map(|a| a.id))
+            .collect();
+        Self { unlocked, total_lines, ..Self::default() }
+    }
+
+    pub fn unlocked_ids(&self) -> &[&'static str] {
+        &self.unlocked
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(&id)
+    }
+
+    /// Lines cleared across every game observed so far.
+    pub fn total_lines(&self) -> u32 {
+        self.total_lines
+    }
+
+    /// Forgets per-game streaks; lifetime totals are kept.
+    pub fn new_game(&mut self) {
+        self.back_to_back = 0;
+        self.t_spin_clear = false;
+    }
+
+    fn unlock(&mut self, achievement: &'static Achievement, done: &mut Vec<&'static Achievement>) {
+        if !self.is_unlocked(achievement.id) {
+            self.unlocked.push(achievement.id);
+            done.push(achievement);
+        }
+    }
+
+    /// Returns the achievements `event` newly unlocked.
+    pub fn observe(&mut self, event: &GameEvent) -> Vec<&'static Achievement> {
+        let mut done = Vec::new();
+        match *event {
+            GameEvent::PieceLocked { .. } => self.t_spin_clear = false,
+            GameEvent::TSpin { lines } => self.t_spin_clear = lines > 0,
+            GameEvent::LinesCleared { count } => {
+                self.total_lines += count;
+                if count >= 4 {
+                    self.unlock(&FIRST_TETRIS, &mut done);
+                }
+                if count >= 4 || self.t_spin_clear {
+                    self.back_to_back += 1;
+                } else {
+                    self.back_to_back = 0;
+                }
+                if self.back_to_back >= 3 {
+                    self.unlock(&BACK_TO_BACK_3, &mut done);
+                }
+                if self.total_lines >= CENTURY_LINES {
+                    self.unlock(&CENTURY, &mut done);
+                }
+            }
+            _ => {}
+        }
+        done
+    }
+
+    /// Checks goals that depend on the current game's stats rather than a
+    /// single event.
+    pub fn observe_session(&mut self, lines_cleared: u32, elapsed_ms: f64) -> Vec<&'static Achievement> {
+        let mut done = Vec::new();
+        if lines_cleared >= SPRINT_LINES && elapsed_ms < SPRINT_MS {
+            self.unlock(&SPRINT_2M, &mut done);
+        }
+        done
+    }
+}
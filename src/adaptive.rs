@@ -0,0 +1,103 @@
+//! Dynamic difficulty for casual sessions. A `FlowController` follows the
+//! player's `stats` and sets gravity and garbage pressure from a level
+//! between 0.0 and 1.0: it eases off after misdrops and pushes harder while
+//! pieces go down quickly and cleanly, to keep the game in a flow zone.
+
+use crate::stats::RollingStats;
+use crate::GameEvent;
+
+pub const START_LEVEL: f64 = 0.2;
+/// Gravity tick at level 0.0 and 1.0.
+pub const SLOWEST_GRAVITY_MS: f64 = 1000.0;
+pub const FASTEST_GRAVITY_MS: f64 = 200.0;
+/// Garbage rises from this level on: a row every 20 s, down to every 6 s at
+/// level 1.0.
+pub const GARBAGE_FROM_LEVEL: f64 = 0.4;
+
+/// Locks needed before the level starts moving.
+const MIN_LOCKS: usize = 5;
+/// Misdrop rates above `EASE_OFF` lower the level; at or below `PUSH` the
+/// level rises if the player keeps up.
+const EASE_OFF: f64 = 0.25;
+const PUSH: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowController {
+    stats: RollingStats,
+    level: f64,
+    last_garbage_ms: f64,
+}
+
+impl Default for FlowController {
+    fn default() -> Self {
+        Self { stats: RollingStats::default(), level: START_LEVEL, last_garbage_ms: 0.0 }
+    }
+}
+
+impl FlowController {
+    pub fn stats(&self) -> &RollingStats {
+        &self.stats
+    }
+
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    /// Records locks and misdrops into `stats`, adjusting the level on each
+    /// lock from the pieces before it.
+    pub fn observe(&mut self, event: &GameEvent, elapsed_ms: f64) {
+        match event {
+            GameEvent::PieceLocked { .. } => {
+                self.adjust();
+                self.stats.observe_lock(elapsed_ms);
+            }
+            GameEvent::Misdrop(_) => self.stats.mark_misdrop(),
+            _ => {}
+        }
+    }
+
+    fn adjust(&mut self) {
+        if self.stats.len() < MIN_LOCKS {
+            return;
+        }
+        let (Some(pps), Some(misdrops)) = (self.stats.pps(), self.stats.misdrop_rate()) else { return };
+        if misdrops > EASE_OFF {
+            self.level -= 0.05;
+        } else if misdrops <= PUSH && pps >= self.target_pps() {
+            self.level += 0.02;
+        }
+        self.level = self.level.clamp(0.0, 1.0);
+    }
+
+    /// Placement speed that counts as keeping up at the current level.
+    pub fn target_pps(&self) -> f64 {
+        0.8 + self.level
+    }
+
+    /// Milliseconds between gravity ticks at the current level.
+    pub fn gravity_ms(&self) -> f64 {
+        SLOWEST_GRAVITY_MS - (SLOWEST_GRAVITY_MS - FASTEST_GRAVITY_MS) * self.level
+    }
+
+    /// Milliseconds between garbage rows, or `None` below `GARBAGE_FROM_LEVEL`.
+    pub fn garbage_interval_ms(&self) -> Option<f64> {
+        let t = (self.level - GARBAGE_FROM_LEVEL) / (1.0 - GARBAGE_FROM_LEVEL);
+        (self.level >= GARBAGE_FROM_LEVEL).then_some(20_000.0 - 14_000.0 * t)
+    }
+
+    /// Garbage rows due at game time `elapsed_ms`. The interval counts from
+    /// the last row, or from when garbage was switched on by the level.
+    pub fn garbage_due(&mut self, elapsed_ms: f64) -> u32 {
+        match self.garbage_interval_ms() {
+            Some(interval) if elapsed_ms - self.last_garbage_ms >= interval => {
+                self.last_garbage_ms = elapsed_ms;
+                1
+            }
+            Some(_) => 0,
+            None => {
+                self.last_garbage_ms = elapsed_ms;
+                0
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Aggregate statistics over many replays, for `--analyze`: how lines were
+//! cleared, how often pieces were misdropped (see `stats::judge`) and how
+//! board health developed over the course of a game.
+
+use crate::metrics::BoardMetrics;
+use crate::replay::Replay;
+use crate::stats::GameStats;
+use crate::GameEvent;
+
+/// Pieces per health sample.
+pub const HEALTH_INTERVAL: u32 = 10;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Analysis {
+    pub games: u32,
+    /// Singles, doubles, triples and tetrises.
+    pub clears: [u32; 4],
+    pub t_spins: u32,
+    pub perfect_clears: u32,
+    pub misdrops: GameStats,
+    /// Health summed over games and how many games got that far, every
+    /// `HEALTH_INTERVAL` pieces.
+    health: Vec<(u64, u32)>,
+}
+
+impl Analysis {
+    /// Plays `replay` back and adds it in.
+    pub fn add(&mut self, replay: &Replay) {
+        self.games += 1;
+        replay.play(|tetris, events| {
+            for event in events {
+                self.misdrops.observe(event);
+                match *event {
+                    GameEvent::LinesCleared { count } => {
+                        if let Some(n) = self.clears.get_mut(count.clamp(1, 4) as usize - 1) {
+                            *n += 1;
+                        }
+                    }
+                    GameEvent::TSpin { .. } => self.t_spins += 1,
+                    GameEvent::PerfectClear => self.perfect_clears += 1,
+                    GameEvent::PieceLocked { .. } if tetris.pieces_placed() % HEALTH_INTERVAL == 0 => {
+                        let i = (tetris.pieces_placed() / HEALTH_INTERVAL) as usize - 1;
+                        if self.health.len() <= i {
+                            self.health.resize(i + 1, (0, 0));
+                        }
+                        let health = BoardMetrics::of(&tetris.board).health(tetris.height);
+                        self.health[i].0 += health as u64;
+                        self.health[i].1 += 1;
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Average health after every `HEALTH_INTERVAL` pieces, as (pieces,
+    /// health) over the games that lasted that long.
+    pub fn health_over_time(&self) -> Vec<(u32, f64)> {
+        self.health
+            .iter()
+            .enumerate()
+            .map(|(i, &(sum, games))| ((i as u32 + 1) * HEALTH_INTERVAL, sum as f64 / games as f64))
+            .collect()
+    }
+}
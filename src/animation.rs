@@ -0,0 +1,96 @@
+//! Animation timing as pure functions of time, so renderers running at any
+//! refresh rate agree on how far along an animation is. Renderers that count
+//! frames convert them with `frames_to_ms` first.
+
+use crate::events::Rotation;
+use crate::{GameEvent, Position};
+
+/// Suggested time for a piece to slide between cells on screen; short enough
+/// that it never lags more than a move behind at high gravity.
+pub const PIECE_TWEEN_MS: f64 = 50.0;
+
+pub const SHAKE_MS: f64 = 300.0;
+pub const FLASH_MS: f64 = 150.0;
+/// Real time a close call's slow motion lasts, and the speed game time runs
+/// at meanwhile.
+pub const SLOW_MOTION_MS: f64 = 1000.0;
+pub const SLOW_MOTION_FACTOR: f64 = 0.5;
+
+/// How far an animation that started at `started_at_ms` and lasts
+/// `duration_ms` has progressed at `now_ms`, clamped to `0.0..=1.0`. A
+/// zero-length animation is always complete.
+pub fn progress(started_at_ms: f64, duration_ms: f64, now_ms: f64) -> f64 {
+    if duration_ms <= 0.0 {
+        return 1.0;
+    }
+    ((now_ms - started_at_ms) / duration_ms).clamp(0.0, 1.0)
+}
+
+/// Milliseconds covered by `frames` frames at `hz` frames per second.
+pub fn frames_to_ms(frames: u64, hz: f64) -> f64 {
+    if hz <= 0.0 {
+        return 0.0;
+    }
+    frames as f64 * 1000.0 / hz
+}
+
+/// The falling piece's latest move, for renderers that tween it between
+/// cells instead of snapping. Spawns and rotations start at rest
+/// (`from == to`), so only shifts and drops slide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PieceMotion {
+    pub from: Position,
+    pub to: Position,
+    /// Game-clock time of the move.
+    pub moved_at_ms: f64,
+}
+
+impl PieceMotion {
+    pub fn at_rest(pos: Position, now_ms: f64) -> Self {
+        Self { from: pos, to: pos, moved_at_ms: now_ms }
+    }
+
+    /// Position in fractional cells at `now_ms` for a slide lasting
+    /// `duration_ms`.
+    pub fn position_at(&self, now_ms: f64, duration_ms: f64) -> (f64, f64) {
+        let t = progress(self.moved_at_ms, duration_ms, now_ms);
+        let lerp = |a: i32, b: i32| a as f64 + (b - a) as f64 * t;
+        (lerp(self.from.0, self.to.0), lerp(self.from.1, self.to.1))
+    }
+}
+
+/// A whole-board effect a renderer may play for a heavy event. Frontends
+/// scale or drop these according to the player's effect settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderHint {
+    /// Shake the board for `SHAKE_MS`; `strength` is 0.0–1.0.
+    Shake { strength: f64 },
+    /// Flash the board white for `FLASH_MS`.
+    Flash,
+    /// Play the board in slow motion for `SLOW_MOTION_MS`.
+    SlowMotion,
+    /// Knock the board lightly for `SHAKE_MS`, the "clack" of a wall kick.
+    Kick,
+    /// Flash the board in the spinning piece's glow for `FLASH_MS`.
+    SpinGlow,
+}
+
+impl RenderHint {
+    /// The effect `event` calls for, if any: a full shake for a Tetris, a
+    /// smaller one for a little garbage, a flash for a perfect clear, slow
+    /// motion for a close call, and a knock or a glow for a kicked or
+    /// spinning rotation. Plain rotations call for nothing.
+    pub fn for_event(event: &GameEvent) -> Option<RenderHint> {
+        match *event {
+            GameEvent::LinesCleared { count } if count >= 4 => Some(RenderHint::Shake { strength: 1.0 }),
+            GameEvent::GarbageReceived { rows } => Some(RenderHint::Shake {
+                strength: (rows as f64 / 4.0).min(1.0),
+            }),
+            GameEvent::PerfectClear => Some(RenderHint::Flash),
+            GameEvent::CloseCall => Some(RenderHint::SlowMotion),
+            GameEvent::Rotated(Rotation::Kicked) => Some(RenderHint::Kick),
+            GameEvent::Rotated(Rotation::Spin) => Some(RenderHint::SpinGlow),
+            _ => None,
+        }
+    }
+}
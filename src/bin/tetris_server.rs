@@ -0,0 +1,45 @@
+//! Serves the engine over JSON-RPC; see `tetris_core::rpc` for the methods.
+//!
+//! Usage: `tetris_server [--listen ADDR] [--broadcast ADDR]`. The server
+//! listens on `127.0.0.1:7878` by default; `--broadcast` (with the
+//! `broadcast` feature) also feeds every game to stream overlays over a
+//! WebSocket, see `tetris_core::broadcast`.
+
+use std::net::TcpListener;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    let addr = flag("--listen").map_or(DEFAULT_ADDR, |a| a);
+    let listener = match TcpListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("cannot listen on {addr}: {e}");
+            std::process::exit(1);
+        }
+    };
+    #[allow(unused_mut)]
+    let mut engine = tetris_core::rpc::Engine::new();
+    if let Some(overlay) = flag("--broadcast") {
+        #[cfg(feature = "broadcast")]
+        match tetris_core::broadcast::Broadcaster::bind(overlay.as_str()) {
+            Ok(broadcaster) => {
+                eprintln!("broadcasting games on ws://{}", broadcaster.local_addr());
+                engine.broadcast_to(broadcaster);
+            }
+            Err(e) => {
+                eprintln!("cannot broadcast on {overlay}: {e}");
+                std::process::exit(1);
+            }
+        }
+        #[cfg(not(feature = "broadcast"))]
+        eprintln!("--broadcast {overlay} needs a build with `--features broadcast`");
+    }
+    eprintln!("tetris_server listening on {addr}");
+    if let Err(e) = tetris_core::rpc::serve(listener, engine) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
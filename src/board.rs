@@ -0,0 +1,266 @@
+use std::fmt;
+
+use crate::{pieces, Position, Tetromino};
+
+/// Narrowest board a tetromino spawn box (4 cells) fits in.
+pub const MIN_WIDTH: u32 = 4;
+/// Shortest board every rotation of every piece fits in.
+pub const MIN_HEIGHT: u32 = 4;
+/// Upper bound on either dimension, keeping cell coordinates and buffers sane.
+pub const MAX_DIMENSION: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    TooNarrow(u32),
+    TooShort(u32),
+    TooLarge { width: u32, height: u32 },
+}
+
+impl BoardError {
+    /// Stable numeric code reported over the FFI (0 means no error).
+    pub fn code(&self) -> i32 {
+        match self {
+            BoardError::TooNarrow(_) => 1,
+            BoardError::TooShort(_) => 2,
+            BoardError::TooLarge { .. } => 3,
+        }
+    }
+
+    pub fn validate(width: u32, height: u32) -> Result<(), BoardError> {
+        if width > MAX_DIMENSION || height > MAX_DIMENSION {
+            Err(BoardError::TooLarge { width, height })
+        } else if width < MIN_WIDTH {
+            Err(BoardError::TooNarrow(width))
+        } else if height < MIN_HEIGHT {
+            Err(BoardError::TooShort(height))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::TooNarrow(w) => write!(f, "board width {} is below the minimum of {}", w, MIN_WIDTH),
+            BoardError::TooShort(h) => write!(f, "board height {} is below the minimum of {}", h, MIN_HEIGHT),
+            BoardError::TooLarge { width, height } => {
+                write!(f, "board {}x{} exceeds the maximum dimension of {}", width, height, MAX_DIMENSION)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// Kind used for cells that did not come from a tetromino (e.g. garbage rows).
+/// Renderers draw it gray.
+pub const GARBAGE_KIND: &str = "X";
+
+/// The settled stack, stored as a row-major grid of piece kinds.
+///
+/// Each cell remembers the kind of the piece that filled it, so rows keep their
+/// original colors when the stack shifts down after a clear. Column heights are
+/// cached so drop distances don't need a row-by-row simulation.
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: u32,
+    height: u32,
+    cells: Vec<Option<&'static str>>,
+    heights: Vec<u32>,
+}
+
+impl Board {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![None; (width * height) as usize],
+            heights: vec![0; width as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        pos.0 >= 0 && pos.0 < self.width as i32 && pos.1 >= 0 && pos.1 < self.height as i32
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        self.in_bounds(pos)
+            .then(|| pos.1 as usize * self.width as usize + pos.0 as usize)
+    }
+
+    /// Kind of the settled cell at `pos`, or `None` when empty or out of bounds.
+    pub fn get(&self, pos: Position) -> Option<&'static str> {
+        self.index(pos).and_then(|i| self.cells[i])
+    }
+
+    pub fn set(&mut self, pos: Position, kind: Option<&'static str>) {
+        if let Some(i) = self.index(pos) {
+            self.cells[i] = kind;
+            let x = pos.0 as usize;
+            let from_bottom = self.height - pos.1 as u32;
+            if kind.is_some() {
+                self.heights[x] = self.heights[x].max(from_bottom);
+            } else if from_bottom == self.heights[x] {
+                self.recompute_height(x);
+            }
+        }
+    }
+
+    /// The board flipped left to right, each cell's kind swapped for its
+    /// mirror image (see `pieces::mirror_kind`).
+    pub fn mirrored(&self) -> Board {
+        let mut mirrored = Board::new(self.width, self.height);
+        let right = self.width as i32 - 1;
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if let Some(kind) = self.get(Position(x, y)) {
+                    mirrored.set(Position(right - x, y), Some(pieces::mirror_kind(kind)));
+                }
+            }
+        }
+        mirrored
+    }
+
+    fn recompute_height(&mut self, x: usize) {
+        let w = self.width as usize;
+        self.heights[x] = (0..self.height as usize)
+            .find(|&y| self.cells[y * w + x].is_some())
+            .map_or(0, |top| self.height - top as u32);
+    }
+
+    /// Number of rows from the floor up to and including the highest filled
+    /// cell of column `x`.
+    pub fn column_height(&self, x: u32) -> u32 {
+        self.heights[x as usize]
+    }
+
+    /// Height of the tallest column.
+    pub fn max_height(&self) -> u32 {
+        self.heights.iter().copied().max().unwrap_or(0)
+    }
+
+    /// How many rows `tetromino` can fall before it lands.
+    ///
+    /// When every cell is above its column's stack this only looks at the
+    /// cached heights; a piece tucked under an overhang falls back to scanning
+    /// its columns.
+    pub fn drop_distance(&self, tetromino: &Tetromino) -> i32 {
+        tetromino
+            .cells()
+            .map(|p| {
+                if p.0 < 0 || p.0 >= self.width as i32 {
+                    return 0;
+                }
+                let surface = (self.height - self.heights[p.0 as usize]) as i32;
+                if p.1 < surface {
+                    return surface - 1 - p.1;
+                }
+                let mut y = p.1 + 1;
+                while y < self.height as i32 && !self.is_occupied(Position(p.0, y)) {
+                    y += 1;
+                }
+                y - 1 - p.1
+            })
+            .min()
+            .unwrap_or(0)
+            .max(0)
+    }
+
+    /// Empty cells with a block somewhere above them in the same column.
+    pub fn holes(&self) -> u32 {
+        (0..self.width).map(|x| self.column_holes(x)).sum()
+    }
+
+    /// `holes` in column `x` alone.
+    pub fn column_holes(&self, x: u32) -> u32 {
+        let (w, x) = (self.width as usize, x as usize);
+        let top = (self.height - self.heights[x]) as usize;
+        (top..self.height as usize).filter(|&y| self.cells[y * w + x].is_none()).count() as u32
+    }
+
+    /// Filled cells in row `y`.
+    pub fn row_fill_count(&self, y: usize) -> u32 {
+        self.row(y).iter().filter(|c| c.is_some()).count() as u32
+    }
+
+    pub fn is_occupied(&self, pos: Position) -> bool {
+        self.get(pos).is_some()
+    }
+
+    /// Writes the cells of a locked piece into the grid.
+    pub fn place(&mut self, tetromino: &Tetromino) {
+        for pos in tetromino.cells() {
+            self.set(pos, Some(tetromino.kind));
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[Option<&'static str>] {
+        let w = self.width as usize;
+        &self.cells[y * w..(y + 1) * w]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Option<&'static str>]> {
+        self.cells.chunks(self.width as usize)
+    }
+
+    pub fn full_lines(&self) -> Vec<usize> {
+        self.rows()
+            .enumerate()
+            .filter(|(_, row)| row.iter().all(|c| c.is_some()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Removes the given rows and shifts everything above them down,
+    /// filling the top with empty rows.
+    pub fn remove_rows(&mut self, lines: &[usize]) {
+        let w = self.width as usize;
+        let kept: Vec<Option<&'static str>> = self
+            .rows()
+            .enumerate()
+            .filter(|(i, _)| !lines.contains(i))
+            .flat_map(|(_, row)| row.iter().copied())
+            .collect();
+        let mut cells = vec![None; self.cells.len() - kept.len()];
+        cells.extend(kept);
+        debug_assert_eq!(cells.len() % w, 0);
+        self.cells = cells;
+        (0..w).for_each(|x| self.recompute_height(x));
+    }
+
+    /// Raises the stack by `rows` garbage rows, each full except for column
+    /// `hole`. Returns false when settled blocks were pushed out of the top.
+    pub fn push_garbage(&mut self, rows: u32, hole: u32) -> bool {
+        self.push_garbage_rows(&vec![vec![hole]; rows as usize])
+    }
+
+    /// Raises the stack by one garbage row per entry of `holes`, each full
+    /// except for the columns listed; the last entry ends up at the bottom.
+    pub fn push_garbage_rows(&mut self, holes: &[Vec<u32>]) -> bool {
+        let w = self.width as usize;
+        let holes = &holes[holes.len().saturating_sub(self.height as usize)..];
+        let rows = holes.len();
+        let overflow = self.cells[..rows * w].iter().any(Option::is_some);
+        self.cells.drain(..rows * w);
+        for row in holes {
+            self.cells
+                .extend((0..w).map(|x| (!row.contains(&(x as u32))).then_some(GARBAGE_KIND)));
+        }
+        (0..w).for_each(|x| self.recompute_height(x));
+        !overflow
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|c| *c = None);
+        self.heights.iter_mut().for_each(|h| *h = 0);
+    }
+}
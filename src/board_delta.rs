@@ -0,0 +1,76 @@
+//! Cell-level changes to what a board shows, for frontends that poll over
+//! slow links (LED matrices, serial lines) and only want to move what
+//! changed. `DeltaTracker` keeps the last view it was synced with and the
+//! version each cell last changed at; a sync that finds any difference
+//! bumps the version. See `Tetris::board_changes_since` and the
+//! `tetris_get_board_delta` FFI call.
+
+/// One cell that changed, as the FFI hands it out.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellChange {
+    pub x: u16,
+    /// Row, counting down from the top.
+    pub y: u16,
+    /// ASCII byte of the kind now shown there (`b'G'` for the ghost), 0 for
+    /// empty.
+    pub kind: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeltaTracker {
+    version: u32,
+    width: usize,
+    cells: Vec<u8>,
+    changed_at: Vec<u32>,
+}
+
+/// The byte a `render_view` kind is reported as.
+fn cell_byte(kind: &str) -> u8 {
+    match kind {
+        "B" => 0,
+        _ => kind.as_bytes().first().copied().unwrap_or(0),
+    }
+}
+
+impl DeltaTracker {
+    /// Takes in `view` (rows of kinds, as `Tetris::render_view` gives them)
+    /// and returns the version it is. The first view, and any of a new
+    /// size, changes every cell.
+    pub fn sync(&mut self, view: &[Vec<&'static str>]) -> u32 {
+        let width = view.first().map_or(0, Vec::len);
+        let cells: Vec<u8> = view.iter().flatten().map(|kind| cell_byte(kind)).collect();
+        if width != self.width || cells.len() != self.cells.len() || self.version == 0 {
+            self.version += 1;
+            self.width = width;
+            self.changed_at = vec![self.version; cells.len()];
+            self.cells = cells;
+            return self.version;
+        }
+        let changed: Vec<usize> = (0..cells.len()).filter(|&i| cells[i] != self.cells[i]).collect();
+        if !changed.is_empty() {
+            self.version += 1;
+            for i in changed {
+                self.cells[i] = cells[i];
+                self.changed_at[i] = self.version;
+            }
+        }
+        self.version
+    }
+
+    /// Version of the last sync; 0 before the first.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Cells that changed after version `since`, row by row from the top.
+    /// Since 0, that is every cell.
+    pub fn changes_since(&self, since: u32) -> impl Iterator<Item = CellChange> + '_ {
+        let width = self.width.max(1);
+        self.changed_at.iter().enumerate().filter(move |&(_, &at)| at > since).map(move |(i, _)| CellChange {
+            x: (i % width) as u16,
+            y: (i / width) as u16,
+            kind: self.cells[i],
+        })
+    }
+}
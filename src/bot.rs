@@ -0,0 +1,172 @@
+//! A simple greedy player for demos and stress tests. For each new piece it
+//! picks the hard-drop placement (see `metrics::placements`) whose board
+//! `Weights::cost` is lowest, then walks the piece there one `Action` at a time, as a
+//! player pressing keys would. `Weights` tune the cost, e.g. to compare
+//! settings in `versus` matches. Early on, placements from an
+//! `opening::OpeningBook` come before the search. With the `mcts` feature,
+//! `Policy::Mcts` looks pieces ahead with `mcts` instead of greedily, and
+//! with `onnx`, `Policy::Onnx` leaves the choice to a learned network.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::metrics::{self, BoardMetrics};
+use crate::opening::{self, OpeningBook};
+use crate::{Action, InputSource, Tetris, Tetromino};
+
+/// Moves spent on one piece before giving up on reaching the target and
+/// dropping it where it is, e.g. when a kick shifted it out of reach.
+const MAX_MOVES: u32 = 12;
+
+/// How much each `BoardMetrics` term adds to a board's cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weights {
+    pub holes: u32,
+    pub height: u32,
+    pub bumpiness: u32,
+    pub parity: u32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self { holes: 40, height: 3, bumpiness: 4, parity: 1 }
+    }
+}
+
+impl Weights {
+    /// How bad a board is to play on. Unlike `BoardMetrics::health` nothing
+    /// is capped, so placements still compare on an already rough stack.
+    pub fn cost(&self, m: &BoardMetrics) -> u32 {
+        m.holes * self.holes + m.max_height * self.height + m.bumpiness * self.bumpiness + m.parity * self.parity
+    }
+}
+
+/// Written and parsed as `holes,height,bumpiness,parity`, e.g. `40,3,4,1`.
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.holes, self.height, self.bumpiness, self.parity)
+    }
+}
+
+impl FromStr for Weights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let terms = s
+            .split(',')
+            .map(|t| t.trim().parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("bad weight in {s:?}: {e}"))?;
+        match terms[..] {
+            [holes, height, bumpiness, parity] => Ok(Self { holes, height, bumpiness, parity }),
+            _ => Err(format!("expected holes,height,bumpiness,parity, got {s:?}")),
+        }
+    }
+}
+
+/// How the bot searches once the book runs out, from weakest to strongest.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Policy {
+    /// The cheapest board after this piece.
+    #[default]
+    Greedy,
+    /// Tree search over the next few pieces, within a time budget.
+    #[cfg(feature = "mcts")]
+    Mcts(crate::mcts::Budget),
+    /// A learned network rates the board after each placement; greedy for
+    /// boards it wasn't made for.
+    #[cfg(all(feature = "onnx", not(target_arch = "wasm32")))]
+    Onnx(&'static crate::onnx::ValueNet),
+}
+
+impl Policy {
+    /// The strongest policy this build has.
+    pub fn strongest() -> Self {
+        #[cfg(feature = "mcts")]
+        return Policy::Mcts(Default::default());
+        #[cfg(not(feature = "mcts"))]
+        Policy::Greedy
+    }
+
+    fn best_placement(&self, tetris: &Tetris, weights: &Weights) -> Option<Tetromino> {
+        match *self {
+            Policy::Greedy => best_placement(tetris, weights),
+            #[cfg(feature = "mcts")]
+            Policy::Mcts(budget) => crate::mcts::best_placement(tetris, weights, budget),
+            #[cfg(all(feature = "onnx", not(target_arch = "wasm32")))]
+            Policy::Onnx(net) => crate::onnx::best_placement(tetris, net).or_else(|| best_placement(tetris, weights)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bot {
+    pub weights: Weights,
+    pub policy: Policy,
+    /// Consulted before the search; the bundled book by default.
+    pub book: Option<&'static OpeningBook>,
+    /// Pieces placed when `target` was chosen, to notice a new piece.
+    piece: u32,
+    /// Rotation and column to steer the current piece to.
+    target: Option<(usize, i32)>,
+    moves: u32,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self { weights: Weights::default(), policy: Policy::Greedy, book: Some(opening::bundled()), piece: 0, target: None, moves: 0 }
+    }
+}
+
+impl Bot {
+    pub fn with_weights(weights: Weights) -> Self {
+        Self { weights, ..Self::default() }
+    }
+
+    /// The action to take next for `tetris`'s falling piece, or `None` without
+    /// one.
+    pub fn next_action(&mut self, tetris: &Tetris) -> Option<Action> {
+        let current = tetris.current_tetromino.as_ref()?;
+        if self.target.is_none() || self.piece != tetris.pieces_placed() {
+            self.piece = tetris.pieces_placed();
+            self.target = self
+                .book
+                .and_then(|book| book.lookup(&tetris.board, current.kind))
+                .or_else(|| self.policy.best_placement(tetris, &self.weights))
+                .map(|t| (t.rotation, t.data.position.0));
+            self.moves = 0;
+        }
+        let (rotation, x) = self.target?;
+        self.moves += 1;
+        let x_now = current.data.position.0;
+        Some(match () {
+            _ if self.moves > MAX_MOVES => Action::HardDrop,
+            _ if current.rotation != rotation => Action::Rotate,
+            _ if x_now < x => Action::MoveRight,
+            _ if x_now > x => Action::MoveLeft,
+            _ => Action::HardDrop,
+        })
+    }
+
+    /// Takes the next action on `tetris`.
+    pub fn act(&mut self, tetris: &mut Tetris) {
+        if let Some(action) = self.next_action(tetris) {
+            tetris.apply(action);
+        }
+    }
+}
+
+/// One action per poll, as fast as the loop polls.
+impl InputSource for Bot {
+    fn poll(&mut self, tetris: &Tetris, now_ms: f64) -> Vec<(f64, Action)> {
+        self.next_action(tetris).map(|a| (now_ms, a)).into_iter().collect()
+    }
+}
+
+/// Where the falling piece would leave the lowest-cost board, if anywhere.
+pub fn best_placement(tetris: &Tetris, weights: &Weights) -> Option<Tetromino> {
+    let kind = tetris.current_tetromino.as_ref()?.kind;
+    metrics::placements(&tetris.board, kind)
+        .into_iter()
+        .min_by_key(|p| weights.cost(&metrics::after_lock(&tetris.board, p)))
+}
@@ -0,0 +1,92 @@
+//! A local WebSocket feed of games for stream overlays (OBS browser sources
+//! and the like). Every `publish` sends one JSON text frame per connected
+//! client holding the game's stats, board and the events since the last
+//! frame, so an overlay can show live stats, the next queue and attack sent
+//! without touching the game's own UI.
+//!
+//! Frame shape: `{game, score, lines, pieces, attack, next, board, events}`,
+//! where `board` is one string per row, top first, in `render_view` letters,
+//! `next` lists the queued piece kinds known in advance, and `events` holds
+//! `GameEvent::to_json` objects.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::json;
+use tungstenite::{Message, WebSocket};
+
+use crate::{versus, GameEvent, Tetris};
+
+/// How long a frame may wait on a slow client before that client is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+    addr: SocketAddr,
+    /// Garbage rows each game's clears would have sent, by game id.
+    attack: HashMap<u64, u32>,
+}
+
+impl Broadcaster {
+    /// Listens on `addr` and accepts overlay clients in the background.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                // Handshakes happen off the accept loop so a stalled client
+                // cannot block new ones.
+                let accepted = Arc::clone(&accepted);
+                std::thread::spawn(move || {
+                    if let Ok(socket) = tungstenite::accept(stream) {
+                        accepted.lock().unwrap_or_else(|e| e.into_inner()).push(socket);
+                    }
+                });
+            }
+        });
+        Ok(Self { clients, addr, attack: HashMap::new() })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Overlays connected right now.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Sends game `game` as it is now, with `events` drained since the last
+    /// publish. Clients that went away are dropped.
+    pub fn publish(&mut self, game: u64, tetris: &Tetris, events: &[GameEvent]) {
+        let attack = self.attack.entry(game).or_default();
+        if tetris.pieces_placed() == 0 {
+            *attack = 0;
+        }
+        for event in events {
+            if let GameEvent::LinesCleared { count } = event {
+                *attack += versus::attack(*count);
+            }
+        }
+        let board: Vec<String> = tetris.render_view().iter().map(|row| row.concat()).collect();
+        let frame = json!({
+            "game": game,
+            "score": tetris.get_score(),
+            "lines": tetris.lines_cleared(),
+            "pieces": tetris.pieces_placed(),
+            "attack": *attack,
+            "next": tetris.upcoming_pieces().collect::<Vec<_>>(),
+            "board": board,
+            "events": events.iter().map(GameEvent::to_json).collect::<Vec<_>>(),
+        })
+        .to_string();
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.retain_mut(|socket| socket.send(Message::text(frame.clone())).is_ok());
+    }
+}
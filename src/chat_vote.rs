@@ -0,0 +1,153 @@
+//! "Chat plays Tetris": viewers vote for moves in Twitch chat, and every
+//! `window_ms` the move with the most votes is played. This module reads
+//! the chat lines (Twitch chat is IRC) and counts the votes; where the lines
+//! come from is up to the frontend, e.g. `TwitchChat` natively or Twitch's
+//! IRC WebSocket in the browser.
+
+use std::collections::HashMap;
+
+use crate::input::{Action, InputSource};
+use crate::Tetris;
+
+/// Twitch's IRC endpoint for browsers.
+pub const TWITCH_IRC_WS: &str = "wss://irc-ws.chat.twitch.tv:443";
+/// Twitch's plain IRC endpoint.
+pub const TWITCH_IRC_ADDR: &str = "irc.chat.twitch.tv:6667";
+pub const DEFAULT_WINDOW_MS: f64 = 2000.0;
+
+/// Commands that join `channel` anonymously (read-only, no account needed).
+pub fn login_lines(channel: &str) -> [String; 3] {
+    [
+        "PASS SCHMOOPIIE".to_string(),
+        "NICK justinfan31415".to_string(),
+        format!("JOIN #{}", channel.trim_start_matches('#').to_lowercase()),
+    ]
+}
+
+/// The reply a server line needs, i.e. the PONG for a PING.
+pub fn reply_to(line: &str) -> Option<String> {
+    line.strip_prefix("PING").map(|rest| format!("PONG{}", rest))
+}
+
+/// The sender and text of a chat message line, e.g.
+/// `:viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :left`.
+pub fn parse_privmsg(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end();
+    let rest = line.strip_prefix(':')?;
+    let (prefix, rest) = rest.split_once(' ')?;
+    let rest = rest.strip_prefix("PRIVMSG ")?;
+    let (_, text) = rest.split_once(" :")?;
+    Some((prefix.split('!').next()?, text))
+}
+
+/// The action a chat message votes for, if it is one of the vote words.
+pub fn vote_of(text: &str) -> Option<Action> {
+    match text.trim().to_lowercase().as_str() {
+        "left" | "l" => Some(Action::MoveLeft),
+        "right" | "r" => Some(Action::MoveRight),
+        "rotate" | "up" => Some(Action::Rotate),
+        "down" | "soft" => Some(Action::SoftDrop),
+        "drop" | "hard" => Some(Action::HardDrop),
+        "hold" | "h" => Some(Action::Hold),
+        _ => None,
+    }
+}
+
+/// Votes of the current window: one per viewer, the latest counting.
+#[derive(Debug, Clone)]
+pub struct ChatVotes {
+    pub window_ms: f64,
+    opened_at: Option<f64>,
+    ballots: HashMap<String, Action>,
+}
+
+impl Default for ChatVotes {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW_MS)
+    }
+}
+
+impl ChatVotes {
+    pub fn new(window_ms: f64) -> Self {
+        Self { window_ms, opened_at: None, ballots: HashMap::new() }
+    }
+
+    /// Counts `line` if it is a chat message voting for a move; the first
+    /// vote opens a window.
+    pub fn observe_line(&mut self, line: &str, now_ms: f64) {
+        if let Some((user, action)) = parse_privmsg(line).and_then(|(user, text)| Some((user, vote_of(text)?))) {
+            self.opened_at.get_or_insert(now_ms);
+            self.ballots.insert(user.to_string(), action);
+        }
+    }
+
+    /// Votes per action so far, most first, ties in `Action::ALL` order.
+    pub fn tally(&self) -> Vec<(Action, u32)> {
+        let mut tally: Vec<(Action, u32)> = Action::ALL
+            .into_iter()
+            .map(|a| (a, self.ballots.values().filter(|&&b| b == a).count() as u32))
+            .filter(|&(_, n)| n > 0)
+            .collect();
+        tally.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        tally
+    }
+
+    /// Milliseconds left in the open window, if one is open.
+    pub fn remaining_ms(&self, now_ms: f64) -> Option<f64> {
+        self.opened_at.map(|at| (at + self.window_ms - now_ms).max(0.0))
+    }
+
+    /// The winning action once the window has run out, starting afresh.
+    pub fn poll(&mut self, now_ms: f64) -> Option<Action> {
+        if self.remaining_ms(now_ms)? > 0.0 {
+            return None;
+        }
+        let winner = self.tally().first().map(|&(a, _)| a);
+        self.opened_at = None;
+        self.ballots.clear();
+        winner
+    }
+}
+
+/// The winning vote, as soon as its window closes. Windows run on
+/// `clock::now_ms` like the votes fed in, as chat keeps real time whatever
+/// the game's time scale.
+impl InputSource for ChatVotes {
+    fn poll(&mut self, _: &Tetris, now_ms: f64) -> Vec<(f64, Action)> {
+        ChatVotes::poll(self, crate::clock::now_ms()).map(|a| (now_ms, a)).into_iter().collect()
+    }
+}
+
+/// Reads a channel's chat natively, over plain IRC on a background thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct TwitchChat {
+    lines: std::sync::mpsc::Receiver<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TwitchChat {
+    pub fn connect(channel: &str) -> std::io::Result<Self> {
+        use std::io::{BufRead, BufReader, Write};
+        let mut stream = std::net::TcpStream::connect(TWITCH_IRC_ADDR)?;
+        for line in login_lines(channel) {
+            write!(stream, "{}\r\n", line)?;
+        }
+        let mut writer = stream.try_clone()?;
+        let (tx, lines) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                if let Some(reply) = reply_to(&line) {
+                    let _ = write!(writer, "{}\r\n", reply);
+                } else if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { lines })
+    }
+
+    /// Lines received since the last call.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.lines.try_iter()
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(target_arch = "wasm32")]
+use web_sys::window;
+
+/// Monotonic milliseconds: `performance.now()` in the browser, time since the
+/// first call on native targets.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    window()
+        .and_then(|win| win.performance())
+        .map(|perf| perf.now())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    use std::{sync::OnceLock, time::Instant};
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// Time source for a game. `System` reads `now_ms`; `Manual` only moves when
+/// advanced, which keeps embedders deterministic and lets tests fast-forward.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Clock {
+    #[default]
+    System,
+    Manual(f64),
+}
+
+impl Clock {
+    pub fn now(&self) -> f64 {
+        match self {
+            Clock::System => now_ms(),
+            Clock::Manual(now) => *now,
+        }
+    }
+
+    /// Moves a manual clock forward; the system clock ignores this.
+    pub fn advance(&mut self, ms: f64) {
+        if let Clock::Manual(now) = self {
+            *now += ms.max(0.0);
+        }
+    }
+}
+
+/// Stopwatch that only accumulates while running, so pauses (and any
+/// pre-game countdown before the first `start`) do not count as play time.
+///
+/// Every method takes the current time explicitly, keeping the timer pure.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameTimer {
+    accumulated_ms: f64,
+    running_since: Option<f64>,
+}
+
+impl GameTimer {
+    pub fn start(&mut self, now: f64) {
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// A timer already running at `now` with `elapsed_ms` on it, for games
+    /// picked back up from a snapshot.
+    pub fn resumed(elapsed_ms: f64, now: f64) -> Self {
+        Self { accumulated_ms: elapsed_ms.max(0.0), running_since: Some(now) }
+    }
+
+    pub fn stop(&mut self, now: f64) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated_ms += (now - since).max(0.0);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn elapsed_ms(&self, now: f64) -> f64 {
+        self.accumulated_ms + self.running_since.map_or(0.0, |since| (now - since).max(0.0))
+    }
+}
+
+/// Slowest and fastest speeds `TimeScale::set_scale` accepts.
+pub const MIN_TIME_SCALE: f64 = 0.25;
+pub const MAX_TIME_SCALE: f64 = 2.0;
+
+/// Maps a clock's readings to game time, which runs at `scale` times real
+/// time and can be slowed down further for a while (see `slow_down`). Game
+/// time stays continuous: it never jumps or runs backwards when the speed
+/// changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale {
+    scale: f64,
+    real_anchor: f64,
+    game_anchor: f64,
+    /// Real time until which game time runs at `slow_factor`.
+    slow_until: Option<f64>,
+    slow_factor: f64,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self { scale: 1.0, real_anchor: 0.0, game_anchor: 0.0, slow_until: None, slow_factor: 1.0 }
+    }
+}
+
+impl TimeScale {
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Runs game time at `scale` times real time from clock reading `real`
+    /// on, clamped to `MIN_TIME_SCALE..=MAX_TIME_SCALE`.
+    pub fn set_scale(&mut self, scale: f64, real: f64) {
+        self.rebase(real);
+        self.scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+
+    /// Game time at clock reading `real`.
+    pub fn game_time(&self, real: f64) -> f64 {
+        let since = (real - self.real_anchor).max(0.0);
+        let game = match self.slow_until {
+            Some(until) => {
+                let slow = since.min((until - self.real_anchor).max(0.0));
+                slow * self.slow_factor + (since - slow)
+            }
+            None => since,
+        };
+        self.game_anchor + game * self.scale
+    }
+
+    /// Real time it takes `game_ms` of game time to pass, outside slow motion.
+    pub fn real_duration(&self, game_ms: f64) -> f64 {
+        game_ms / self.scale
+    }
+
+    /// Whether game time is running slow at clock reading `real`.
+    pub fn is_slow(&self, real: f64) -> bool {
+        self.slow_until.is_some_and(|until| real < until)
+    }
+
+    /// Runs game time at `factor` of real time for the next `duration_ms`.
+    pub fn slow_down(&mut self, factor: f64, duration_ms: f64, real: f64) {
+        self.rebase(real);
+        self.slow_until = Some(real + duration_ms.max(0.0));
+        self.slow_factor = factor.clamp(0.0, 1.0);
+    }
+
+    /// Starts over at `real`, with game time equal to it and no slow-down,
+    /// e.g. after the clock was replaced. The scale is kept.
+    pub fn restart(&mut self, real: f64) {
+        *self = TimeScale { scale: self.scale, real_anchor: real, game_anchor: real, ..TimeScale::default() };
+    }
+
+    fn rebase(&mut self, real: f64) {
+        self.game_anchor = self.game_time(real);
+        self.real_anchor = real;
+    }
+}
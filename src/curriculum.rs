@@ -0,0 +1,48 @@
+//! Easier episodes for curriculum learning: only some piece kinds, garbage
+//! already at the bottom, and a step limit after which an episode counts as
+//! truncated. Smaller boards come from the board size itself (down to
+//! `board::MIN_HEIGHT` rows). Set with `Tetris::set_curriculum` or, over the
+//! FFI, `tetris_set_curriculum`; it lasts across resets.
+
+use rand::Rng;
+
+use crate::pieces;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Curriculum {
+    /// Bit `i` allows `pieces::KINDS[i]`; 0 allows every kind.
+    pub piece_mask: u32,
+    /// Garbage rows, each with one random hole, under every new episode.
+    pub garbage_rows: u32,
+    /// Steps after which the episode is truncated; 0 for no limit.
+    pub max_steps: u32,
+}
+
+impl Curriculum {
+    /// The mask allowing just `kinds`, e.g. `"IO"`; unknown letters are
+    /// ignored.
+    pub fn mask_of(kinds: &str) -> u32 {
+        kinds
+            .chars()
+            .filter_map(|c| pieces::KINDS.iter().position(|k| k.starts_with(c)))
+            .fold(0, |mask, i| mask | 1 << i)
+    }
+
+    /// A random kind the mask allows (any kind when it allows none), drawn
+    /// from `rng`.
+    pub fn random_kind(&self, rng: &mut impl Rng) -> &'static str {
+        let allowed: Vec<&'static str> = pieces::KINDS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.piece_mask & (1 << i) != 0)
+            .map(|(_, &k)| k)
+            .collect();
+        let kinds = if allowed.is_empty() { &pieces::KINDS[..] } else { &allowed[..] };
+        kinds[rng.random_range(0..kinds.len())]
+    }
+
+    pub fn is_truncated(&self, steps: u32) -> bool {
+        self.max_steps > 0 && steps >= self.max_steps
+    }
+}
@@ -0,0 +1,46 @@
+//! Downstack training: garbage rises from below at a steady, optionally
+//! quickening pace until the player tops out. Runs are scored by the garbage
+//! rows cleared; see `Tetris::garbage_lines_cleared`.
+
+/// The feed never gets faster than one row per this many milliseconds.
+pub const MIN_INTERVAL_MS: f64 = 1000.0;
+/// When speeding up, each row shortens the next interval by this factor.
+pub const SPEEDUP: f64 = 0.97;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GarbageFeed {
+    interval_ms: f64,
+    speeds_up: bool,
+    last_ms: f64,
+    rows_sent: u32,
+}
+
+impl GarbageFeed {
+    /// A feed sending a row every `interval_ms`, starting from game time 0.
+    pub fn new(interval_ms: f64, speeds_up: bool) -> Self {
+        Self { interval_ms: interval_ms.max(MIN_INTERVAL_MS), speeds_up, last_ms: 0.0, rows_sent: 0 }
+    }
+
+    /// Time until the next row after the current one.
+    pub fn interval_ms(&self) -> f64 {
+        self.interval_ms
+    }
+
+    pub fn rows_sent(&self) -> u32 {
+        self.rows_sent
+    }
+
+    /// Rows due by game time `elapsed_ms` that haven't been sent yet.
+    pub fn due(&mut self, elapsed_ms: f64) -> u32 {
+        let mut rows = 0;
+        while elapsed_ms - self.last_ms >= self.interval_ms {
+            self.last_ms += self.interval_ms;
+            rows += 1;
+            if self.speeds_up {
+                self.interval_ms = (self.interval_ms * SPEEDUP).max(MIN_INTERVAL_MS);
+            }
+        }
+        self.rows_sent += rows;
+        rows
+    }
+}
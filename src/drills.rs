@@ -0,0 +1,194 @@
+//! Practice drills: short scenarios built from a `Template` and a seed. The
+//! seed fixes the starting stack and the piece sequence, so a drill can be
+//! replayed or shared; each template has its own success criterion.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{pieces, GameEvent, Position, Tetris, GARBAGE_KIND};
+
+/// Rows of mess in a downstacking drill.
+pub const DOWNSTACK_ROWS: u32 = 8;
+/// Scripted pieces per drill, after which random ones resume.
+const SCRIPTED_PIECES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    /// A ready T-slot over a couple of garbage rows.
+    TSpinDouble,
+    /// A four-wide well with three cells left in its bottom row.
+    FourWide,
+    /// Holey garbage to dig through.
+    Downstack,
+}
+
+impl Template {
+    pub const ALL: [Template; 3] = [Template::TSpinDouble, Template::FourWide, Template::Downstack];
+
+    /// Stable id, for persisting results and locale keys.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Template::TSpinDouble => "t-spin-double",
+            Template::FourWide => "four-wide",
+            Template::Downstack => "downstack",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Template::TSpinDouble => "T-spin double",
+            Template::FourWide => "4-wide combo",
+            Template::Downstack => "Downstack",
+        }
+    }
+
+    pub fn goal(&self) -> Goal {
+        match self {
+            Template::TSpinDouble => Goal::TSpin { lines: 2 },
+            Template::FourWide => Goal::Combo(4),
+            Template::Downstack => Goal::ClearLines(DOWNSTACK_ROWS),
+        }
+    }
+}
+
+/// What completes a drill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// A T-spin clearing at least `lines` rows.
+    TSpin { lines: u32 },
+    /// This many pieces in a row that each clear lines.
+    Combo(u32),
+    /// This many rows cleared in total.
+    ClearLines(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drill {
+    pub template: Template,
+    pub seed: u64,
+    combo: u32,
+    lines: u32,
+    /// Whether the piece locked last has cleared lines so far.
+    last_lock_cleared: bool,
+    completed_ms: Option<f64>,
+}
+
+impl Drill {
+    pub fn new(template: Template, seed: u64) -> Self {
+        Self { template, seed, combo: 0, lines: 0, last_lock_cleared: false, completed_ms: None }
+    }
+
+    /// The same drill on a new seed from `rng`, for a retry that should not
+    /// replay the last layout.
+    pub fn reseeded(&self, rng: &mut impl Rng) -> Self {
+        let mut seed = self.seed;
+        while seed == self.seed {
+            seed = rng.random::<u32>() as u64;
+        }
+        Drill::new(self.template, seed)
+    }
+
+    /// Game time at which the goal was met, if it was.
+    pub fn completed_ms(&self) -> Option<f64> {
+        self.completed_ms
+    }
+
+    /// Lays out the drill on `tetris`'s board through the editing API and
+    /// deals its pieces, starting progress over.
+    pub fn start(&mut self, tetris: &mut Tetris) {
+        *self = Drill::new(self.template, self.seed);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let (width, height) = (tetris.width as i32, tetris.height as i32);
+        tetris.board.clear();
+        for (pos, kind) in layout(self.template, &mut rng, width, height) {
+            tetris.board.set(pos, Some(kind));
+        }
+        // Layouts are drawn one way round; half the seeds play them mirrored.
+        if rng.random_bool(0.5) {
+            tetris.board = tetris.board.mirrored();
+        }
+        let first: &'static str = match self.template {
+            Template::TSpinDouble => "T",
+            _ => random_kind(&mut rng),
+        };
+        let rest: Vec<&'static str> = (1..SCRIPTED_PIECES).map(|_| random_kind(&mut rng)).collect();
+        tetris.spawn_piece(first);
+        tetris.set_upcoming_pieces(&rest);
+    }
+
+    /// Tracks progress from `event`; true when it completes the drill.
+    pub fn observe(&mut self, event: &GameEvent, elapsed_ms: f64) -> bool {
+        if self.completed_ms.is_some() {
+            return false;
+        }
+        match *event {
+            GameEvent::PieceLocked { .. } => {
+                if !self.last_lock_cleared {
+                    self.combo = 0;
+                }
+                self.last_lock_cleared = false;
+            }
+            GameEvent::LinesCleared { count } => {
+                self.lines += count;
+                if !self.last_lock_cleared {
+                    self.combo += 1;
+                }
+                self.last_lock_cleared = true;
+            }
+            _ => {}
+        }
+        let met = match (self.template.goal(), event) {
+            (Goal::TSpin { lines }, GameEvent::TSpin { lines: cleared }) => *cleared >= lines,
+            (Goal::Combo(n), GameEvent::LinesCleared { .. }) => self.combo >= n,
+            (Goal::ClearLines(n), GameEvent::LinesCleared { .. }) => self.lines >= n,
+            _ => false,
+        };
+        if met {
+            self.completed_ms = Some(elapsed_ms);
+        }
+        met
+    }
+}
+
+fn random_kind(rng: &mut StdRng) -> &'static str {
+    pieces::KINDS[rng.random_range(0..pieces::KINDS.len())]
+}
+
+/// A garbage row at `y`, full except for the columns in `holes`.
+fn row_except(width: i32, y: i32, holes: &[i32]) -> impl Iterator<Item = (Position, &'static str)> + '_ {
+    (0..width).filter(move |x| !holes.contains(x)).map(move |x| (Position(x, y), GARBAGE_KIND))
+}
+
+fn layout(template: Template, rng: &mut StdRng, width: i32, height: i32) -> Vec<(Position, &'static str)> {
+    let bottom = height - 1;
+    match template {
+        Template::TSpinDouble => {
+            let garbage = rng.random_range(0..=2);
+            let mut cells: Vec<_> = (0..garbage)
+                .flat_map(|i| row_except(width, bottom - i, &[rng.random_range(0..width)]).collect::<Vec<_>>())
+                .collect();
+            // The slot: one cell deep, three wide above it, with the
+            // overhang running out to the right wall.
+            let slot = rng.random_range(1..width - 1);
+            let y = bottom - garbage;
+            cells.extend(row_except(width, y, &[slot]));
+            cells.extend(row_except(width, y - 1, &[slot - 1, slot, slot + 1]));
+            cells.extend((slot + 1..width).map(|x| (Position(x, y - 2), GARBAGE_KIND)));
+            cells
+        }
+        Template::FourWide => {
+            let well = rng.random_range(0..=width - 4);
+            let open = well + rng.random_range(0..4);
+            let mut cells: Vec<_> = (0..12)
+                .flat_map(|i| row_except(width, bottom - i, &[well, well + 1, well + 2, well + 3]).collect::<Vec<_>>())
+                .collect();
+            cells.extend((well..well + 4).filter(|&x| x != open).map(|x| (Position(x, bottom), GARBAGE_KIND)));
+            cells
+        }
+        Template::Downstack => (0..DOWNSTACK_ROWS as i32)
+            .flat_map(|i| {
+                let holes: Vec<i32> = (0..rng.random_range(1..=2)).map(|_| rng.random_range(0..width)).collect();
+                row_except(width, bottom - i, &holes).collect::<Vec<_>>()
+            })
+            .collect(),
+    }
+}
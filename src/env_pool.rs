@@ -0,0 +1,132 @@
+//! Many RL environments stepped together. The caller owns one observation
+//! buffer for the whole pool (`len() * observation_len()` floats, env after
+//! env), and `EnvPool::step` writes every env's observation, reward and done
+//! flag into it in one call, spread over a few threads. Over the FFI this is
+//! `tetris_env_pool_create` and `tetris_env_pool_step`, so a training loop
+//! pays one Python↔native call per batch instead of several per env.
+
+use crate::clock::Clock;
+use crate::curriculum::Curriculum;
+use crate::observation::{ObservationEncoder, ObservationFormat};
+use crate::reward::RewardWeights;
+use crate::{BoardError, Tetris};
+
+/// Done flags written by `EnvPool::step`.
+pub const RUNNING: u8 = 0;
+pub const TERMINATED: u8 = 1;
+pub const TRUNCATED: u8 = 2;
+
+/// Below this many envs per thread, spawning threads costs more than it saves.
+const MIN_ENVS_PER_THREAD: usize = 8;
+
+pub struct EnvPool {
+    envs: Vec<Tetris>,
+    encoder: ObservationEncoder,
+    observation_len: usize,
+}
+
+impl EnvPool {
+    /// `count` games of `width × height`, observed in `format` and rewarded
+    /// by `weights`.
+    pub fn new(
+        count: u32,
+        width: u32,
+        height: u32,
+        format: ObservationFormat,
+        weights: RewardWeights,
+    ) -> Result<Self, BoardError> {
+        let envs = (0..count)
+            .map(|_| {
+                let mut tetris = Tetris::try_new(width, height)?;
+                tetris.set_clock(Clock::Manual(0.0));
+                tetris.set_reward_weights(Some(weights));
+                Ok(tetris)
+            })
+            .collect::<Result<_, _>>()?;
+        let encoder = ObservationEncoder::new(format);
+        Ok(Self { envs, encoder, observation_len: encoder.len(width, height) })
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    /// Floats per env in the observation buffer.
+    pub fn observation_len(&self) -> usize {
+        self.observation_len
+    }
+
+    pub fn envs(&self) -> &[Tetris] {
+        &self.envs
+    }
+
+    /// Restarts every env under `curriculum`.
+    pub fn set_curriculum(&mut self, curriculum: Curriculum) {
+        for tetris in &mut self.envs {
+            tetris.set_curriculum(curriculum);
+        }
+    }
+
+    /// Resets every env and writes the first observations to `obs`.
+    pub fn reset(&mut self, obs: &mut [f32]) {
+        for (tetris, out) in self.envs.iter_mut().zip(obs.chunks_mut(self.observation_len)) {
+            tetris.reset();
+            out.copy_from_slice(&self.encoder.encode(tetris));
+        }
+    }
+
+    /// Applies `actions[i]` (a `TETRIS_ACTION_*` code) to env `i` and writes
+    /// its reward and done flag. Finished envs are reset straight away, so
+    /// their observation is the first of the next episode.
+    ///
+    /// # Panics
+    ///
+    /// If a slice is shorter than the pool needs.
+    pub fn step(&mut self, actions: &[u32], obs: &mut [f32], rewards: &mut [f32], dones: &mut [u8]) {
+        let (n, len) = (self.envs.len(), self.observation_len);
+        assert!(actions.len() >= n && obs.len() >= n * len && rewards.len() >= n && dones.len() >= n);
+        let threads = std::thread::available_parallelism().map_or(1, |p| p.get()).min(n / MIN_ENVS_PER_THREAD).max(1);
+        let per_thread = n.div_ceil(threads).max(1);
+        let encoder = self.encoder;
+        std::thread::scope(|scope| {
+            let batches = self
+                .envs
+                .chunks_mut(per_thread)
+                .zip(actions.chunks(per_thread))
+                .zip(obs.chunks_mut(per_thread * len))
+                .zip(rewards.chunks_mut(per_thread))
+                .zip(dones.chunks_mut(per_thread));
+            for ((((envs, actions), obs), rewards), dones) in batches {
+                let mut batch = move || {
+                    for (i, tetris) in envs.iter_mut().enumerate() {
+                        (rewards[i], dones[i]) = step_one(tetris, actions[i]);
+                        obs[i * len..(i + 1) * len].copy_from_slice(&encoder.encode(tetris));
+                    }
+                };
+                if threads == 1 {
+                    batch();
+                } else {
+                    scope.spawn(batch);
+                }
+            }
+        });
+    }
+}
+
+fn step_one(tetris: &mut Tetris, action: u32) -> (f32, u8) {
+    crate::step_action(tetris, action);
+    let reward = tetris.reward.map_or(0.0, |r| r.last_reward());
+    let done = if tetris.lost {
+        TERMINATED
+    } else if tetris.is_truncated() {
+        TRUNCATED
+    } else {
+        return (reward, RUNNING);
+    };
+    tetris.reset();
+    (reward, done)
+}
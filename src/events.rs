@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+use crate::input::Action;
+use crate::placements::Placement;
+use crate::scoring::{ScoreBreakdown, ScoreSource};
+use crate::stats::Misdrop;
+
+/// Upper bound on undrained events; embedders that never drain (e.g. the FFI)
+/// only lose the oldest ones.
+const MAX_PENDING_EVENTS: usize = 256;
+
+/// How a rotation that went through got there, so frontends can make each
+/// sound and look different while players learn the kicks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Turned in place.
+    Plain,
+    /// Turned only by a later test of `pieces::kicks` than the first.
+    Kicked,
+    /// Ended up wedged: a T-spin by the three-corner rule, or any other
+    /// piece that can no longer move left, right or up.
+    Spin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// Points were awarded. `breakdown` holds the totals after the award.
+    Score {
+        source: ScoreSource,
+        points: i32,
+        breakdown: ScoreBreakdown,
+    },
+    /// A player action passed to `Tetris::apply` took effect.
+    Input(Action),
+    /// The falling piece turned, and how; see `Rotation`.
+    Rotated(Rotation),
+    /// A piece of `kind` settled into the board.
+    PieceLocked { kind: &'static str },
+    /// Where and how the piece that just locked was placed.
+    Placed(Placement),
+    /// The piece that just locked was a misdrop; see `stats::judge`.
+    Misdrop(Misdrop),
+    /// The piece that just locked was a T-spin, completing `lines` rows.
+    TSpin { lines: u32 },
+    /// `count` full rows started clearing.
+    LinesCleared { count: u32 },
+    /// The rows that started clearing held every block left on the board.
+    PerfectClear,
+    /// The rows that started clearing bring the stack back out of the danger
+    /// zone (see `DANGER_ROWS`).
+    CloseCall,
+    /// `rows` garbage rows were pushed in from the bottom.
+    GarbageReceived { rows: u32 },
+    /// The zone started and lasts `duration_ms`.
+    ZoneStarted { duration_ms: f64 },
+    /// The zone ended, scoring the `lines` cleared in it.
+    ZoneEnded { lines: u32 },
+    /// The stack topped out with `score` points.
+    GameOver { score: i32 },
+    /// The game's line goal was met after `elapsed_ms` of play, ending it.
+    GoalReached { lines: u32, elapsed_ms: f64 },
+}
+
+impl GameEvent {
+    /// The event as a JSON object tagged by its variant name, e.g.
+    /// `{"type": "LinesCleared", "count": 2}`, for out-of-process listeners.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+        match self {
+            GameEvent::Score { source, points, breakdown } => json!({
+                "type": "Score",
+                "source": format!("{:?}", source),
+                "points": points,
+                "total": breakdown.total(),
+            }),
+            GameEvent::Input(action) => json!({ "type": "Input", "action": format!("{:?}", action) }),
+            GameEvent::Rotated(rotation) => json!({ "type": "Rotated", "rotation": format!("{:?}", rotation) }),
+            GameEvent::PieceLocked { kind } => json!({ "type": "PieceLocked", "kind": kind }),
+            GameEvent::Placed(placement) => {
+                let mut json = serde_json::to_value(placement).unwrap_or_default();
+                json["type"] = json!("Placed");
+                json
+            }
+            GameEvent::Misdrop(misdrop) => json!({ "type": "Misdrop", "reason": format!("{:?}", misdrop) }),
+            GameEvent::TSpin { lines } => json!({ "type": "TSpin", "lines": lines }),
+            GameEvent::LinesCleared { count } => json!({ "type": "LinesCleared", "count": count }),
+            GameEvent::PerfectClear => json!({ "type": "PerfectClear" }),
+            GameEvent::CloseCall => json!({ "type": "CloseCall" }),
+            GameEvent::GarbageReceived { rows } => json!({ "type": "GarbageReceived", "rows": rows }),
+            GameEvent::ZoneStarted { duration_ms } => json!({ "type": "ZoneStarted", "duration_ms": duration_ms }),
+            GameEvent::ZoneEnded { lines } => json!({ "type": "ZoneEnded", "lines": lines }),
+            GameEvent::GameOver { score } => json!({ "type": "GameOver", "score": score }),
+            GameEvent::GoalReached { lines, elapsed_ms } => {
+                json!({ "type": "GoalReached", "lines": lines, "elapsed_ms": elapsed_ms })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    pending: VecDeque<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn push(&mut self, event: GameEvent) {
+        if self.pending.len() == MAX_PENDING_EVENTS {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(event);
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        self.pending.drain(..).collect()
+    }
+}
@@ -0,0 +1,110 @@
+//! Checks a piece generator for fairness by drawing a long run of pieces
+//! and measuring droughts (stretches without a kind), floods (a kind piling
+//! up) and how often the run splits into whole bags of seven. For
+//! validating a new generator against what players expect of it; see the
+//! `--audit-pieces` CLI flag.
+
+use std::fmt;
+
+use crate::pieces::KINDS;
+
+/// A kind showing up this many times within one group of seven is a flood.
+pub const FLOOD_COUNT: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audit {
+    pub pieces: u64,
+    /// Pieces dealt per kind, indexed like `pieces::KINDS`.
+    pub counts: [u64; 7],
+    /// Most pieces in a row without each kind, counting the start and end
+    /// of the run.
+    pub longest_drought: [u64; 7],
+    /// Average pieces between two of the same kind.
+    pub mean_drought: f64,
+    /// Share of pieces that repeat the one before.
+    pub repeat_rate: f64,
+    /// Share of groups of seven (pieces 1-7, 8-14, ...) in which some kind
+    /// comes up `FLOOD_COUNT` times or more.
+    pub flood_rate: f64,
+    /// Share of groups of seven holding every kind once, i.e. whole bags.
+    pub bag_integrity: f64,
+}
+
+/// A generator dealt a piece that is not one of `pieces::KINDS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKind {
+    pub kind: &'static str,
+    /// Pieces dealt before it.
+    pub dealt: u64,
+}
+
+impl fmt::Display for UnknownKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown piece {:?} after {} pieces", self.kind, self.dealt)
+    }
+}
+
+impl std::error::Error for UnknownKind {}
+
+/// Draws `pieces` pieces from `next` and measures them, stopping at the
+/// first piece that is not a known kind.
+pub fn audit(pieces: u64, mut next: impl FnMut() -> &'static str) -> Result<Audit, UnknownKind> {
+    let mut counts = [0u64; 7];
+    let mut last_seen = [None::<u64>; 7];
+    let mut longest_drought = [0u64; 7];
+    let (mut gaps, mut gap_total) = (0u64, 0u64);
+    let mut repeats = 0u64;
+    let mut previous = None;
+    let (mut groups, mut floods, mut bags) = (0u64, 0u64, 0u64);
+    let mut group = [0u32; 7];
+    for i in 0..pieces {
+        let kind = next();
+        let k = KINDS.iter().position(|&c| c == kind).ok_or(UnknownKind { kind, dealt: i })?;
+        counts[k] += 1;
+        let gap = i - last_seen[k].map_or(0, |seen| seen + 1);
+        longest_drought[k] = longest_drought[k].max(gap);
+        if last_seen[k].is_some() {
+            gaps += 1;
+            gap_total += gap;
+        }
+        last_seen[k] = Some(i);
+        repeats += u64::from(previous == Some(k));
+        previous = Some(k);
+        group[k] += 1;
+        if (i + 1).is_multiple_of(7) {
+            groups += 1;
+            floods += u64::from(group.iter().any(|&n| n >= FLOOD_COUNT));
+            bags += u64::from(group.iter().all(|&n| n == 1));
+            group = [0; 7];
+        }
+    }
+    for k in 0..7 {
+        let tail = pieces - last_seen[k].map_or(0, |seen| seen + 1);
+        longest_drought[k] = longest_drought[k].max(tail);
+    }
+    let share = |n: u64, of: u64| if of == 0 { 0.0 } else { n as f64 / of as f64 };
+    Ok(Audit {
+        pieces,
+        counts,
+        longest_drought,
+        mean_drought: share(gap_total, gaps),
+        repeat_rate: share(repeats, pieces.saturating_sub(1)),
+        flood_rate: share(floods, groups),
+        bag_integrity: share(bags, groups),
+    })
+}
+
+impl Audit {
+    /// The report as `metric,value` lines under a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("metric,value\npieces,{}\n", self.pieces);
+        for (k, kind) in KINDS.iter().enumerate() {
+            csv += &format!("count_{kind},{}\nlongest_drought_{kind},{}\n", self.counts[k], self.longest_drought[k]);
+        }
+        csv += &format!(
+            "mean_drought,{:.3}\nrepeat_rate,{:.5}\nflood_rate,{:.5}\nbag_integrity,{:.5}\n",
+            self.mean_drought, self.repeat_rate, self.flood_rate, self.bag_integrity
+        );
+        csv
+    }
+}
@@ -0,0 +1,188 @@
+//! Where the holes go in incoming garbage. `Clean` garbage keeps its hole
+//! in one column for a streak of rows, so it comes out in a few big clears;
+//! `Cheese` moves the hole every row, so it has to be dug out row by row.
+//! Rows can have more than one hole to make either easier. `GarbageHoles`
+//! deals the holes for one board, and `Tetris::receive_garbage_rows` pushes
+//! them in. In a match, garbage waits in the receiver's `GarbageQueue`
+//! first, telegraphed for a couple of seconds, while their own clears can
+//! counter it.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Most holes a garbage row can have.
+pub const MAX_HOLES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GarbagePattern {
+    /// The holes stay put for `GarbageStyle::streak` rows.
+    #[default]
+    Clean,
+    /// The holes move every row.
+    Cheese,
+}
+
+impl GarbagePattern {
+    pub const ALL: [GarbagePattern; 2] = [GarbagePattern::Clean, GarbagePattern::Cheese];
+}
+
+impl std::str::FromStr for GarbagePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clean" => Ok(GarbagePattern::Clean),
+            "cheese" | "cheesy" => Ok(GarbagePattern::Cheese),
+            _ => Err(format!("unknown garbage pattern {s:?}, expected clean or cheese")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GarbageStyle {
+    pub pattern: GarbagePattern,
+    /// Holes per row, 1 to `MAX_HOLES`.
+    pub holes: u32,
+    /// Rows a `Clean` hole lasts before it moves.
+    pub streak: u32,
+}
+
+impl Default for GarbageStyle {
+    fn default() -> Self {
+        Self { pattern: GarbagePattern::Clean, holes: 1, streak: 8 }
+    }
+}
+
+/// Deals garbage holes in a `GarbageStyle`, remembering the current streak
+/// between calls.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GarbageHoles {
+    pub style: GarbageStyle,
+    holes: Vec<u32>,
+    rows_left: u32,
+}
+
+impl GarbageHoles {
+    pub fn new(style: GarbageStyle) -> Self {
+        Self { style, holes: Vec::new(), rows_left: 0 }
+    }
+
+    /// The holes of the next `rows` rows on a board `width` wide, in the
+    /// order they are pushed in.
+    pub fn rows(&mut self, rows: u32, width: u32, rng: &mut impl Rng) -> Vec<Vec<u32>> {
+        (0..rows).map(|_| self.next_row(width, rng)).collect()
+    }
+
+    fn next_row(&mut self, width: u32, rng: &mut impl Rng) -> Vec<u32> {
+        let count = self.style.holes.clamp(1, MAX_HOLES.min(width - 1)) as usize;
+        let moves = match self.style.pattern {
+            GarbagePattern::Clean => self.rows_left == 0 || self.holes.len() != count,
+            GarbagePattern::Cheese => true,
+        };
+        if moves {
+            let previous = std::mem::take(&mut self.holes);
+            // A cheese hole never stays in the column it was just in.
+            let avoid = self.style.pattern == GarbagePattern::Cheese && count as u32 + (previous.len() as u32) <= width;
+            while self.holes.len() < count {
+                let x = rng.random_range(0..width);
+                let taken = self.holes.contains(&x) || avoid && previous.contains(&x);
+                if !taken {
+                    self.holes.push(x);
+                }
+            }
+            self.holes.sort_unstable();
+            self.rows_left = self.style.streak.max(1);
+        }
+        self.rows_left -= 1;
+        self.holes.clone()
+    }
+}
+
+/// Incoming garbage rows per 100 rows of attack a clear cancels by default.
+pub const DEFAULT_CANCEL_PERCENT: u32 = 100;
+/// Game time garbage waits in a `GarbageQueue` by default before it can
+/// come in.
+pub const DEFAULT_DELAY_MS: f64 = 2000.0;
+
+/// One attack's worth of queued garbage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Incoming {
+    pub rows: u32,
+    /// Receiver's game time from which the rows come in on the next lock.
+    pub arrives_ms: f64,
+}
+
+/// Garbage sent to a board that hasn't entered it yet. Each batch waits
+/// `delay_ms` of the receiver's game time, then goes in when the board's
+/// player next locks a piece without clearing. Until then a clear first
+/// counters what is queued (see `counter`), arrived or not, and only sends
+/// on what is left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GarbageQueue {
+    pub delay_ms: f64,
+    /// Batches as they were sent, oldest first.
+    batches: Vec<Incoming>,
+}
+
+impl Default for GarbageQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_DELAY_MS)
+    }
+}
+
+impl GarbageQueue {
+    pub fn new(delay_ms: f64) -> Self {
+        Self { delay_ms, batches: Vec::new() }
+    }
+
+    /// Queues `rows` sent at the receiver's game time `now_ms`.
+    pub fn push(&mut self, rows: u32, now_ms: f64) {
+        if rows > 0 {
+            self.batches.push(Incoming { rows, arrives_ms: now_ms + self.delay_ms });
+        }
+    }
+
+    /// Rows waiting to enter, for a garbage meter.
+    pub fn pending(&self) -> u32 {
+        self.batches.iter().map(|b| b.rows).sum()
+    }
+
+    pub fn batches(&self) -> &[Incoming] {
+        &self.batches
+    }
+
+    /// Time left at `now_ms` until the oldest batch arrives; 0 once it has,
+    /// `None` with nothing queued.
+    pub fn countdown_ms(&self, now_ms: f64) -> Option<f64> {
+        self.batches.first().map(|b| (b.arrives_ms - now_ms).max(0.0))
+    }
+
+    /// Spends `attack` rows cancelling queued garbage, oldest first, each
+    /// row of attack cancelling `cancel_percent`% of a row. Returns the
+    /// attack left over to send on.
+    pub fn counter(&mut self, attack: u32, cancel_percent: u32) -> u32 {
+        if cancel_percent == 0 {
+            return attack;
+        }
+        let mut cancelled = (attack * cancel_percent / 100).min(self.pending());
+        let spent = (cancelled * 100).div_ceil(cancel_percent).min(attack);
+        while cancelled > 0 {
+            let front = &mut self.batches[0];
+            let n = cancelled.min(front.rows);
+            front.rows -= n;
+            cancelled -= n;
+            if front.rows == 0 {
+                self.batches.remove(0);
+            }
+        }
+        attack - spent
+    }
+
+    /// Takes the batches that have arrived by `now_ms`, oldest first, as row
+    /// counts to push in.
+    pub fn take_arrived(&mut self, now_ms: f64) -> Vec<u32> {
+        let arrived = self.batches.iter().take_while(|b| b.arrives_ms <= now_ms).count();
+        self.batches.drain(..arrived).map(|b| b.rows).collect()
+    }
+}
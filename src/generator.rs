@@ -0,0 +1,284 @@
+//! The random source new pieces are drawn from. Its whole state is one
+//! `u64`, so a game's future pieces can be saved and restored exactly: read
+//! it with `Tetris::piece_rng`, put it back with `Tetris::set_piece_rng`,
+//! serialize it with serde, or keep it in a replay's `rng` line.
+//!
+//! By default pieces come in shuffled bags of seven (see `PieceBag`), so the
+//! rest of the current bag is part of what deals next too; it shows up among
+//! `Tetris::upcoming_pieces`, which snapshots and replays already keep.
+//!
+//! In a seeded match, `MatchGenerators` gives every player a generator
+//! derived from the one match seed and checks, as pieces are dealt, that
+//! each game's generator still follows it.
+
+use std::fmt;
+
+use rand::{seq::SliceRandom, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::curriculum::Curriculum;
+use crate::pieces;
+
+/// SplitMix64: fast, small and plenty random for dealing pieces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceRng {
+    state: u64,
+}
+
+impl PieceRng {
+    /// A generator that will deal the same pieces for the same `state`.
+    pub fn from_state(state: u64) -> Self {
+        Self { state }
+    }
+
+    /// Generator number `stream` of a match seeded with `match_seed`. The
+    /// same pair always deals the same pieces; different streams deal
+    /// unrelated ones.
+    pub fn derived(match_seed: u64, stream: u64) -> Self {
+        let mut mixer = Self::from_state(match_seed ^ stream.wrapping_mul(0xd1b5_4a32_d192_ed03));
+        Self::from_state(mixer.next_u64())
+    }
+
+    /// A freshly seeded generator, different every game.
+    pub fn from_entropy() -> Self {
+        Self::from_state(rand::random())
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl RngCore for PieceRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// The state as 16 hex digits, as in replays.
+impl fmt::Display for PieceRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.state)
+    }
+}
+
+impl std::str::FromStr for PieceRng {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s.trim(), 16).map(Self::from_state)
+    }
+}
+
+/// How new pieces are picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Randomizer {
+    /// Every kind once per bag of seven, in shuffled order: no kind waits
+    /// more than 12 pieces, and none comes up three times in a row.
+    #[default]
+    Bag,
+    /// Every piece drawn on its own, as in classic games, droughts and
+    /// floods included.
+    Classic,
+}
+
+/// Deals pieces as its `Randomizer` says, drawing from the `PieceRng` it is
+/// handed. In bag mode it holds the rest of the current bag and shuffles a
+/// new one, of the kinds the curriculum allows, once that runs out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PieceBag {
+    randomizer: Randomizer,
+    /// The bag, dealt from the back; only the first `left` are still in it.
+    kinds: [&'static str; 7],
+    left: usize,
+}
+
+impl PieceBag {
+    pub fn new(randomizer: Randomizer) -> Self {
+        Self { randomizer, kinds: [""; 7], left: 0 }
+    }
+
+    pub fn randomizer(&self) -> Randomizer {
+        self.randomizer
+    }
+
+    pub fn deal(&mut self, curriculum: &Curriculum, rng: &mut PieceRng) -> &'static str {
+        if self.randomizer == Randomizer::Classic {
+            return curriculum.random_kind(rng);
+        }
+        if self.left == 0 {
+            for (i, &kind) in pieces::KINDS.iter().enumerate() {
+                if curriculum.piece_mask & (1 << i) != 0 {
+                    self.kinds[self.left] = kind;
+                    self.left += 1;
+                }
+            }
+            // An empty mask allows every kind, as with `random_kind`.
+            if self.left == 0 {
+                self.kinds = pieces::KINDS;
+                self.left = pieces::KINDS.len();
+            }
+            self.kinds[..self.left].shuffle(rng);
+        }
+        self.left -= 1;
+        self.kinds[self.left]
+    }
+
+    /// What is left of the current bag, in the order it will be dealt.
+    pub fn queued(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.kinds[..self.left].iter().rev().copied()
+    }
+
+    /// Empties the bag; the next piece starts a new one.
+    pub fn clear(&mut self) {
+        self.left = 0;
+    }
+}
+
+/// FNV-1a over a run of dealt kinds, and how many there were. Two runs
+/// with equal digests dealt the same pieces, so games and streams can be
+/// compared without keeping the sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindDigest {
+    dealt: u64,
+    value: u64,
+}
+
+impl Default for KindDigest {
+    fn default() -> Self {
+        Self { dealt: 0, value: 0xcbf2_9ce4_8422_2325 }
+    }
+}
+
+impl KindDigest {
+    pub fn push(&mut self, kind: &str) {
+        let index = pieces::kind_index(kind).map_or(0, |i| i as u64 + 1);
+        self.value = (self.value ^ index).wrapping_mul(0x0100_0000_01b3);
+        self.dealt += 1;
+    }
+
+    pub fn dealt(&self) -> u64 {
+        self.dealt
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl fmt::Display for KindDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.value)
+    }
+}
+
+/// One player's pieces as their generator should deal them, and a digest
+/// of the kinds dealt so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceStream {
+    rng: PieceRng,
+    bag: PieceBag,
+    digest: KindDigest,
+}
+
+impl PieceStream {
+    pub fn new(rng: PieceRng) -> Self {
+        Self { rng, bag: PieceBag::default(), digest: KindDigest::default() }
+    }
+
+    /// Deals the next piece, as a game with the default curriculum and
+    /// randomizer would.
+    pub fn deal(&mut self) -> &'static str {
+        let kind = self.bag.deal(&Curriculum::default(), &mut self.rng);
+        self.digest.push(kind);
+        kind
+    }
+
+    /// The generator as it stands after `dealt` pieces.
+    pub fn rng(&self) -> PieceRng {
+        self.rng
+    }
+
+    pub fn dealt(&self) -> u64 {
+        self.digest.dealt()
+    }
+
+    /// The kinds dealt so far; see `Tetris::dealt_digest` for a game's.
+    pub fn digest(&self) -> KindDigest {
+        self.digest
+    }
+}
+
+/// A game that dealt other pieces than its stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamMismatch {
+    pub player: usize,
+    /// Pieces dealt when it was caught.
+    pub dealt: u64,
+    pub expected: KindDigest,
+    pub found: KindDigest,
+}
+
+impl fmt::Display for StreamMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "player {} left their piece stream by piece {}: expected digest {}, found {}", self.player, self.dealt, self.expected, self.found)
+    }
+}
+
+impl std::error::Error for StreamMismatch {}
+
+/// The piece generators of a seeded match: player `i` deals from stream
+/// `streams[i]` of the match seed (see `PieceRng::derived`), so players on
+/// the same stream get the same pieces. Games deal for themselves from
+/// `rng(i)`; the match engine calls `check` as they go to make sure they
+/// still follow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchGenerators {
+    pub seed: u64,
+    streams: Vec<u64>,
+    players: Vec<PieceStream>,
+}
+
+impl MatchGenerators {
+    pub fn new(seed: u64, streams: &[u64]) -> Self {
+        let players = streams.iter().map(|&stream| PieceStream::new(PieceRng::derived(seed, stream))).collect();
+        Self { seed, streams: streams.to_vec(), players }
+    }
+
+    /// The generator to hand `player`'s game before it deals anything.
+    pub fn rng(&self, player: usize) -> PieceRng {
+        PieceRng::derived(self.seed, self.streams[player])
+    }
+
+    pub fn stream(&self, player: usize) -> &PieceStream {
+        &self.players[player]
+    }
+
+    /// Deals `player`'s reference stream as far as their game has dealt
+    /// and compares the digests; see `Tetris::dealt_digest`.
+    pub fn check(&mut self, player: usize, found: KindDigest) -> Result<(), StreamMismatch> {
+        let stream = &mut self.players[player];
+        while stream.dealt() < found.dealt() {
+            stream.deal();
+        }
+        if stream.digest != found {
+            return Err(StreamMismatch { player, dealt: found.dealt(), expected: stream.digest, found });
+        }
+        Ok(())
+    }
+}
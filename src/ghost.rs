@@ -0,0 +1,50 @@
+//! Racing a previous run. A `Trace` samples a run's lines and score each
+//! time rows are cleared; played back against the game clock it acts as a
+//! ghost the current run can be compared with while it is going on.
+
+/// Progress at a moment: game time in milliseconds, lines and score.
+pub type Sample = (f64, u32, i32);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    samples: Vec<Sample>,
+}
+
+impl Trace {
+    pub fn from_samples(samples: Vec<Sample>) -> Self {
+        Self { samples }
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Adds a sample unless progress hasn't changed since the last one.
+    pub fn record(&mut self, elapsed_ms: f64, lines: u32, score: i32) {
+        if self.samples.last().is_some_and(|&(_, l, s)| (l, s) == (lines, score)) {
+            return;
+        }
+        self.samples.push((elapsed_ms, lines, score));
+    }
+
+    /// Lines and score the run had reached at `elapsed_ms`.
+    pub fn at(&self, elapsed_ms: f64) -> (u32, i32) {
+        self.samples
+            .iter()
+            .take_while(|&&(ms, _, _)| ms <= elapsed_ms)
+            .last()
+            .map_or((0, 0), |&(_, lines, score)| (lines, score))
+    }
+
+    /// The run's final lines and score.
+    pub fn result(&self) -> (u32, i32) {
+        self.samples.last().map_or((0, 0), |&(_, lines, score)| (lines, score))
+    }
+
+    /// How far ahead of this ghost (negative when behind) a run with `lines`
+    /// and `score` at `elapsed_ms` is, in lines and points.
+    pub fn lead(&self, elapsed_ms: f64, lines: u32, score: i32) -> (i64, i64) {
+        let (ghost_lines, ghost_score) = self.at(elapsed_ms);
+        (lines as i64 - ghost_lines as i64, score as i64 - ghost_score as i64)
+    }
+}
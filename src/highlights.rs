@@ -0,0 +1,78 @@
+//! Best moments of a recorded game, for a replay viewer to jump to: every
+//! Tetris, runs of at least `COMBO_PIECES` clearing pieces, and recoveries
+//! from near a top-out (`GameEvent::CloseCall`). Found by playing the replay
+//! back and reading the events each input caused.
+
+use crate::replay::Replay;
+use crate::GameEvent;
+
+/// Clearing pieces in a row that make a combo worth showing.
+pub const COMBO_PIECES: u32 = 4;
+/// How much of the game before a moment a jump shows first.
+pub const LEAD_IN_MS: f64 = 2000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    Tetris,
+    /// `pieces` pieces in a row cleared lines.
+    Combo { pieces: u32 },
+    /// A clear that got the stack out of the danger zone.
+    Recovery,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    pub kind: HighlightKind,
+    /// Game time of the moment; for a combo, of its first clear.
+    pub at_ms: f64,
+    /// Inputs to play before watching it, `LEAD_IN_MS` ahead of `at_ms`.
+    pub start: usize,
+}
+
+/// The highlights of `replay`, in the order they happened.
+pub fn find(replay: &Replay) -> Vec<Highlight> {
+    let mut found = Vec::new();
+    let mut combo: Option<(f64, u32)> = None;
+    let mut cleared = false;
+    let mut at_ms = 0.0;
+    fn end_combo(combo: &mut Option<(f64, u32)>, found: &mut Vec<(HighlightKind, f64)>) {
+        if let Some((start_ms, pieces)) = combo.take().filter(|&(_, pieces)| pieces >= COMBO_PIECES) {
+            found.push((HighlightKind::Combo { pieces }, start_ms));
+        }
+    }
+    let mut inputs = replay.inputs.iter();
+    replay.play(|_, events| {
+        at_ms = inputs.next().map_or(at_ms, |&(ms, _)| ms);
+        for event in events {
+            match *event {
+                GameEvent::PieceLocked { .. } => {
+                    if !cleared {
+                        end_combo(&mut combo, &mut found);
+                    }
+                    cleared = false;
+                }
+                GameEvent::LinesCleared { count } => {
+                    if count >= 4 {
+                        found.push((HighlightKind::Tetris, at_ms));
+                    }
+                    if !cleared {
+                        combo.get_or_insert((at_ms, 0)).1 += 1;
+                    }
+                    cleared = true;
+                }
+                GameEvent::CloseCall => found.push((HighlightKind::Recovery, at_ms)),
+                _ => {}
+            }
+        }
+    });
+    end_combo(&mut combo, &mut found);
+    found.sort_by(|a, b| a.1.total_cmp(&b.1));
+    found
+        .into_iter()
+        .map(|(kind, at_ms)| Highlight {
+            kind,
+            at_ms,
+            start: replay.inputs.iter().take_while(|(ms, _)| *ms < at_ms - LEAD_IN_MS).count(),
+        })
+        .collect()
+}
@@ -0,0 +1,177 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Tetris;
+
+/// Player intents understood by `Tetris::apply`, independent of where they
+/// came from (keyboard, touch buttons, FFI, ...; see `InputSource`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Rotate,
+    SoftDrop,
+    HardDrop,
+    /// Drops to the ghost position without locking.
+    SonicDrop,
+    Tick,
+    Pause,
+    Resume,
+    /// Spends the zone meter; see `zone`.
+    Zone,
+    /// Swaps the falling piece with the held one; see `Tetris::hold`.
+    Hold,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Rotate,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::SonicDrop,
+        Action::Tick,
+        Action::Pause,
+        Action::Resume,
+        Action::Zone,
+        Action::Hold,
+    ];
+
+    /// Shifts and rotations can be carried over to the next piece.
+    pub fn is_bufferable(&self) -> bool {
+        matches!(self, Action::MoveLeft | Action::MoveRight | Action::Rotate)
+    }
+}
+
+/// How fast the piece falls while soft drop is held; see
+/// `Tetris::set_soft_drop_held`. Each press also moves it at once: one row,
+/// or with `Instant` all the way down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoftDropSpeed {
+    /// Five times gravity.
+    Times5,
+    /// Twenty times gravity.
+    #[default]
+    Times20,
+    /// Straight down to the stack, without locking.
+    Instant,
+}
+
+impl SoftDropSpeed {
+    pub const ALL: [SoftDropSpeed; 3] = [SoftDropSpeed::Times5, SoftDropSpeed::Times20, SoftDropSpeed::Instant];
+
+    /// How many times faster than gravity ticks come while held. `Instant`
+    /// ticks as fast as `Times20`; each tick drops the whole way.
+    pub fn factor(&self) -> u32 {
+        match self {
+            SoftDropSpeed::Times5 => 5,
+            SoftDropSpeed::Times20 | SoftDropSpeed::Instant => 20,
+        }
+    }
+}
+
+/// Default for `InputBuffer::window_ms`.
+pub const DEFAULT_INPUT_BUFFER_MS: f64 = 100.0;
+
+/// Holds one shift or rotation that could not be applied (blocked, or pressed
+/// while rows were being cleared) so it can be applied to the next piece if
+/// that spawns within `window_ms`.
+#[derive(Debug, Clone, Copy)]
+pub struct InputBuffer {
+    pub window_ms: f64,
+    pending: Option<(Action, f64)>,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        Self {
+            window_ms: DEFAULT_INPUT_BUFFER_MS,
+            pending: None,
+        }
+    }
+}
+
+impl InputBuffer {
+    /// Remembers `action`; the latest buffered action wins.
+    pub fn buffer(&mut self, action: Action, now: f64) {
+        if action.is_bufferable() && self.window_ms > 0.0 {
+            self.pending = Some((action, now));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+
+    /// Restarts the window of an action buffered at or after `since`, e.g.
+    /// one pressed while rows were clearing and no piece could move.
+    pub fn renew_since(&mut self, since: f64, now: f64) {
+        if let Some((_, at)) = &mut self.pending {
+            if *at >= since {
+                *at = now;
+            }
+        }
+    }
+
+    /// Takes the buffered action if it is still inside the window.
+    pub fn take(&mut self, now: f64) -> Option<Action> {
+        self.pending
+            .take()
+            .filter(|&(_, at)| now - at <= self.window_ms)
+            .map(|(action, _)| action)
+    }
+}
+
+/// Where actions come from: keyboard, touch buttons, a gamepad, a bot, the
+/// network, a replay, chat votes. Game loops poll a list of sources and
+/// apply whatever they produce (see `apply_sources`), so a new control
+/// scheme is a new source rather than a change to the loop.
+pub trait InputSource {
+    /// Actions that happened up to `now_ms` on the game clock, oldest first,
+    /// each with its time. `tetris` is for sources that react to the game,
+    /// such as bots.
+    fn poll(&mut self, tetris: &Tetris, now_ms: f64) -> Vec<(f64, Action)>;
+}
+
+/// A source shared with whatever feeds it, e.g. a key handler.
+impl<S: InputSource> InputSource for Rc<RefCell<S>> {
+    fn poll(&mut self, tetris: &Tetris, now_ms: f64) -> Vec<(f64, Action)> {
+        self.borrow_mut().poll(tetris, now_ms)
+    }
+}
+
+/// Actions pushed from outside, e.g. by keyboard or touch handlers, handed
+/// out on the next poll.
+#[derive(Debug, Default)]
+pub struct ActionQueue {
+    pending: VecDeque<(f64, Action)>,
+}
+
+impl ActionQueue {
+    pub fn push(&mut self, at_ms: f64, action: Action) {
+        self.pending.push_back((at_ms, action));
+    }
+}
+
+impl InputSource for ActionQueue {
+    fn poll(&mut self, _: &Tetris, now_ms: f64) -> Vec<(f64, Action)> {
+        let due = self.pending.iter().take_while(|&&(at, _)| at <= now_ms).count();
+        self.pending.drain(..due).collect()
+    }
+}
+
+/// Polls every source and applies what they produced to `tetris`, in time
+/// order (sources earlier in the list first on ties). Returns the actions
+/// applied, e.g. to record them.
+pub fn apply_sources(tetris: &mut Tetris, sources: &mut [Box<dyn InputSource>], now_ms: f64) -> Vec<(f64, Action)> {
+    let mut actions: Vec<(f64, Action)> = sources.iter_mut().flat_map(|s| s.poll(tetris, now_ms)).collect();
+    actions.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for &(_, action) in &actions {
+        tetris.apply(action);
+    }
+    actions
+}
@@ -1,21 +1,78 @@
-use lazy_static::lazy_static;
-use leptos::leptos_dom::logging::console_log; // console_log is used in Tetromino::remove_at and Tetris::clear_lines
 use rand::Rng;
 use reactive_stores::Store; // Used by #[derive(Store)] on Tetris
-use std::{
-    cmp::Reverse,
-    collections::HashSet,
-    ops::{Add, Sub},
-};
+use animation::PieceMotion;
+use board_delta::{CellChange, DeltaTracker};
+use clock::{Clock, GameTimer, TimeScale};
+use curriculum::Curriculum;
+use events::EventQueue;
+use input::{InputBuffer, SoftDropSpeed};
+use metrics::BoardMetrics;
+use pieces::SpawnOrientations;
+use generator::{KindDigest, PieceBag, PieceRng, Randomizer};
+use zone::Zone;
+use placements::Placement;
+use reward::{RewardShaper, RewardWeights};
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
 // use wasm_bindgen::prelude::*; // For JsValue, etc. if needed by console_log or other web_sys features
-use web_sys::window; // Used in Tetris::tick and Tetris::clear_lines for performance.now()
+
+pub mod achievements;
+pub mod adaptive;
+pub mod analysis;
+pub mod animation;
+pub mod board;
+pub mod board_delta;
+#[cfg(feature = "twitch")]
+pub mod chat_vote;
+pub mod bot;
+#[cfg(all(feature = "broadcast", not(target_arch = "wasm32")))]
+pub mod broadcast;
+pub mod clock;
+pub mod curriculum;
+pub mod downstack;
+pub mod drills;
+pub mod env_pool;
+pub mod events;
+pub mod fairness;
+pub mod garbage;
+pub mod generator;
+pub mod ghost;
+pub mod highlights;
+pub mod input;
+pub mod lobby;
+#[cfg(feature = "mcts")]
+pub mod mcts;
+pub mod metrics;
+pub mod objectives;
+pub mod observation;
+#[cfg(all(feature = "onnx", not(target_arch = "wasm32")))]
+pub mod onnx;
+pub mod opening;
+pub mod pieces;
+pub mod placements;
+pub mod render;
+pub mod replay;
+pub mod review;
+pub mod reward;
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod rpc;
+pub mod scoring;
+pub mod session;
+pub mod simulation;
+pub mod sprint;
+pub mod stats;
+pub mod tutorial;
+pub mod versus;
+pub mod zone;
+pub use board::{Board, BoardError, GARBAGE_KIND};
+pub use events::GameEvent;
+pub use input::{Action, InputSource};
+pub use scoring::{ScoreBreakdown, ScoreSource};
 
 // It's good practice to make only necessary items public.
 // For the library's external Rust API (if used by main.rs), `pub` is needed.
 // For FFI, `#[no_mangle] pub extern "C"` makes functions accessible.
 
-const ANIMATION_DURATION: u32 = 3; // Appears unused now, but was part of original logic
-
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position(pub i32, pub i32);
 
@@ -35,156 +92,141 @@ impl Sub<Position> for Position {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Every tetromino has exactly four cells, so they are kept in a fixed array.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct TetrominoData {
-    pub position: Position,      // absolute position in the grid
-    pub data: HashSet<Position>, // relative positions of the blocks
+    pub position: Position,   // absolute position in the grid
+    pub cells: [Position; 4], // relative positions of the blocks
+    pub occupied: u8,         // bit `i` is set while `cells[i]` is still part of the piece
+}
+
+impl TetrominoData {
+    pub const ALL_CELLS: u8 = 0b1111;
+
+    pub fn new(position: Position, cells: [Position; 4]) -> Self {
+        Self {
+            position,
+            cells,
+            occupied: Self::ALL_CELLS,
+        }
+    }
+
+    /// Relative positions of the cells that are still occupied.
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.occupied & (1 << i) != 0)
+            .map(|(_, p)| *p)
+    }
+
+    pub fn len(&self) -> usize {
+        self.occupied.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occupied == 0
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Tetromino {
     pub kind: &'static str,
     pub data: TetrominoData,
-    rotation: usize,
-}
-
-macro_rules! place_it {
-    ($($pos:expr),*) => {
-        [$(Position($pos.0, $pos.1)),*].into()
-    };
-}
-
-lazy_static! {
-    static ref S_OPTS: [[HashSet<Position>; 4]; 7] = [
-        [
-            place_it!((1, 0), (1, 1), (1, 2), (1, 3)),
-            place_it!((0, 1), (1, 1), (2, 1), (3, 1)),
-            place_it!((2, 0), (2, 1), (2, 2), (2, 3)),
-            place_it!((0, 2), (1, 2), (2, 2), (3, 2)),
-        ],
-        [
-            place_it!((1, 0), (0, 1), (1, 1), (2, 1)),
-            place_it!((1, 0), (1, 1), (2, 1), (1, 2)),
-            place_it!((0, 1), (1, 1), (2, 1), (1, 2)),
-            place_it!((1, 0), (0, 1), (1, 1), (1, 2)),
-        ],
-        [
-            place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
-            place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
-            place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
-            place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
-        ],
-        [
-            place_it!((1, 0), (1, 1), (1, 2), (0, 2)),
-            place_it!((0, 0), (0, 1), (1, 1), (2, 1)),
-            place_it!((1, 0), (2, 0), (1, 1), (1, 2)),
-            place_it!((0, 1), (1, 1), (2, 1), (2, 2)),
-        ],
-        [
-            place_it!((1, 0), (1, 1), (1, 2), (2, 2)),
-            place_it!((0, 1), (1, 1), (2, 1), (0, 2)),
-            place_it!((0, 0), (1, 0), (1, 1), (1, 2)),
-            place_it!((0, 1), (1, 1), (2, 1), (2, 0)),
-        ],
-        [
-            place_it!((1, 0), (2, 0), (1, 1), (0, 1)),
-            place_it!((1, 0), (1, 1), (2, 1), (2, 2)),
-            place_it!((1, 0), (2, 0), (1, 1), (0, 1)),
-            place_it!((1, 0), (1, 1), (2, 1), (2, 2)),
-        ],
-        [
-            place_it!((0, 0), (1, 0), (1, 1), (2, 1)),
-            place_it!((1, 0), (1, 1), (0, 1), (0, 2)),
-            place_it!((0, 0), (1, 0), (1, 1), (2, 1)),
-            place_it!((1, 0), (1, 1), (0, 1), (0, 2)),
-        ],
-    ];
+    pub rotation: usize,
 }
 
 impl Tetromino {
-    pub fn new_random(pos: Position) -> Self {
-        let mut rng = rand::thread_rng(); // Changed from rand::rng() for broader context compatibility
-        let index = rng.gen_range(0..7);
-
+    pub fn new(kind: &'static str, pos: Position) -> Self {
         let rotation = 0;
-        let kind;
-        let mut data = TetrominoData::default();
-        match index {
-            0 => kind = "I",
-            1 => kind = "T",
-            2 => kind = "O",
-            3 => kind = "J",
-            4 => kind = "L",
-            5 => kind = "S",
-            6 => kind = "Z",
-            _ => unreachable!(),
-        }
-        data.position = pos;
-        data.data = Tetromino::get_rotation_data(kind, rotation);
         Tetromino {
             kind,
-            data,
+            data: TetrominoData::new(pos, Tetromino::get_rotation_data(kind, rotation)),
             rotation,
         }
     }
 
-    pub fn get_rotation_data(kind: &str, rotation: usize) -> HashSet<Position> {
-        match kind {
-            "I" => S_OPTS[0][rotation].clone(),
-            "T" => S_OPTS[1][rotation].clone(),
-            "O" => S_OPTS[2][rotation].clone(),
-            "J" => S_OPTS[3][rotation].clone(),
-            "L" => S_OPTS[4][rotation].clone(),
-            "S" => S_OPTS[5][rotation].clone(),
-            "Z" => S_OPTS[6][rotation].clone(),
-            _ => unreachable!(),
-        }
+    pub fn new_random(pos: Position) -> Self {
+        Tetromino::new(Tetromino::random_kind(), pos)
     }
 
-    pub fn remove_at(&mut self, pos: Position) {
-        let pos = pos - self.data.position;
-        if !self.data.data.remove(&pos) {
-            // console_log is a Leptos feature, for core logic, prefer standard logging or remove
-            // For now, keeping as it was, but this is a dependency leakage.
-            #[cfg(target_arch = "wasm32")]
-            console_log(&format!("remove_at: position not found: {:?}", pos));
-            #[cfg(not(target_arch = "wasm32"))]
-            println!("remove_at: position not found: {:?}", pos);
-        }
+    pub fn random_kind() -> &'static str {
+        pieces::KINDS[rand::rng().random_range(0..pieces::KINDS.len())]
     }
 
-    pub fn fall_down(&mut self, y: i32) {
-        let y = y - self.data.position.1;
-        let mut new_data = HashSet::new();
-        for pos in self.data.data.iter() {
-            if pos.1 < y {
-                new_data.insert(Position(pos.0, pos.1 + 1));
-            } else {
-                new_data.insert(*pos);
-            }
+    /// A piece of `kind` placed at the top of a board `width` cells wide,
+    /// see `pieces::spawn_position`.
+    pub fn spawn(kind: &'static str, width: u32) -> Self {
+        Tetromino::spawn_rotated(kind, 0, width)
+    }
+
+    /// `spawn` in `rotation` instead of the usual orientation.
+    pub fn spawn_rotated(kind: &'static str, rotation: usize, width: u32) -> Self {
+        let rotation = rotation % 4;
+        Tetromino {
+            kind,
+            data: TetrominoData::new(
+                pieces::spawn_position_rotated(kind, rotation, width),
+                Tetromino::get_rotation_data(kind, rotation),
+            ),
+            rotation,
         }
-        self.data.data = new_data;
+    }
+
+    /// The piece flipped left to right on a board `width` cells wide: the
+    /// mirror kind (see `pieces::mirror_kind`) in whichever rotation covers
+    /// the flipped cells, preferring the mirror of the current one. Cells
+    /// already cleared away stay cleared.
+    pub fn mirrored(&self, width: u32) -> Tetromino {
+        let kind = pieces::mirror_kind(self.kind);
+        let flip = |p: Position| Position(width as i32 - 1 - p.0, p.1);
+        let target = self.data.cells.map(|p| flip(self.data.position + p));
+        let min = |cells: &[Position]| Position(cells.iter().map(|p| p.0).min().unwrap(), cells.iter().map(|p| p.1).min().unwrap());
+        std::iter::once((4 - self.rotation) % 4)
+            .chain(0..4)
+            .find_map(|rotation| {
+                let cells = Tetromino::get_rotation_data(kind, rotation);
+                let position = min(&target) - min(&cells);
+                let index = |p: &Position| cells.iter().position(|&c| position + c == *p);
+                let indices: Vec<usize> = target.iter().map_while(index).collect();
+                (indices.len() == 4).then(|| {
+                    let mut data = TetrominoData::new(position, cells);
+                    data.occupied = (0..4).filter(|&i| self.data.occupied & (1 << i) != 0).fold(0, |bits, i| bits | 1 << indices[i]);
+                    Tetromino { kind, data, rotation }
+                })
+            })
+            .unwrap_or_else(|| unreachable!("no rotation of {kind} mirrors {:?}", self.kind))
+    }
+
+    /// Inclusive `(min, max)` corners of the cells of `kind` in `rotation`,
+    /// relative to the piece position.
+    pub fn bounding_box(kind: &str, rotation: usize) -> (Position, Position) {
+        pieces::bounding_box(kind, rotation)
+    }
+
+    pub fn get_rotation_data(kind: &str, rotation: usize) -> [Position; 4] {
+        pieces::cells(kind, rotation)
+    }
+
+    /// Absolute positions of the occupied cells, without allocating.
+    pub fn cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.data.iter().map(|p| self.data.position + p)
     }
 
     pub fn collect_positions(&self) -> Vec<Position> {
-        self.data
-            .data
-            .iter()
-            .map(|p| self.data.position + *p)
-            .collect()
+        self.cells().collect()
     }
 
     pub fn is_colliding(&self, other: &Tetromino) -> bool {
-        let other_positions = other.collect_positions();
-        self.collect_positions()
-            .iter()
-            .any(|p| other_positions.contains(p))
+        self.cells().any(|p| other.cells().any(|o| o == p))
     }
 
     pub fn rotated(&self) -> Self {
-        let mut data = self.data.clone();
         let rotation = (self.rotation + 1) % 4;
-        data.data = Tetromino::get_rotation_data(self.kind, rotation);
+        let data = TetrominoData::new(
+            self.data.position,
+            Tetromino::get_rotation_data(self.kind, rotation),
+        );
         Self {
             kind: self.kind,
             data,
@@ -193,6 +235,28 @@ impl Tetromino {
     }
 }
 
+/// Gravity interval of the web UI. Each FFI tick advances the game's manual
+/// clock by this much.
+pub const TICK_MS: f64 = 1000.0;
+
+/// Default time cleared rows stay on screen, matching the CSS fade.
+pub const DEFAULT_CLEAR_ANIMATION_MS: f64 = 500.0;
+
+/// The stack is in the danger zone when it reaches within this many rows of
+/// the top of the board.
+pub const DANGER_ROWS: u32 = 5;
+
+/// Coarse state of a game, mainly for embedders that only see the FFI.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePhase {
+    Playing = 0,
+    /// Full rows are being animated out; input is ignored.
+    Clearing = 1,
+    Paused = 2,
+    GameOver = 3,
+}
+
 #[derive(Debug, Store)]
 pub struct Tetris {
     pub width: u32,
@@ -200,50 +264,427 @@ pub struct Tetris {
 
     pub current_tetromino: Option<Tetromino>, // Made pub for tests/main.rs direct access
     pub ghost_tetromino: Option<Tetromino>,   // Made pub for tests/main.rs direct access
-    pub fixed_blocks: Vec<Tetromino>,        // Made pub for tests/main.rs direct access
+    pub board: Board,                         // Made pub for tests/main.rs direct access
     speed: i32,
 
     pub score: i32, // Made pub for tests/main.rs direct access
+    pub score_breakdown: ScoreBreakdown,
+    lines_cleared: u32,
+    /// Cleared rows that held garbage.
+    garbage_lines_cleared: u32,
+    pieces_placed: u32,
+    timer: GameTimer,
     pub lost: bool,  // Made pub for tests/main.rs direct access
     paused: bool,
     pub lines_being_cleared: Option<Vec<usize>>, // Made pub for tests/main.rs direct access
     pub animation_start_time: Option<f64>, // Made pub for tests/main.rs direct access
+    events: EventQueue,
+    input_buffer: InputBuffer,
+    scripted: VecDeque<&'static str>,
+    /// Kind set aside with `hold`.
+    held: Option<&'static str>,
+    /// Whether the falling piece came out of or went into the hold slot;
+    /// cleared when a piece locks.
+    hold_used: bool,
+    /// Whether the falling piece's last successful move was a rotation, the
+    /// precondition for a T-spin.
+    last_move_rotated: bool,
+    /// How long cleared rows stay visible before collapsing; 0 collapses
+    /// them immediately.
+    clear_animation_ms: f64,
+    clock: Clock,
+    /// Maps `clock` to the game time animations and input timing run on.
+    time_scale: TimeScale,
+    /// Whether close calls slow game time down; see `set_slow_motion`.
+    slow_motion: bool,
+    piece_motion: Option<PieceMotion>,
+    /// Rows to clear to finish the game; see `set_line_goal`.
+    line_goal: Option<u32>,
+    /// Shapes the reward FFI steps report; see `tetris_create_with_reward`.
+    reward: Option<RewardShaper>,
+    curriculum: Curriculum,
+    /// Actions applied this episode, for `Curriculum::max_steps`.
+    steps: u32,
+    /// Rotation each kind spawns in; see `set_spawn_orientations`.
+    spawn_orientations: SpawnOrientations,
+    /// When the falling piece spawned and the actions it got since, for
+    /// `GameEvent::Placed`.
+    piece_spawned_ms: f64,
+    piece_inputs: u32,
+    /// Where random pieces come from; see `piece_rng`.
+    piece_rng: PieceRng,
+    /// Deals from `piece_rng` once `scripted` runs out; see `set_randomizer`.
+    bag: PieceBag,
+    /// Kinds dealt this game; see `dealt_digest`.
+    dealt: KindDigest,
+    /// `None` unless enabled with `set_zone`.
+    zone: Option<Zone>,
+    soft_drop_speed: SoftDropSpeed,
+    /// Whether the soft drop key is down; see `set_soft_drop_held`.
+    soft_drop_held: bool,
+    /// What `board_changes_since` last reported from.
+    board_delta: DeltaTracker,
 }
 
 impl Tetris {
+    /// Creates a game, panicking on dimensions `try_new` would reject.
     pub fn new(width: u32, height: u32) -> Self {
-        Self {
+        Self::try_new(width, height).unwrap_or_else(|e| panic!("invalid board: {}", e))
+    }
+
+    pub fn try_new(width: u32, height: u32) -> Result<Self, BoardError> {
+        BoardError::validate(width, height)?;
+        let mut piece_rng = PieceRng::from_entropy();
+        let mut bag = PieceBag::default();
+        let first = bag.deal(&Curriculum::default(), &mut piece_rng);
+        let mut tetris = Self {
             width,
             height,
-            fixed_blocks: vec![],
+            board: Board::new(width, height),
             speed: 1,
-            current_tetromino: Some(Tetromino::new_random(Position((width - 4) as i32 / 2, 0))),
+            current_tetromino: Some(Tetromino::spawn(first, width)),
             ghost_tetromino: None,
             score: 0,
+            score_breakdown: ScoreBreakdown::default(),
+            lines_cleared: 0,
+            garbage_lines_cleared: 0,
+            pieces_placed: 0,
+            timer: GameTimer::default(),
             lost: false,
             paused: false,
             lines_being_cleared: None,
             animation_start_time: None,
+            events: EventQueue::default(),
+            input_buffer: InputBuffer::default(),
+            scripted: VecDeque::new(),
+            held: None,
+            hold_used: false,
+            last_move_rotated: false,
+            clear_animation_ms: DEFAULT_CLEAR_ANIMATION_MS,
+            clock: Clock::default(),
+            time_scale: TimeScale::default(),
+            slow_motion: false,
+            piece_motion: None,
+            line_goal: None,
+            reward: None,
+            curriculum: Curriculum::default(),
+            steps: 0,
+            spawn_orientations: SpawnOrientations::default(),
+            piece_spawned_ms: 0.0,
+            piece_inputs: 0,
+            piece_rng,
+            bag,
+            dealt: KindDigest::default(),
+            zone: None,
+            soft_drop_speed: SoftDropSpeed::default(),
+            soft_drop_held: false,
+            board_delta: DeltaTracker::default(),
+        };
+        tetris.timer.start(tetris.clock.now());
+        tetris.note_spawn();
+        tetris.note_motion(None);
+        tetris.update_ghost();
+        Ok(tetris)
+    }
+
+    /// Starts a fresh game on a board of the same size.
+    pub fn reset(&mut self) {
+        let window_ms = self.input_buffer.window_ms;
+        let clear_animation_ms = self.clear_animation_ms;
+        let clock = self.clock;
+        let slow_motion = self.slow_motion;
+        let time_scale = self.time_scale();
+        let reward = self.reward;
+        let curriculum = self.curriculum;
+        let spawn_orientations = self.spawn_orientations;
+        let zone = self.zone.is_some();
+        let piece_rng = self.piece_rng;
+        let randomizer = self.bag.randomizer();
+        let soft_drop_speed = self.soft_drop_speed;
+        // Versions keep counting, so pollers' last versions stay valid.
+        let board_delta = std::mem::take(&mut self.board_delta);
+        *self = Tetris::new(self.width, self.height);
+        self.board_delta = board_delta;
+        self.piece_rng = piece_rng;
+        self.bag = PieceBag::new(randomizer);
+        self.soft_drop_speed = soft_drop_speed;
+        self.spawn_orientations = spawn_orientations;
+        self.set_zone(zone);
+        self.input_buffer.window_ms = window_ms;
+        self.clear_animation_ms = clear_animation_ms;
+        self.slow_motion = slow_motion;
+        self.set_clock(clock);
+        self.set_time_scale(time_scale);
+        self.curriculum = curriculum;
+        self.start_episode();
+        self.reward = reward.map(|mut r| {
+            r.restart(self);
+            r
+        });
+    }
+
+    /// Sets up the board for `curriculum`: garbage at the bottom and an
+    /// allowed first piece, both drawn from the generator carried over from
+    /// the last game, so a seeded game starts on the same board every time.
+    fn start_episode(&mut self) {
+        let rows = self.curriculum.garbage_rows.min(self.height - board::MIN_HEIGHT);
+        for _ in 0..rows {
+            let hole = self.piece_rng.random_range(0..self.width);
+            self.board.push_garbage(1, hole);
         }
+        let first = self.deal();
+        self.spawn_piece(first);
     }
 
-    pub fn render_view(&self) -> Vec<Vec<&'static str>> {
-        let mut output = vec![vec!["B"; self.width as usize]; self.height as usize];
+    /// The generator random pieces are drawn from. Saving it along with the
+    /// board and `upcoming_pieces` is enough to deal the same pieces again
+    /// after a restore.
+    pub fn piece_rng(&self) -> PieceRng {
+        self.piece_rng
+    }
 
-        for block in &self.fixed_blocks {
-            for pos in &block.collect_positions() {
-                if pos.1 >= 0 && pos.1 < self.height as i32 && pos.0 >= 0 && pos.0 < self.width as i32 {
-                    output[pos.1 as usize][pos.0 as usize] = block.kind;
-                }
+    /// Replaces the generator, e.g. to seed games or roll back to a saved
+    /// state; pieces queued with `set_upcoming_pieces` still come first,
+    /// and the next random one starts a new bag. `reset` carries it over, so
+    /// the next game continues its sequence.
+    pub fn set_piece_rng(&mut self, rng: PieceRng) {
+        self.piece_rng = rng;
+        self.bag.clear();
+    }
+
+    /// The game as it stands, for autosaves; see `session`. `None` once it
+    /// is lost, and while rows are clearing, since the clear would be lost
+    /// with the animation.
+    pub fn snapshot(&self) -> Option<session::Snapshot> {
+        if self.lost || self.lines_being_cleared.is_some() {
+            return None;
+        }
+        Some(session::Snapshot {
+            width: self.width,
+            height: self.height,
+            rows: self
+                .board
+                .rows()
+                .map(|row| row.iter().map(|cell| cell.map_or('.', session::kind_char)).collect())
+                .collect(),
+            piece: self.current_tetromino.as_ref().map(|t| session::PieceState {
+                kind: session::kind_char(t.kind),
+                rotation: t.rotation,
+                x: t.data.position.0,
+                y: t.data.position.1,
+            }),
+            upcoming: self.upcoming_pieces().map(session::kind_char).collect(),
+            held: self.held.map(session::kind_char),
+            hold_used: self.hold_used,
+            rng: self.piece_rng,
+            score: self.score,
+            breakdown: self.score_breakdown,
+            lines_cleared: self.lines_cleared,
+            garbage_lines_cleared: self.garbage_lines_cleared,
+            pieces_placed: self.pieces_placed,
+            elapsed_ms: self.elapsed_ms(),
+        })
+    }
+
+    /// Picks up the game `snapshot` was taken of, paused, keeping this
+    /// game's settings (clock, speeds, zone, ...). Nothing changes if the
+    /// snapshot doesn't fit.
+    pub fn restore(&mut self, snapshot: &session::Snapshot) -> Result<(), session::SnapshotError> {
+        use session::{parse_kind, SnapshotError};
+        if snapshot.width != self.width || snapshot.height != self.height || snapshot.rows.len() != self.height as usize {
+            return Err(SnapshotError::Size);
+        }
+        let mut board = Board::new(self.width, self.height);
+        for (y, row) in snapshot.rows.iter().enumerate() {
+            if row.chars().count() != self.width as usize {
+                return Err(SnapshotError::Size);
             }
+            for (x, c) in row.chars().enumerate() {
+                board.set(Position(x as i32, y as i32), parse_kind(c)?);
+            }
+        }
+        let piece = match &snapshot.piece {
+            Some(p) => {
+                let kind = parse_kind(p.kind)?.ok_or(SnapshotError::UnknownKind(p.kind))?;
+                let mut piece = Tetromino::spawn_rotated(kind, p.rotation, self.width);
+                piece.data.position = Position(p.x, p.y);
+                Some(piece)
+            }
+            None => None,
+        };
+        let upcoming = snapshot
+            .upcoming
+            .chars()
+            .map(|c| parse_kind(c)?.ok_or(SnapshotError::UnknownKind(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let held = match snapshot.held {
+            Some(c) => Some(parse_kind(c)?.ok_or(SnapshotError::UnknownKind(c))?),
+            None => None,
+        };
+        self.reset();
+        self.board = board;
+        self.current_tetromino = piece;
+        self.scripted = upcoming.into_iter().collect();
+        self.bag.clear();
+        self.held = held;
+        self.hold_used = snapshot.hold_used;
+        self.piece_rng = snapshot.rng;
+        self.score = snapshot.score;
+        self.score_breakdown = snapshot.breakdown;
+        self.lines_cleared = snapshot.lines_cleared;
+        self.garbage_lines_cleared = snapshot.garbage_lines_cleared;
+        self.pieces_placed = snapshot.pieces_placed;
+        self.timer = GameTimer::resumed(snapshot.elapsed_ms, self.clock.now());
+        self.note_spawn();
+        self.note_motion(None);
+        self.update_ghost();
+        self.pause();
+        self.events.drain();
+        Ok(())
+    }
+
+    /// Restarts the game under `curriculum`, which also applies to every
+    /// later reset.
+    pub fn set_curriculum(&mut self, curriculum: Curriculum) {
+        self.curriculum = curriculum;
+        self.reset();
+    }
+
+    pub fn curriculum(&self) -> Curriculum {
+        self.curriculum
+    }
+
+    /// Actions applied since the last reset.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Whether the episode ran into `Curriculum::max_steps`.
+    pub fn is_truncated(&self) -> bool {
+        self.curriculum.is_truncated(self.steps)
+    }
+
+    /// Starts shaping rewards with `weights` from the game as it is now, or
+    /// stops with `None`.
+    pub fn set_reward_weights(&mut self, weights: Option<RewardWeights>) {
+        self.reward = weights.map(|w| RewardShaper::new(w, self));
+    }
+
+    /// Reward since the previous call, by the weights set with
+    /// `set_reward_weights` (0.0 without).
+    pub fn shape_reward(&mut self) -> f32 {
+        let Some(mut shaper) = self.reward.take() else { return 0.0 };
+        let reward = shaper.step(self);
+        self.reward = Some(shaper);
+        reward
+    }
+
+    /// True once the player has locked a piece in a game that is not over yet;
+    /// resetting such a game throws progress away.
+    pub fn is_in_progress(&self) -> bool {
+        !self.lost && self.pieces_placed > 0
+    }
+
+    fn award(&mut self, source: ScoreSource, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        let points = self.score_breakdown.award(source, amount);
+        self.score += points;
+        self.events.push(GameEvent::Score {
+            source,
+            points,
+            breakdown: self.score_breakdown,
+        });
+    }
+
+    fn game_over(&mut self) {
+        self.end(GameEvent::GameOver { score: self.score });
+    }
+
+    /// Stops the game for good, announcing why with `event`. `lost` is set
+    /// either way, since nothing can be played afterwards.
+    fn end(&mut self, event: GameEvent) {
+        if self.lost {
+            return;
+        }
+        self.lost = true;
+        self.timer.stop(self.clock.now());
+        self.events.push(event);
+    }
+
+    /// Ends the game once `goal` rows have been cleared, as in Sprint, with
+    /// `GameEvent::GoalReached` instead of `GameOver`. `reset` clears it.
+    pub fn set_line_goal(&mut self, goal: Option<u32>) {
+        self.line_goal = goal;
+    }
+
+    /// Returns and clears the events produced since the last call.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain()
+    }
+
+    /// Height of each settled column, left to right, as in
+    /// `Board::column_height`. Like the other board queries below it ignores
+    /// the falling piece, and counts past 255 read as 255.
+    pub fn column_heights(&self) -> Vec<u8> {
+        (0..self.width).map(|x| u8::try_from(self.board.column_height(x)).unwrap_or(u8::MAX)).collect()
+    }
+
+    /// Filled cells in each row, top row first.
+    pub fn row_fill_counts(&self) -> Vec<u8> {
+        (0..self.height as usize).map(|y| u8::try_from(self.board.row_fill_count(y)).unwrap_or(u8::MAX)).collect()
+    }
+
+    /// Holes (see `Board::holes`) in each column, left to right.
+    pub fn holes_per_column(&self) -> Vec<u8> {
+        (0..self.width).map(|x| u8::try_from(self.board.column_holes(x)).unwrap_or(u8::MAX)).collect()
+    }
+
+    /// Whether the stack fills `pos`; false off the board.
+    pub fn is_cell_occupied(&self, pos: Position) -> bool {
+        self.board.is_occupied(pos)
+    }
+
+    /// Cells that would become holes if the falling piece were hard dropped
+    /// now (see `metrics::new_holes`); empty without a piece.
+    pub fn hole_preview(&self) -> Vec<Position> {
+        match (&self.ghost_tetromino, self.lines_being_cleared.is_some()) {
+            (Some(ghost), false) => metrics::new_holes(&self.board, ghost),
+            _ => Vec::new(),
         }
+    }
+
+    /// Syncs the board delta with what the board shows now and returns its
+    /// version; see `board_delta`.
+    pub fn board_version(&mut self) -> u32 {
+        let view = self.render_view();
+        self.board_delta.sync(&view)
+    }
+
+    /// The cells whose shown kind changed after board version `since`, as of
+    /// now (see `board_version`). Since 0, every cell.
+    pub fn board_changes_since(&mut self, since: u32) -> Vec<CellChange> {
+        self.board_version();
+        self.board_delta.changes_since(since).collect()
+    }
+
+    pub fn render_view(&self) -> Vec<Vec<&'static str>> {
+        let mut output: Vec<Vec<&'static str>> = self
+            .board
+            .rows()
+            .map(|row| row.iter().map(|c| c.unwrap_or("B")).collect())
+            .collect();
 
         if let Some(tetromino) = &self.ghost_tetromino {
             for pos in tetromino.collect_positions() {
-                if pos.1 >= 0 && pos.1 < self.height as i32 && pos.0 >= 0 && pos.0 < self.width as i32 {
-                    if output[pos.1 as usize][pos.0 as usize] == "B" {
-                         output[pos.1 as usize][pos.0 as usize] = "G";
-                    }
+                if pos.1 >= 0
+                    && pos.1 < self.height as i32
+                    && pos.0 >= 0
+                    && pos.0 < self.width as i32
+                    && output[pos.1 as usize][pos.0 as usize] == "B"
+                {
+                    output[pos.1 as usize][pos.0 as usize] = "G";
                 }
             }
         }
@@ -277,161 +718,308 @@ impl Tetris {
         if self.paused {
             return;
         }
+        self.update_zone();
 
-        if let (Some(lines_to_clear_vec), Some(start_time)) = (self.lines_being_cleared.clone(), self.animation_start_time) {
-            let mut animation_over = false;
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                match window() {
-                    Some(win) => match win.performance() {
-                        Some(perf) => {
-                            let current_time = perf.now();
-                            if current_time - start_time >= 500.0 {
-                                animation_over = true;
-                            }
-                        }
-                        None => {
-                            console_log("Performance API not available; cannot determine animation end.");
-                        }
-                    },
-                    None => {
-                        console_log("Window object not available; cannot determine animation end.");
-                    }
-                }
-            }
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                 animation_over = true;
+        if let Some(lines_to_clear_vec) = self.lines_being_cleared.clone() {
+            if self.clear_animation_progress() >= Some(1.0) {
+                self.finish_clear(&lines_to_clear_vec);
             }
+        } else if self.soft_drop_held {
+            self.press_soft_drop();
+        } else if !self.zone.is_some_and(|z| z.is_active()) {
+            self.move_down();
+        }
+    }
 
-            if animation_over {
-                let mut mutable_lines_to_clear = lines_to_clear_vec.clone();
-                mutable_lines_to_clear.sort_by_key(|&k| Reverse(k));
-
-                for line_index_usize in &mutable_lines_to_clear {
-                    let line_index_i32 = *line_index_usize as i32;
-                    for block in &mut self.fixed_blocks {
-                        let positions_to_remove: Vec<Position> = block
-                            .collect_positions()
-                            .into_iter()
-                            .filter(|p| p.1 == line_index_i32)
-                            .collect();
-                        for pos in positions_to_remove {
-                            block.remove_at(pos);
-                        }
-                    }
-                    for block in &mut self.fixed_blocks {
-                        block.fall_down(line_index_i32);
-                    }
-                }
+    pub fn set_soft_drop_speed(&mut self, speed: SoftDropSpeed) {
+        self.soft_drop_speed = speed;
+    }
 
-                self.fixed_blocks.retain(|block| !block.data.data.is_empty());
-                self.lines_being_cleared = None;
-                self.animation_start_time = None;
-                self.clear_lines();
-            }
-        } else {
-            self.move_down();
+    pub fn soft_drop_speed(&self) -> SoftDropSpeed {
+        self.soft_drop_speed
+    }
+
+    /// Tells the game whether the soft drop key is down. While it is, ticks
+    /// soft drop instead of falling (even in the zone) and
+    /// `gravity_interval_ms` shrinks by the speed's factor. Frontends
+    /// without key releases, such as touch buttons, can leave it alone and
+    /// send `Action::SoftDrop` per press.
+    pub fn set_soft_drop_held(&mut self, held: bool) {
+        self.soft_drop_held = held;
+    }
+
+    pub fn is_soft_drop_held(&self) -> bool {
+        self.soft_drop_held
+    }
+
+    /// `Action::SoftDrop`, and a tick while it is held: one row, or all the
+    /// way at `SoftDropSpeed::Instant`. Locks a piece already resting.
+    fn press_soft_drop(&mut self) {
+        if self.soft_drop_speed != SoftDropSpeed::Instant || !self.soft_drop_to_stack() {
+            self.soft_drop();
+        }
+    }
+
+    /// Soft drops onto the stack without locking, a point per row. False if
+    /// the piece was already resting on it.
+    fn soft_drop_to_stack(&mut self) -> bool {
+        let Some(current) = &self.current_tetromino else { return false };
+        let dropped = self.board.drop_distance(current);
+        if dropped <= 0 {
+            return false;
+        }
+        self.award(ScoreSource::SoftDrop, dropped as u32);
+        self.translate(Position(0, dropped))
+    }
+
+    /// Turns the zone meter on or off, starting it empty; `reset` keeps the
+    /// choice.
+    pub fn set_zone(&mut self, enabled: bool) {
+        self.zone = enabled.then(Zone::default);
+    }
+
+    pub fn zone(&self) -> Option<&Zone> {
+        self.zone.as_ref()
+    }
+
+    /// Starts the zone if it is enabled and the meter allows. Gravity stops
+    /// until it ends.
+    pub fn activate_zone(&mut self) -> bool {
+        if self.lost {
+            return false;
+        }
+        let now = self.elapsed_ms();
+        let Some(duration_ms) = self.zone.as_mut().and_then(|z| z.activate(now)) else {
+            return false;
+        };
+        self.events.push(GameEvent::ZoneStarted { duration_ms });
+        true
+    }
+
+    /// Ends the zone once its time is up, scoring the lines it banked.
+    fn update_zone(&mut self) {
+        let now = self.elapsed_ms();
+        if let Some(lines) = self.zone.as_mut().and_then(|z| z.expire(now)) {
+            self.award(ScoreSource::Lines, lines * zone::MULTIPLIER);
+            self.events.push(GameEvent::ZoneEnded { lines });
+        }
+    }
+
+    /// Runs exactly one update, as `tick` would with the game running, even
+    /// while paused; for stepping through a paused game frame by frame.
+    pub fn step(&mut self) {
+        let paused = std::mem::replace(&mut self.paused, false);
+        self.tick();
+        self.paused = paused;
+    }
+
+    /// Collapses the cleared `lines` and lets play continue.
+    fn finish_clear(&mut self, lines: &[usize]) {
+        // Input given while the rows were clearing is held for the new piece.
+        if let Some(started_at) = self.animation_start_time {
+            self.input_buffer.renew_since(started_at, self.now_ms());
+        }
+        self.board.remove_rows(lines);
+        self.update_ghost();
+        self.lines_being_cleared = None;
+        self.animation_start_time = None;
+        self.clear_lines();
+        if self.lines_being_cleared.is_none() {
+            self.apply_buffered_input();
+        }
+    }
+
+    /// How far the line-clear animation is at the game clock's current time,
+    /// from 0.0 to 1.0, or `None` when no rows are clearing. Renderers should
+    /// draw from this instead of counting their own frames.
+    pub fn clear_animation_progress(&self) -> Option<f64> {
+        self.clear_animation_progress_at(self.now_ms())
+    }
+
+    /// `clear_animation_progress` at an arbitrary time, e.g. a renderer's
+    /// frame timestamp.
+    pub fn clear_animation_progress_at(&self, now_ms: f64) -> Option<f64> {
+        self.lines_being_cleared.as_ref()?;
+        let started_at = self.animation_start_time?;
+        Some(animation::progress(started_at, self.clear_animation_ms, now_ms))
+    }
+
+    /// Replaces the game's time source; timers keep their elapsed time.
+    pub fn set_clock(&mut self, clock: Clock) {
+        let running = self.timer.is_running();
+        self.timer.stop(self.clock.now());
+        self.clock = clock;
+        self.time_scale.restart(self.clock.now());
+        if running {
+            self.timer.start(self.clock.now());
         }
+        self.note_spawn();
+    }
+
+    /// Fast-forwards a `Clock::Manual`; no effect on the system clock.
+    pub fn advance_clock(&mut self, ms: f64) {
+        self.clock.advance(ms);
+    }
+
+    /// Current game time: the clock's reading at the game's time scale, and
+    /// slowed down during slow motion. Animations and input timing run on it;
+    /// `elapsed_ms` doesn't.
+    pub fn now_ms(&self) -> f64 {
+        self.time_scale.game_time(self.clock.now())
+    }
+
+    /// Runs the game at `scale` times normal speed, from 0.25 to 2 (see
+    /// `clock::MIN_TIME_SCALE`), for debugging or as an accessibility aid.
+    /// Animations and input timing follow it directly; whoever drives
+    /// gravity should space its ticks `gravity_interval_ms` apart.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        let now = self.clock.now();
+        self.time_scale.set_scale(scale as f64, now);
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale.scale() as f32
+    }
+
+    /// Real time between gravity ticks for a game that falls one row every
+    /// `base_ms` at normal speed, shorter while soft drop is held.
+    pub fn gravity_interval_ms(&self, base_ms: f64) -> f64 {
+        let factor = if self.soft_drop_held { self.soft_drop_speed.factor() } else { 1 };
+        self.time_scale.real_duration(base_ms) / factor as f64
+    }
+
+    /// Enables slowing game time down to `animation::SLOW_MOTION_FACTOR` for
+    /// `animation::SLOW_MOTION_MS` after a close call. The
+    /// `GameEvent::CloseCall` event is sent either way.
+    pub fn set_slow_motion(&mut self, enabled: bool) {
+        self.slow_motion = enabled;
+    }
+
+    pub fn slow_motion_enabled(&self) -> bool {
+        self.slow_motion
+    }
+
+    /// Whether game time is currently slowed down.
+    pub fn is_slow_motion(&self) -> bool {
+        self.time_scale.is_slow(self.clock.now())
+    }
+
+    /// Sets how long cleared rows are shown before they collapse. With 0
+    /// (e.g. for reduced motion) rows collapse as soon as they fill.
+    pub fn set_clear_animation_ms(&mut self, ms: f64) {
+        self.clear_animation_ms = ms.max(0.0);
+    }
+
+    pub fn clear_animation_ms(&self) -> f64 {
+        self.clear_animation_ms
     }
 
-    fn translate(&mut self, pos: Position) {
-        if self.current_tetromino.is_none() { return; } // Guard against no current tetromino
+    fn translate(&mut self, pos: Position) -> bool {
+        if self.lost || self.current_tetromino.is_none() { return false; } // Guard against no current tetromino
         let mut new_tetromino = self.current_tetromino.clone().unwrap();
         new_tetromino.data.position = new_tetromino.data.position + pos;
         if self.is_oob(&new_tetromino) || self.is_colliding(&new_tetromino) {
-            return;
+            return false;
         }
-        self.current_tetromino.replace(new_tetromino);
+        let from = self.current_tetromino.replace(new_tetromino).map(|t| t.data.position);
+        self.last_move_rotated = false;
+        self.note_motion(from);
+        self.update_ghost();
+        true
     }
 
     pub fn move_left(&mut self) {
-        if self.lost { return; }
         self.translate(Position(-1, 0));
-        self.udpate_ghost();
     }
 
     pub fn move_right(&mut self) {
-        if self.lost { return; }
         self.translate(Position(1, 0));
-        self.udpate_ghost();
     }
 
-    pub fn speed_up(&mut self) {
+    /// Drops the piece straight onto the stack and locks it, two points per
+    /// row. See `soft_drop` for one row at a time.
+    pub fn hard_drop(&mut self) {
         if self.lost || self.current_tetromino.is_none() { return; }
-        let mut new_tetromino = self.current_tetromino.clone().unwrap();
-        loop {
-            let mut next = new_tetromino.clone();
-            next.data.position = next.data.position + Position(0, 1);
-            if self.is_oob(&next) || self.is_colliding(&next) {
-                self.fixed_blocks.push(new_tetromino);
-                let next_random = Tetromino::new_random(Position((self.width - 4) as i32 / 2, 0));
-                if self.is_colliding(&next_random) {
-                    self.lost = true;
-                }
-                self.current_tetromino = Some(next_random);
-                self.udpate_ghost();
-                if self.lines_being_cleared.is_none() {
-                    self.clear_lines();
-                }
-                break;
+        let mut new_tetromino = self.current_tetromino.take().unwrap();
+        let dropped = self.board.drop_distance(&new_tetromino);
+        new_tetromino.data.position = new_tetromino.data.position + Position(0, dropped);
+        self.award(ScoreSource::HardDrop, dropped as u32);
+        self.lock_piece(new_tetromino);
+    }
+
+    #[deprecated(note = "renamed to `hard_drop`")]
+    pub fn speed_up(&mut self) {
+        self.hard_drop();
+    }
+
+    /// Moves the piece straight to its ghost position without locking it, so
+    /// it can still be shifted or rotated before gravity locks it. Awards no
+    /// points.
+    pub fn sonic_drop(&mut self) {
+        if let Some(current) = &self.current_tetromino {
+            let dropped = self.board.drop_distance(current);
+            if dropped > 0 {
+                self.translate(Position(0, dropped));
             }
-            new_tetromino = next;
         }
     }
 
-    fn clear_lines(&mut self) {
+    pub fn clear_lines(&mut self) {
         if self.lost || self.lines_being_cleared.is_some() {
             return;
         }
 
-        let mut occupied = vec![vec![false; self.width as usize]; self.height as usize];
-        for block in &self.fixed_blocks {
-            for pos in &block.collect_positions() {
-                if pos.1 >=0 && pos.1 < self.height as i32 && pos.0 >= 0 && pos.0 < self.width as i32 {
-                    occupied[pos.1 as usize][pos.0 as usize] = true;
-                }
-            }
-        }
-
-        let full_lines: Vec<usize> = occupied
-            .into_iter()
-            .enumerate()
-            .filter(|(_, row)| row.iter().all(|&c| c))
-            .map(|(i, _)| i)
-            .collect();
+        let full_lines = self.board.full_lines();
 
         if !full_lines.is_empty() {
-            #[cfg(target_arch = "wasm32")]
-            console_log(&format!("full_lines: {:?}", full_lines));
-            #[cfg(not(target_arch = "wasm32"))]
-            println!("full_lines: {:?}", full_lines);
-
-            self.score += full_lines.len() as i32;
-            self.lines_being_cleared = Some(full_lines);
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                match window() {
-                    Some(win) => match win.performance() {
-                        Some(perf) => {
-                            self.animation_start_time = Some(perf.now());
-                        }
-                        None => {
-                            console_log("Performance API not available, using fallback time for animation.");
-                            self.animation_start_time = Some(0.0);
-                        }
-                    },
-                    None => {
-                        console_log("Window object not available, using fallback time for animation.");
-                        self.animation_start_time = Some(0.0);
+            self.lines_cleared += full_lines.len() as u32;
+            self.garbage_lines_cleared += full_lines
+                .iter()
+                .filter(|&&y| self.board.row(y).contains(&Some(GARBAGE_KIND)))
+                .count() as u32;
+            self.events.push(GameEvent::LinesCleared { count: full_lines.len() as u32 });
+            let perfect = self
+                .board
+                .rows()
+                .enumerate()
+                .all(|(y, row)| full_lines.contains(&y) || row.iter().all(Option::is_none));
+            if perfect {
+                self.events.push(GameEvent::PerfectClear);
+            }
+            // Lines cleared in the zone score when it ends.
+            let in_zone = self.zone.is_some_and(|z| z.is_active());
+            if let Some(zone) = &mut self.zone {
+                zone.record(full_lines.len() as u32);
+            }
+            if !in_zone {
+                self.award(ScoreSource::Lines, full_lines.len() as u32);
+            }
+            let danger = self.height - DANGER_ROWS;
+            if self.board.max_height() > danger {
+                let mut after = self.board.clone();
+                after.remove_rows(&full_lines);
+                if after.max_height() <= danger {
+                    self.events.push(GameEvent::CloseCall);
+                    if self.slow_motion {
+                        let now = self.clock.now();
+                        self.time_scale.slow_down(animation::SLOW_MOTION_FACTOR, animation::SLOW_MOTION_MS, now);
                     }
                 }
             }
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.animation_start_time = Some(0.0);
+            self.lines_being_cleared = Some(full_lines);
+
+            self.animation_start_time = Some(self.now_ms());
+
+            // The final clear of a goal collapses at once; the game stops
+            // with it, so an animation would never finish.
+            let goal_reached = self.line_goal.is_some_and(|goal| self.lines_cleared >= goal);
+            if self.clear_animation_ms <= 0.0 || goal_reached {
+                let lines = self.lines_being_cleared.clone().unwrap_or_default();
+                self.finish_clear(&lines);
+            }
+            if goal_reached {
+                let elapsed_ms = self.elapsed_ms();
+                self.end(GameEvent::GoalReached { lines: self.lines_cleared, elapsed_ms });
             }
         }
     }
@@ -442,96 +1030,591 @@ impl Tetris {
         let mut new_tetromino = self.current_tetromino.clone().unwrap();
         new_tetromino.data.position = new_tetromino.data.position + Position(0, self.speed);
         if self.is_oob(&new_tetromino) || self.is_colliding(&new_tetromino) {
-            self.fixed_blocks
-                .push(self.current_tetromino.take().unwrap());
-            let next = Tetromino::new_random(Position((self.width - 4) as i32 / 2, 0));
-            if self.is_colliding(&next) {
-                self.lost = true;
-            }
-            self.current_tetromino = Some(next);
-            self.ghost_tetromino = None;
+            let piece = self.current_tetromino.take().unwrap();
+            self.lock_piece(piece);
         } else {
-            self.current_tetromino = Some(new_tetromino);
-            self.udpate_ghost();
+            let from = self.current_tetromino.replace(new_tetromino).map(|t| t.data.position);
+            self.last_move_rotated = false;
+            self.note_motion(from);
+            self.update_ghost();
         }
+    }
 
+    /// Settles `piece` into the board, spawns the next one and starts a line
+    /// clear if the lock completed any rows.
+    fn lock_piece(&mut self, piece: Tetromino) {
+        let t_spin = self.is_t_spin(&piece);
+        let misdrop = stats::judge(&self.board, &piece);
+        let health_before = BoardMetrics::of(&self.board).health(self.height);
+        let health_after = metrics::after_lock(&self.board, &piece).health(self.height);
+        self.board.place(&piece);
+        self.pieces_placed += 1;
+        self.hold_used = false;
+        self.events.push(GameEvent::PieceLocked { kind: piece.kind });
+        self.events.push(GameEvent::Placed(Placement {
+            piece: self.pieces_placed,
+            kind: piece.kind,
+            x: piece.data.position.0,
+            y: piece.data.position.1,
+            rotation: piece.rotation,
+            time_ms: (self.now_ms() - self.piece_spawned_ms).max(0.0),
+            inputs: self.piece_inputs,
+            lines: self.board.full_lines().len() as u32,
+            health_before,
+            health_after,
+        }));
+        if let Some(misdrop) = misdrop {
+            self.events.push(GameEvent::Misdrop(misdrop));
+        }
+        if t_spin {
+            let lines = self.board.full_lines().len() as u32;
+            self.events.push(GameEvent::TSpin { lines });
+        }
+        self.last_move_rotated = false;
+        let kind = self.deal();
+        let next = self.spawned(kind);
+        if self.is_colliding(&next) {
+            self.game_over();
+        }
+        self.current_tetromino = Some(next);
+        self.note_spawn();
+        self.note_motion(None);
+        self.update_ghost();
         if self.lines_being_cleared.is_none() {
             self.clear_lines();
         }
+        if self.lines_being_cleared.is_none() {
+            self.apply_buffered_input();
+        }
     }
 
-    pub fn udpate_ghost(&mut self) {
-        if self.current_tetromino.is_none() {
-            self.ghost_tetromino = None;
-            return;
-        }
+    /// Player-initiated one-row drop. Scores a point when the piece moves and
+    /// locks it like gravity would when it cannot. See `hard_drop` for the
+    /// whole way at once.
+    pub fn soft_drop(&mut self) {
+        if self.lost || self.current_tetromino.is_none() { return; }
         let mut next = self.current_tetromino.clone().unwrap();
-        loop {
-            next.data.position = next.data.position + Position(0, 1);
-            if self.is_oob(&next) || self.is_colliding(&next) {
-                next.data.position = next.data.position - Position(0, 1);
-                self.ghost_tetromino = Some(next);
-                break;
-            }
+        next.data.position = next.data.position + Position(0, 1);
+        if !self.is_oob(&next) && !self.is_colliding(&next) {
+            self.award(ScoreSource::SoftDrop, 1);
         }
+        self.move_down();
     }
 
-    pub fn get_score(&self) -> i32 {
-        self.score
+    #[deprecated(note = "the ghost is now kept up to date automatically; use `ghost_position()` to read it")]
+    pub fn udpate_ghost(&mut self) {
+        self.update_ghost();
     }
 
-    pub fn rotate(&mut self) {
-        if self.lost || self.current_tetromino.is_none() { return; }
-        let current = self.current_tetromino.as_ref().unwrap();
+    /// Position the current piece would land at, or `None` without a piece.
+    pub fn ghost_position(&self) -> Option<Position> {
+        self.ghost_tetromino.as_ref().map(|g| g.data.position)
+    }
 
-        let new_tetromino = current.rotated();
-        if self.is_oob(&new_tetromino) || self.is_colliding(&new_tetromino) {
-            return;
+    /// Makes the next pieces `kinds`, in order, before random ones resume
+    /// with a new bag. Replaces anything queued earlier.
+    pub fn set_upcoming_pieces(&mut self, kinds: &[&'static str]) {
+        self.scripted = kinds.iter().copied().collect();
+        self.bag.clear();
+    }
+
+    /// Kinds queued with `set_upcoming_pieces` and not dealt yet, then the
+    /// rest of the current bag. Classic random pieces are only drawn when
+    /// needed, so they never show up here.
+    pub fn upcoming_pieces(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.scripted.iter().copied().chain(self.bag.queued())
+    }
+
+    /// Swaps the falling piece with the held one, or sets it aside and deals
+    /// the next if nothing is held yet. The piece coming out spawns afresh
+    /// at the top. Once per piece: after a hold, nothing happens (and this
+    /// returns false) until the piece in play locks.
+    pub fn hold(&mut self) -> bool {
+        if self.hold_used || self.lost || self.paused {
+            return false;
         }
-        self.current_tetromino.replace(new_tetromino);
-        self.udpate_ghost();
+        let Some(current) = self.current_tetromino.take() else { return false };
+        let next = match self.held.replace(current.kind) {
+            Some(kind) => kind,
+            None => self.deal(),
+        };
+        self.hold_used = true;
+        self.spawn_piece(next);
+        true
     }
 
-    pub fn is_oob(&self, t: &Tetromino) -> bool {
-        t.collect_positions()
-            .iter()
-            .any(|&p| p.0 < 0 || p.0 >= self.width as i32 || p.1 < 0 || p.1 >= self.height as i32)
+    /// Kind in the hold slot, if any.
+    pub fn held_piece(&self) -> Option<&'static str> {
+        self.held
     }
 
-    pub fn is_colliding(&self, t: &Tetromino) -> bool {
-        self.fixed_blocks.iter().any(|b| b.is_colliding(t))
+    /// Whether `hold` would do anything now.
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used && !self.lost && self.current_tetromino.is_some()
     }
 
-    pub fn pause(&mut self) {
-        self.paused = true;
+    /// The next piece: a queued one, or one from the bag.
+    fn deal(&mut self) -> &'static str {
+        let kind = self.scripted.pop_front().unwrap_or_else(|| self.bag.deal(&self.curriculum, &mut self.piece_rng));
+        self.dealt.push(kind);
+        kind
     }
 
-    pub fn resume(&mut self) {
-        self.paused = false;
+    /// Replaces the falling piece with the next one dealt.
+    pub fn spawn_next(&mut self) {
+        let kind = self.deal();
+        self.spawn_piece(kind);
     }
 
-    pub fn is_paused(&self) -> bool {
-        self.paused
+    /// The kinds dealt into play this game, queued or random, in order; a
+    /// match checks it against the player's stream (see
+    /// `generator::MatchGenerators::check`).
+    pub fn dealt_digest(&self) -> KindDigest {
+        self.dealt
     }
-}
 
+    /// Deals random pieces in shuffled bags of seven (the default) or each
+    /// on its own for classic play, from the next random piece on; kept
+    /// across resets.
+    pub fn set_randomizer(&mut self, randomizer: Randomizer) {
+        self.bag = PieceBag::new(randomizer);
+    }
 
-// FFI C-compatible API
+    pub fn randomizer(&self) -> Randomizer {
+        self.bag.randomizer()
+    }
 
-#[repr(C)]
-pub struct GameState {
-    score: i32,
-    lost: bool,
-    width: u32,
-    height: u32,
+    /// A fresh piece of `kind` at the top of the board, in the orientation
+    /// set for it.
+    pub fn spawned(&self, kind: &'static str) -> Tetromino {
+        Tetromino::spawn_rotated(kind, self.spawn_orientations.of(kind), self.width)
+    }
+
+    /// Makes every piece spawn in the rotation `orientations` gives its kind,
+    /// from the next spawn on; kept across resets.
+    pub fn set_spawn_orientations(&mut self, orientations: SpawnOrientations) {
+        self.spawn_orientations = orientations;
+    }
+
+    pub fn spawn_orientations(&self) -> SpawnOrientations {
+        self.spawn_orientations
+    }
+
+    /// Replaces the falling piece with a freshly spawned `kind`; ends the game
+    /// if the spawn is blocked.
+    pub fn spawn_piece(&mut self, kind: &'static str) {
+        let piece = self.spawned(kind);
+        if self.is_colliding(&piece) {
+            self.game_over();
+        }
+        self.current_tetromino = Some(piece);
+        self.last_move_rotated = false;
+        self.note_spawn();
+        self.note_motion(None);
+        self.update_ghost();
+    }
+
+    /// Three-corner rule: a T piece that got into place by rotating and has
+    /// at least three of the four cells diagonal to its center blocked.
+    fn is_t_spin(&self, piece: &Tetromino) -> bool {
+        if piece.kind != "T" || !self.last_move_rotated {
+            return false;
+        }
+        let pos = piece.data.position;
+        [Position(0, 0), Position(2, 0), Position(0, 2), Position(2, 2)]
+            .into_iter()
+            .map(|corner| pos + corner)
+            .filter(|&p| !self.board.in_bounds(p) || self.board.is_occupied(p))
+            .count()
+            >= 3
+    }
+
+    /// Whether `piece` is stuck where it is: blocked left, right and above.
+    /// O pieces look the same however they turn, so never count.
+    fn is_immobile(&self, piece: &Tetromino) -> bool {
+        piece.kind != "O"
+            && [Position(-1, 0), Position(1, 0), Position(0, -1)].into_iter().all(|offset| {
+                let mut moved = piece.clone();
+                moved.data.position = moved.data.position + offset;
+                self.is_oob(&moved) || self.is_colliding(&moved)
+            })
+    }
+
+    /// Where a piece of `kind` would spawn right now and where it would land
+    /// from there, without touching the game. `None` if the spawn is blocked.
+    ///
+    /// Meant for previews such as showing where a hold swap would put the
+    /// held piece before committing to it.
+    pub fn spawn_preview(&self, kind: &'static str) -> Option<(Tetromino, Tetromino)> {
+        let spawned = self.spawned(kind);
+        if self.is_oob(&spawned) || self.is_colliding(&spawned) {
+            return None;
+        }
+        let mut ghost = spawned.clone();
+        ghost.data.position = ghost.data.position + Position(0, self.board.drop_distance(&ghost));
+        Some((spawned, ghost))
+    }
+
+    /// `spawn_preview` for the piece a hold would bring in now: the held one,
+    /// or the next upcoming one if nothing is held yet. `None` if a hold is
+    /// not allowed, or the next piece is not drawn yet (classic random).
+    pub fn hold_swap_preview(&self) -> Option<(Tetromino, Tetromino)> {
+        if !self.can_hold() {
+            return None;
+        }
+        let kind = self.held.or_else(|| self.upcoming_pieces().next())?;
+        self.spawn_preview(kind)
+    }
+
+    /// Pushes `rows` garbage rows, open at column `hole`, under the stack.
+    /// The falling piece is lifted out of the way if it now overlaps; the
+    /// game ends if the stack or the piece is pushed off the top.
+    pub fn receive_garbage(&mut self, rows: u32, hole: u32) {
+        self.receive_garbage_rows(&vec![vec![hole.min(self.width - 1)]; rows as usize]);
+    }
+
+    /// Like `receive_garbage`, with each row's holes given separately, e.g.
+    /// by `garbage::GarbageHoles`.
+    pub fn receive_garbage_rows(&mut self, holes: &[Vec<u32>]) {
+        if self.lost || holes.is_empty() {
+            return;
+        }
+        let rows = holes.len() as u32;
+        let fits = self.board.push_garbage_rows(holes);
+        self.events.push(GameEvent::GarbageReceived { rows });
+        if let Some(mut piece) = self.current_tetromino.clone() {
+            while self.is_colliding(&piece) && !self.is_oob(&piece) {
+                piece.data.position = piece.data.position + Position(0, -1);
+            }
+            if self.is_oob(&piece) {
+                self.game_over();
+            } else {
+                self.current_tetromino = Some(piece);
+                self.note_motion(None);
+            }
+        }
+        if !fits {
+            self.game_over();
+        }
+        self.update_ghost();
+    }
+
+    /// Starts timing a freshly spawned piece for `GameEvent::Placed`.
+    fn note_spawn(&mut self) {
+        self.piece_spawned_ms = self.now_ms();
+        self.piece_inputs = 0;
+    }
+
+    /// Records that the current piece just moved here from `from`; `None`
+    /// (spawns, rotations) puts it at rest.
+    fn note_motion(&mut self, from: Option<Position>) {
+        let now = self.now_ms();
+        self.piece_motion = self.current_tetromino.as_ref().map(|t| {
+            let to = t.data.position;
+            PieceMotion { from: from.unwrap_or(to), to, moved_at_ms: now }
+        });
+    }
+
+    /// The falling piece's latest move, or `None` without a piece.
+    pub fn piece_motion(&self) -> Option<PieceMotion> {
+        self.piece_motion
+    }
+
+    /// Where a renderer tweening over `animation::PIECE_TWEEN_MS` should draw
+    /// the falling piece at `now_ms`, in fractional cells.
+    pub fn interpolated_piece_position(&self, now_ms: f64) -> Option<(f64, f64)> {
+        self.piece_motion.map(|m| m.position_at(now_ms, animation::PIECE_TWEEN_MS))
+    }
+
+    /// Recomputes the landing preview; every method that moves the current
+    /// piece or changes the board calls this.
+    fn update_ghost(&mut self) {
+        if self.current_tetromino.is_none() {
+            self.ghost_tetromino = None;
+            return;
+        }
+        let mut ghost = self.current_tetromino.clone().unwrap();
+        ghost.data.position = ghost.data.position + Position(0, self.board.drop_distance(&ghost));
+        self.ghost_tetromino = Some(ghost);
+    }
+
+    pub fn get_score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn lines_cleared(&self) -> u32 {
+        self.lines_cleared
+    }
+
+    /// Cleared rows that held at least one garbage block.
+    pub fn garbage_lines_cleared(&self) -> u32 {
+        self.garbage_lines_cleared
+    }
+
+    pub fn pieces_placed(&self) -> u32 {
+        self.pieces_placed
+    }
+
+    /// Milliseconds of actual play: the timer stops while paused and once the game is lost.
+    pub fn elapsed_ms(&self) -> f64 {
+        self.timer.elapsed_ms(self.clock.now())
+    }
+
+    pub fn rotate(&mut self) {
+        self.rotate_with_kicks();
+    }
+
+    /// Turns the falling piece clockwise by the Super Rotation System: the
+    /// first of `pieces::kicks` that fits wins. Returns which test that was,
+    /// 0 for a plain turn, or `None` if every test was blocked. Reports how
+    /// it went with a `GameEvent::Rotated`.
+    pub fn rotate_with_kicks(&mut self) -> Option<usize> {
+        if self.lost || self.current_tetromino.is_none() { return None; }
+        let current = self.current_tetromino.as_ref().unwrap();
+
+        let turned = current.rotated();
+        let kicked = pieces::kicks(current.kind, current.rotation).iter().enumerate().find_map(|(test, &offset)| {
+            let mut candidate = turned.clone();
+            candidate.data.position = candidate.data.position + offset;
+            (!self.is_oob(&candidate) && !self.is_colliding(&candidate)).then_some((test, candidate))
+        });
+        let (test, new_tetromino) = kicked?;
+        self.last_move_rotated = true;
+        let rotation = if self.is_t_spin(&new_tetromino) || self.is_immobile(&new_tetromino) {
+            events::Rotation::Spin
+        } else if test > 0 {
+            events::Rotation::Kicked
+        } else {
+            events::Rotation::Plain
+        };
+        self.current_tetromino.replace(new_tetromino);
+        self.events.push(GameEvent::Rotated(rotation));
+        self.note_motion(None);
+        self.update_ghost();
+        Some(test)
+    }
+
+    /// Applies a player action. Shifts and rotations that are blocked, or
+    /// that arrive while rows are being cleared, are buffered and replayed on
+    /// the next piece if it spawns within the buffer window.
+    pub fn apply(&mut self, action: Action) {
+        self.steps += 1;
+        match action {
+            Action::Pause => return self.pause(),
+            Action::Resume => return self.resume(),
+            _ if self.paused || self.lost => return,
+            _ => {}
+        }
+        self.update_zone();
+        if self.lines_being_cleared.is_some() && action != Action::Tick {
+            self.input_buffer.buffer(action, self.now_ms());
+            return;
+        }
+        if action != Action::Tick {
+            self.piece_inputs += 1;
+        }
+        let applied = match action {
+            Action::MoveLeft => self.translate(Position(-1, 0)),
+            Action::MoveRight => self.translate(Position(1, 0)),
+            Action::Rotate => self.rotate_with_kicks().is_some(),
+            Action::SoftDrop => {
+                self.press_soft_drop();
+                true
+            }
+            Action::HardDrop => {
+                self.hard_drop();
+                true
+            }
+            Action::SonicDrop => {
+                self.sonic_drop();
+                true
+            }
+            Action::Tick => {
+                self.tick();
+                true
+            }
+            Action::Zone => self.activate_zone(),
+            Action::Hold => self.hold(),
+            Action::Pause | Action::Resume => unreachable!(),
+        };
+        if applied {
+            if action.is_bufferable() {
+                self.input_buffer.clear();
+            }
+            if action != Action::Tick {
+                self.events.push(GameEvent::Input(action));
+            }
+        } else {
+            self.input_buffer.buffer(action, self.now_ms());
+        }
+    }
+
+    /// How long a buffered shift/rotation stays eligible for the next piece;
+    /// 0 disables buffering.
+    pub fn set_input_buffer_ms(&mut self, window_ms: f64) {
+        self.input_buffer.window_ms = window_ms.max(0.0);
+        self.input_buffer.clear();
+    }
+
+    pub fn input_buffer_ms(&self) -> f64 {
+        self.input_buffer.window_ms
+    }
+
+    /// Replays a still-fresh buffered action on a newly playable piece.
+    fn apply_buffered_input(&mut self) {
+        match self.input_buffer.take(self.now_ms()) {
+            Some(Action::MoveLeft) => { self.translate(Position(-1, 0)); }
+            Some(Action::MoveRight) => { self.translate(Position(1, 0)); }
+            Some(Action::Rotate) => { self.rotate_with_kicks(); }
+            _ => {}
+        }
+    }
+
+    pub fn is_oob(&self, t: &Tetromino) -> bool {
+        t.collect_positions()
+            .iter()
+            .any(|&p| p.0 < 0 || p.0 >= self.width as i32 || p.1 < 0 || p.1 >= self.height as i32)
+    }
+
+    pub fn is_colliding(&self, t: &Tetromino) -> bool {
+        t.cells().any(|p| self.board.is_occupied(p))
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.timer.stop(self.clock.now());
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        if !self.lost {
+            self.timer.start(self.clock.now());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn phase(&self) -> GamePhase {
+        if self.lost {
+            GamePhase::GameOver
+        } else if self.paused {
+            GamePhase::Paused
+        } else if self.lines_being_cleared.is_some() {
+            GamePhase::Clearing
+        } else {
+            GamePhase::Playing
+        }
+    }
+}
+
+
+// FFI C-compatible API
+
+#[repr(C)]
+pub struct GameState {
+    pub score: i32,
+    pub lost: bool,
+    pub width: u32,
+    pub height: u32,
+    pub paused: bool,
+    pub phase: GamePhase,
+}
+
+impl GameState {
+    /// State reported for a null game pointer.
+    fn invalid() -> Self {
+        GameState { score: 0, lost: true, width: 0, height: 0, paused: false, phase: GamePhase::GameOver }
+    }
+}
+
+/// Action codes accepted by `tetris_step`.
+pub const TETRIS_ACTION_LEFT: u32 = 0;
+pub const TETRIS_ACTION_RIGHT: u32 = 1;
+pub const TETRIS_ACTION_ROTATE: u32 = 2;
+pub const TETRIS_ACTION_DROP: u32 = 3;
+pub const TETRIS_ACTION_TICK: u32 = 4;
+pub const TETRIS_ACTION_PAUSE: u32 = 5;
+pub const TETRIS_ACTION_RESUME: u32 = 6;
+pub const TETRIS_ACTION_SONIC_DROP: u32 = 7;
+pub const TETRIS_ACTION_ZONE: u32 = 8;
+pub const TETRIS_ACTION_HOLD: u32 = 9;
+pub const TETRIS_ACTION_SOFT_DROP: u32 = 10;
+
+thread_local! {
+    static LAST_ERROR: std::cell::Cell<i32> = const { std::cell::Cell::new(0) };
 }
 
+/// Error code of the last failed call on this thread (0 if the last call
+/// succeeded). See `BoardError::code` for the values.
+#[no_mangle]
+pub extern "C" fn tetris_last_error() -> i32 {
+    LAST_ERROR.with(|e| e.get())
+}
+
+/// Returns null for dimensions outside the supported range; the reason is
+/// then available from `tetris_last_error`.
+///
+/// # Safety
+///
+/// The returned pointer owns the game and must be released with `tetris_destroy`.
 #[no_mangle]
 pub unsafe extern "C" fn tetris_create(width: u32, height: u32) -> *mut Tetris {
-    let tetris = Tetris::new(width, height);
-    Box::into_raw(Box::new(tetris))
+    match Tetris::try_new(width, height) {
+        Ok(mut tetris) => {
+            // Embedders step the game themselves, so time only moves per tick.
+            tetris.set_clock(Clock::Manual(0.0));
+            LAST_ERROR.with(|e| e.set(0));
+            Box::into_raw(Box::new(tetris))
+        }
+        Err(err) => {
+            LAST_ERROR.with(|e| e.set(err.code()));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Like `tetris_create`, but each `tetris_step` also computes a reward shaped
+/// by `weights` (the defaults when null), read with `tetris_last_reward`.
+///
+/// # Safety
+///
+/// `weights` must be null or point to a valid `RewardWeights`; the returned
+/// pointer is as for `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_create_with_reward(width: u32, height: u32, weights: *const RewardWeights) -> *mut Tetris {
+    let ptr = tetris_create(width, height);
+    if !ptr.is_null() {
+        let weights = if weights.is_null() { RewardWeights::default() } else { *weights };
+        (*ptr).set_reward_weights(Some(weights));
+    }
+    ptr
 }
 
+/// The weights `tetris_create_with_reward` uses for null.
+#[no_mangle]
+pub extern "C" fn tetris_default_reward_weights() -> RewardWeights {
+    RewardWeights::default()
+}
+
+/// Reward of the last `tetris_step`, or 0.0 for games created without
+/// reward weights.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_last_reward(ptr: *const Tetris) -> f32 {
+    if ptr.is_null() {
+        return 0.0;
+    }
+    (*ptr).reward.map_or(0.0, |r| r.last_reward())
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a pointer returned by `tetris_create` that has not been destroyed yet.
 #[no_mangle]
 pub unsafe extern "C" fn tetris_destroy(ptr: *mut Tetris) {
     if ptr.is_null() {
@@ -541,20 +1624,24 @@ pub unsafe extern "C" fn tetris_destroy(ptr: *mut Tetris) {
     let _ = Box::from_raw(ptr);
 }
 
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
 #[no_mangle]
 pub unsafe extern "C" fn tetris_reset(ptr: *mut Tetris) {
     if ptr.is_null() {
         return;
     }
-    // unsafe block already present for ptr write and dereference
-    let new_tetris_instance = Tetris::new((*ptr).width, (*ptr).height);
-    std::ptr::write(ptr, new_tetris_instance);
+    (*ptr).reset();
 }
 
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
 #[no_mangle]
 pub unsafe extern "C" fn tetris_get_game_state(ptr: *const Tetris) -> GameState {
     if ptr.is_null() {
-        return GameState { score: 0, lost: true, width: 0, height: 0 };
+        return GameState::invalid();
     }
     // unsafe block already present for ptr dereference
     let tetris = &*ptr;
@@ -563,9 +1650,15 @@ pub unsafe extern "C" fn tetris_get_game_state(ptr: *const Tetris) -> GameState
         lost: tetris.lost,
         width: tetris.width,
         height: tetris.height,
+        paused: tetris.is_paused(),
+        phase: tetris.phase(),
     }
 }
 
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `out_board_buffer` must be valid for `width * height` bytes.
 #[no_mangle]
 pub unsafe extern "C" fn tetris_get_board(ptr: *const Tetris, out_board_buffer: *mut u8) {
     if ptr.is_null() || out_board_buffer.is_null() {
@@ -576,9 +1669,8 @@ pub unsafe extern "C" fn tetris_get_board(ptr: *const Tetris, out_board_buffer:
     let board_view = tetris.render_view();
 
     let mut buffer_idx = 0;
-    for r in 0..tetris.height as usize {
-        for c in 0..tetris.width as usize {
-            let cell = board_view[r][c];
+    for row in &board_view {
+        for &cell in row {
             let val = match cell {
                 "B" | "G" => 0,
                 _ => 1,
@@ -589,27 +1681,603 @@ pub unsafe extern "C" fn tetris_get_board(ptr: *const Tetris, out_board_buffer:
     }
 }
 
+/// Writes up to `cap` of `values` to `out` and returns how many there are
+/// (which may exceed `cap`).
+///
+/// # Safety
+///
+/// `out` must be null or valid for `cap` byte writes.
+unsafe fn write_bytes(values: &[u8], out: *mut u8, cap: u32) -> u32 {
+    if !out.is_null() {
+        for (i, &v) in values.iter().take(cap as usize).enumerate() {
+            *out.add(i) = v;
+        }
+    }
+    values.len() as u32
+}
+
+/// Writes up to `cap` column heights (see `Tetris::column_heights`) to `out`
+/// and returns the board width, or 0 for a null game.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `out` must be null or valid for `cap` byte writes.
 #[no_mangle]
-pub unsafe extern "C" fn tetris_step(ptr: *mut Tetris, action: u32) -> GameState {
+pub unsafe extern "C" fn tetris_get_column_heights(ptr: *const Tetris, out: *mut u8, cap: u32) -> u32 {
+    ptr.as_ref().map_or(0, |tetris| write_bytes(&tetris.column_heights(), out, cap))
+}
+
+/// Like `tetris_get_column_heights` for `Tetris::row_fill_counts`, one
+/// per row from the top; returns the board height.
+///
+/// # Safety
+///
+/// As for `tetris_get_column_heights`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_row_fill_counts(ptr: *const Tetris, out: *mut u8, cap: u32) -> u32 {
+    ptr.as_ref().map_or(0, |tetris| write_bytes(&tetris.row_fill_counts(), out, cap))
+}
+
+/// Like `tetris_get_column_heights` for `Tetris::holes_per_column`.
+///
+/// # Safety
+///
+/// As for `tetris_get_column_heights`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_holes_per_column(ptr: *const Tetris, out: *mut u8, cap: u32) -> u32 {
+    ptr.as_ref().map_or(0, |tetris| write_bytes(&tetris.holes_per_column(), out, cap))
+}
+
+/// Whether the settled stack fills cell (`x`, `y`), `y` counting down from
+/// the top row; false off the board or for a null game.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_is_cell_occupied(ptr: *const Tetris, x: i32, y: i32) -> bool {
+    ptr.as_ref().is_some_and(|tetris| tetris.is_cell_occupied(Position(x, y)))
+}
+
+/// Syncs the board delta (see `board_delta`) and returns its version, or 0
+/// for a null game. Pass it to `tetris_get_board_delta` next time.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_board_version(ptr: *mut Tetris) -> u32 {
+    ptr.as_mut().map_or(0, Tetris::board_version)
+}
+
+/// Writes up to `cap` of the cells that changed after board version
+/// `last_version` to `out_buf` and returns how many there are (which may
+/// exceed `cap`), or 0 for a null game. Version 0 gets every cell.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `out_buf` must be null or valid for `cap` `CellChange` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_board_delta(ptr: *mut Tetris, last_version: u32, out_buf: *mut CellChange, cap: u32) -> u32 {
+    let Some(tetris) = ptr.as_mut() else { return 0 };
+    let changes = tetris.board_changes_since(last_version);
+    if !out_buf.is_null() {
+        for (i, &change) in changes.iter().take(cap as usize).enumerate() {
+            *out_buf.add(i) = change;
+        }
+    }
+    changes.len() as u32
+}
+
+/// Restarts the game under `curriculum` (see `curriculum::Curriculum`), which
+/// lasts across `tetris_reset`.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `curriculum` null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_set_curriculum(ptr: *mut Tetris, curriculum: *const Curriculum) {
     if ptr.is_null() {
-        return GameState { score: 0, lost: true, width: 0, height: 0 };
+        return;
     }
-    // unsafe block already present for ptr dereference
-    let tetris = &mut *ptr;
+    let curriculum = if curriculum.is_null() { Curriculum::default() } else { *curriculum };
+    (*ptr).set_curriculum(curriculum);
+}
+
+/// Whether the episode reached its curriculum's step limit.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_is_truncated(ptr: *const Tetris) -> bool {
+    !ptr.is_null() && (*ptr).is_truncated()
+}
 
-    match action {
-        0 => tetris.move_left(),
-        1 => tetris.move_right(),
-        2 => tetris.rotate(),
-        3 => tetris.speed_up(),
-        4 => tetris.tick(),
-        _ => {}
+/// Format codes of `tetris_get_observation`; see `observation`.
+pub const TETRIS_OBSERVATION_FLAT: u32 = 0;
+pub const TETRIS_OBSERVATION_GRID: u32 = 1;
+pub const TETRIS_OBSERVATION_COLUMNS: u32 = 2;
+
+fn observation_encoder(format: u32) -> Option<observation::ObservationEncoder> {
+    observation::ObservationFormat::ALL.get(format as usize).copied().map(observation::ObservationEncoder::new)
+}
+
+/// Number of floats `tetris_get_observation` writes in `format` for a
+/// `width × height` board, or 0 for an unknown format.
+#[no_mangle]
+pub extern "C" fn tetris_observation_len(width: u32, height: u32, format: u32) -> u32 {
+    observation_encoder(format).map_or(0, |e| e.len(width, height) as u32)
+}
+
+/// Writes up to `cap` floats of the game's observation in `format` (a
+/// `TETRIS_OBSERVATION_*` code) to `out`, and returns how many it has (which
+/// may exceed `cap`), or 0 for an unknown format.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `out` must be null or valid for `cap` `f32` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_observation(ptr: *const Tetris, format: u32, out: *mut f32, cap: u32) -> u32 {
+    let Some(encoder) = observation_encoder(format) else { return 0 };
+    if ptr.is_null() {
+        return 0;
+    }
+    write_observation(&encoder.encode(&*ptr), out, cap)
+}
+
+/// `tetris_get_observation` of the game mirrored left to right (see
+/// `ObservationEncoder::encode_mirrored`), for data augmentation.
+///
+/// # Safety
+///
+/// As for `tetris_get_observation`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_mirrored_observation(ptr: *const Tetris, format: u32, out: *mut f32, cap: u32) -> u32 {
+    let Some(encoder) = observation_encoder(format) else { return 0 };
+    if ptr.is_null() {
+        return 0;
+    }
+    write_observation(&encoder.encode_mirrored(&*ptr), out, cap)
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for `cap` `f32` writes.
+unsafe fn write_observation(values: &[f32], out: *mut f32, cap: u32) -> u32 {
+    if !out.is_null() {
+        for (i, &v) in values.iter().take(cap as usize).enumerate() {
+            *out.add(i) = v;
+        }
     }
+    values.len() as u32
+}
+
+/// Creates `count` games stepped together by `tetris_env_pool_step`,
+/// observed in `format` (a `TETRIS_OBSERVATION_*` code) and rewarded by
+/// `weights` (the defaults when null). Returns null for an unknown format or
+/// bad dimensions, the latter with the reason in `tetris_last_error`.
+///
+/// # Safety
+///
+/// `weights` must be null or valid. The returned pointer owns the pool and
+/// must be released with `tetris_env_pool_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_env_pool_create(
+    count: u32,
+    width: u32,
+    height: u32,
+    format: u32,
+    weights: *const RewardWeights,
+) -> *mut env_pool::EnvPool {
+    let Some(&format) = observation::ObservationFormat::ALL.get(format as usize) else {
+        return std::ptr::null_mut();
+    };
+    let weights = if weights.is_null() { RewardWeights::default() } else { *weights };
+    match env_pool::EnvPool::new(count, width, height, format, weights) {
+        Ok(pool) => {
+            LAST_ERROR.with(|e| e.set(0));
+            Box::into_raw(Box::new(pool))
+        }
+        Err(err) => {
+            LAST_ERROR.with(|e| e.set(err.code()));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `pool` must be null or a pointer returned by `tetris_env_pool_create` that
+/// has not been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_env_pool_destroy(pool: *mut env_pool::EnvPool) {
+    if !pool.is_null() {
+        let _ = Box::from_raw(pool);
+    }
+}
+
+/// Restarts every game in the pool under `curriculum`, as
+/// `tetris_set_curriculum` does for one.
+///
+/// # Safety
+///
+/// `pool` must be null or a live pool, and `curriculum` null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_env_pool_set_curriculum(pool: *mut env_pool::EnvPool, curriculum: *const Curriculum) {
+    if pool.is_null() {
+        return;
+    }
+    let curriculum = if curriculum.is_null() { Curriculum::default() } else { *curriculum };
+    (*pool).set_curriculum(curriculum);
+}
+
+/// Resets every game and writes their observations to `obs`, one after
+/// another.
+///
+/// # Safety
+///
+/// `pool` must be null or a live pool, and `obs` valid for `count *
+/// tetris_observation_len(width, height, format)` `f32` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_env_pool_reset(pool: *mut env_pool::EnvPool, obs: *mut f32) {
+    if pool.is_null() || obs.is_null() {
+        return;
+    }
+    let pool = &mut *pool;
+    pool.reset(std::slice::from_raw_parts_mut(obs, pool.len() * pool.observation_len()));
+}
+
+/// Applies one `TETRIS_ACTION_*` code per game, in parallel, and writes each
+/// game's observation, reward and done flag (0 running, 1 game over, 2
+/// truncated by the curriculum). Finished games restart at once, so their
+/// observation is the next episode's first.
+///
+/// # Safety
+///
+/// `pool` must be null or a live pool. `actions`, `rewards` and `dones` must
+/// be valid for `count` elements and `obs` as for `tetris_env_pool_reset`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_env_pool_step(
+    pool: *mut env_pool::EnvPool,
+    actions: *const u32,
+    obs: *mut f32,
+    rewards: *mut f32,
+    dones: *mut u8,
+) {
+    if pool.is_null() || actions.is_null() || obs.is_null() || rewards.is_null() || dones.is_null() {
+        return;
+    }
+    let pool = &mut *pool;
+    let n = pool.len();
+    pool.step(
+        std::slice::from_raw_parts(actions, n),
+        std::slice::from_raw_parts_mut(obs, n * pool.observation_len()),
+        std::slice::from_raw_parts_mut(rewards, n),
+        std::slice::from_raw_parts_mut(dones, n),
+    );
+}
+
+/// Overlay codes passed to `TetrisRendererCallbacks::draw_overlay`; see
+/// `render::Overlay`.
+pub const TETRIS_OVERLAY_CLEARING_ROW: u32 = 0;
+pub const TETRIS_OVERLAY_PAUSED: u32 = 1;
+pub const TETRIS_OVERLAY_GAME_OVER: u32 = 2;
+
+/// A `render::Renderer` implemented in C, e.g. for an LED matrix. Cells are
+/// passed as the ASCII byte of their kind (`B` empty, `G` ghost, `X`
+/// garbage, or a piece letter); overlays as a `TETRIS_OVERLAY_*` code with
+/// the clearing row and its progress (0 and 0.0 for the others). Missing
+/// callbacks are skipped; `ctx` is passed through untouched.
+#[repr(C)]
+pub struct TetrisRendererCallbacks {
+    pub ctx: *mut std::ffi::c_void,
+    pub begin_frame: Option<extern "C" fn(ctx: *mut std::ffi::c_void, width: u32, height: u32)>,
+    pub draw_cell: Option<extern "C" fn(ctx: *mut std::ffi::c_void, x: u32, y: u32, cell: u8)>,
+    pub draw_overlay: Option<extern "C" fn(ctx: *mut std::ffi::c_void, overlay: u32, row: u32, progress: f64)>,
+    pub end_frame: Option<extern "C" fn(ctx: *mut std::ffi::c_void)>,
+}
+
+impl render::Renderer for &TetrisRendererCallbacks {
+    fn begin_frame(&mut self, width: u32, height: u32) {
+        if let Some(f) = self.begin_frame {
+            f(self.ctx, width, height);
+        }
+    }
+
+    fn draw_cell(&mut self, x: u32, y: u32, kind: &'static str) {
+        if let Some(f) = self.draw_cell {
+            f(self.ctx, x, y, kind.as_bytes()[0]);
+        }
+    }
+
+    fn draw_overlay(&mut self, overlay: render::Overlay) {
+        let Some(f) = self.draw_overlay else { return };
+        match overlay {
+            render::Overlay::ClearingRow { y, progress } => f(self.ctx, TETRIS_OVERLAY_CLEARING_ROW, y, progress),
+            render::Overlay::Paused => f(self.ctx, TETRIS_OVERLAY_PAUSED, 0, 0.0),
+            render::Overlay::GameOver => f(self.ctx, TETRIS_OVERLAY_GAME_OVER, 0, 0.0),
+        }
+    }
+
+    fn end_frame(&mut self) {
+        if let Some(f) = self.end_frame {
+            f(self.ctx);
+        }
+    }
+}
+
+/// Draws one frame of the game through `callbacks`.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `callbacks` must be null or point to a valid `TetrisRendererCallbacks`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_draw(ptr: *const Tetris, callbacks: *const TetrisRendererCallbacks) {
+    if ptr.is_null() || callbacks.is_null() {
+        return;
+    }
+    render::draw(&*ptr, &mut &*callbacks);
+}
+
+/// Writes the falling piece's latest move as `from_x, from_y, to_x, to_y,
+/// moved_at_ms` to `out`, for embedders that tween between cells. Times are on
+/// the game clock (see `tetris_game_time_ms`). Returns false without a piece.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`, and
+/// `out` must be null or valid for 5 `f64` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_piece_motion(ptr: *const Tetris, out: *mut f64) -> bool {
+    if ptr.is_null() || out.is_null() {
+        return false;
+    }
+    let Some(m) = (*ptr).piece_motion() else { return false };
+    let values = [m.from.0 as f64, m.from.1 as f64, m.to.0 as f64, m.to.1 as f64, m.moved_at_ms];
+    for (i, v) in values.into_iter().enumerate() {
+        *out.add(i) = v;
+    }
+    true
+}
+
+/// Writes up to `cap` of the rows being cleared, counting down from the top,
+/// to `out_rows` and how far their clear animation is, from 0.0 to 1.0 on
+/// the game clock (see `Tetris::clear_animation_progress`), to
+/// `out_progress`. Returns how many rows are clearing: 0 while none are, or
+/// for a null game.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`,
+/// `out_rows` null or valid for `cap` `u32` writes, and `out_progress` null
+/// or valid for one `f64` write.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_clearing_rows(ptr: *const Tetris, out_rows: *mut u32, cap: u32, out_progress: *mut f64) -> u32 {
+    let Some(tetris) = ptr.as_ref() else { return 0 };
+    let (Some(rows), Some(progress)) = (&tetris.lines_being_cleared, tetris.clear_animation_progress()) else {
+        return 0;
+    };
+    if !out_rows.is_null() {
+        for (i, &y) in rows.iter().take(cap as usize).enumerate() {
+            *out_rows.add(i) = y as u32;
+        }
+    }
+    if !out_progress.is_null() {
+        *out_progress = progress;
+    }
+    rows.len() as u32
+}
+
+/// The piece generator's state; see `Tetris::piece_rng`. Save it with a
+/// rollout to deal the same pieces when restoring.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_rng_state(ptr: *const Tetris) -> u64 {
+    if ptr.is_null() {
+        return 0;
+    }
+    (*ptr).piece_rng().state()
+}
+
+/// Restores a state read with `tetris_get_rng_state`, or seeds the generator
+/// with any value; the next reset's pieces follow from it.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_set_rng_state(ptr: *mut Tetris, state: u64) {
+    if let Some(tetris) = ptr.as_mut() {
+        tetris.set_piece_rng(PieceRng::from_state(state));
+    }
+}
+
+/// Deals each random piece on its own, as classic games do, instead of in
+/// shuffled bags of seven; see `Tetris::set_randomizer`.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_set_classic_randomizer(ptr: *mut Tetris, classic: bool) {
+    if let Some(tetris) = ptr.as_mut() {
+        tetris.set_randomizer(if classic { Randomizer::Classic } else { Randomizer::Bag });
+    }
+}
+
+/// Current time on the game clock, in milliseconds.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_game_time_ms(ptr: *const Tetris) -> f64 {
+    if ptr.is_null() {
+        return 0.0;
+    }
+    (*ptr).now_ms()
+}
+
+/// Runs the game at `scale` times normal speed (0.25–2, clamped). Each tick
+/// then advances the manual clock by less or more than `TICK_MS`, so a tick
+/// is still one gravity step of game time.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_set_time_scale(ptr: *mut Tetris, scale: f32) {
+    if !ptr.is_null() {
+        (*ptr).set_time_scale(scale);
+    }
+}
+
+/// Applies one `TETRIS_ACTION_*` code. While paused, everything except
+/// pause/resume is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_step(ptr: *mut Tetris, action: u32) -> GameState {
+    if ptr.is_null() {
+        return GameState::invalid();
+    }
+    step_action(&mut *ptr, action);
     // Call to another unsafe extern "C" function, or rely on its own internal unsafety.
     // For consistency, the call itself isn't in an unsafe block here as tetris_get_game_state handles its own ptr.
     tetris_get_game_state(ptr as *const Tetris)
 }
 
+/// The `input::Action` a `TETRIS_ACTION_*` code stands for.
+fn action_of(code: u32) -> Option<Action> {
+    Some(match code {
+        TETRIS_ACTION_LEFT => Action::MoveLeft,
+        TETRIS_ACTION_RIGHT => Action::MoveRight,
+        TETRIS_ACTION_ROTATE => Action::Rotate,
+        TETRIS_ACTION_DROP => Action::HardDrop,
+        TETRIS_ACTION_TICK => Action::Tick,
+        TETRIS_ACTION_PAUSE => Action::Pause,
+        TETRIS_ACTION_RESUME => Action::Resume,
+        TETRIS_ACTION_SONIC_DROP => Action::SonicDrop,
+        TETRIS_ACTION_ZONE => Action::Zone,
+        TETRIS_ACTION_HOLD => Action::Hold,
+        TETRIS_ACTION_SOFT_DROP => Action::SoftDrop,
+        _ => return None,
+    })
+}
+
+/// The body of `tetris_step`, shared with `env_pool::EnvPool::step`. Codes
+/// go through `Tetris::apply` like the frontend's inputs do; a tick also
+/// moves the game's manual clock on by one gravity interval.
+pub(crate) fn step_action(tetris: &mut Tetris, action: u32) {
+    match action_of(action) {
+        Some(Action::Tick) if !tetris.is_paused() => {
+            tetris.advance_clock(tetris.gravity_interval_ms(TICK_MS));
+            tetris.apply(Action::Tick);
+        }
+        Some(action) => tetris.apply(action),
+        None => tetris.steps += 1,
+    }
+    tetris.shape_reward();
+}
+
+/// ASCII letter of the held piece's kind (e.g. `b'T'`), or 0 with nothing
+/// held; see `Tetris::hold`.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live pointer returned by `tetris_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_get_held_kind(ptr: *const Tetris) -> u8 {
+    match ptr.as_ref().and_then(Tetris::held_piece) {
+        Some(kind) => kind.as_bytes()[0],
+        None => 0,
+    }
+}
+
+/// Number of piece kinds; `kind` arguments of the `tetris_piece_*` queries
+/// index `pieces::KINDS`.
+#[no_mangle]
+pub extern "C" fn tetris_piece_count() -> u32 {
+    pieces::KINDS.len() as u32
+}
+
+/// ASCII letter of piece `kind` (e.g. `b'I'`), or 0 when out of range.
+#[no_mangle]
+pub extern "C" fn tetris_piece_kind(kind: u32) -> u8 {
+    pieces::KINDS.get(kind as usize).map_or(0, |k| k.as_bytes()[0])
+}
+
+/// Writes `x0, y0, .. x3, y3` of piece `kind` in `rotation` (0..4) to
+/// `out_xy`. Returns false for an unknown kind or rotation.
+///
+/// # Safety
+///
+/// `out_xy` must be null or valid for 8 `i32` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_piece_cells(kind: u32, rotation: u32, out_xy: *mut i32) -> bool {
+    let Some(&kind) = pieces::KINDS.get(kind as usize) else { return false };
+    if out_xy.is_null() || rotation >= 4 {
+        return false;
+    }
+    for (i, p) in pieces::cells(kind, rotation as usize).iter().enumerate() {
+        *out_xy.add(2 * i) = p.0;
+        *out_xy.add(2 * i + 1) = p.1;
+    }
+    true
+}
+
+/// Writes the spawn position `x, y` of piece `kind` on a board `width` cells
+/// wide to `out_xy`. Returns false for an unknown kind.
+///
+/// # Safety
+///
+/// `out_xy` must be null or valid for 2 `i32` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_piece_spawn(kind: u32, width: u32, out_xy: *mut i32) -> bool {
+    let Some(&kind) = pieces::KINDS.get(kind as usize) else { return false };
+    if out_xy.is_null() {
+        return false;
+    }
+    let pos = pieces::spawn_position(kind, width);
+    *out_xy = pos.0;
+    *out_xy.add(1) = pos.1;
+    true
+}
+
+/// Writes up to `cap` kick offsets (`x, y` pairs) tried when rotating piece
+/// `kind` clockwise out of `from_rotation`, and returns how many the table
+/// holds (which may exceed `cap`).
+///
+/// # Safety
+///
+/// `out_xy` must be null or valid for `2 * cap` `i32` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetris_piece_kicks(kind: u32, from_rotation: u32, out_xy: *mut i32, cap: u32) -> u32 {
+    let Some(&kind) = pieces::KINDS.get(kind as usize) else { return 0 };
+    if from_rotation >= 4 {
+        return 0;
+    }
+    let kicks = pieces::kicks(kind, from_rotation as usize);
+    if !out_xy.is_null() {
+        for (i, p) in kicks.iter().take(cap as usize).enumerate() {
+            *out_xy.add(2 * i) = p.0;
+            *out_xy.add(2 * i + 1) = p.1;
+        }
+    }
+    kicks.len() as u32
+}
+
 // Placeholder for ANIMATION_DURATION if it's meant to be used by FFI or lib consumers
 // pub const FFI_ANIMATION_DURATION: u32 = ANIMATION_DURATION;
 // Or make it part of GameState if relevant to C consumers.
@@ -0,0 +1,326 @@
+//! King of the hill for up to `MAX_PLAYERS` boards. Everyone is dealt the
+//! same seeded pieces, the garbage a clear earns (see `versus::attack`) goes
+//! to an opponent picked by the sender's `Targeting`, where it waits in
+//! their `GarbageQueue`, telegraphed, for their own clears to counter, and
+//! players drop out as they top out until one is left. Whoever sent a
+//! player their last garbage before they topped out is credited with the KO
+//! and takes their badges plus one, and badges make outgoing attacks hit
+//! harder (see `badge_bonus_percent`). Bots play
+//! themselves on every `step`; human players get their moves in through
+//! `Lobby::apply` and can switch targeting mid-game with
+//! `Lobby::cycle_targeting`, bound to T in the web app's lobby.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::bot::{Bot, Weights};
+use crate::garbage::{GarbageHoles, GarbageQueue, GarbageStyle, DEFAULT_CANCEL_PERCENT};
+use crate::versus::{self, ACTIONS_PER_TICK, MAX_PIECES};
+use crate::{Action, GameEvent, Tetris, TICK_MS};
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 4;
+
+/// Badges needed for each further 25% of attack bonus.
+const BADGE_TIERS: [u32; 4] = [2, 6, 14, 30];
+
+/// Extra attack, in percent, that `badges` give: 25% per tier reached, up
+/// to double at 30 badges.
+pub fn badge_bonus_percent(badges: u32) -> u32 {
+    BADGE_TIERS.iter().filter(|&&tier| badges >= tier).count() as u32 * 25
+}
+
+/// `rows` of attack raised by the bonus of `badges`, rounded down.
+pub fn boosted_attack(rows: u32, badges: u32) -> u32 {
+    rows * (100 + badge_bonus_percent(badges)) / 100
+}
+
+/// Which opponent a clear's garbage goes to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Targeting {
+    /// The surviving opponent with the highest score.
+    #[default]
+    Leader,
+    Random,
+    /// The surviving opponent with the most badges, by score on a tie.
+    Badges,
+    /// Whoever last sent this player garbage, or a random opponent while
+    /// nobody has.
+    Attacker,
+    /// Each player works through the surviving opponents in turn.
+    Even,
+}
+
+impl Targeting {
+    pub const ALL: [Targeting; 5] =
+        [Targeting::Leader, Targeting::Random, Targeting::Badges, Targeting::Attacker, Targeting::Even];
+
+    /// The strategy after this one in `ALL`, wrapping around.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&t| t == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Name for `--target`, also used for the UI's labels.
+    pub fn id(self) -> &'static str {
+        match self {
+            Targeting::Leader => "leader",
+            Targeting::Random => "random",
+            Targeting::Badges => "badges",
+            Targeting::Attacker => "attacker",
+            Targeting::Even => "even",
+        }
+    }
+}
+
+impl std::str::FromStr for Targeting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leader" => Ok(Targeting::Leader),
+            "random" => Ok(Targeting::Random),
+            "badges" | "ko" => Ok(Targeting::Badges),
+            "attacker" => Ok(Targeting::Attacker),
+            "even" => Ok(Targeting::Even),
+            _ => Err(format!("unknown targeting {s:?}, expected leader, random, badges, attacker or even")),
+        }
+    }
+}
+
+pub struct Player {
+    pub tetris: Tetris,
+    /// `None` for a human player.
+    pub bot: Option<Bot>,
+    pub targeting: Targeting,
+    /// Garbage rows sent so far.
+    pub sent: u32,
+    /// Opponents topped out by this player's garbage.
+    pub kos: u32,
+    /// One per KO plus the badges of everyone knocked out.
+    pub badges: u32,
+    /// Who sent this player garbage last.
+    pub last_attacker: Option<usize>,
+    /// Where `Targeting::Even` sends next, as an index into the players.
+    next_target: usize,
+    /// Garbage sent to this player that hasn't come in yet.
+    pub incoming: GarbageQueue,
+    /// Holes of the garbage this player receives.
+    holes: GarbageHoles,
+}
+
+pub struct Lobby {
+    players: Vec<Player>,
+    rng: StdRng,
+    /// Players in the order they topped out.
+    eliminated: Vec<usize>,
+    rounds: u32,
+    /// See `GarbageQueue::counter`.
+    cancel_percent: u32,
+}
+
+impl Lobby {
+    /// One player per entry of `bots`: a bot with those weights, or a human
+    /// for `None`, all targeting by `targeting` to begin with. Pieces,
+    /// garbage holes and random targets are drawn from `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Unless there are `MIN_PLAYERS` to `MAX_PLAYERS` entries.
+    pub fn new(bots: &[Option<Weights>], targeting: Targeting, seed: u64) -> Self {
+        assert!((MIN_PLAYERS..=MAX_PLAYERS).contains(&bots.len()), "a lobby seats 2 to 4 players");
+        let mut rng = StdRng::seed_from_u64(seed);
+        let setup = versus::deal(&mut rng);
+        let players = bots
+            .iter()
+            .enumerate()
+            .map(|(i, weights)| Player {
+                tetris: setup.start(),
+                bot: weights.map(Bot::with_weights),
+                targeting,
+                sent: 0,
+                kos: 0,
+                badges: 0,
+                last_attacker: None,
+                next_target: (i + 1) % bots.len(),
+                incoming: GarbageQueue::default(),
+                holes: GarbageHoles::default(),
+            })
+            .collect();
+        Self { players, rng, eliminated: Vec::new(), rounds: 0, cancel_percent: DEFAULT_CANCEL_PERCENT }
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// `player`'s seat, e.g. to set up a position.
+    pub fn player_mut(&mut self, player: usize) -> Option<&mut Player> {
+        self.players.get_mut(player)
+    }
+
+    /// Applies a human player's move; garbage it earns goes out on the next
+    /// `step`.
+    pub fn apply(&mut self, player: usize, action: Action) {
+        if let Some(p) = self.players.get_mut(player) {
+            p.tetris.apply(action);
+        }
+    }
+
+    pub fn set_targeting(&mut self, player: usize, targeting: Targeting) {
+        if let Some(p) = self.players.get_mut(player) {
+            p.targeting = targeting;
+        }
+    }
+
+    /// How the holes of everyone's incoming garbage are laid out from now on.
+    pub fn set_garbage(&mut self, style: GarbageStyle) {
+        for p in &mut self.players {
+            p.holes = GarbageHoles::new(style);
+        }
+    }
+
+    /// Game time garbage waits in everyone's queue before it can come in.
+    pub fn set_garbage_delay(&mut self, delay_ms: f64) {
+        for p in &mut self.players {
+            p.incoming.delay_ms = delay_ms;
+        }
+    }
+
+    /// How much queued garbage each row of attack cancels, in percent of a
+    /// row; 0 turns countering off.
+    pub fn set_cancel_percent(&mut self, percent: u32) {
+        self.cancel_percent = percent;
+    }
+
+    /// Switches `player` to the next strategy in `Targeting::ALL` and
+    /// returns it.
+    pub fn cycle_targeting(&mut self, player: usize) -> Option<Targeting> {
+        let p = self.players.get_mut(player)?;
+        p.targeting = p.targeting.next();
+        Some(p.targeting)
+    }
+
+    fn alive(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.players.len()).filter(|&i| !self.players[i].tetris.lost)
+    }
+
+    /// Over once one player is left, or every survivor has played
+    /// `versus::MAX_PIECES` pieces.
+    pub fn is_over(&self) -> bool {
+        self.alive().count() <= 1 || self.alive().all(|i| self.players[i].tetris.pieces_placed() >= MAX_PIECES)
+    }
+
+    /// One action for every bot, gravity every `ACTIONS_PER_TICK` rounds,
+    /// then clears counter queued garbage and send on the rest, garbage that
+    /// has waited out its delay comes in on locks that cleared nothing, and
+    /// players who topped out are eliminated.
+    pub fn step(&mut self) {
+        if self.is_over() {
+            return;
+        }
+        self.rounds += 1;
+        let tick = self.rounds.is_multiple_of(ACTIONS_PER_TICK);
+        for player in &mut self.players {
+            let game = &mut player.tetris;
+            if game.lost {
+                continue;
+            }
+            if let Some(bot) = &mut player.bot {
+                if game.pieces_placed() < MAX_PIECES {
+                    bot.act(game);
+                }
+            }
+            if tick {
+                game.advance_clock(TICK_MS);
+                game.apply(Action::Tick);
+            }
+        }
+        for attacker in 0..self.players.len() {
+            let events = self.players[attacker].tetris.drain_events();
+            let player = &mut self.players[attacker];
+            if player.tetris.lost {
+                continue;
+            }
+            let rows: u32 = events
+                .iter()
+                .map(|e| match e {
+                    GameEvent::LinesCleared { count } => versus::attack(*count),
+                    _ => 0,
+                })
+                .sum();
+            let rows = player.incoming.counter(boosted_attack(rows, player.badges), self.cancel_percent);
+            if rows > 0 {
+                if let Some(target) = self.target(attacker) {
+                    self.players[attacker].sent += rows;
+                    let victim = &mut self.players[target];
+                    victim.incoming.push(rows, victim.tetris.elapsed_ms());
+                    victim.last_attacker = Some(attacker);
+                }
+            }
+            // Garbage that has arrived comes in once a piece locks without a clear.
+            let locked = events.iter().any(|e| matches!(e, GameEvent::PieceLocked { .. }));
+            let cleared = events.iter().any(|e| matches!(e, GameEvent::LinesCleared { .. }));
+            if locked && !cleared {
+                let player = &mut self.players[attacker];
+                for batch in player.incoming.take_arrived(player.tetris.elapsed_ms()) {
+                    let holes = player.holes.rows(batch, player.tetris.width, &mut self.rng);
+                    player.tetris.receive_garbage_rows(&holes);
+                }
+            }
+        }
+        let lost: Vec<usize> =
+            (0..self.players.len()).filter(|&i| self.players[i].tetris.lost && !self.eliminated.contains(&i)).collect();
+        for &i in &lost {
+            if let Some(attacker) = self.players[i].last_attacker {
+                let taken = std::mem::take(&mut self.players[i].badges);
+                self.players[attacker].kos += 1;
+                self.players[attacker].badges += taken + 1;
+            }
+        }
+        self.eliminated.extend(lost);
+    }
+
+    /// Steps until `is_over`.
+    pub fn play(&mut self) {
+        while !self.is_over() {
+            self.step();
+        }
+    }
+
+    /// The opponent `attacker`'s next garbage goes to, if anyone is left.
+    pub fn target(&mut self, attacker: usize) -> Option<usize> {
+        let opponents: Vec<usize> = self.alive().filter(|&i| i != attacker).collect();
+        let score = |i: usize| self.players[i].tetris.get_score();
+        match self.players[attacker].targeting {
+            Targeting::Leader => opponents.into_iter().max_by_key(|&i| (score(i), usize::MAX - i)),
+            Targeting::Badges => opponents.into_iter().max_by_key(|&i| (self.players[i].badges, score(i), usize::MAX - i)),
+            Targeting::Attacker if self.players[attacker].last_attacker.is_some_and(|a| opponents.contains(&a)) => {
+                self.players[attacker].last_attacker
+            }
+            Targeting::Random | Targeting::Attacker => {
+                (!opponents.is_empty()).then(|| opponents[self.rng.random_range(0..opponents.len())])
+            }
+            Targeting::Even => {
+                let n = self.players.len();
+                let player = &mut self.players[attacker];
+                let target = (0..n).map(|k| (player.next_target + k) % n).find(|i| opponents.contains(i))?;
+                player.next_target = (target + 1) % n;
+                Some(target)
+            }
+        }
+    }
+
+    /// Players in the order they topped out.
+    pub fn eliminated(&self) -> &[usize] {
+        &self.eliminated
+    }
+
+    /// Final placing, first place first: survivors by garbage sent, then the
+    /// eliminated, last out first.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut survivors: Vec<usize> = self.alive().collect();
+        survivors.sort_by_key(|&i| std::cmp::Reverse(self.players[i].sent));
+        survivors.extend(self.eliminated.iter().rev());
+        survivors
+    }
+}
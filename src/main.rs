@@ -1,8 +1,64 @@
 // Import the core logic from the tetris_core library
 use tetris_core::*;
+use tetris_core::Action;
+use tetris_core::animation::{RenderHint, SLOW_MOTION_MS};
+use tetris_core::achievements::{Achievement, AchievementTracker};
+use tetris_core::adaptive::FlowController;
+use tetris_core::downstack::GarbageFeed;
+use tetris_core::garbage::{GarbageHoles, GarbageQueue};
+use tetris_core::zone::Zone;
+use tetris_core::drills::{self, Drill, Template};
+use tetris_core::ghost::Trace;
+use tetris_core::input::{self, ActionQueue};
+use tetris_core::metrics::BoardMetrics;
+use tetris_core::objectives::{Objective, Objectives};
+use tetris_core::placements::PlacementLog;
+use tetris_core::replay::Replay;
+use tetris_core::review::Review;
+use tetris_core::sprint::{self, SplitTimer, SPRINT_LINES};
+use tetris_core::stats::{GameStats, Horizon, Pace, Projection, Trend, MARATHON_LINES};
+use tetris_core::tutorial::{self, Tutorial};
 
-use leptos::{ev, leptos_dom::logging::console_log, prelude::*};
-use leptos_use::{use_event_listener, use_interval_fn};
+#[cfg(all(feature = "tui", not(target_arch = "wasm32")))]
+mod spectator;
+mod ui;
+use ui::{
+    background::{Background, CustomBackground},
+    benchmark,
+    challenges::ChallengesDialog,
+    confirm::ConfirmDialog,
+    drills::DrillsDialog,
+    files,
+    focus,
+    garbage_meter::{GarbageMeter, MeterReading},
+    hold::{self, HoldSlot},
+    i18n::{self, tr, tr_args, tr_or, use_locale},
+    input::HoldKey,
+    inspector::Inspector,
+    latency::{LatencyOverlay, LatencyProbe},
+    mini::{self, MiniMode},
+    motion::use_animation_style,
+    settings::{Settings, SettingsDialog},
+    skin::{self, CustomAtlas},
+    splits::SplitTable,
+    layout::{use_layout, Layout, MINI_FRAME_PX},
+    lobby::LobbyDialog,
+    profiles::ProfilePicker,
+    power::{use_render_pace, RenderPace},
+    pwa::InstallPrompt,
+    review::ReviewDialog,
+    replays::ReplayLibrary,
+    renderers::{CanvasRenderer, DomFrame, DomRenderer, RendererKind},
+    storage::{self, SavedAchievements, SavedDrills, SavedGhosts, SavedProfiles, SavedReplay, SavedReplays, SavedSession, SavedSprintBest, SavedStats},
+    stress::{self, StressDemo},
+    sync::{self, SyncTarget},
+    telemetry::{self, ClearCounts, GameReport},
+    toast::{ToastStack, Toaster},
+    views::{self, View, ViewFrame, ViewWindow},
+};
+
+use leptos::{ev, html, leptos_dom::logging::console_log, prelude::*};
+use leptos_use::{use_event_listener, use_interval_fn, use_raf_fn};
 // No need for rand::Rng here if Tetris::new_random is in lib.rs
 // No need for reactive_stores::Store here if Tetris struct (with derive) is in lib.rs
 use web_sys::window; // Still needed for some UI logic if not fully abstracted
@@ -11,6 +67,7 @@ use std::{
     cell::RefCell,
     // collections::HashSet, // Moved to lib.rs
     // ops::{Add, Sub}, // Moved to lib.rs (Position ops)
+    rc::Rc,
     sync::Arc,
 };
 
@@ -21,17 +78,413 @@ use wasm_bindgen::prelude::*; // Still needed for JsValue, Closure, etc. in UI/W
 // All game logic structs (Position, TetrominoData, Tetromino, Tetris)
 // and their impl blocks, S_OPTS, and FFI functions have been moved to src/lib.rs.
 
+/// How far the board moves at full shake strength and 100% intensity.
+const MAX_SHAKE_PX: f64 = 8.0;
+/// How far the board knocks for a wall kick at 100% intensity.
+const KICK_SHAKE_PX: f64 = 2.0;
+/// How often input sources without their own events (chat votes, ...) are
+/// polled.
+const SOURCE_POLL_MS: u64 = 16;
+/// Size of the board the web UI plays on.
+const BOARD_COLS: u32 = 10;
+const BOARD_ROWS: u32 = 25;
+/// How often a Marathon in progress is autosaved for crash recovery.
+const AUTOSAVE_MS: u64 = 5000;
+/// Hold key presses shorter than this hold on release; longer ones only
+/// peek at where the hold would put the piece coming in.
+const HOLD_PEEK_MS: f64 = 250.0;
+
+/// What a restart starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameMode {
+    Marathon,
+    Tutorial,
+    Sprint,
+    /// Gravity and garbage follow the player's form; see `tetris_core::adaptive`.
+    Adaptive,
+    /// A practice scenario; see `tetris_core::drills`.
+    Drill,
+    /// Garbage rises steadily until the player tops out; see
+    /// `tetris_core::downstack`.
+    Downstack,
+}
+
+impl GameMode {
+    /// Names the mode in telemetry and per-mode records.
+    fn id(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "marathon",
+            GameMode::Tutorial => "tutorial",
+            GameMode::Sprint => "sprint",
+            GameMode::Adaptive => "adaptive",
+            GameMode::Drill => "drill",
+            GameMode::Downstack => "downstack",
+        }
+    }
+}
+
+/// What the post-game screen shows about a finished game.
+#[derive(Debug, Clone)]
+struct GameSummary {
+    stats: GameStats,
+    placements: PlacementLog,
+    review: Review,
+}
+
 #[component]
 fn TetrisGame(
-    restart: ReadSignal<bool>,
+    /// Bumped by the parent each time a new game should start.
+    restart: ReadSignal<u32>,
+    /// Mode of the game being played.
+    mode: ReadSignal<GameMode>,
+    set_level: WriteSignal<u32>,
+    set_garbage_cleared: WriteSignal<u32>,
+    /// Receives the board health score after every lock.
+    set_board_health: WriteSignal<u32>,
+    /// Receives the game's summary once it ends, and `None` on restart.
+    set_game_summary: WriteSignal<Option<GameSummary>>,
+    set_in_progress: WriteSignal<bool>,
     set_score: WriteSignal<i32>,
+    set_last_award: WriteSignal<Option<(ScoreSource, i32, ScoreBreakdown)>>,
+    set_lines: WriteSignal<u32>,
+    set_elapsed_ms: WriteSignal<f64>,
+    /// Receives Marathon's projected result after every clear, and `None` on
+    /// restart.
+    set_pace: WriteSignal<Option<(Projection, Trend)>>,
+    /// Receives the zone meter after every update, `None` with it off.
+    set_zone: WriteSignal<Option<Zone>>,
+    /// Receives the held piece and whether a hold is allowed now.
+    set_held: WriteSignal<(Option<&'static str>, bool)>,
+    /// Whether to show where a hold would spawn and land the piece coming
+    /// in; set while the hold slot is hovered and while the hold key is down.
+    hold_preview: RwSignal<bool>,
+    /// Active tutorial, if any; advanced here as game events come in.
+    tutorial: RwSignal<Option<Tutorial>>,
+    /// Splits of the Sprint run, if one is being played.
+    sprint: RwSignal<Option<SplitTimer>>,
+    /// Persisted splits of the fastest Sprint, replaced when it is beaten.
+    saved_sprint_best: RwSignal<SavedSprintBest>,
+    /// Persisted best run of each mode, replaced when it is beaten.
+    saved_ghosts: RwSignal<SavedGhosts>,
+    /// Active drill, if any; laid out on each restart.
+    drill: RwSignal<Option<Drill>>,
+    /// Persisted drill results, updated on each completion.
+    saved_drills: RwSignal<SavedDrills>,
+    /// Persisted ids of completed objectives; new completions are appended.
+    completed_objectives: RwSignal<Vec<String>>,
+    /// Persisted achievement progress, rewritten whenever something unlocks or
+    /// lines are cleared.
+    saved_achievements: RwSignal<SavedAchievements>,
+    /// Persisted games played and best score, updated when a game ends.
+    saved_stats: RwSignal<SavedStats>,
+    /// Replay library; every finished game is added to it.
+    saved_replays: RwSignal<SavedReplays>,
+    /// Autosave of the game in progress, cleared when it ends.
+    saved_session: RwSignal<SavedSession>,
+    /// Set to an autosave to pick it back up instead of the current game.
+    resume: RwSignal<Option<SavedSession>>,
     btn_pressed: ReadSignal<&'static str>,
 ) -> impl IntoView {
     // Tetris struct now comes from tetris_core
-    let tetris_instance = Arc::new(RefCell::new(Tetris::new(10, 25)));
+    #[allow(clippy::arc_with_non_send_sync)] // only ever used on the wasm main thread
+    let tetris_instance = Arc::new(RefCell::new(Tetris::new(BOARD_COLS, BOARD_ROWS)));
     let state = RwSignal::new_local(tetris_instance); // RwSignal expects the argument to be Send + Sync if used across threads, check Tetris if it is. For single-threaded wasm, this is fine.
-    let (board, set_board) = signal(vec![]);
+    let (board, set_board) = signal(DomFrame::default());
+    let (column_heights, set_column_heights) = signal(Vec::<u32>::new());
     let (paused, set_paused) = signal(false);
+    // Debug builds' frame stepping; see `ui::inspector`.
+    let (stepping, set_stepping) = signal(false);
+    let (step_deltas, set_step_deltas) = signal(Vec::<String>::new());
+    let objectives = StoredValue::new(completed_objectives.with_untracked(|ids| {
+        Objectives::from_ids(ids.iter().map(String::as_str))
+    }));
+    let achievements = StoredValue::new(saved_achievements.with_untracked(|saved| {
+        AchievementTracker::from_saved(saved.unlocked.iter().map(String::as_str), saved.total_lines)
+    }));
+    let clears = StoredValue::new(ClearCounts::default());
+    let game_stats = StoredValue::new(GameStats::default());
+    let placements = StoredValue::new(PlacementLog::default());
+    let review = StoredValue::new(Review::default());
+    let pace = StoredValue::new(Pace::new(Horizon::Lines(MARATHON_LINES)));
+    // This game's progress, kept as the mode's ghost if it turns out best.
+    let trace = StoredValue::new(Trace::default());
+    // This game's replay, with inputs timed from `replay_start_ms` of the
+    // game clock; saved to the library when the game ends.
+    let replay = StoredValue::new(Replay::default());
+    let replay_start_ms = StoredValue::new(0.0);
+    // Whether this game may overwrite `saved_session`: not until the one
+    // offered on launch has been resumed or discarded.
+    let autosave_armed = StoredValue::new(false);
+    let flow = StoredValue::new(FlowController::default());
+    let (gravity_ms, set_gravity_ms) = signal(TICK_MS as u64);
+    let feed = StoredValue::new(GarbageFeed::new(5000.0, false));
+    let garbage_holes = StoredValue::new(GarbageHoles::default());
+    // Downstack and adaptive garbage waits here, shown on the meter, until it
+    // arrives and a piece locks without a clear.
+    let incoming = StoredValue::new(GarbageQueue::default());
+    let (meter, set_meter) = signal(MeterReading::default());
+    // Applies the adaptive mode's current level to gravity and the level badge.
+    let follow_flow = move || {
+        let (gravity, level) = flow.with_value(|f| (f.gravity_ms() as u64, stage(f.level())));
+        if gravity_ms.get_untracked() != gravity {
+            set_gravity_ms.set(gravity);
+        }
+        set_level.set(level);
+    };
+    let toaster = expect_context::<Toaster>();
+    let locale = use_locale();
+    let animation_style = use_animation_style();
+    let layout = use_layout(BOARD_COLS, BOARD_ROWS);
+    let settings = expect_context::<RwSignal<Settings>>();
+    let canvas = NodeRef::<html::Canvas>::new();
+    let renderer = Memo::new(move |_| settings.with(|s| s.renderer));
+    let render_pace = use_render_pace();
+    let draw_canvas = move |st: &Tetris| {
+        if let Some(mut canvas) = canvas.get_untracked().and_then(CanvasRenderer::new) {
+            render::draw(st, &mut canvas);
+            if settings.with_untracked(|s| s.hole_preview) {
+                canvas.mark_holes(&st.hole_preview());
+            }
+            if hold_preview.get_untracked() {
+                canvas.shade_cells(&hold::swap_preview_cells(st));
+            }
+        }
+    };
+    // Active board effects: shake distance in pixels, and flash opacity and
+    // colour.
+    let (shake_px, set_shake_px) = signal(None::<f64>);
+    let (flash, set_flash) = signal(None::<(f64, &'static str)>);
+    let (slow_motion, set_slow_motion) = signal(false);
+    let play_hint = move |hint: RenderHint| {
+        let scale = settings.with_untracked(|s| s.effect_intensity) as f64 / 100.0;
+        if scale <= 0.0 || !animation_style.get_untracked().shows_board_effects() {
+            return;
+        }
+        match hint {
+            RenderHint::Shake { strength } => set_shake_px.set(Some(MAX_SHAKE_PX * strength * scale)),
+            RenderHint::Flash => set_flash.set(Some((0.8 * scale, "bg-white"))),
+            RenderHint::Kick => set_shake_px.set(Some(KICK_SHAKE_PX * scale)),
+            RenderHint::SpinGlow => set_flash.set(Some((0.4 * scale, "bg-fuchsia-400"))),
+            // The core only slows down when the setting allows it.
+            RenderHint::SlowMotion if state.with_untracked(|st| st.borrow().is_slow_motion()) => {
+                set_slow_motion.set(true);
+                set_timeout(move || set_slow_motion.set(false), std::time::Duration::from_millis(SLOW_MOTION_MS as u64));
+            }
+            RenderHint::SlowMotion => {}
+        }
+    };
+    Effect::new(move || {
+        let ms = animation_style.get().clear_animation_ms();
+        state.with_untracked(|st| st.borrow_mut().set_clear_animation_ms(ms));
+    });
+    Effect::new(move || {
+        let enabled = settings.with(|s| s.close_call_slow_motion) && animation_style.get().shows_board_effects();
+        state.with_untracked(|st| st.borrow_mut().set_slow_motion(enabled));
+    });
+    Effect::new(move || {
+        let orientations = pieces::SpawnOrientations(settings.with(|s| s.spawn_rotations));
+        state.with_untracked(|st| st.borrow_mut().set_spawn_orientations(orientations));
+    });
+    Effect::new(move || {
+        let enabled = settings.with(|s| s.zone_meter);
+        state.with_untracked(|st| st.borrow_mut().set_zone(enabled));
+    });
+    Effect::new(move || {
+        let speed = settings.with(|s| s.soft_drop);
+        state.with_untracked(|st| st.borrow_mut().set_soft_drop_speed(speed));
+    });
+    let game_speed = Memo::new(move |_| settings.with(|s| s.game_speed));
+    Effect::new(move || {
+        let scale = game_speed.get() as f32 / 100.0;
+        state.with_untracked(|st| st.borrow_mut().set_time_scale(scale));
+    });
+    let pause_menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || paused.get(), pause_menu);
+    let record_objectives = move |done: Vec<&'static Objective>| {
+        if !done.is_empty() {
+            completed_objectives.update(|ids| ids.extend(done.iter().map(|o| o.id.to_string())));
+            let l = locale.get_untracked();
+            done.iter().for_each(|o| {
+                let title = tr_or(l, &format!("objective.{}", o.id), o.title);
+                toaster.push(tr_args(l, "challenge.complete", &[("title", title)]));
+            });
+        }
+    };
+    let record_achievements = move |done: Vec<&'static Achievement>| {
+        let l = locale.get_untracked();
+        done.iter().for_each(|a| {
+            let title = tr_or(l, &format!("achievement.{}", a.id), a.title);
+            toaster.push(tr_args(l, "achievement.unlocked", &[("title", title)]));
+        });
+        let tracker = achievements.read_value();
+        let saved = SavedAchievements {
+            unlocked: tracker.unlocked_ids().iter().map(|id| id.to_string()).collect(),
+            total_lines: tracker.total_lines(),
+        };
+        if saved_achievements.with_untracked(|s| *s != saved) {
+            saved_achievements.set(saved);
+        }
+    };
+
+    // Saves stats, the telemetry report and any personal best once a game is over.
+    let record_game_end = move |st: &Tetris, event: &GameEvent| {
+        let (score, lines, elapsed) = (st.get_score(), st.lines_cleared(), st.elapsed_ms());
+        saved_stats.update(|s| {
+            s.games_played += 1;
+            s.best_score = s.best_score.max(score);
+        });
+        set_game_summary.set(Some(GameSummary {
+            stats: game_stats.get_value(),
+            placements: placements.get_value(),
+            review: review.get_value(),
+        }));
+        let mode = mode.get_untracked();
+        telemetry::record(GameReport::new(mode.id(), elapsed, score, lines, clears.get_value()));
+        trace.write_value().record(elapsed, lines, score);
+        saved_session.set(SavedSession::default());
+        if st.pieces_placed() > 0 {
+            saved_replays.update(|r| {
+                r.add(SavedReplay {
+                    mode: mode.id().to_string(),
+                    score,
+                    lines,
+                    date_ms: js_sys::Date::now(),
+                    duration_ms: elapsed,
+                    replay: replay.read_value().to_string(),
+                    ..Default::default()
+                });
+            });
+        }
+        if mode == GameMode::Downstack {
+            let cleared = st.garbage_lines_cleared();
+            if saved_stats.with_untracked(|s| cleared > s.best_downstack) {
+                saved_stats.update(|s| s.best_downstack = cleared);
+                toaster.push(tr(locale.get_untracked(), "pb.new_best"));
+            }
+        }
+        let new_best = match (mode, event, sprint.get_untracked()) {
+            (GameMode::Sprint, GameEvent::GoalReached { .. }, Some(timer)) => {
+                let beaten = saved_sprint_best.with_untracked(|b| sprint::is_new_best(timer.reached(), &b.splits));
+                if beaten {
+                    saved_sprint_best.set(SavedSprintBest { splits: timer.reached().to_vec() });
+                }
+                beaten
+            }
+            (GameMode::Marathon, ..) => {
+                score > 0 && saved_ghosts.with_untracked(|g| g.trace(mode.id())).is_none_or(|t| score > t.result().1)
+            }
+            _ => false,
+        };
+        if new_best {
+            let samples = trace.read_value().samples().to_vec();
+            saved_ghosts.update(|g| {
+                g.traces.insert(mode.id().to_string(), samples);
+            });
+            toaster.push(tr(locale.get_untracked(), "pb.new_best"));
+        }
+    };
+
+    // Windows opened from `views` follow the game through events.
+    let desktop = views::is_desktop();
+    // Pushes the game state into the view signals and forwards pending events.
+    let sync_view = move |st: &RefCell<Tetris>| {
+        let mut events = st.borrow_mut().drain_events();
+        let locked = events.iter().any(|e| matches!(e, GameEvent::PieceLocked { .. }));
+        let cleared = events.iter().any(|e| matches!(e, GameEvent::LinesCleared { .. }));
+        if locked && !cleared {
+            let now = st.borrow().elapsed_ms();
+            let rows: u32 = incoming.write_value().take_arrived(now).into_iter().sum();
+            if rows > 0 {
+                let width = st.borrow().width;
+                let holes = garbage_holes.write_value().rows(rows, width, &mut rand::rng());
+                st.borrow_mut().receive_garbage_rows(&holes);
+                events.extend(st.borrow_mut().drain_events());
+            }
+        }
+        for event in events {
+            if let GameEvent::Score { source, points, breakdown } = event {
+                set_last_award.set(Some((source, points, breakdown)));
+            }
+            if let Some(hint) = RenderHint::for_event(&event) {
+                play_hint(hint);
+            }
+            clears.write_value().observe(&event);
+            game_stats.write_value().observe(&event);
+            placements.write_value().observe(&event);
+            review.write_value().observe(&event, &st.borrow());
+            if let GameEvent::PieceLocked { .. } | GameEvent::GarbageReceived { .. } = event {
+                let st = st.borrow();
+                set_board_health.set(BoardMetrics::of(&st.board).health(st.height));
+            }
+            if let GameEvent::LinesCleared { .. } = event {
+                let st = st.borrow();
+                trace.write_value().record(st.elapsed_ms(), st.lines_cleared(), st.get_score());
+                if mode.get_untracked() == GameMode::Marathon
+                    && pace.write_value().observe(&event, st.elapsed_ms(), st.lines_cleared(), st.get_score())
+                {
+                    pace.with_value(|p| set_pace.set(p.projection().map(|proj| (proj, p.trend()))));
+                }
+            }
+            if let Some(mut d) = drill.get_untracked() {
+                if d.observe(&event, st.borrow().elapsed_ms()) {
+                    let ms = d.completed_ms().unwrap_or_default();
+                    let faster = saved_drills.try_update(|s| s.record(d.template.id(), ms)).unwrap_or_default();
+                    let l = locale.get_untracked();
+                    let key = if faster { "drills.new_best" } else { "drills.complete" };
+                    toaster.push(tr_args(l, key, &[("time", i18n::format_time(l, ms))]));
+                    drill.set(Some(d));
+                }
+            }
+            if mode.get_untracked() == GameMode::Adaptive {
+                let st = st.borrow();
+                flow.write_value().observe(&event, st.elapsed_ms());
+                if let GameEvent::PieceLocked { .. } = event {
+                    follow_flow();
+                }
+            }
+            if let Some(mut timer) = sprint.get_untracked() {
+                if timer.observe(&event, st.borrow().elapsed_ms()) > 0 {
+                    sprint.set(Some(timer));
+                }
+            }
+            if matches!(event, GameEvent::GameOver { .. } | GameEvent::GoalReached { .. }) {
+                record_game_end(&st.borrow(), &event);
+            }
+            record_objectives(objectives.write_value().observe(&event));
+            record_achievements(achievements.write_value().observe(&event));
+            if let Some(mut t) = tutorial.get_untracked() {
+                if t.observe(&event) {
+                    tutorial.set(Some(t));
+                    t.start_step(&mut st.borrow_mut());
+                }
+            }
+        }
+        let (lines, elapsed) = (st.borrow().lines_cleared(), st.borrow().elapsed_ms());
+        record_achievements(achievements.write_value().observe_session(lines, elapsed));
+        set_score.set(st.borrow().get_score());
+        set_lines.set(st.borrow().lines_cleared());
+        set_garbage_cleared.set(st.borrow().garbage_lines_cleared());
+        set_zone.set(st.borrow().zone().copied());
+        set_held.set((st.borrow().held_piece(), st.borrow().can_hold()));
+        set_in_progress.set(st.borrow().is_in_progress());
+        set_meter.set(incoming.with_value(|q| MeterReading::of(q, elapsed)));
+        if desktop {
+            views::publish(&ViewFrame::of(&st.borrow()));
+        }
+        match (renderer.get_untracked(), render_pace.get_untracked()) {
+            (_, RenderPace::Paused) | (RendererKind::Canvas, RenderPace::EveryFrame) => {}
+            (RendererKind::Dom, _) => {
+                let mut dom = DomRenderer::default();
+                render::draw(&st.borrow(), &mut dom);
+                set_board.set(dom.into_frame());
+            }
+            (RendererKind::Canvas, RenderPace::OnChange) => draw_canvas(&st.borrow()),
+        }
+        if settings.with_untracked(|s| s.show_column_heights) {
+            let st = st.borrow();
+            set_column_heights.set((0..st.width).map(|x| st.board.column_height(x)).collect());
+        }
+    };
 
     Effect::new(move || {
         state.with(|st| {
@@ -44,21 +497,132 @@ fn TetrisGame(
         });
     });
 
+    let restart_game = move || {
+        state.with(|st| {
+            st.borrow_mut().set_randomizer(settings.with_untracked(|s| s.randomizer));
+            st.borrow_mut().reset();
+            achievements.write_value().new_game();
+            clears.set_value(ClearCounts::default());
+            game_stats.set_value(GameStats::default());
+            placements.set_value(PlacementLog::default());
+            review.set_value(Review::default());
+            pace.set_value(Pace::new(Horizon::Lines(MARATHON_LINES)));
+            set_pace.set(None);
+            set_game_summary.set(None);
+            trace.set_value(Trace::default());
+            feed.set_value(settings.with_untracked(|s| {
+                GarbageFeed::new(s.downstack_interval_s as f64 * 1000.0, s.downstack_speed_up)
+            }));
+            garbage_holes.set_value(GarbageHoles::new(settings.with_untracked(|s| s.garbage)));
+            flow.set_value(FlowController::default());
+            incoming.set_value(GarbageQueue::default());
+            if mode.get_untracked() == GameMode::Adaptive {
+                follow_flow();
+            } else {
+                set_gravity_ms.set(TICK_MS as u64);
+                set_level.set(1);
+            }
+            if let Some(mut d) = drill.get_untracked() {
+                d.start(&mut st.borrow_mut());
+                drill.set(Some(d));
+            }
+            if let Some(timer) = sprint.get_untracked() {
+                sprint.set(Some(SplitTimer::new(timer.targets())));
+                st.borrow_mut().set_line_goal(Some(SPRINT_LINES));
+            }
+            if let Some(t) = tutorial.get_untracked() {
+                t.start_step(&mut st.borrow_mut());
+            }
+            {
+                let st = st.borrow();
+                let pieces = st.current_tetromino.iter().map(|t| t.kind).chain(st.upcoming_pieces()).collect();
+                replay.set_value(Replay { pieces, rng: Some(st.piece_rng()), randomizer: st.randomizer(), inputs: Vec::new() });
+                replay_start_ms.set_value(st.now_ms());
+            }
+            autosave_armed.set_value(true);
+            set_paused.set(false);
+            set_last_award.set(None);
+            set_board_health.set(BoardMetrics::of(&st.borrow().board).health(st.borrow().height));
+            sync_view(st);
+        });
+    };
+
     Effect::new(move || {
-        if restart.get() {
-            state.with(|st| {
-                // Tetris::new is from tetris_core
-                st.replace(Tetris::new(10, 25));
-                set_board.set(st.borrow().render_view());
-                set_paused.set(false);
-                set_score.set(0);
-            });
+        if restart.get() > 0 {
+            restart_game();
+        }
+    });
+
+    // Unlike a restart, which replays a drill's seed, the retry key starts
+    // over on a fresh seed: a new drill layout and new pieces.
+    let retry_game = move || {
+        if let Some(d) = drill.get_untracked() {
+            drill.set(Some(d.reseeded(&mut rand::rng())));
         }
+        state.with(|st| st.borrow_mut().set_piece_rng(tetris_core::generator::PieceRng::from_entropy()));
+        restart_game();
+    };
+
+    // Picks up an autosave: a fresh game with the saved state put back,
+    // paused so the player can find their bearings first.
+    Effect::new(move || {
+        let Some(session) = resume.try_update(Option::take).flatten() else { return };
+        let Some(snapshot) = session.snapshot.as_ref() else { return };
+        restart_game();
+        state.with(|st| {
+            if let Err(e) = st.borrow_mut().restore(snapshot) {
+                console_log(&format!("Could not resume the saved game: {}", e));
+                return;
+            }
+            if let Ok(saved) = Replay::parse(&session.replay) {
+                replay.set_value(saved);
+                replay_start_ms.set_value(st.borrow().now_ms() - session.replay_ms);
+            }
+            set_paused.set(true);
+            sync_view(st);
+        });
+        saved_session.set(session);
     });
 
+    use_interval_fn(
+        move || {
+            if mode.get_untracked() != GameMode::Marathon {
+                return;
+            }
+            if !autosave_armed.get_value() && saved_session.with_untracked(|s| s.snapshot.is_some()) {
+                return;
+            }
+            state.with_untracked(|st| {
+                let st = st.borrow();
+                let Some(snapshot) = st.snapshot().filter(|_| st.is_in_progress()) else { return };
+                saved_session.set(SavedSession {
+                    mode: GameMode::Marathon.id().to_string(),
+                    saved_ms: js_sys::Date::now(),
+                    snapshot: Some(snapshot),
+                    replay: replay.read_value().to_string(),
+                    replay_ms: st.now_ms() - replay_start_ms.get_value(),
+                });
+            });
+        },
+        AUTOSAVE_MS,
+    );
+
     Effect::new(move |_| {
         let callback_focus_lost = move || set_paused.set(true);
-        let callback_focus_gained = move || set_paused.set(false);
+        let callback_focus_gained = move || {
+            if settings.get_untracked().auto_resume_on_focus {
+                set_paused.set(false);
+            }
+        };
+
+        // Switching tabs (notably on mobile) hides the page without blurring the window.
+        let _ = use_event_listener(document(), ev::visibilitychange, move |_| {
+            if document().hidden() {
+                callback_focus_lost();
+            } else {
+                callback_focus_gained();
+            }
+        });
 
         match js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("__TAURI__")) {
             Ok(true) => {
@@ -69,9 +633,9 @@ fn TetrisGame(
                 }
 
                 let callback_focus_lost_closure =
-                    Closure::wrap(Box::new(callback_focus_lost.clone()) as Box<dyn FnMut()>);
+                    Closure::wrap(Box::new(callback_focus_lost) as Box<dyn FnMut()>);
                 let callback_focus_gained_closure =
-                    Closure::wrap(Box::new(callback_focus_gained.clone()) as Box<dyn FnMut()>);
+                    Closure::wrap(Box::new(callback_focus_gained) as Box<dyn FnMut()>);
 
                 console_log("tauri detected, registering listeners");
                 listen("tauri://blur", callback_focus_lost_closure.as_ref());
@@ -91,56 +655,269 @@ fn TetrisGame(
         }
     });
 
+    // Key press latency, measured while the overlay is shown. `keydown_ms`
+    // holds the timestamp of the key event being handled, so presses from
+    // touch buttons aren't counted.
+    let show_latency = Signal::derive(move || settings.with(|s| s.show_latency));
+    let (latency, set_latency) = signal(LatencyProbe::default());
+    let keydown_ms = StoredValue::new(None::<f64>);
+
+    // Whether ArrowDown is down. Pausing lets go, as the key-up never comes
+    // if the window loses focus first.
+    let soft_drop_held = RwSignal::new(false);
+    Effect::new(move || {
+        if paused.get() {
+            soft_drop_held.set(false);
+        }
+    });
+    Effect::new(move || {
+        let held = soft_drop_held.get();
+        state.with_untracked(|st| st.borrow_mut().set_soft_drop_held(held));
+    });
+
     use_interval_fn(
         move || {
+            if stepping.get_untracked() {
+                return;
+            }
             state.with(|st| {
+                if !st.borrow().lost && !paused.get_untracked() {
+                    let at = st.borrow().now_ms() - replay_start_ms.get_value();
+                    replay.write_value().record(at, Action::Tick);
+                }
                 st.borrow_mut().tick(); // tick() is from tetris_core::Tetris
-                set_score.set(st.borrow().get_score()); // get_score() from tetris_core::Tetris
-                set_board.set(st.borrow().render_view()); // render_view() from tetris_core::Tetris
+                let elapsed = st.borrow().elapsed_ms();
+                let rows = match mode.get_untracked() {
+                    _ if paused.get_untracked() => 0,
+                    GameMode::Adaptive => flow.write_value().garbage_due(elapsed),
+                    GameMode::Downstack => feed.write_value().due(elapsed),
+                    _ => 0,
+                };
+                incoming.write_value().push(rows, elapsed);
+                sync_view(st);
             });
         },
-        1000,
+        // Same spacing as `Tetris::gravity_interval_ms`, without depending on
+        // the effects above having applied the new speeds yet.
+        Signal::derive(move || {
+            let factor = if soft_drop_held.get() { settings.with(|s| s.soft_drop.factor()) } else { 1 };
+            (gravity_ms.get() * 100 / game_speed.get().max(1) as u64 / factor as u64).max(1)
+        }),
+    );
+
+    // The canvas renderer redraws every animation frame, unless energy
+    // saver stops the loop; the DOM one only when `sync_view` hands it a new
+    // frame.
+    let frames = use_raf_fn(move |_| {
+        if show_latency.get_untracked() {
+            set_latency.maybe_update(|p| p.frame(clock::now_ms()));
+        }
+        if renderer.get_untracked() == RendererKind::Canvas {
+            state.with_untracked(|st| draw_canvas(&st.borrow()));
+        }
+    });
+    Effect::new(move || {
+        if render_pace.get() == RenderPace::EveryFrame {
+            (frames.resume)();
+        } else {
+            (frames.pause)();
+        }
+    });
+    // Switching back to the DOM, or to drawing on change or at all, needs a
+    // fresh frame right away.
+    Effect::new(move || {
+        let pace = render_pace.get();
+        if renderer.get() == RendererKind::Dom || pace == RenderPace::OnChange {
+            state.with_untracked(|st| sync_view(st));
+        }
+    });
+    // The hold preview comes and goes without a game update; the DOM board
+    // follows it by itself.
+    Effect::new(move || {
+        hold_preview.track();
+        if renderer.get_untracked() == RendererKind::Canvas && render_pace.get_untracked() == RenderPace::OnChange {
+            state.with_untracked(|st| draw_canvas(&st.borrow()));
+        }
+    });
+
+    // Keyboard and touch input arrive through `keys`; other sources (chat
+    // votes, ...) add themselves to `sources` and are polled here too.
+    let keys = StoredValue::new_local(Rc::new(RefCell::new(ActionQueue::default())));
+    let sources = StoredValue::new_local(vec![Box::new(keys.get_value()) as Box<dyn InputSource>]);
+    let poll_sources = move |st: &RefCell<Tetris>| {
+        let now = st.borrow().now_ms();
+        let applied = sources.try_update_value(|s| input::apply_sources(&mut st.borrow_mut(), s, now));
+        if let Some(applied) = applied.filter(|a| !a.is_empty()) {
+            let start = replay_start_ms.get_value();
+            replay.update_value(|r| applied.iter().for_each(|&(at, action)| r.record(at - start, action)));
+            set_latency.update_untracked(|p| p.applied(clock::now_ms()));
+            sync_view(st);
+        }
+    };
+    use_interval_fn(
+        move || {
+            if !stepping.get_untracked() {
+                state.with(|st| poll_sources(st));
+            }
+        },
+        SOURCE_POLL_MS,
+    );
+
+    // The clock needs a finer resolution than the gravity tick, unless
+    // energy saver is on.
+    use_interval_fn(
+        move || {
+            if !render_pace.get_untracked().draws() {
+                return;
+            }
+            state.with(|st| {
+                let st = st.borrow();
+                set_elapsed_ms.set(st.elapsed_ms());
+                if !st.lost {
+                    record_objectives(objectives.write_value().observe_time(st.elapsed_ms()));
+                }
+            })
+        },
+        Signal::derive(move || render_pace.get().clock_interval_ms()),
     );
 
     let click_handler = move |key: &str| {
         state.with(|st| {
+            // Debug builds: period freezes the game and runs one update per
+            // press; P lets it run again.
+            #[cfg(debug_assertions)]
+            if key == "Period" && !paused.get_untracked() {
+                if !stepping.get_untracked() {
+                    set_stepping.set(true);
+                    st.borrow_mut().pause();
+                }
+                let before = ui::inspector::Snapshot::of(&st.borrow());
+                st.borrow_mut().step();
+                set_step_deltas.set(ui::inspector::deltas(&before, &ui::inspector::Snapshot::of(&st.borrow())));
+                sync_view(st);
+                return;
+            }
+            if stepping.get_untracked() && key == "KeyP" {
+                set_stepping.set(false);
+                set_step_deltas.set(Vec::new());
+                st.borrow_mut().resume();
+                return;
+            }
+
             if st.borrow().is_paused() && key != "KeyP" { // is_paused() from tetris_core::Tetris
                 return;
             }
 
+            if key == "KeyH" {
+                settings.update(|s| s.show_column_heights = !s.show_column_heights);
+                sync_view(st);
+                return;
+            }
+
+            // Debug builds step through the game speeds with - and =.
+            #[cfg(debug_assertions)]
+            if let Some(step) = match key { "Minus" => Some(-1), "Equal" => Some(1), _ => None } {
+                use ui::settings::GAME_SPEEDS;
+                let current = GAME_SPEEDS.iter().position(|&n| n == game_speed.get_untracked()).unwrap_or(3);
+                let speed = GAME_SPEEDS[current.saturating_add_signed(step).min(GAME_SPEEDS.len() - 1)];
+                settings.update(|s| s.game_speed = speed);
+                toaster.push(tr_args(locale.get_untracked(), "debug.game_speed", &[("speed", speed.to_string())]));
+                return;
+            }
+
             if key == "KeyP" {
                 if st.borrow().is_paused() {
                     set_paused.set(false);
                 } else {
                     set_paused.set(true);
                 }
-                set_score.set(st.borrow().get_score());
-                set_board.set(st.borrow().render_view());
-                return;
-            }
-
-            // Accessing lines_being_cleared from tetris_core::Tetris (made pub in lib.rs)
-            if st.borrow().lines_being_cleared.is_some() {
+                sync_view(st);
                 return;
             }
 
-            match key {
-                // Methods like rotate, move_left etc are from tetris_core::Tetris
-                "ArrowUp" => st.borrow_mut().rotate(),
-                "ArrowLeft" => st.borrow_mut().move_left(),
-                "ArrowRight" => st.borrow_mut().move_right(),
-                "ArrowDown" => st.borrow_mut().tick(),
-                "Space" => st.borrow_mut().speed_up(),
+            // Input during a line clear is buffered by the core and applied
+            // to the next piece.
+            let action = match key {
+                "ArrowUp" => Action::Rotate,
+                "ArrowLeft" => Action::MoveLeft,
+                "ArrowRight" => Action::MoveRight,
+                "ArrowDown" => Action::SoftDrop,
+                "Space" => Action::HardDrop,
+                "KeyS" => Action::SonicDrop,
+                "KeyZ" => Action::Zone,
+                key if is_hold_key(key) => Action::Hold,
                 _ => return,
+            };
+            if let Some(pressed_ms) = keydown_ms.write_value().take() {
+                set_latency.update_untracked(|p| p.pressed(pressed_ms));
             }
-
-            set_score.set(st.borrow().get_score());
-            set_board.set(st.borrow().render_view());
+            let now = st.borrow().now_ms();
+            keys.with_value(|k| k.borrow_mut().push(now, action));
+            poll_sources(st);
         });
     };
 
+    // Holding the retry key restarts immediately, without the confirmation the
+    // Restart button asks for.
+    let retry_key = StoredValue::new(HoldKey::default());
+    // When the hold key went down, while it is down; see `HOLD_PEEK_MS`.
+    let hold_pressed_at = StoredValue::new(None::<f64>);
     window_event_listener(ev::keydown, move |e| {
-        click_handler(e.code().as_str());
+        let code = e.code();
+        // Menus own the keyboard while they have focus; P still toggles pause.
+        if !focus::game_has_focus() && (code != "KeyP" || focus::is_menu_open()) {
+            return;
+        }
+        let settings = settings.get_untracked();
+        if code == "ArrowDown" {
+            // Held soft drop is gravity's job; key repeat would add to it.
+            if e.repeat() {
+                return;
+            }
+            soft_drop_held.set(!paused.get_untracked());
+        }
+        if is_hold_key(&code) {
+            if !e.repeat() {
+                hold_pressed_at.set_value(Some(clock::now_ms()));
+                hold_preview.set(true);
+            }
+            return;
+        }
+        if code != settings.retry_key {
+            keydown_ms.set_value(settings.show_latency.then(|| e.time_stamp()));
+            click_handler(code.as_str());
+            keydown_ms.set_value(None);
+            return;
+        }
+        if e.repeat() {
+            return;
+        }
+        let hold_ms = settings.retry_hold_ms as f64;
+        let pressed_at = retry_key.write_value().press(clock::now_ms());
+        set_timeout(
+            move || {
+                if retry_key.with_value(|k| k.is_held(pressed_at, clock::now_ms(), hold_ms)) {
+                    retry_key.update_value(|k| k.release());
+                    retry_game();
+                }
+            },
+            std::time::Duration::from_millis(settings.retry_hold_ms as u64),
+        );
+    });
+    window_event_listener(ev::keyup, move |e| {
+        if e.code() == "ArrowDown" {
+            soft_drop_held.set(false);
+        }
+        if e.code() == settings.get_untracked().retry_key {
+            retry_key.update_value(|k| k.release());
+        }
+        if is_hold_key(&e.code()) {
+            hold_preview.set(false);
+            let pressed_at = hold_pressed_at.write_value().take();
+            if pressed_at.is_some_and(|at| clock::now_ms() - at < HOLD_PEEK_MS) {
+                click_handler(e.code().as_str());
+            }
+        }
     });
 
     Effect::new(move || {
@@ -149,79 +926,604 @@ fn TetrisGame(
         }
     });
 
-    let kind2color = |c| match c {
-        "I" => "blue", "T" => "purple", "O" => "yellow", "J" => "green",
-        "L" => "orange", "S" => "red", "Z" => "cyan",
-        "B" => "rgb(119, 119, 119)", "G" => "rgba(121, 119, 119, 0.76)",
-        _ => unreachable!(),
-    };
+    #[cfg(feature = "twitch")]
+    let chat_banner = ui::chat_plays::use_chat_plays(sources);
+    #[cfg(not(feature = "twitch"))]
+    let chat_banner = ();
+
+    let custom_atlas = expect_context::<RwSignal<CustomAtlas>>();
+    let background = expect_context::<RwSignal<CustomBackground>>();
+    let has_background = Memo::new(move |_| background.with(|b| b.0.is_some()));
+    let atlas = Memo::new(move |_| {
+        let custom = custom_atlas.read();
+        settings.with(|s| s.skin).atlas(custom.0.as_deref()).map(str::to_string)
+    });
 
     view! {
-        <div class="flex flex-col items-center justify-center h-full relative">
-            {move || {
-                board.get().iter().enumerate().map(move |(row_idx, row_data)| {
-                    view! {
-                        <div class="row flex flex-row h-[calc(100%/25)]">
-                            {row_data.iter().map(|&c| {
-                                let cell_class = {
-                                    let base_class = "cell aspect-square";
-                                    // Accessing lines_being_cleared from tetris_core::Tetris
-                                    let is_clearing = state.with(|s| {
-                                        if let Some(clearing_lines) = &s.borrow().lines_being_cleared {
-                                            clearing_lines.contains(&row_idx)
+        <div
+            class="flex flex-col items-center justify-center relative isolate"
+            class:h-full=move || layout.get().0 != Layout::Stacked
+            class:shrink-0=move || layout.get().0 == Layout::Stacked
+            class:board-shake=move || shake_px.get().is_some()
+            class:board-slow-motion=move || slow_motion.get()
+            style=move || {
+                let shake = shake_px.get().map(|px| format!("--shake-px: {:.1}px; ", px)).unwrap_or_default();
+                // Stacked, the board sizes itself instead of filling the window.
+                let height = match layout.get() {
+                    (Layout::Stacked, cell_px) => format!("height: {:.1}px; ", cell_px * BOARD_ROWS as f64),
+                    _ => String::new(),
+                };
+                format!("{}{}--time-scale: {}", shake, height, game_speed.get() as f64 / 100.0)
+            }
+            on:animationend=move |ev| {
+                // Cells run their own animations; only the board's shake ends here.
+                if ev.target() == ev.current_target() {
+                    set_shake_px.set(None);
+                }
+            }>
+            <Background />
+            <Inspector stepping=stepping deltas=step_deltas />
+            <LatencyOverlay shown=show_latency probe=latency />
+            {chat_banner}
+            {move || flash.get().map(|(opacity, colour)| view! {
+                <div class="absolute inset-0 pointer-events-none z-10" style:opacity=opacity.to_string()>
+                    <div class=format!("w-full h-full board-flash {}", colour) on:animationend=move |_| set_flash.set(None)></div>
+                </div>
+            })}
+            <div
+                class=move || format!("flex flex-col h-full relative {}", settings.with(|s| s.grid_lines.class()))
+                class:playfield-border=move || settings.with(|s| s.playfield_border)>
+                <GarbageMeter reading=meter height=BOARD_ROWS />
+                {move || {
+                    if renderer.get() == RendererKind::Canvas {
+                        return view! { <canvas node_ref=canvas class="h-full"></canvas> }.into_any();
+                    }
+                    // Columns the falling piece spawned in, when highlighted.
+                    let spawn_columns: Vec<i32> = if settings.with(|s| s.highlight_spawn) {
+                        state.with(|s| {
+                            let tetris = s.borrow();
+                            tetris.current_tetromino.as_ref().map_or_else(Vec::new, |t| {
+                                tetris.spawned(t.kind).cells().map(|p| p.0).collect()
+                            })
+                        })
+                    } else {
+                        Vec::new()
+                    };
+                    let holes = if settings.with(|s| s.hole_preview) {
+                        state.with(|s| s.borrow().hole_preview())
+                    } else {
+                        Vec::new()
+                    };
+                    let hold_cells = if hold_preview.get() {
+                        state.with(|s| hold::swap_preview_cells(&s.borrow()))
+                    } else {
+                        Vec::new()
+                    };
+                    let frame = board.get();
+                    frame.cells.iter().enumerate().map(|(row_idx, row_data)| {
+                        view! {
+                            <div class="row flex flex-row h-[calc(100%/25)]">
+                                {row_data.iter().enumerate().map(|(col_idx, &c)| {
+                                    let cell_class = {
+                                        let mut base_class = if spawn_columns.contains(&(col_idx as i32)) {
+                                            "cell aspect-square spawn-column".to_string()
                                         } else {
-                                            false
+                                            "cell aspect-square".to_string()
+                                        };
+                                        if holes.contains(&Position(col_idx as i32, row_idx as i32)) {
+                                            base_class += " hole-preview";
+                                        }
+                                        if hold_cells.contains(&Position(col_idx as i32, row_idx as i32)) {
+                                            base_class += " hold-preview";
                                         }
-                                    });
-                                    if is_clearing {
-                                        format!("{} line-clearing-animation", base_class)
-                                    } else {
-                                        base_class.to_string()
+                                        let is_clearing = frame.clearing.contains(&(row_idx as u32));
+                                        match animation_style.get().line_clear_class() {
+                                            Some(class) if is_clearing => format!("{} {}", base_class, class),
+                                            _ => base_class.to_string(),
+                                        }
+                                    };
+                                    view! {
+                                        <div
+                                            class=cell_class
+                                            style=move || atlas.with(|a| skin::cell_style(c, a.as_deref(), has_background.get())) >
+                                        </div>
                                     }
-                                };
-                                view! {
-                                    <div
-                                        class=cell_class
-                                        style:background-color=move || kind2color(c) >
-                                    </div>
-                                }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        }
+                    }).collect::<Vec<_>>().into_any()
+                }}
+                {move || settings.with(|s| s.show_column_heights).then(|| {
+                    let rows = state.with(|s| s.borrow().height) as f64;
+                    view! {
+                        // Thin translucent bars over the stack, one per column.
+                        <div class="absolute inset-0 flex flex-row pointer-events-none z-10">
+                            {column_heights.get().into_iter().map(|h| view! {
+                                <div class="flex-1 flex justify-center items-end">
+                                    <div class="w-1/5 bg-accent opacity-70" style:height=format!("{:.2}%", h as f64 * 100.0 / rows)></div>
+                                </div>
                             }).collect::<Vec<_>>()}
                         </div>
                     }
-                }).collect::<Vec<_>>()
-            }}
+                })}
+            </div>
             <div
                 class="absolute top-0 left-0 w-full h-full bg-black bg-opacity-70 flex items-center justify-center"
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()
                 style:display=move || if paused.get() { "flex" } else { "none" } >
-                <div class="text-white text-2xl font-bold">PAUSED</div>
+                <div class="flex flex-col items-center gap-2" node_ref=pause_menu>
+                    <div class="text-white text-2xl font-bold">{move || tr(locale.get(), "paused")}</div>
+                    <div class="text-white text-sm opacity-80">{move || tr(locale.get(), "paused.hint")}</div>
+                    <button class="btn btn-primary btn-sm" on:click=move |_| set_paused.set(false)>
+                        {move || tr(locale.get(), "button.resume")}
+                    </button>
+                </div>
             </div>
         </div>
     }
 }
 
+/// Asks for a profile (unless there is only one) and runs the game for it.
+/// Switching profiles rebuilds `App`, so every per-profile signal is reloaded.
 #[component]
-fn App() -> impl IntoView {
-    let (restart, set_restart) = signal(false);
+fn Root() -> impl IntoView {
+    if stress::is_requested() {
+        return view! { <StressDemo /> }.into_any();
+    }
+    if let Some(view) = views::requested() {
+        return view! { <ViewWindow view=view /> }.into_any();
+    }
+    // Pick up changes made on other devices; this reloads the page if any.
+    if let Some(target) = storage::load::<SyncTarget>(storage::SYNC_KEY) {
+        sync::sync_in_background(target, |_| {});
+    }
+    let profiles = storage::persisted(storage::PROFILES_KEY, SavedProfiles::default);
+    let active = RwSignal::new(profiles.with_untracked(SavedProfiles::only));
+    let pick = move |id: u32| {
+        profiles.update(|p| p.last_used = Some(id));
+        active.set(Some(id));
+    };
+    (move || match active.get() {
+        Some(profile) => view! { <App profile=profile on_switch_profile=move || active.set(None) /> }.into_any(),
+        None => view! { <ProfilePicker profiles=profiles on_pick=pick /> }.into_any(),
+    })
+    .into_any()
+}
+
+#[component]
+fn App(
+    /// Id of the profile whose settings and progress are loaded.
+    profile: u32,
+    on_switch_profile: impl Fn() + 'static,
+) -> impl IntoView {
+    let key = |base| storage::profile_key(base, profile);
+    provide_context(storage::persisted(key(storage::SETTINGS_KEY), Settings::default));
+    provide_context(storage::persisted(key(storage::CUSTOM_SKIN_KEY), CustomAtlas::default));
+    provide_context(storage::persisted(key(storage::BACKGROUND_KEY), CustomBackground::default));
+    let toaster = Toaster::new();
+    provide_context(toaster);
+    let mini = MiniMode::provide();
+    window_event_listener(ev::keydown, move |e| {
+        if e.code() == mini::KEY && !e.repeat() && focus::game_has_focus() && views::is_desktop() {
+            mini.toggle();
+        }
+    });
+
+    let (restart, set_restart) = signal(0u32);
+    let (in_progress, set_in_progress) = signal(false);
+    let (confirm_restart, set_confirm_restart) = signal(false);
+    let settings = expect_context::<RwSignal<Settings>>();
+    // On first launch, and whenever automatic quality is turned back on,
+    // measure the renderers and pick what this device can afford.
+    Effect::new(move || {
+        if !settings.with(|s| s.auto_quality && s.benchmark.is_none()) {
+            return;
+        }
+        let Some(result) = benchmark::run() else { return };
+        settings.update(|s| {
+            s.benchmark = Some(result);
+            s.renderer = result.renderer();
+            s.effect_intensity = result.effect_intensity();
+        });
+    });
+    let tutorial = RwSignal::new(None::<Tutorial>);
+    let sprint = RwSignal::new(None::<SplitTimer>);
+    let drill = RwSignal::new(None::<Drill>);
+    // Template of the drill the pending restart starts.
+    let next_drill = StoredValue::new(Template::TSpinDouble);
+    let completed_objectives = storage::persisted(key(storage::OBJECTIVES_KEY), Vec::<String>::new);
+    let saved_achievements = storage::persisted(key(storage::ACHIEVEMENTS_KEY), SavedAchievements::default);
+    let saved_stats = storage::persisted(key(storage::STATS_KEY), SavedStats::default);
+    let saved_sprint_best = storage::persisted(key(storage::SPRINT_BEST_KEY), SavedSprintBest::default);
+    let saved_ghosts = storage::persisted(key(storage::GHOSTS_KEY), SavedGhosts::default);
+    let saved_drills = storage::persisted(key(storage::DRILLS_KEY), SavedDrills::default);
+    let (show_drills, set_show_drills) = signal(false);
+    let saved_replays = storage::persisted(key(storage::REPLAYS_KEY), SavedReplays::default);
+    // A Marathon the page was closed or killed in the middle of, offered
+    // back on launch.
+    let saved_session = storage::persisted(key(storage::SESSION_KEY), SavedSession::default);
+    let resume = RwSignal::new(None::<SavedSession>);
+    let (confirm_resume, set_confirm_resume) = signal(saved_session.with_untracked(|s| s.snapshot.is_some()));
+    let (show_replays, set_show_replays) = signal(false);
+    let (show_lobby, set_show_lobby) = signal(false);
+    let (show_challenges, set_show_challenges) = signal(false);
+    // What the pending restart starts, and what is being played.
+    let (next_mode, set_next_mode) = signal(GameMode::Marathon);
+    let (mode, set_mode) = signal(GameMode::Marathon);
+    let do_restart = move || {
+        set_confirm_restart.set(false);
+        let mode = next_mode.get_untracked();
+        set_mode.set(mode);
+        tutorial.set((mode == GameMode::Tutorial).then(Tutorial::new));
+        sprint.set((mode == GameMode::Sprint).then(|| settings.with_untracked(|s| SplitTimer::new(&s.sprint_splits))));
+        drill.set((mode == GameMode::Drill).then(|| Drill::new(next_drill.get_value(), rand::random::<u32>() as u64)));
+        set_restart.update(|n| *n += 1);
+    };
+    let request_restart = move |mode: GameMode| {
+        set_next_mode.set(mode);
+        if in_progress.get_untracked() {
+            set_confirm_restart.set(true);
+        } else {
+            do_restart();
+        }
+    };
     let (score, set_score) = signal(0);
+    let (last_award, set_last_award) = signal(None::<(ScoreSource, i32, ScoreBreakdown)>);
+    let (lines, set_lines) = signal(0u32);
+    let (elapsed_ms, set_elapsed_ms) = signal(0.0);
+    let (pace, set_pace) = signal(None::<(Projection, Trend)>);
+    let (zone, set_zone) = signal(None::<Zone>);
+    let (held, set_held) = signal((None::<&'static str>, true));
+    let hold_preview = RwSignal::new(false);
+    let (level, set_level) = signal(1u32);
+    let (garbage_cleared, set_garbage_cleared) = signal(0u32);
+    let (board_health, set_board_health) = signal(100u32);
+    let (game_summary, set_game_summary) = signal(None::<GameSummary>);
+    let (show_review, set_show_review) = signal(false);
     let (btn_pressed, set_btn_pressed) = signal("");
+    let (show_settings, set_show_settings) = signal(false);
+    let install = InstallPrompt::listen();
+    let locale = use_locale();
+    let t = move |key: &str| tr(locale.get(), key);
+    let sidebar_menu = NodeRef::<html::Div>::new();
+    let animation_style = use_animation_style();
+    let layout = use_layout(BOARD_COLS, BOARD_ROWS);
+    let stacked = move || layout.get().0 == Layout::Stacked;
 
     view! {
-        <div class="flex flex-row h-screen w-screen place-content-center gap-4">
-            <TetrisGame restart=restart set_score=set_score  btn_pressed=btn_pressed/>
-            <div class="flex flex-col h-full justify-between py-4">
+        // Cells size themselves from `--cell-size`, so a resize or a turn of
+        // the screen redraws the board to fit.
+        <div
+            class=move || match layout.get().0 {
+                Layout::Stacked => "flex flex-col min-h-screen w-screen items-center gap-4",
+                Layout::SideBySide => "flex flex-row h-screen w-screen place-content-center gap-4",
+                Layout::Mini => "flex flex-row h-screen w-screen place-content-center",
+            }
+            class:flex-row-reverse=move || !stacked() && settings.with(|s| s.left_handed)
+            // Borderless in mini mode: the margin around the board moves the window.
+            data-tauri-drag-region=move || mini.get().then_some("")
+            style=move || match layout.get() {
+                (Layout::Mini, cell_px) => format!("--cell-size: {:.2}px; padding: {}px", cell_px, MINI_FRAME_PX),
+                (_, cell_px) => format!("--cell-size: {:.2}px", cell_px),
+            }>
+            <TetrisGame restart=restart mode=mode set_level=set_level set_garbage_cleared=set_garbage_cleared set_board_health=set_board_health set_game_summary=set_game_summary set_in_progress=set_in_progress set_score=set_score set_last_award=set_last_award
+                set_lines=set_lines set_elapsed_ms=set_elapsed_ms set_pace=set_pace set_zone=set_zone set_held=set_held hold_preview=hold_preview tutorial=tutorial
+                sprint=sprint saved_sprint_best=saved_sprint_best saved_ghosts=saved_ghosts
+                drill=drill saved_drills=saved_drills
+                completed_objectives=completed_objectives saved_achievements=saved_achievements saved_stats=saved_stats
+                saved_replays=saved_replays saved_session=saved_session resume=resume btn_pressed=btn_pressed/>
+            // Fixed width so long or wide (CJK) labels wrap instead of pushing
+            // the board around; `dir` mirrors the sidebar for RTL locales.
+            <div
+                class=move || if stacked() {
+                    "flex flex-col w-full max-w-md gap-4 px-4 pb-4"
+                } else {
+                    "flex flex-col h-full w-48 justify-between py-4"
+                }
+                class:hidden=move || mini.get()
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
                 <div class="flex flex-col gap-4 items-center">
-                    <div class="badge badge-soft badge-accent"> Scores: {score} </div>
-                    <div class="badge badge-soft badge-primary"> Level: 1 </div>
+                    <div
+                        class="badge badge-soft badge-accent h-auto min-h-6 text-center"
+                        title=move || last_award.get().map(|(_, _, b)| {
+                            let l = locale.get();
+                            tr_args(l, "score.breakdown", &[
+                                ("lines", i18n::format_number(l, b.lines as i64)),
+                                ("soft_drop", i18n::format_number(l, b.soft_drop as i64)),
+                                ("hard_drop", i18n::format_number(l, b.hard_drop as i64)),
+                            ])
+                        })>
+                        {move || {
+                            let l = locale.get();
+                            tr_args(l, "score", &[("value", i18n::format_number(l, score.get() as i64))])
+                        }}
+                    </div>
+                    <div class="text-xs opacity-70 h-4">
+                        {move || last_award.get().filter(|_| animation_style.get().shows_score_popups()).map(|(source, points, _)| {
+                            let l = locale.get();
+                            let source = tr_or(l, &format!("source.{}", source.label().replace(' ', "_")), source.label());
+                            tr_args(l, "score.award", &[("points", i18n::format_number(l, points as i64)), ("source", source)])
+                        })}
+                    </div>
+                    <div class="badge badge-soft badge-secondary h-auto min-h-6 text-center">
+                        {move || tr_args(locale.get(), "lines", &[("value", i18n::format_number(locale.get(), lines.get() as i64))])}
+                    </div>
+                    <div class="badge badge-soft badge-info font-mono h-auto min-h-6 text-center">
+                        {move || tr_args(locale.get(), "time", &[("value", i18n::format_time(locale.get(), elapsed_ms.get()))])}
+                    </div>
+                    <div class="badge badge-soft badge-primary h-auto min-h-6 text-center">
+                        {move || tr_args(locale.get(), "level", &[("value", i18n::format_number(locale.get(), level.get() as i64))])}
+                    </div>
+                    {move || tutorial.get().map(|tut| {
+                        let l = locale.get();
+                        let text = match tut.current() {
+                            Some(step) => tr_args(l, "tutorial.step", &[
+                                ("step", (tut.step_index() + 1).to_string()),
+                                ("total", tutorial::STEPS.len().to_string()),
+                                ("prompt", tr_or(l, tutorial_key(step.goal), step.prompt)),
+                            ]),
+                            None => tr(l, "tutorial.done"),
+                        };
+                        view! { <div class="alert alert-info text-sm w-full">{text}</div> }
+                    })}
+                    {move || settings.with(|s| s.show_board_health).then(|| {
+                        let health = board_health.get();
+                        let color = match health {
+                            70.. => "text-success",
+                            40.. => "text-warning",
+                            _ => "text-error",
+                        };
+                        view! {
+                            <div class="flex flex-row gap-2 items-center text-sm" title=move || t("health.hint")>
+                                <div
+                                    class=format!("radial-progress text-xs {}", color)
+                                    style=format!("--value: {}; --size: 2.5rem; --thickness: 4px;", health)
+                                    role="progressbar">
+                                    {health}
+                                </div>
+                                <span>{move || t("health.label")}</span>
+                            </div>
+                        }
+                    })}
+                    {move || (mode.get() == GameMode::Downstack).then(|| {
+                        let l = locale.get();
+                        let best = saved_stats.with(|s| s.best_downstack);
+                        view! {
+                            <div class="badge badge-soft badge-warning h-auto min-h-6 text-center">
+                                {tr_args(l, "downstack.cleared", &[
+                                    ("value", i18n::format_number(l, garbage_cleared.get() as i64)),
+                                    ("best", i18n::format_number(l, best as i64)),
+                                ])}
+                            </div>
+                        }
+                    })}
+                    {move || drill.get().map(|d| {
+                        let l = locale.get();
+                        let title = tr_or(l, &format!("drill.{}", d.template.id()), d.template.title());
+                        let text = match d.completed_ms() {
+                            Some(ms) => tr_args(l, "drills.done", &[("title", title), ("time", i18n::format_time(l, ms))]),
+                            None => tr_args(l, "drills.active", &[
+                                ("title", title),
+                                ("goal", drill_goal_text(l, d.template.goal())),
+                                ("seed", d.seed.to_string()),
+                            ]),
+                        };
+                        view! { <div class="alert alert-info text-sm w-full">{text}</div> }
+                    })}
+                    {move || game_summary.get().map(|GameSummary { stats, placements, .. }| {
+                        let l = locale.get();
+                        let accuracy = (stats.accuracy().unwrap_or(1.0) * 100.0).round() as i64;
+                        let (csv, json) = (placements.to_csv(), placements.to_json());
+                        view! {
+                            <div class="alert alert-info text-sm w-full" title=move || t("misdrops.hint")>
+                                {tr_args(l, "misdrops.summary", &[
+                                    ("misdrops", i18n::format_number(l, stats.misdrops() as i64)),
+                                    ("pieces", i18n::format_number(l, stats.pieces as i64)),
+                                    ("accuracy", i18n::format_number(l, accuracy)),
+                                    ("holes", i18n::format_number(l, stats.hole_misdrops as i64)),
+                                ])}
+                            </div>
+                            <div class="flex flex-row flex-wrap gap-2 w-full">
+                                <button class="btn btn-sm" on:click=move |_| set_show_review.set(true)>
+                                    {move || t("review.open")}
+                                </button>
+                                <button class="btn btn-sm" on:click=move |_| files::download("tetris-placements.csv", "text/csv", &csv)>
+                                    {move || t("placements.csv")}
+                                </button>
+                                <button class="btn btn-sm" on:click=move |_| files::download("tetris-placements.json", "application/json", &json)>
+                                    {move || t("placements.json")}
+                                </button>
+                            </div>
+                        }
+                    })}
+                    <SplitTable timer=sprint best=saved_sprint_best />
+                    // Live comparison with the mode's best run: lines in
+                    // Sprint, points otherwise.
+                    {move || {
+                        let mode = mode.get();
+                        let ghost = saved_ghosts.with(|g| g.trace(mode.id())).filter(|_| settings.with(|s| s.race_personal_best))?;
+                        let (line_lead, score_lead) = ghost.lead(elapsed_ms.get(), lines.get(), score.get());
+                        let (key, lead) = if mode == GameMode::Sprint { ("pb.lines", line_lead) } else { ("pb.points", score_lead) };
+                        let l = locale.get();
+                        let sign = if lead < 0 { "-" } else { "+" };
+                        let text = tr_args(l, key, &[("lead", format!("{}{}", sign, i18n::format_number(l, lead.abs())))]);
+                        let ahead = lead >= 0;
+                        Some(view! {
+                            <div class="badge badge-soft h-auto min-h-6 text-center" class:badge-success=ahead class:badge-error=!ahead>
+                                {text}
+                            </div>
+                        })
+                    }}
+                    {move || pace.get().filter(|_| mode.get() == GameMode::Marathon).map(|(projection, trend)| {
+                        let l = locale.get();
+                        let arrow = match trend {
+                            Trend::Up => "↑",
+                            Trend::Down => "↓",
+                            Trend::Flat => "→",
+                        };
+                        let text = tr_args(l, "pace.projected", &[
+                            ("score", i18n::format_number(l, projection.score)),
+                            ("lines", i18n::format_number(l, projection.lines as i64)),
+                        ]);
+                        view! {
+                            <div class="badge badge-soft badge-info h-auto min-h-6 text-center" title=move || t("pace.hint")>
+                                {text} " " {arrow}
+                            </div>
+                        }
+                    })}
+                    <HoldSlot held=held label=Signal::derive(move || t("hold")) preview=hold_preview on_hold=move || set_btn_pressed.set("KeyC") />
+                    <Show when=views::is_desktop>
+                        <div class="flex flex-row flex-wrap gap-1">
+                            {View::ALL.map(|v| view! {
+                                <button class="btn btn-xs btn-ghost" on:click=move |_| views::open(v)>
+                                    {move || t(v.label_key())}
+                                </button>
+                            })}
+                            <button class="btn btn-xs btn-ghost" on:click=move |_| mini.set(true)>
+                                {move || t("mini_mode")}
+                            </button>
+                        </div>
+                    </Show>
+                    {move || zone.get().map(|z| {
+                        let l = locale.get();
+                        let text = match z.remaining_ms(elapsed_ms.get()) {
+                            Some(ms) => tr_args(l, "zone.active", &[
+                                ("seconds", format!("{:.1}", ms / 1000.0)),
+                                ("lines", i18n::format_number(l, z.lines() as i64)),
+                            ]),
+                            None => tr(l, "zone.meter"),
+                        };
+                        view! {
+                            <button
+                                class="btn btn-sm btn-ghost flex flex-col h-auto w-full gap-1"
+                                title=move || t("zone.hint")
+                                disabled=!z.can_activate()
+                                on:click=move |_| set_btn_pressed.set("KeyZ")>
+                                <span>{text}</span>
+                                <progress class="progress progress-secondary w-full" max="1" value=z.meter().to_string()></progress>
+                            </button>
+                        }
+                    })}
                 </div>
-                <div class="grid grid-cols-3 gap-0">
+                // Arrow buttons keep their physical layout in every locale;
+                // only the player's mirror setting flips them.
+                <div
+                    class="grid grid-cols-3 gap-0"
+                    class:order-first=stacked
+                    dir=move || if settings.with(|s| s.mirror_touch_controls) { "rtl" } else { "ltr" }>
                     <div class="btn btn-sm col-span-1 col-start-2" on:click=move |_| set_btn_pressed.set("ArrowUp")>U</div>
                     <div class="btn btn-sm col-span-1 col-start-1" on:click=move |_| set_btn_pressed.set("ArrowLeft")>L</div>
                     <div class="btn btn-sm col-span-1" on:click=move |_| set_btn_pressed.set("ArrowDown")>D</div>
                     <div class="btn btn-sm col-span-1" on:click=move |_| set_btn_pressed.set("ArrowRight")>R</div>
-                    <div class="btn btn-sm col-span-3" on:click=move |_| set_btn_pressed.set("Space")>Space</div>
+                    <div class="btn btn-sm col-span-3" on:click=move |_| set_btn_pressed.set("Space")>{move || t("button.space")}</div>
+                </div>
+                // Reachable with Tab; arrows move between entries and Escape
+                // returns the keyboard to the game. Mouse clicks don't take
+                // focus, so game keys keep working afterwards.
+                <div
+                    class="flex flex-col gap-2"
+                    node_ref=sidebar_menu
+                    on:keydown=move |ev| {
+                        if let Some(el) = sidebar_menu.get_untracked() {
+                            focus::handle_menu_key(&ev, &el, focus::blur_active);
+                        }
+                    }
+                    on:mousedown=|ev| ev.prevent_default()>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| set_show_settings.set(true)>
+                        {move || t("button.settings")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| set_show_challenges.set(true)>
+                        {move || t("button.challenges")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| request_restart(GameMode::Tutorial)>
+                        {move || t("button.tutorial")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| request_restart(GameMode::Sprint)>
+                        {move || t("button.sprint")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| request_restart(GameMode::Adaptive)>
+                        {move || t("button.adaptive")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| request_restart(GameMode::Downstack)>
+                        {move || t("button.downstack")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| set_show_drills.set(true)>
+                        {move || t("button.drills")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| set_show_replays.set(true)>
+                        {move || t("button.replays")}
+                    </button>
+                    <button class="btn btn-outline btn-sm" on:click=move |_| set_show_lobby.set(true)>
+                        {move || t("button.lobby")}
+                    </button>
+                    {move || install.is_available().then(|| view! {
+                        <button class="btn btn-outline btn-sm" on:click=move |_| install.prompt()>
+                            {move || t("button.install")}
+                        </button>
+                    })}
+                    <button class="btn btn-outline btn-sm" on:click=move |_| on_switch_profile()>
+                        {move || t("button.switch_profile")}
+                    </button>
+                    <button class="btn btn-neutral" on:click=move |_| request_restart(GameMode::Marathon)>
+                        {move || t("button.restart")}
+                    </button>
                 </div>
-                <div class="btn btn-neutral" on:click=move |_| set_restart.set(true)> Restart </div>
             </div>
+            <ConfirmDialog
+                open=confirm_restart
+                message=Signal::derive(move || t("confirm.restart"))
+                confirm_label=Signal::derive(move || t("button.restart"))
+                on_confirm=do_restart
+                on_cancel=move || set_confirm_restart.set(false) />
+            <ConfirmDialog
+                open=confirm_resume
+                message=Signal::derive(move || {
+                    let l = locale.get();
+                    let (score, lines) = saved_session.with(|s| s.snapshot.as_ref().map_or((0, 0), |s| (s.score, s.lines_cleared)));
+                    tr_args(l, "confirm.resume", &[
+                        ("score", i18n::format_number(l, score as i64)),
+                        ("lines", i18n::format_number(l, lines as i64)),
+                    ])
+                })
+                confirm_label=Signal::derive(move || t("button.resume"))
+                on_confirm=move || {
+                    set_confirm_resume.set(false);
+                    set_mode.set(GameMode::Marathon);
+                    tutorial.set(None);
+                    sprint.set(None);
+                    drill.set(None);
+                    resume.set(Some(saved_session.get_untracked()));
+                }
+                on_cancel=move || {
+                    set_confirm_resume.set(false);
+                    saved_session.set(SavedSession::default());
+                } />
+            <ToastStack toaster=toaster />
+            <ChallengesDialog
+                open=show_challenges
+                completed=completed_objectives
+                on_close=move || set_show_challenges.set(false) />
+            <ReviewDialog
+                open=show_review
+                review=Signal::derive(move || game_summary.with(|s| s.as_ref().map(|s| s.review.clone()).unwrap_or_default()))
+                on_close=move || set_show_review.set(false) />
+            <DrillsDialog
+                open=show_drills
+                saved=saved_drills
+                on_start=move |template| {
+                    set_show_drills.set(false);
+                    next_drill.set_value(template);
+                    request_restart(GameMode::Drill);
+                }
+                on_close=move || set_show_drills.set(false) />
+            <ReplayLibrary
+                open=show_replays
+                saved=saved_replays
+                on_close=move || set_show_replays.set(false) />
+            <LobbyDialog open=show_lobby on_close=move || set_show_lobby.set(false) />
+            <SettingsDialog open=show_settings on_close=move || set_show_settings.set(false) />
         </div>
     }
 }
@@ -229,22 +1531,134 @@ fn App() -> impl IntoView {
 #[cfg(target_arch = "wasm32")]
 fn main() {
     console_error_panic_hook::set_once();
-    mount_to_body(|| view! { <App /> });
+    mount_to_body(|| view! { <Root /> });
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
     console_error_panic_hook::set_once();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    #[cfg(feature = "onnx")]
+    let observation = || {
+        let name = args.iter().position(|a| a == "--observation").and_then(|i| args.get(i + 1));
+        match name.map(|n| observation::ObservationFormat::from_name(n)) {
+            None => Default::default(),
+            Some(Some(format)) => observation::ObservationEncoder::new(format),
+            Some(None) => {
+                eprintln!("unknown --observation; expected flat, grid or columns");
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Some(i) = args.iter().position(|a| a == "--simulate") {
+        let games = args.get(i + 1).and_then(|n| n.parse().ok()).unwrap_or(SIMULATED_GAMES);
+        let record = args.iter().position(|a| a == "--record").and_then(|i| args.get(i + 1));
+        let policy = match args.iter().position(|a| a == "--ai").and_then(|i| args.get(i + 1)).map(String::as_str) {
+            None | Some("greedy") => bot::Policy::Greedy,
+            Some("strongest") => bot::Policy::strongest(),
+            #[cfg(feature = "mcts")]
+            Some("mcts") => bot::Policy::Mcts(Default::default()),
+            #[cfg(feature = "onnx")]
+            Some(ai) if ai.starts_with("onnx:") => match tetris_core::onnx::ValueNet::load(&ai[5..], 10, 25, observation()) {
+                Ok(net) => bot::Policy::Onnx(Box::leak(Box::new(net))),
+                Err(e) => {
+                    eprintln!("{}: {e}", &ai[5..]);
+                    std::process::exit(1);
+                }
+            },
+            Some(other) => {
+                eprintln!("unknown --ai {other:?}; expected greedy, strongest, mcts (`--features mcts`) or onnx:<path> (`--features onnx`)");
+                std::process::exit(1);
+            }
+        };
+        simulate(games, policy, args.iter().any(|a| a == "--tui"), record.map(std::path::Path::new));
+        return;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--versus") {
+        let matches = args.get(i + 1).and_then(|n| n.parse().ok()).unwrap_or(VERSUS_MATCHES);
+        let weights = |flag: &str| {
+            args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map_or(Ok(Default::default()), |w| w.parse())
+        };
+        let best_of = args.iter().position(|a| a == "--best-of").and_then(|i| args.get(i + 1)?.parse().ok());
+        match (weights("--a"), weights("--b")) {
+            (Ok(a), Ok(b)) => match best_of {
+                Some(best_of) => {
+                    use tetris_core::versus::{SeedPolicy, Series};
+                    let policy = if args.iter().any(|a| a == "--swap-seeds") { SeedPolicy::Swapped } else { SeedPolicy::Shared };
+                    let handicap = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)?.parse().ok()).unwrap_or(0);
+                    series(a, b, Series { best_of, seed: 0, policy, handicap: [handicap("--handicap-a"), handicap("--handicap-b")] })
+                }
+                None => versus(a, b, matches),
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--lobby") {
+        let players = args.get(i + 1).and_then(|n| n.parse().ok()).unwrap_or(tetris_core::lobby::MAX_PLAYERS);
+        let targeting = args.iter().position(|a| a == "--target").and_then(|i| args.get(i + 1)).map_or(Ok(Default::default()), |t| t.parse());
+        let pattern = args.iter().position(|a| a == "--garbage").and_then(|i| args.get(i + 1)).map_or(Ok(Default::default()), |p| p.parse());
+        let mut style = tetris_core::garbage::GarbageStyle::default();
+        if let Some(holes) = args.iter().position(|a| a == "--garbage-holes").and_then(|i| args.get(i + 1)).and_then(|n| n.parse().ok()) {
+            style.holes = holes;
+        }
+        match targeting.and_then(|t| Ok((t, pattern?))) {
+            Ok(_) if !(tetris_core::lobby::MIN_PLAYERS..=tetris_core::lobby::MAX_PLAYERS).contains(&players) => {
+                eprintln!("a lobby seats 2 to 4 players");
+                std::process::exit(1);
+            }
+            Ok((targeting, pattern)) => {
+                let cancel = args.iter().position(|a| a == "--cancel").and_then(|i| args.get(i + 1)).and_then(|n| n.parse().ok());
+                let cancel = cancel.unwrap_or(tetris_core::garbage::DEFAULT_CANCEL_PERCENT);
+                let delay = args.iter().position(|a| a == "--garbage-delay").and_then(|i| args.get(i + 1)).and_then(|n| n.parse().ok());
+                let delay = delay.unwrap_or(tetris_core::garbage::DEFAULT_DELAY_MS);
+                lobby(players, targeting, tetris_core::garbage::GarbageStyle { pattern, ..style }, cancel, delay)
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--audit-pieces") {
+        let pieces = args.get(i + 1).and_then(|n| n.parse().ok()).unwrap_or(1_000_000);
+        use tetris_core::generator::{PieceBag, PieceRng, Randomizer};
+        let curriculum = tetris_core::curriculum::Curriculum::default();
+        let randomizer = if args.iter().any(|a| a == "--classic") { Randomizer::Classic } else { Randomizer::Bag };
+        let (mut bag, mut rng) = (PieceBag::new(randomizer), PieceRng::from_entropy());
+        match tetris_core::fairness::audit(pieces, || bag.deal(&curriculum, &mut rng)) {
+            Ok(audit) => print!("{}", audit.to_csv()),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(dir) = args.iter().position(|a| a == "--analyze").and_then(|i| args.get(i + 1)) {
+        if let Err(e) = analyze(std::path::Path::new(dir), args.iter().any(|a| a == "--json")) {
+            eprintln!("{}: {e}", dir);
+            std::process::exit(1);
+        }
+        return;
+    }
     // Tetris::new and other methods now from tetris_core
     let mut tetris = Tetris::new(10, 25);
+    let mut terminal = render::TextRenderer::default();
     println!("{:?}", tetris.current_tetromino); // Accessing pub field from lib
     clear_screen();
-    println!("\n{}", tetris.render()); // render from lib
+    render::draw(&tetris, &mut terminal);
+    println!("\n{}", terminal.text());
 
     loop {
         tetris.tick(); // tick from lib
         clear_screen();
-        println!("{}", tetris.render());
+        render::draw(&tetris, &mut terminal);
+        println!("{}", terminal.text());
         if tetris.lost { // lost field from lib
             println!("Game Over! Score: {}", tetris.score); // score field from lib
             break;
@@ -252,32 +1666,1992 @@ fn main() {
     }
 }
 
-#[allow(unused)]
-fn clear_screen() {
-    print!("\x1b[2J");
-}
+#[cfg(not(target_arch = "wasm32"))]
+const SIMULATED_GAMES: u32 = 100;
+
+/// Plays `games` bot games headless, eight at a time, with `policy`, and
+/// prints a summary, watching them live in the terminal with `tui`. With
+/// `record`, each game's replay is saved there as `game-0001.replay` and so
+/// on.
+#[cfg(not(target_arch = "wasm32"))]
+fn simulate(games: u32, policy: bot::Policy, tui: bool, record: Option<&std::path::Path>) {
+    let mut simulation = tetris_core::simulation::Simulation::new(games, 8);
+    simulation.set_policy(policy);
+    let mut saved = 0;
+    let mut save = |simulation: &mut tetris_core::simulation::Simulation| {
+        let Some(dir) = record else { return };
+        for replay in simulation.take_replays() {
+            saved += 1;
+            let path = dir.join(format!("game-{saved:04}.replay"));
+            if let Err(e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, replay.to_string())) {
+                eprintln!("{}: {e}", path.display());
+            }
+        }
+    };
+    if record.is_some() {
+        simulation.keep_replays();
+    }
+    let start = std::time::Instant::now();
+    if tui {
+        #[cfg(feature = "tui")]
+        if let Err(e) = spectator::run(&mut simulation) {
+            eprintln!("Terminal error: {e}");
+        }
+        #[cfg(not(feature = "tui"))]
+        eprintln!("--tui needs a build with `--features tui`");
+    }
+    let mut finished = u32::MAX;
+    while !simulation.is_done() {
+        simulation.step();
+        save(&mut simulation);
+        if simulation.summary().games != finished {
+            finished = simulation.summary().games;
+            print!("\r{finished}/{games} games");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+    }
+    save(&mut simulation);
+    println!();
+    let summary = simulation.summary();
+    let seconds = start.elapsed().as_secs_f64();
+    println!("{summary}");
+    println!("Time: {seconds:.1} s ({:.0} pieces/s)", summary.pieces as f64 / seconds.max(0.001));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const VERSUS_MATCHES: u32 = 20;
+
+/// Pits bot weights `a` against `b` over `matches` matches and prints `a`'s
+/// results.
+#[cfg(not(target_arch = "wasm32"))]
+fn versus(a: tetris_core::bot::Weights, b: tetris_core::bot::Weights, matches: u32) {
+    let tally = match tetris_core::versus::evaluate(a, b, matches, 0) {
+        Ok(tally) => tally,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    println!("A ({a}) vs B ({b}): {} wins, {} losses, {} draws", tally.wins, tally.losses, tally.draws);
+    if let (Some(score), Some((low, high))) = (tally.score(), tally.confidence_interval()) {
+        println!("A scores {:.1}% (95% CI {:.1}% to {:.1}%)", score * 100.0, low * 100.0, high * 100.0);
+    }
+}
+
+/// Plays a best-of series of bot weights `a` against `b` and prints each
+/// game and the winner.
+#[cfg(not(target_arch = "wasm32"))]
+fn series(a: tetris_core::bot::Weights, b: tetris_core::bot::Weights, series: tetris_core::versus::Series) {
+    use tetris_core::versus::Outcome;
+    let outcomes = match series.play(a, b) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let mut wins = [0; 2];
+    for (game, outcome) in outcomes.iter().enumerate() {
+        let [stream_a, stream_b] = series.streams(game as u32);
+        let result = match outcome {
+            Outcome::FirstWins => { wins[0] += 1; "A wins" }
+            Outcome::SecondWins => { wins[1] += 1; "B wins" }
+            Outcome::Draw => "draw",
+        };
+        println!("game {} (streams A {stream_a}, B {stream_b}): {result}", game + 1);
+    }
+    let winner = if wins[0] >= series.wins_needed() { "A" } else if wins[1] >= series.wins_needed() { "B" } else { "nobody" };
+    println!("A ({a}) vs B ({b}), best of {}: {} to {}, {winner} takes the series", series.best_of, wins[0], wins[1]);
+}
+
+/// Plays a king-of-the-hill lobby of `players` bots and prints the final
+/// boards side by side with the standings.
+#[cfg(not(target_arch = "wasm32"))]
+fn lobby(players: usize, targeting: tetris_core::lobby::Targeting, garbage: tetris_core::garbage::GarbageStyle, cancel_percent: u32, delay_ms: f64) {
+    use tetris_core::lobby::Lobby;
+    let mut lobby = Lobby::new(&vec![Some(Default::default()); players], targeting, 0);
+    lobby.set_garbage(garbage);
+    lobby.set_cancel_percent(cancel_percent);
+    lobby.set_garbage_delay(delay_ms);
+    lobby.play();
+    let frames: Vec<String> = lobby
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mut terminal = render::TextRenderer::default();
+            render::draw(&p.tetris, &mut terminal);
+            let pending = match p.incoming.countdown_ms(p.tetris.elapsed_ms()) {
+                Some(ms) => format!("{} in {:.1}s", p.incoming.pending(), ms / 1000.0),
+                None => "0".to_string(),
+            };
+            format!("P{} sent {} KOs {} badges {} pending {}\n{}", i + 1, p.sent, p.kos, p.badges, pending, terminal.text())
+        })
+        .collect();
+    println!("{}", render::side_by_side(&frames.iter().map(String::as_str).collect::<Vec<_>>(), 3));
+    let standings: Vec<String> = lobby.standings().iter().map(|i| format!("P{}", i + 1)).collect();
+    println!("standings: {}", standings.join(", "));
+}
+
+/// Plays back every `.replay` file in `dir` and prints the aggregate
+/// analysis as `metric,value` CSV, or as JSON with `json`.
+#[cfg(not(target_arch = "wasm32"))]
+fn analyze(dir: &std::path::Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|p| p.extension().is_some_and(|e| e == "replay"));
+    paths.sort();
+    let mut analysis = tetris_core::analysis::Analysis::default();
+    for path in &paths {
+        let replay = tetris_core::replay::Replay::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        analysis.add(&replay);
+    }
+    let [singles, doubles, triples, tetrises] = analysis.clears;
+    let counts = [
+        ("games", analysis.games),
+        ("singles", singles),
+        ("doubles", doubles),
+        ("triples", triples),
+        ("tetrises", tetrises),
+        ("t_spins", analysis.t_spins),
+        ("perfect_clears", analysis.perfect_clears),
+        ("pieces", analysis.misdrops.pieces),
+        ("hole_misdrops", analysis.misdrops.hole_misdrops),
+        ("eval_misdrops", analysis.misdrops.eval_misdrops),
+    ];
+    if json {
+        let mut out: serde_json::Map<String, serde_json::Value> =
+            counts.iter().map(|&(k, v)| (k.to_string(), v.into())).collect();
+        let health: Vec<_> = analysis
+            .health_over_time()
+            .into_iter()
+            .map(|(pieces, health)| serde_json::json!({ "pieces": pieces, "health": health }))
+            .collect();
+        out.insert("health_over_time".into(), health.into());
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        println!("metric,value");
+        for (name, value) in counts {
+            println!("{name},{value}");
+        }
+        for (pieces, health) in analysis.health_over_time() {
+            println!("health_after_{pieces}_pieces,{health:.1}");
+        }
+    }
+    Ok(())
+}
+
+/// Level shown for an adaptive difficulty between 0.0 and 1.0, from 1 to 10.
+/// Whether `code` is one of the hold keys, C and either Shift.
+fn is_hold_key(code: &str) -> bool {
+    matches!(code, "KeyC" | "ShiftLeft" | "ShiftRight")
+}
+
+fn stage(level: f64) -> u32 {
+    1 + (level.clamp(0.0, 1.0) * 9.0).round() as u32
+}
+
+/// A drill's success criterion, spelled out.
+fn drill_goal_text(locale: i18n::Locale, goal: drills::Goal) -> String {
+    match goal {
+        drills::Goal::TSpin { lines } => tr_args(locale, "drills.goal.t_spin", &[("lines", lines.to_string())]),
+        drills::Goal::Combo(n) => tr_args(locale, "drills.goal.combo", &[("count", n.to_string())]),
+        drills::Goal::ClearLines(n) => tr_args(locale, "drills.goal.lines", &[("count", n.to_string())]),
+    }
+}
+
+/// Locale key of a tutorial step's prompt.
+fn tutorial_key(goal: tutorial::Goal) -> &'static str {
+    match goal {
+        tutorial::Goal::Move => "tutorial.move",
+        tutorial::Goal::Rotate => "tutorial.rotate",
+        tutorial::Goal::SoftDrop => "tutorial.soft_drop",
+        tutorial::Goal::HardDrop => "tutorial.hard_drop",
+        tutorial::Goal::Hold => "tutorial.hold",
+        tutorial::Goal::ClearLine => "tutorial.clear_line",
+    }
+}
+
+#[allow(unused)]
+fn clear_screen() {
+    print!("\x1b[2J");
+}
 
 #[cfg(test)]
 mod tests {
     use super::*; // This will bring tetris_core types into scope
+    use tetris_core::{animation, clock::Clock};
     use std::collections::HashSet; // Keep this for test-local HashSet usage if any
 
-    // Helper functions now use Tetris from tetris_core
-    fn count_blocks_at_line(tetris: &Tetris, line_y: i32) -> usize {
-        tetris
-            .fixed_blocks // fixed_blocks is pub in tetris_core::Tetris
-            .iter()
-            .flat_map(|t| t.collect_positions()) // collect_positions is pub
-            .filter(|p| p.1 == line_y)
-            .count()
+    // Helper functions now use Tetris from tetris_core
+    fn count_blocks_at_line(tetris: &Tetris, line_y: i32) -> usize {
+        (0..tetris.width as i32)
+            .filter(|&x| tetris.board.is_occupied(Position(x, line_y))) // board is pub in tetris_core::Tetris
+            .count()
+    }
+
+    fn get_block_positions(tetris: &Tetris) -> HashSet<Position> { // Position is from tetris_core
+        (0..tetris.height as i32)
+            .flat_map(|y| (0..tetris.width as i32).map(move |x| Position(x, y)))
+            .filter(|&p| tetris.board.is_occupied(p))
+            .collect()
+    }
+
+    #[test]
+    fn test_t_spin_double_completes_objectives() {
+        let mut tetris = Tetris::new(10, 10);
+        tetris.current_tetromino = Some(Tetromino {
+            kind: "T",
+            rotation: 1,
+            data: TetrominoData::new(Position(0, 7), Tetromino::get_rotation_data("T", 1)),
+        });
+        for x in (0..10).filter(|&x| x != 1) {
+            tetris.board.set(Position(x, 9), Some("I"));
+        }
+        for x in 3..10 {
+            tetris.board.set(Position(x, 8), Some("I"));
+        }
+        tetris.board.set(Position(0, 7), Some("I"));
+
+        tetris.apply(Action::Rotate);
+        tetris.apply(Action::HardDrop);
+        let events = tetris.drain_events();
+        assert!(events.contains(&GameEvent::TSpin { lines: 2 }));
+
+        let mut objectives = Objectives::from_ids(["unknown"]);
+        let done: Vec<_> = events.iter().flat_map(|e| objectives.observe(e)).map(|o| o.id).collect();
+        assert_eq!(done, vec!["t-spin", "double"]);
+        assert!(objectives.observe_time(60_000.0).is_empty());
+        assert_eq!(objectives.observe_time(120_000.0).len(), 1);
+        assert_eq!(Objectives::from_ids(objectives.completed_ids().iter().copied()), objectives);
+    }
+
+    #[test]
+    fn test_achievements_track_back_to_back_and_totals() {
+        use tetris_core::achievements::{BACK_TO_BACK_3, CENTURY, FIRST_TETRIS, SPRINT_2M};
+
+        let mut tracker = AchievementTracker::from_saved(["gone"], 96);
+        let lock = GameEvent::PieceLocked { kind: "I" };
+        let tetris = GameEvent::LinesCleared { count: 4 };
+
+        let ids = |done: Vec<&Achievement>| done.iter().map(|a| a.id).collect::<Vec<_>>();
+        tracker.observe(&lock);
+        assert_eq!(ids(tracker.observe(&tetris)), vec![FIRST_TETRIS.id, CENTURY.id]);
+        // A plain single breaks the chain; a T-spin single keeps it going.
+        tracker.observe(&GameEvent::LinesCleared { count: 1 });
+        tracker.observe(&tetris);
+        tracker.observe(&GameEvent::TSpin { lines: 1 });
+        assert!(tracker.observe(&GameEvent::LinesCleared { count: 1 }).is_empty());
+        assert_eq!(ids(tracker.observe(&tetris)), vec![BACK_TO_BACK_3.id]);
+        assert_eq!(tracker.total_lines(), 96 + 14);
+
+        assert!(tracker.observe_session(40, 130_000.0).is_empty());
+        assert_eq!(ids(tracker.observe_session(40, 110_000.0)), vec![SPRINT_2M.id]);
+        assert!(tracker.observe_session(40, 110_000.0).is_empty());
+        assert_eq!(tracker.unlocked_ids().len(), 4);
+    }
+
+    #[test]
+    fn test_tutorial_advances_on_requested_actions() {
+        let mut tetris = Tetris::new(10, 25);
+        let mut tutorial = Tutorial::new();
+        tutorial.start_step(&mut tetris);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, "T");
+
+        let feed = |tetris: &mut Tetris, tutorial: &mut Tutorial, action| {
+            tetris.apply(action);
+            for event in tetris.drain_events() {
+                if tutorial.observe(&event) {
+                    tutorial.start_step(tetris);
+                }
+            }
+        };
+
+        // Gated: the wrong action does not advance.
+        feed(&mut tetris, &mut tutorial, Action::Rotate);
+        assert_eq!(tutorial.step_index(), 0);
+        feed(&mut tetris, &mut tutorial, Action::MoveLeft);
+        assert_eq!(tutorial.step_index(), 1);
+        feed(&mut tetris, &mut tutorial, Action::Rotate);
+        feed(&mut tetris, &mut tutorial, Action::SoftDrop);
+        feed(&mut tetris, &mut tutorial, Action::HardDrop);
+        assert_eq!(tutorial.current().unwrap().goal, tutorial::Goal::Hold);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, "S");
+        // Dropping the S does not count; holding the Z after it does.
+        feed(&mut tetris, &mut tutorial, Action::HardDrop);
+        assert_eq!(tutorial.current().unwrap().goal, tutorial::Goal::Hold);
+        feed(&mut tetris, &mut tutorial, Action::Hold);
+        assert_eq!(tetris.held_piece(), Some("Z"));
+        assert_eq!(tutorial.current().unwrap().goal, tutorial::Goal::ClearLine);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, "I");
+
+        feed(&mut tetris, &mut tutorial, Action::HardDrop);
+        assert!(tutorial.is_finished());
+        assert_eq!(tetris.lines_cleared(), 1);
+    }
+
+    #[test]
+    fn test_sonic_drop_does_not_lock() {
+        let mut tetris = Tetris::new(10, 20);
+        let ghost = tetris.ghost_position().unwrap();
+        tetris.apply(Action::SonicDrop);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, ghost);
+        assert_eq!(tetris.pieces_placed(), 0);
+        assert_eq!(tetris.score, 0);
+        // The piece can still slide along the floor.
+        tetris.apply(Action::MoveLeft);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, ghost + Position(-1, 0));
+    }
+
+    #[test]
+    fn test_piece_motion_interpolates_between_cells() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.spawn_piece("O");
+        let start = tetris.current_tetromino.as_ref().unwrap().data.position;
+        assert_eq!(tetris.piece_motion().unwrap().from, start);
+
+        tetris.advance_clock(100.0);
+        tetris.apply(Action::MoveLeft);
+        let motion = tetris.piece_motion().unwrap();
+        assert_eq!((motion.from, motion.to, motion.moved_at_ms), (start, start + Position(-1, 0), 100.0));
+        let x = start.0 as f64;
+        let y = start.1 as f64;
+        assert_eq!(tetris.interpolated_piece_position(100.0), Some((x, y)));
+        assert_eq!(
+            tetris.interpolated_piece_position(100.0 + animation::PIECE_TWEEN_MS / 2.0),
+            Some((x - 0.5, y))
+        );
+        assert_eq!(tetris.interpolated_piece_position(1000.0), Some((x - 1.0, y)));
+
+        // Rotations snap rather than slide.
+        tetris.apply(Action::Rotate);
+        let motion = tetris.piece_motion().unwrap();
+        assert_eq!(motion.from, motion.to);
+    }
+
+    #[test]
+    fn test_input_buffered_during_line_clear() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(Clock::Manual(0.0));
+        for x in 0..10 {
+            tetris.board.set(Position(x, 19), Some("I"));
+        }
+        tetris.clear_lines();
+        let before = tetris.current_tetromino.as_ref().unwrap().data.position;
+
+        tetris.apply(Action::MoveLeft);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, before);
+
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS);
+        tetris.tick();
+        assert!(tetris.lines_being_cleared.is_none());
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, before + Position(-1, 0));
+
+        // With buffering disabled the input is dropped.
+        tetris.set_input_buffer_ms(0.0);
+        for x in 0..10 {
+            tetris.board.set(Position(x, 19), Some("I"));
+        }
+        tetris.clear_lines();
+        let before = tetris.current_tetromino.as_ref().unwrap().data.position;
+        tetris.apply(Action::MoveLeft);
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS);
+        tetris.tick();
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, before);
+    }
+
+    #[test]
+    fn test_instant_line_clear_without_animation() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clear_animation_ms(0.0);
+        tetris.reset();
+        for x in 0..10 {
+            tetris.board.set(Position(x, 19), Some("I"));
+        }
+        tetris.board.set(Position(0, 18), Some("S"));
+        tetris.clear_lines();
+
+        assert!(tetris.lines_being_cleared.is_none());
+        assert_eq!(tetris.lines_cleared(), 1);
+        assert_eq!(tetris.board.get(Position(0, 19)), Some("S"));
+        assert!(tetris.drain_events().contains(&GameEvent::LinesCleared { count: 1 }));
+    }
+
+    #[test]
+    fn test_line_goal_ends_game_with_splits() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_line_goal(Some(4));
+        tetris.advance_clock(1500.0);
+        for y in 16..20 {
+            for x in 0..10 {
+                tetris.board.set(Position(x, y), Some("I"));
+            }
+        }
+        tetris.clear_lines();
+        // The final clear collapses at once and stops the clock.
+        assert!(tetris.lost && tetris.lines_being_cleared.is_none());
+        tetris.advance_clock(1000.0);
+        let events = tetris.drain_events();
+        let finished = events.iter().find_map(|e| match *e {
+            GameEvent::GoalReached { lines, elapsed_ms } => Some((lines, elapsed_ms)),
+            _ => None,
+        });
+        assert!(matches!(finished, Some((4, ms)) if (1500.0..1600.0).contains(&ms)));
+        assert_eq!(tetris.elapsed_ms(), finished.unwrap().1);
+        assert!(!events.iter().any(|e| matches!(e, GameEvent::GameOver { .. })));
+
+        // A Tetris can pass two splits at once; the finish is always a split.
+        let mut timer = SplitTimer::new(&[10, 12, 50]);
+        assert_eq!(timer.targets(), &[10, 12, SPRINT_LINES]);
+        assert_eq!(timer.observe(&GameEvent::LinesCleared { count: 9 }, 10_000.0), 0);
+        assert_eq!(timer.observe(&GameEvent::LinesCleared { count: 4 }, 14_000.0), 2);
+        assert_eq!(timer.observe(&GameEvent::LinesCleared { count: 27 }, 60_000.0), 1);
+        assert!(timer.is_finished());
+        let best = [(10, 15_000.0), (SPRINT_LINES, 58_000.0)];
+        assert_eq!(sprint::delta(timer.reached()[0], &best), Some(-1000.0));
+        assert_eq!(sprint::delta(timer.reached()[1], &best), None);
+        assert!(!sprint::is_new_best(timer.reached(), &best));
+        assert!(sprint::is_new_best(timer.reached(), &[]));
+        assert_eq!(ui::splits::format_delta(ui::i18n::Locale::En, -1000.0), "-00:01.00");
+    }
+
+    #[test]
+    fn test_ghost_trace_replays_progress() {
+        use tetris_core::ghost::Trace;
+        let mut trace = Trace::default();
+        trace.record(10_000.0, 2, 300);
+        trace.record(12_000.0, 2, 300);
+        trace.record(30_000.0, 6, 1100);
+        assert_eq!(trace.samples().len(), 2);
+        assert_eq!(trace.at(5_000.0), (0, 0));
+        assert_eq!(trace.at(29_999.0), (2, 300));
+        assert_eq!(trace.result(), (6, 1100));
+        assert_eq!(trace.lead(31_000.0, 8, 900), (2, -200));
+
+        let mut ghosts = SavedGhosts::default();
+        ghosts.traces.insert("marathon".to_string(), trace.samples().to_vec());
+        let raw = storage::encode(&ghosts).unwrap();
+        let (loaded, _) = storage::decode::<SavedGhosts>(&raw).unwrap();
+        assert_eq!(loaded.trace("marathon"), Some(trace));
+        assert_eq!(loaded.trace("sprint"), None);
+    }
+
+    #[test]
+    fn test_adaptive_difficulty_follows_form() {
+        use tetris_core::adaptive::{FlowController, START_LEVEL};
+        use tetris_core::stats::{Misdrop, RollingStats};
+        let mut board = tetris_core::board::Board::new(4, 4);
+        board.set(Position(1, 1), Some("I"));
+        board.set(Position(1, 3), Some("I"));
+        board.set(Position(2, 2), Some("I"));
+        assert_eq!(board.holes(), 2);
+
+        let mut stats = RollingStats::default();
+        assert_eq!((stats.pps(), stats.misdrop_rate()), (None, None));
+        stats.observe_lock(0.0);
+        stats.observe_lock(500.0);
+        stats.mark_misdrop();
+        stats.observe_lock(1000.0);
+        stats.observe_lock(1500.0);
+        assert_eq!(stats.pps(), Some(2.0));
+        assert_eq!(stats.misdrop_rate(), Some(0.25));
+
+        // Quick, clean placements raise the level: faster gravity, then garbage.
+        let mut flow = FlowController::default();
+        assert_eq!(flow.garbage_due(10_000.0), 0);
+        let locked = GameEvent::PieceLocked { kind: "T" };
+        for i in 0..60 {
+            flow.observe(&locked, i as f64 * 300.0);
+        }
+        assert!(flow.level() > START_LEVEL && flow.gravity_ms() < 1000.0);
+        assert!(flow.garbage_interval_ms().is_some());
+        assert_eq!(flow.garbage_due(10_000.0 + 25_000.0), 1);
+        assert_eq!(flow.garbage_due(10_000.0 + 26_000.0), 0);
+
+        // A run of misdrops backs it off again.
+        let level = flow.level();
+        for i in 0..10 {
+            flow.observe(&locked, 20_000.0 + i as f64 * 300.0);
+            flow.observe(&GameEvent::Misdrop(Misdrop::Hole), 20_000.0 + i as f64 * 300.0);
+        }
+        assert!(flow.level() < level);
+        assert_eq!((stage(0.0), stage(1.0)), (1, 10));
+    }
+
+    #[test]
+    fn test_drills_are_seeded_and_judged() {
+        use rand::{rngs::StdRng, SeedableRng};
+        use tetris_core::drills::{Drill, Template, DOWNSTACK_ROWS};
+        let mut tetris = Tetris::new(10, 25);
+        let mut other = Tetris::new(10, 25);
+        for template in Template::ALL {
+            let mut drill = Drill::new(template, 42);
+            drill.start(&mut tetris);
+            Drill::new(template, 42).start(&mut other);
+            assert_eq!(get_block_positions(&tetris), get_block_positions(&other));
+            assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, other.current_tetromino.as_ref().unwrap().kind);
+        }
+        Drill::new(Template::Downstack, 7).start(&mut other);
+        assert_ne!(get_block_positions(&tetris), get_block_positions(&other));
+
+        // Each retry deals the drill on a seed of its own.
+        let mut rng = StdRng::seed_from_u64(5);
+        let first = Drill::new(Template::Downstack, 42).reseeded(&mut rng);
+        let second = first.reseeded(&mut rng);
+        assert_eq!((first.template, second.template), (Template::Downstack, Template::Downstack));
+        assert!(first.seed != 42 && second.seed != first.seed);
+        assert_eq!(tetris.board.column_height(0).max(tetris.board.column_height(9)), DOWNSTACK_ROWS);
+
+        // The T-slot fits a downward T: three cells wide over one deep.
+        let mut drill = Drill::new(Template::TSpinDouble, 3);
+        drill.start(&mut tetris);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, "T");
+        let empty = |y: i32| (0..10).filter(|&x| !tetris.board.is_occupied(Position(x, y))).collect::<Vec<_>>();
+        let slot = (1..25).find(|&y| empty(y).len() == 1 && empty(y - 1).len() == 3).unwrap();
+        let x = empty(slot)[0];
+        assert_eq!(empty(slot - 1), vec![x - 1, x, x + 1]);
+
+        assert!(!drill.observe(&GameEvent::TSpin { lines: 1 }, 1000.0));
+        assert!(drill.observe(&GameEvent::TSpin { lines: 2 }, 2000.0));
+        assert_eq!(drill.completed_ms(), Some(2000.0));
+
+        // A lock that clears nothing breaks the combo.
+        let mut drill = Drill::new(Template::FourWide, 1);
+        let lock = GameEvent::PieceLocked { kind: "I" };
+        let clear = GameEvent::LinesCleared { count: 1 };
+        for event in [&lock, &clear, &lock, &clear, &lock, &lock, &clear, &lock, &clear, &lock, &clear] {
+            assert!(!drill.observe(event, 0.0));
+        }
+        assert!(!drill.observe(&lock, 0.0));
+        assert!(drill.observe(&clear, 5000.0));
+
+        let mut saved = SavedDrills::default();
+        assert!(saved.record("downstack", 9000.0));
+        assert!(!saved.record("downstack", 9500.0));
+        assert_eq!((saved.best_ms["downstack"], saved.completed["downstack"]), (9000.0, 2));
+    }
+
+    #[test]
+    fn test_layout_follows_window_shape() {
+        use ui::layout::Layout;
+        assert_eq!(Layout::for_window(1280.0, 800.0), Layout::SideBySide);
+        assert_eq!(Layout::for_window(390.0, 844.0), Layout::Stacked);
+        assert_eq!(Layout::SideBySide.cell_px(1280.0, 800.0, 10, 25), 32.0);
+        // A tall window is limited by its height share, a narrow one by its
+        // width.
+        let tall = Layout::Stacked.cell_px(390.0, 1000.0, 10, 25);
+        assert!((tall - 26.0).abs() < 1e-9);
+        assert!((Layout::Stacked.cell_px(200.0, 1000.0, 10, 25) - 18.8).abs() < 1e-9);
+        // The mini window fits the board inside its drag margin.
+        assert_eq!(Layout::Mini.cell_px(172.0, 412.0, 10, 25), 16.0);
+        assert_eq!(Layout::Mini.cell_px(300.0, 412.0, 10, 25), 16.0);
+    }
+
+    #[test]
+    fn test_render_benchmark_picks_quality() {
+        use ui::benchmark::RenderBenchmark;
+        use ui::renderers::RendererKind;
+        let fast = RenderBenchmark { dom_ms: 1.0, canvas_ms: 0.2 };
+        assert_eq!((fast.renderer(), fast.effect_intensity()), (RendererKind::Dom, 100));
+        // A slow DOM gives way to a clearly faster canvas, effects following
+        // the canvas' frame time.
+        let slow_dom = RenderBenchmark { dom_ms: 6.0, canvas_ms: 1.0 };
+        assert_eq!((slow_dom.renderer(), slow_dom.effect_intensity()), (RendererKind::Canvas, 100));
+        let close = RenderBenchmark { dom_ms: 5.0, canvas_ms: 4.0 };
+        assert_eq!((close.renderer(), close.effect_intensity()), (RendererKind::Dom, 50));
+        let slow = RenderBenchmark { dom_ms: 30.0, canvas_ms: 12.0 };
+        assert_eq!((slow.renderer(), slow.effect_intensity()), (RendererKind::Canvas, 0));
+
+        // New profiles pick automatically; settings saved before keep their
+        // choices.
+        assert!(Settings::default().auto_quality);
+        let saved: Settings = serde_json::from_str(r#"{"renderer":"Canvas"}"#).unwrap();
+        assert!(!saved.auto_quality && saved.benchmark.is_none());
+    }
+
+    #[test]
+    fn test_view_frames_carry_the_game() {
+        use crate::ui::views::{View, ViewFrame, NEXT_PIECES};
+
+        for view in View::ALL {
+            assert_eq!(View::from_id(view.id()), Some(view));
+        }
+        assert_eq!(View::from_id("board"), None);
+
+        let mut game = Tetris::new(10, 25);
+        game.set_upcoming_pieces(&["T", "O", "I", "L", "J", "S", "Z"]);
+        game.reset();
+        game.hold();
+        let frame = ViewFrame::of(&game);
+        assert_eq!(frame.held.as_deref(), game.held_piece());
+        assert_eq!(frame.next.len(), NEXT_PIECES);
+        assert_eq!(frame.next, game.upcoming_pieces().take(NEXT_PIECES).collect::<Vec<_>>());
+        assert_eq!(frame.board.len(), 25);
+        assert!(frame.board.iter().all(|row| row.chars().count() == 10));
+        // `elapsed_ms` is read off the system clock, and JSON may not give
+        // back its last bit.
+        let back: ViewFrame = serde_json::from_str(&serde_json::to_string(&frame).unwrap()).unwrap();
+        assert!((back.elapsed_ms - frame.elapsed_ms).abs() < 1e-6);
+        assert_eq!(ViewFrame { elapsed_ms: frame.elapsed_ms, ..back }, frame);
+    }
+
+    #[test]
+    fn test_energy_saver_draws_on_change_and_not_when_hidden() {
+        use ui::power::RenderPace;
+        assert_eq!(RenderPace::of(false, true), RenderPace::EveryFrame);
+        assert_eq!(RenderPace::of(true, false), RenderPace::OnChange);
+        assert_eq!(RenderPace::of(true, true), RenderPace::Paused);
+        assert!(RenderPace::OnChange.draws() && !RenderPace::Paused.draws());
+        assert_eq!(RenderPace::OnChange.clock_interval_ms(), TICK_MS as u64);
+        assert!(RenderPace::EveryFrame.clock_interval_ms() < RenderPace::OnChange.clock_interval_ms());
+    }
+
+    #[test]
+    fn test_replay_library_search_and_tags() {
+        use ui::replays::{search, ReplaySort};
+        use ui::storage::MAX_REPLAYS;
+        let game = |mode: &str, score, date_ms, duration_ms| SavedReplay {
+            mode: mode.to_string(),
+            score,
+            date_ms,
+            duration_ms,
+            ..Default::default()
+        };
+        let mut saved = SavedReplays::default();
+        let sprint = saved.add(game("sprint", 900, 1.0, 60_000.0));
+        let marathon = saved.add(game("marathon", 4000, 2.0, 300_000.0));
+        saved.add(game("marathon", 2500, 3.0, 120_000.0));
+        saved.add_tag(marathon, " Best ");
+        saved.add_tag(marathon, "best");
+        saved.add_tag(marathon, "");
+        assert_eq!(saved.replays[1].tags, ["Best"]);
+
+        let ids = |found: Vec<SavedReplay>| found.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(search(&saved, "", ReplaySort::Newest)), [2, 1, 0]);
+        assert_eq!(ids(search(&saved, "", ReplaySort::Score)), [1, 2, 0]);
+        assert_eq!(ids(search(&saved, "", ReplaySort::Duration)), [1, 2, 0]);
+        assert_eq!(ids(search(&saved, "MARA", ReplaySort::Oldest)), [1, 2]);
+        assert_eq!(ids(search(&saved, "marathon best", ReplaySort::Newest)), [1]);
+        assert!(search(&saved, "sprint best", ReplaySort::Newest).is_empty());
+
+        saved.remove_tag(marathon, "Best");
+        saved.remove(sprint);
+        assert_eq!(ids(search(&saved, "", ReplaySort::Oldest)), [1, 2]);
+
+        // A full library makes way for new games, keeping tagged ones.
+        saved.add_tag(marathon, "keep");
+        for n in 0..MAX_REPLAYS {
+            saved.add(game("sprint", 0, 10.0 + n as f64, 0.0));
+        }
+        assert_eq!(saved.replays.len(), MAX_REPLAYS);
+        assert_eq!(saved.replays[0].id, marathon);
+    }
+
+    #[test]
+    fn test_downstack_feed_and_garbage_score() {
+        use tetris_core::downstack::{GarbageFeed, MIN_INTERVAL_MS};
+        let mut steady = GarbageFeed::new(5000.0, false);
+        assert_eq!(steady.due(4999.0), 0);
+        assert_eq!(steady.due(15_000.0), 3);
+        assert_eq!((steady.rows_sent(), steady.interval_ms()), (3, 5000.0));
+
+        let mut quickening = GarbageFeed::new(2000.0, true);
+        quickening.due(2000.0);
+        assert!(quickening.interval_ms() < 2000.0);
+        quickening.due(120_000.0);
+        assert_eq!(quickening.interval_ms(), MIN_INTERVAL_MS);
+
+        // Only rows holding garbage count towards the score.
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clear_animation_ms(0.0);
+        tetris.spawn_piece("I");
+        tetris.receive_garbage(1, 4);
+        for x in 0..10 {
+            tetris.board.set(Position(x, 18), Some("O"));
+        }
+        tetris.board.set(Position(4, 19), Some("I"));
+        tetris.clear_lines();
+        assert_eq!((tetris.lines_cleared(), tetris.garbage_lines_cleared()), (2, 1));
+    }
+
+    #[test]
+    fn test_board_health_metrics() {
+        use tetris_core::metrics::BoardMetrics;
+        let mut board = Board::new(4, 10);
+        assert_eq!(BoardMetrics::of(&board).health(10), 100);
+
+        // A flat, even row is still perfectly healthy.
+        (0..4).for_each(|x| board.set(Position(x, 9), Some("I")));
+        let flat = BoardMetrics::of(&board);
+        assert_eq!((flat.holes, flat.max_height, flat.bumpiness, flat.parity), (0, 1, 0, 0));
+        assert_eq!(flat.health(10), 100);
+
+        // An overhang: one hole, a bump and a parity imbalance.
+        board.set(Position(0, 7), Some("T"));
+        let m = BoardMetrics::of(&board);
+        assert_eq!((m.holes, m.max_height, m.bumpiness, m.parity), (1, 3, 2, 1));
+        assert_eq!(m.health(10), 100 - 6 - 2 - 1);
+
+        // A tower to the top costs the full height penalty.
+        (0..10).for_each(|y| board.set(Position(3, y), Some("I")));
+        assert!(BoardMetrics::of(&board).health(10) < 50);
+    }
+
+    #[test]
+    fn test_misdrops_are_judged_against_other_placements() {
+        use tetris_core::metrics;
+        use tetris_core::stats::{self, GameStats, Misdrop};
+        let mut board = tetris_core::board::Board::new(10, 25);
+        board.set(Position(0, 24), Some("I"));
+        let o_over = |x: i32| {
+            metrics::placements(&board, "O").into_iter().find(|p| p.cells().any(|c| c == Position(x, 23))).unwrap()
+        };
+        // Overhanging the lone block leaves a hole that sliding right avoids.
+        assert_eq!(stats::judge(&board, &o_over(0)), Some(Misdrop::Hole));
+        assert_eq!(stats::judge(&board, &o_over(2)), None);
+        assert!(metrics::placements(&board, "I").iter().all(|p| board.drop_distance(p) == 0));
+
+        let mut game = GameStats::default();
+        assert_eq!(game.accuracy(), None);
+        for event in [
+            GameEvent::PieceLocked { kind: "O" },
+            GameEvent::Misdrop(Misdrop::Hole),
+            GameEvent::PieceLocked { kind: "T" },
+            GameEvent::PieceLocked { kind: "I" },
+            GameEvent::PieceLocked { kind: "L" },
+        ] {
+            game.observe(&event);
+        }
+        assert_eq!((game.pieces, game.misdrops()), (4, 1));
+        assert_eq!(game.accuracy(), Some(0.75));
+    }
+
+    #[test]
+    fn test_placements_log_every_lock() {
+        use tetris_core::placements::PlacementLog;
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        (4..10).for_each(|x| tetris.board.set(Position(x, 24), Some("O")));
+        tetris.spawn_piece("I");
+        tetris.apply(Action::Rotate);
+        // The last steps left bump into the wall but still count as inputs.
+        (0..5).for_each(|_| tetris.apply(Action::MoveLeft));
+        tetris.advance_clock(500.0);
+        tetris.apply(Action::HardDrop);
+
+        let mut log = PlacementLog::default();
+        tetris.drain_events().iter().for_each(|e| log.observe(e));
+        let [p] = log.placements[..] else { panic!("expected one placement, got {:?}", log.placements) };
+        // Positions are the piece origin, which may sit outside its cells.
+        let left = -Tetromino::bounding_box("I", 1).0 .0;
+        assert_eq!((p.piece, p.kind, p.x, p.rotation), (1, "I", left, 1));
+        assert_eq!((p.time_ms, p.inputs, p.lines), (500.0, 7, 1));
+        assert!(p.health_before < 100 && p.health_after == 100);
+
+        let csv = log.to_csv();
+        assert!(csv.starts_with("piece,kind,x,y,rotation,time_ms,inputs,lines,health_before,health_after\n"));
+        assert_eq!(csv.lines().nth(1), Some(format!("1,I,{},{},1,500,7,1,{},100", left, p.y, p.health_before).as_str()));
+        let json: serde_json::Value = serde_json::from_str(&log.to_json()).unwrap();
+        assert_eq!(json[0]["inputs"], 7);
+        assert_eq!(GameEvent::Placed(p).to_json()["type"], "Placed");
+    }
+
+    #[test]
+    fn test_review_snapshots_key_moments() {
+        use tetris_core::review::{MomentKind, Review};
+        use tetris_core::stats::Misdrop;
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        for y in 21..25 {
+            (1..10).for_each(|x| tetris.board.set(Position(x, y), Some("O")));
+        }
+        tetris.spawn_piece("I");
+        (0..5).for_each(|_| tetris.apply(Action::MoveLeft));
+        tetris.apply(Action::HardDrop);
+
+        let mut review = Review::default();
+        for event in tetris.drain_events() {
+            review.observe(&event, &tetris);
+        }
+        review.observe(&GameEvent::Misdrop(Misdrop::Hole), &tetris);
+        review.observe(&GameEvent::GameOver { score: 0 }, &tetris);
+        let kinds: Vec<MomentKind> = review.moments.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, [MomentKind::Tetris, MomentKind::Misdrop(Misdrop::Hole), MomentKind::TopOut]);
+        // The Tetris is caught before its rows collapse.
+        let tetris_moment = &review.moments[0];
+        assert_eq!((tetris_moment.piece, review.pieces), (1, 1));
+        assert!(tetris_moment.board[21..].iter().all(|row| row.iter().all(|&c| c != "B")));
+    }
+
+    #[test]
+    fn test_zone_freezes_gravity_and_scores_at_the_end() {
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_clear_animation_ms(0.0);
+        assert!(!tetris.activate_zone());
+        tetris.set_zone(true);
+        let tetris_clear = |tetris: &mut Tetris| {
+            for y in 21..25 {
+                (1..10).for_each(|x| tetris.board.set(Position(x, y), Some("O")));
+            }
+            tetris.spawn_piece("I");
+            (0..5).for_each(|_| tetris.apply(Action::MoveLeft));
+            tetris.apply(Action::HardDrop);
+        };
+        tetris_clear(&mut tetris);
+        assert_eq!((tetris.zone().unwrap().meter(), tetris.score_breakdown.lines), (0.25, 4));
+
+        tetris.drain_events();
+        tetris.apply(Action::Zone);
+        assert_eq!(tetris.drain_events(), [GameEvent::ZoneStarted { duration_ms: 2000.0 }, GameEvent::Input(Action::Zone)]);
+        let y = tetris.current_tetromino.as_ref().unwrap().data.position.1;
+        tetris.apply(Action::Tick);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position.1, y);
+        // Lines cleared in the zone wait for it to end, then score double.
+        tetris_clear(&mut tetris);
+        assert_eq!((tetris.zone().unwrap().lines(), tetris.score_breakdown.lines), (4, 4));
+        tetris.advance_clock(2000.0);
+        tetris.drain_events();
+        tetris.apply(Action::Tick);
+        assert!(tetris.drain_events().contains(&GameEvent::ZoneEnded { lines: 4 }));
+        assert_eq!(tetris.score_breakdown.lines, 12);
+        assert!(!tetris.zone().unwrap().is_active() && tetris.zone().unwrap().meter() == 0.0);
+
+        tetris.reset();
+        assert!(tetris.zone().is_some());
+    }
+
+    #[test]
+    fn test_fairness_audit_measures_generators() {
+        use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+        use tetris_core::fairness::{audit, UnknownKind};
+        let mut rng = StdRng::seed_from_u64(7);
+        let random = audit(70_000, || pieces::KINDS[rng.random_range(0..7)]).unwrap();
+        assert_eq!(random.counts.iter().sum::<u64>(), 70_000);
+        assert!(random.counts.iter().all(|&n| (9000..11_000).contains(&n)));
+        assert!((random.mean_drought - 6.0).abs() < 0.2 && (random.repeat_rate - 1.0 / 7.0).abs() < 0.01);
+        assert!(random.bag_integrity < 0.02 && random.flood_rate > 0.1);
+        assert!(random.longest_drought.iter().all(|&n| n > 40));
+
+        // A 7-bag deals whole bags, so no kind waits longer than 12 pieces.
+        let mut bag = Vec::new();
+        let bagged = audit(70_000, || {
+            if bag.is_empty() {
+                bag = pieces::KINDS.to_vec();
+                bag.shuffle(&mut rng);
+            }
+            bag.pop().unwrap()
+        })
+        .unwrap();
+        assert_eq!((bagged.bag_integrity, bagged.flood_rate), (1.0, 0.0));
+        assert!(bagged.longest_drought.iter().all(|&n| n <= 12));
+        assert!(bagged.to_csv().contains("bag_integrity,1.00000\n"));
+
+        // A kind outside `pieces::KINDS` is reported, not counted.
+        let mut dealt = ["I", "T", "X"].into_iter();
+        let err = audit(5, || dealt.next().unwrap()).unwrap_err();
+        assert_eq!(err, UnknownKind { kind: "X", dealt: 2 });
+        assert_eq!(err.to_string(), "unknown piece \"X\" after 2 pieces");
+    }
+
+    #[test]
+    fn test_match_generators_follow_each_stream() {
+        use tetris_core::generator::{MatchGenerators, PieceRng, PieceStream};
+        use tetris_core::replay::Replay;
+        let mut generators = MatchGenerators::new(9, &[0, 0, 1]);
+        assert_eq!(generators.rng(0), generators.rng(1));
+        assert_ne!(generators.rng(0), generators.rng(2));
+        assert_eq!(generators.rng(2), PieceRng::derived(9, 1));
+        assert_ne!(PieceRng::derived(9, 1), PieceRng::derived(10, 1));
+
+        // Games dealing from their generators stay on their streams.
+        let mut games: Vec<Tetris> = (0..3).map(|p| Replay::deal_from(1, generators.rng(p)).start()).collect();
+        for _ in 0..20 {
+            for (p, game) in games.iter_mut().enumerate() {
+                game.apply(Action::HardDrop);
+                game.board.clear();
+                generators.check(p, game.dealt_digest()).unwrap();
+            }
+        }
+        let (a, b, c) = (generators.stream(0), generators.stream(1), generators.stream(2));
+        assert_eq!((a.dealt(), a.digest()), (b.dealt(), b.digest()));
+        assert_ne!(a.digest(), c.digest());
+        let mut replayed = PieceStream::new(PieceRng::derived(9, 1));
+        (0..21).for_each(|_| { replayed.deal(); });
+        assert_eq!(replayed, *c);
+
+        // One reseeded game is caught at the first piece its stream
+        // wouldn't have dealt.
+        games[2].set_piece_rng(PieceRng::from_state(1));
+        let mismatch = (0..7)
+            .find_map(|_| {
+                games[2].apply(Action::HardDrop);
+                games[2].board.clear();
+                generators.check(2, games[2].dealt_digest()).err()
+            })
+            .expect("the reseeded game goes unnoticed");
+        assert_eq!(mismatch.player, 2);
+        assert_eq!(mismatch.found, games[2].dealt_digest());
+        assert_ne!(mismatch.expected, mismatch.found);
+    }
+
+    #[test]
+    fn test_piece_bag_deals_every_kind_once_per_bag() {
+        use tetris_core::curriculum::Curriculum;
+        use tetris_core::fairness::audit;
+        use tetris_core::generator::{PieceBag, PieceRng, Randomizer};
+        use tetris_core::replay::Replay;
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_piece_rng(PieceRng::from_state(3));
+        tetris.reset();
+        assert_eq!(tetris.randomizer(), Randomizer::Bag);
+        // The rest of the bag is already known.
+        assert_eq!(tetris.upcoming_pieces().count(), 6);
+        let mut dealt = Vec::new();
+        for _ in 0..21 {
+            dealt.push(tetris.current_tetromino.as_ref().unwrap().kind);
+            tetris.board.clear();
+            tetris.apply(Action::HardDrop);
+        }
+        for bag in dealt.chunks(7) {
+            let mut bag = bag.to_vec();
+            bag.sort();
+            let mut kinds = pieces::KINDS.to_vec();
+            kinds.sort();
+            assert_eq!(bag, kinds);
+        }
+
+        // Bags only hold the kinds the curriculum allows.
+        let mut bag = PieceBag::default();
+        let mut rng = PieceRng::from_state(1);
+        let curriculum = Curriculum { piece_mask: Curriculum::mask_of("IT"), ..Default::default() };
+        let mut two: Vec<_> = (0..2).map(|_| bag.deal(&curriculum, &mut rng)).collect();
+        two.sort();
+        assert_eq!(two, ["I", "T"]);
+
+        let mut bag = PieceBag::default();
+        let bagged = audit(7_000, || bag.deal(&Curriculum::default(), &mut rng)).unwrap();
+        assert_eq!((bagged.bag_integrity, bagged.flood_rate), (1.0, 0.0));
+        let mut classic = PieceBag::new(Randomizer::Classic);
+        let random = audit(7_000, || classic.deal(&Curriculum::default(), &mut rng)).unwrap();
+        assert!(random.bag_integrity < 0.05 && random.flood_rate > 0.1);
+
+        // Classic mode carries over resets and draws pieces only as needed.
+        tetris.set_randomizer(Randomizer::Classic);
+        tetris.reset();
+        assert_eq!((tetris.randomizer(), tetris.upcoming_pieces().count()), (Randomizer::Classic, 0));
+
+        // Replays say how they deal; ones without a randomizer line are classic.
+        let replay = Replay::deal_from(3, PieceRng::from_state(5));
+        assert_eq!((replay.pieces.len(), replay.randomizer), (7, Randomizer::Bag));
+        assert_eq!(Replay::parse(&replay.to_string()).unwrap(), replay);
+        assert_eq!(Replay::parse("pieces IO\nrng 05\n").unwrap().randomizer, Randomizer::Classic);
+        assert!(Replay::parse("pieces IO\nrandomizer nes\n").is_err());
+    }
+
+    #[test]
+    fn test_piece_rng_state_restores_future_pieces() {
+        use tetris_core::generator::PieceRng;
+        use tetris_core::replay::Replay;
+        let deal = |tetris: &mut Tetris| -> Vec<&'static str> {
+            (0..20)
+                .map(|_| {
+                    tetris.board.clear();
+                    tetris.apply(Action::HardDrop);
+                    tetris.current_tetromino.as_ref().unwrap().kind
+                })
+                .collect()
+        };
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_piece_rng(PieceRng::from_state(9));
+        tetris.reset();
+        let first = tetris.current_tetromino.as_ref().unwrap().kind;
+        let (saved, bag) = (tetris.piece_rng(), tetris.upcoming_pieces().collect::<Vec<_>>());
+        let ahead = deal(&mut tetris);
+        // Rolling back to the saved state and the rest of the bag deals the
+        // same pieces again.
+        tetris.set_piece_rng(saved);
+        tetris.set_upcoming_pieces(&bag);
+        assert_eq!(deal(&mut tetris), ahead);
+        // So does seeding a new game the same way.
+        let mut other = Tetris::new(10, 25);
+        other.set_piece_rng(PieceRng::from_state(9));
+        other.reset();
+        assert_eq!(other.current_tetromino.as_ref().unwrap().kind, first);
+        assert_eq!(deal(&mut other), ahead);
+
+        let state: PieceRng = serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        assert_eq!((state, saved.to_string().parse::<PieceRng>().unwrap()), (saved, saved));
+
+        // Replays keep the generator that deals on after their pieces.
+        let replay = Replay::deal_from(3, PieceRng::from_state(5));
+        let parsed = Replay::parse(&replay.to_string()).unwrap();
+        assert_eq!(parsed, replay);
+        let mut a = replay.start();
+        let mut b = parsed.start();
+        assert_eq!(deal(&mut a), deal(&mut b));
+        assert!(Replay::parse("pieces IO\nrng xyz\n").is_err());
+    }
+
+    #[test]
+    fn test_pace_projects_runs_on_each_clear() {
+        use tetris_core::stats::{Horizon, Pace, Projection, Trend};
+        let clear = GameEvent::LinesCleared { count: 2 };
+        let mut marathon = Pace::new(Horizon::Lines(150));
+        assert!(!marathon.observe(&GameEvent::PieceLocked { kind: "I" }, 1000.0, 2, 300));
+        assert_eq!(marathon.projection(), None);
+        assert!(marathon.observe(&clear, 10_000.0, 10, 1000));
+        assert_eq!((marathon.projection(), marathon.trend()), (Some(Projection { lines: 150, score: 15_000 }), Trend::Flat));
+        assert!(marathon.observe(&clear, 20_000.0, 20, 2400));
+        assert_eq!((marathon.projection().unwrap().score, marathon.trend()), (18_000, Trend::Up));
+        marathon.observe(&clear, 30_000.0, 30, 2700);
+        assert_eq!(marathon.trend(), Trend::Down);
+
+        // Ultra scales lines and score to the full three minutes.
+        let mut ultra = Pace::new(Horizon::Time(180_000.0));
+        ultra.observe(&clear, 60_000.0, 12, 2000);
+        assert_eq!(ultra.projection(), Some(Projection { lines: 36, score: 6000 }));
+    }
+
+    #[test]
+    fn test_spawn_orientations_apply_per_piece() {
+        use tetris_core::pieces::SpawnOrientations;
+        let mut tetris = Tetris::new(10, 25);
+        let mut orientations = SpawnOrientations::default();
+        orientations.set("I", 5);
+        assert_eq!((orientations.of("I"), orientations.of("T")), (1, 0));
+        tetris.set_spawn_orientations(orientations);
+
+        // A horizontal I, centered on the top row.
+        tetris.spawn_piece("I");
+        let cells = tetris.current_tetromino.as_ref().unwrap().collect_positions();
+        assert!(cells.iter().all(|p| p.1 == 0));
+        assert_eq!(cells.iter().map(|p| p.0).min(), Some(3));
+        // Rotation carries on from the spawn state, once there is room.
+        tetris.apply(Action::SoftDrop);
+        tetris.apply(Action::SoftDrop);
+        tetris.apply(Action::Rotate);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().rotation, 2);
+
+        tetris.reset();
+        tetris.spawn_piece("T");
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().rotation, 0);
+        assert_eq!(tetris.spawned("I").rotation, 1);
+    }
+
+    #[test]
+    fn test_garbage_patterns_place_holes() {
+        use rand::{rngs::StdRng, SeedableRng};
+        use tetris_core::garbage::{GarbageHoles, GarbagePattern, GarbageStyle};
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut clean = GarbageHoles::new(GarbageStyle { pattern: GarbagePattern::Clean, holes: 1, streak: 4 });
+        let rows = [clean.rows(3, 10, &mut rng), clean.rows(5, 10, &mut rng)].concat();
+        assert!(rows[..4].iter().all(|r| *r == rows[0]));
+        assert!(rows[4..].iter().all(|r| *r == rows[4]));
+
+        let mut cheese = GarbageHoles::new(GarbageStyle { pattern: GarbagePattern::Cheese, holes: 2, streak: 4 });
+        let rows = cheese.rows(20, 10, &mut rng);
+        assert!(rows.iter().all(|r| r.len() == 2 && r[0] < r[1]));
+        assert!(rows.windows(2).all(|w| w[1].iter().all(|x| !w[0].contains(x))));
+
+        let mut tetris = Tetris::new(10, 25);
+        tetris.receive_garbage_rows(&[vec![0, 9], vec![4]]);
+        let open: Vec<Vec<usize>> =
+            tetris.board.rows().skip(23).map(|r| (0..r.len()).filter(|&x| r[x].is_none()).collect()).collect();
+        assert_eq!(open, [vec![0, 9], vec![4]]);
+    }
+
+    #[test]
+    fn test_queued_garbage_is_countered_and_delayed() {
+        use tetris_core::garbage::GarbageQueue;
+        use ui::garbage_meter::MeterReading;
+        let mut queue = GarbageQueue::new(2000.0);
+        queue.push(2, 0.0);
+        queue.push(3, 500.0);
+        assert_eq!(queue.pending(), 5);
+        // Three rows of attack cancel the first batch and one row of the next.
+        assert_eq!(queue.counter(3, 100), 0);
+        assert_eq!(queue.batches().iter().map(|b| b.rows).collect::<Vec<_>>(), [2]);
+        assert_eq!(queue.counter(4, 100), 2);
+        assert_eq!(queue.pending(), 0);
+        // At 50% it takes two rows of attack to cancel one.
+        queue.push(2, 1000.0);
+        assert_eq!(queue.counter(3, 50), 1);
+        assert_eq!(queue.pending(), 1);
+        assert_eq!(queue.counter(4, 0), 4);
+        // Garbage is telegraphed until its delay runs out.
+        assert_eq!(queue.countdown_ms(1500.0), Some(1500.0));
+        assert_eq!(MeterReading::of(&queue, 1500.0), MeterReading { rows: 1, countdown_ms: Some(1500.0) });
+        assert!(queue.take_arrived(2999.0).is_empty());
+        assert_eq!(queue.take_arrived(3000.0), [1]);
+        assert_eq!((queue.pending(), queue.countdown_ms(3000.0)), (0, None));
+        assert_eq!(MeterReading::of(&queue, 3000.0), MeterReading::default());
+    }
+
+    #[test]
+    fn test_close_call_slows_the_game_clock() {
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_slow_motion(true);
+        tetris.set_clear_animation_ms(500.0);
+        // A tower one row into the danger zone, standing on a full row.
+        for y in 4..24 {
+            tetris.board.set(Position(0, y), Some("I"));
+        }
+        for x in 0..10 {
+            tetris.board.set(Position(x, 24), Some("I"));
+        }
+        tetris.clear_lines();
+        assert!(tetris.drain_events().contains(&GameEvent::CloseCall));
+        assert!(tetris.is_slow_motion());
+
+        // Game time, and so the clear animation, runs at half speed for a
+        // second of real time, then catches up to normal speed.
+        tetris.advance_clock(400.0);
+        assert_eq!(tetris.now_ms(), 200.0);
+        assert_eq!(tetris.clear_animation_progress(), Some(0.4));
+        tetris.advance_clock(600.0);
+        assert!(!tetris.is_slow_motion());
+        tetris.advance_clock(100.0);
+        assert_eq!(tetris.now_ms(), 600.0);
+        tetris.tick();
+        assert_eq!(tetris.board.max_height(), 20);
+
+        // Clearing from a safe height is no close call.
+        for x in 1..10 {
+            tetris.board.set(Position(x, 24), Some("I"));
+        }
+        tetris.clear_lines();
+        assert!(!tetris.drain_events().contains(&GameEvent::CloseCall));
+        assert!(!tetris.is_slow_motion());
+    }
+
+    #[test]
+    fn test_time_scale_runs_game_time_uniformly() {
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_time_scale(0.5);
+        tetris.advance_clock(1000.0);
+        assert_eq!(tetris.now_ms(), 500.0);
+        assert_eq!(tetris.gravity_interval_ms(1000.0), 2000.0);
+        // Play time is still real time (plus whatever the system clock ran
+        // before the manual one took over).
+        assert!((1000.0..1100.0).contains(&tetris.elapsed_ms()));
+
+        // Changing speed keeps game time continuous; the range is clamped.
+        tetris.set_time_scale(10.0);
+        assert_eq!(tetris.time_scale(), 2.0);
+        tetris.advance_clock(100.0);
+        assert_eq!(tetris.now_ms(), 700.0);
+        tetris.set_time_scale(0.0);
+        assert_eq!(tetris.time_scale(), 0.25);
+
+        tetris.reset();
+        assert_eq!(tetris.time_scale(), 0.25);
+        let start = tetris.now_ms();
+        tetris.advance_clock(400.0);
+        assert_eq!(tetris.now_ms() - start, 100.0);
+    }
+
+    #[test]
+    fn test_frame_step_runs_one_update_while_paused() {
+        use ui::inspector::{deltas, Snapshot};
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.spawn_piece("T");
+        tetris.pause();
+        let y = tetris.current_tetromino.as_ref().unwrap().data.position.1;
+        tetris.tick();
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position.1, y);
+
+        let before = Snapshot::of(&tetris);
+        tetris.advance_clock(16.0);
+        tetris.step();
+        let after = Snapshot::of(&tetris);
+        assert!(tetris.is_paused());
+        assert_eq!(after.piece.unwrap().1 .1, y + 1);
+        let lines = deltas(&before, &after);
+        assert_eq!(lines[0], "Δt +16.0 ms");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("piece T"));
+    }
+
+    #[test]
+    fn test_bot_steers_pieces_to_cheap_spots() {
+        use tetris_core::bot::{self, Bot};
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_clear_animation_ms(0.0);
+        tetris.spawn_piece("I");
+        // A lone I lies flat on an empty board.
+        let target = bot::best_placement(&tetris, &Default::default()).unwrap();
+        assert!(target.cells().all(|p| p.1 == 24));
+
+        let kinds: Vec<&'static str> = (0..100).map(|i| pieces::KINDS[(i * 5 + i / 7) % 7]).collect();
+        tetris.set_upcoming_pieces(&kinds);
+        let mut bot = Bot::default();
+        while tetris.pieces_placed() < 100 && !tetris.lost {
+            bot.act(&mut tetris);
+        }
+        assert!(!tetris.lost);
+        assert!(tetris.lines_cleared() >= 30);
+    }
+
+    #[test]
+    fn test_renderers_draw_the_same_frame() {
+        use tetris_core::render::{self, TextRenderer};
+        use ui::renderers::DomRenderer;
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        let mut text = TextRenderer::default();
+        render::draw(&tetris, &mut text);
+        assert_eq!(text.text(), tetris.render());
+
+        for x in 0..10 {
+            tetris.board.set(Position(x, 24), Some("I"));
+        }
+        tetris.clear_lines();
+        tetris.pause();
+        let mut dom = DomRenderer::default();
+        render::draw(&tetris, &mut dom);
+        let frame = dom.into_frame();
+        assert_eq!(frame.cells, tetris.render_view());
+        assert_eq!(frame.clearing, vec![24]);
+        render::draw(&tetris, &mut text);
+        assert_eq!(text.text(), format!("{}\nPAUSED", tetris.render()));
+    }
+
+    #[test]
+    fn test_simulation_plays_every_game() {
+        let mut simulation = tetris_core::simulation::Simulation::new(3, 2);
+        assert_eq!(simulation.games().count(), 2);
+        while !simulation.is_done() {
+            simulation.step();
+        }
+        let summary = simulation.summary();
+        assert_eq!(summary.games, 3);
+        assert!(summary.pieces >= 3 && summary.pieces <= 3 * tetris_core::simulation::MAX_PIECES as u64);
+        assert!(summary.average_score().unwrap() <= summary.best_score as f64);
+    }
+
+    #[test]
+    fn test_replay_reruns_a_simulated_game() {
+        let mut simulation = tetris_core::simulation::Simulation::new(1, 1);
+        simulation.keep_replays();
+        while !simulation.is_done() {
+            simulation.step();
+        }
+        let summary = simulation.summary();
+        let replays = simulation.take_replays();
+        assert_eq!(replays.len(), 1);
+        let replay = tetris_core::replay::Replay::parse(&replays[0].to_string()).unwrap();
+        assert_eq!(replay, replays[0]);
+        let mut locks = 0;
+        let game = replay.play(|_, events| {
+            locks += events.iter().filter(|e| matches!(e, GameEvent::PieceLocked { .. })).count() as u64;
+        });
+        assert_eq!((game.get_score(), locks), (summary.best_score, summary.pieces));
+
+        let mut analysis = tetris_core::analysis::Analysis::default();
+        analysis.add(&replay);
+        assert_eq!(analysis.games, 1);
+        assert_eq!(analysis.misdrops.pieces as u64, summary.pieces);
+        let lines: u64 = analysis.clears.iter().enumerate().map(|(i, &n)| (i as u64 + 1) * n as u64).sum();
+        assert_eq!(lines, summary.lines);
+        assert!(analysis.health_over_time().iter().all(|&(_, h)| (0.0..=100.0).contains(&h)));
+        assert!(tetris_core::replay::Replay::parse("0 Tick").is_err());
+    }
+
+    #[test]
+    fn test_input_sources_apply_in_time_order() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use tetris_core::bot::Bot;
+        use tetris_core::input::{self, ActionQueue, InputSource};
+        use tetris_core::replay::Replay;
+
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(clock::Clock::Manual(0.0));
+        tetris.spawn_piece("O");
+        let x = |t: &Tetris| t.current_tetromino.as_ref().unwrap().data.position.0;
+        let spawn_x = x(&tetris);
+        let (keys, touch) = (Rc::new(RefCell::new(ActionQueue::default())), Rc::new(RefCell::new(ActionQueue::default())));
+        keys.borrow_mut().push(5.0, Action::MoveLeft);
+        keys.borrow_mut().push(30.0, Action::MoveLeft);
+        touch.borrow_mut().push(10.0, Action::MoveRight);
+        let mut sources: Vec<Box<dyn InputSource>> = vec![Box::new(keys.clone()), Box::new(touch)];
+        let applied = input::apply_sources(&mut tetris, &mut sources, 20.0);
+        assert_eq!(applied, vec![(5.0, Action::MoveLeft), (10.0, Action::MoveRight)]);
+        assert_eq!(x(&tetris), spawn_x);
+        assert_eq!(input::apply_sources(&mut tetris, &mut sources, 30.0), vec![(30.0, Action::MoveLeft)]);
+        assert_eq!(x(&tetris), spawn_x - 1);
+
+        // A bot is a source too, and so is a replay: playing one back through
+        // the loop rebuilds the recorded game.
+        let mut replay = Replay::deal(100);
+        let mut recorded = replay.start();
+        let mut bot: Vec<Box<dyn InputSource>> = vec![Box::new(Bot::default())];
+        for step in 0..200 {
+            recorded.advance_clock(10.0);
+            let now = recorded.now_ms();
+            for (at, action) in input::apply_sources(&mut recorded, &mut bot, now) {
+                replay.record(at, action);
+            }
+            if step % 4 == 3 {
+                replay.record(recorded.now_ms(), Action::Tick);
+                recorded.apply(Action::Tick);
+            }
+        }
+        assert!(recorded.pieces_placed() > 5);
+        let mut rerun = replay.start();
+        let mut playback: Vec<Box<dyn InputSource>> = vec![Box::new(replay.playback())];
+        for _ in 0..200 {
+            rerun.advance_clock(10.0);
+            let now = rerun.now_ms();
+            input::apply_sources(&mut rerun, &mut playback, now);
+        }
+        assert_eq!(rerun.render_view(), recorded.render_view());
+        assert_eq!(rerun.get_score(), recorded.get_score());
+    }
+
+    #[test]
+    fn test_highlights_jump_into_replays() {
+        use tetris_core::highlights::{self, HighlightKind, LEAD_IN_MS};
+        use tetris_core::replay::ReplayCursor;
+        // Ten I pieces, which spawn upright, one per column a second apart:
+        // the last one clears four rows at once.
+        let mut replay = Replay { pieces: vec!["I"; 12], ..Default::default() };
+        let mut game = replay.start();
+        for column in 0..10 {
+            let at = column as f64 * 1000.0;
+            let mut play = |game: &mut Tetris, action| {
+                replay.record(at, action);
+                game.advance_clock(at - game.now_ms());
+                game.apply(action);
+            };
+            let x = game.current_tetromino.as_ref().unwrap().cells().map(|p| p.0).min().unwrap();
+            let shift = if x < column { Action::MoveRight } else { Action::MoveLeft };
+            for _ in 0..x.abs_diff(column) {
+                play(&mut game, shift);
+            }
+            play(&mut game, Action::HardDrop);
+        }
+        // Clears finish on the next tick.
+        replay.record(9000.0, Action::Tick);
+        game.apply(Action::Tick);
+        assert_eq!(game.lines_cleared(), 4);
+
+        let found = highlights::find(&replay);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].kind, found[0].at_ms), (HighlightKind::Tetris, 9000.0));
+        let start = found[0].start;
+        assert!(replay.inputs[start].0 >= 9000.0 - LEAD_IN_MS && replay.inputs[start - 1].0 < 9000.0 - LEAD_IN_MS);
+
+        // The viewer's cursor seeks both ways to the same boards.
+        let mut cursor = ReplayCursor::new(replay.clone());
+        cursor.seek(usize::MAX);
+        assert!(cursor.is_finished());
+        assert_eq!(cursor.game().render_view(), game.render_view());
+        cursor.seek(start);
+        let before = cursor.game().render_view();
+        assert_eq!((cursor.position(), cursor.game().lines_cleared()), (start, 0));
+        cursor.play_until(9000.0);
+        assert!(cursor.is_finished());
+        assert_eq!(cursor.game().lines_cleared(), 4);
+        cursor.seek(start);
+        assert_eq!(cursor.game().render_view(), before);
+    }
+
+    #[test]
+    fn test_series_streams_deal_matching_pieces() {
+        use tetris_core::generator::MatchGenerators;
+        use tetris_core::replay::Replay;
+        use tetris_core::versus::{SeedPolicy, Series};
+
+        // Plays `pieces` hard drops per side of game `game`, checking each
+        // side against its stream, and returns both streams' digests.
+        let play = |series: Series, game: u32, pieces: u32| {
+            let mut generators = MatchGenerators::new(series.seed, &series.streams(game));
+            for side in 0..2 {
+                let mut tetris = Replay::deal_from(1, generators.rng(side)).start();
+                for _ in 0..pieces {
+                    tetris.apply(Action::HardDrop);
+                    tetris.board.clear();
+                    generators.check(side, tetris.dealt_digest()).unwrap();
+                }
+            }
+            [0, 1].map(|side| generators.stream(side).digest())
+        };
+        let shared = Series { best_of: 3, seed: 11, policy: SeedPolicy::Shared, handicap: [0, 0] };
+        let [first, second] = play(shared, 0, 30);
+        assert_eq!(first, second);
+        assert_eq!(play(shared, 1, 30), [first, second]);
+        let swapped = Series { policy: SeedPolicy::Swapped, ..shared };
+        let [a, b] = play(swapped, 0, 30);
+        assert_ne!(a, b);
+        assert_eq!(play(swapped, 1, 30), [b, a]);
+
+        // Holding deals the next piece early, but from the stream; a game
+        // whose pieces are swapped out is caught at the first one.
+        let mut generators = MatchGenerators::new(shared.seed, &shared.streams(0));
+        let mut tetris = Replay::deal_from(1, generators.rng(0)).start();
+        tetris.hold();
+        generators.check(0, tetris.dealt_digest()).unwrap();
+        let next = tetris.upcoming_pieces().next().unwrap();
+        let other = pieces::KINDS.into_iter().find(|&k| k != next).unwrap();
+        tetris.set_upcoming_pieces(&[other]);
+        tetris.apply(Action::HardDrop);
+        let mismatch = generators.check(0, tetris.dealt_digest()).unwrap_err();
+        assert_eq!((mismatch.player, mismatch.dealt), (0, 3));
+        assert_ne!(mismatch.expected, mismatch.found);
+    }
+
+    #[test]
+    fn test_versus_swaps_sides_per_seed() {
+        use rand::{rngs::StdRng, SeedableRng};
+        use tetris_core::bot::Weights;
+        use tetris_core::generator::MatchGenerators;
+        use tetris_core::versus::{self, Outcome, SeedPolicy, Series, Tally};
+        assert_eq!((1..=4).map(versus::attack).collect::<Vec<_>>(), [0, 1, 2, 4]);
+        assert_eq!("40,3,4,1".parse::<Weights>(), Ok(Weights::default()));
+        assert!("40,3".parse::<Weights>().is_err());
+
+        // Identical bots mirror each other, so each seed is one win and one loss.
+        let tally = versus::evaluate(Weights::default(), Weights::default(), 2, 7).unwrap();
+        assert_eq!(tally.wins, tally.losses);
+        assert_eq!(tally.matches(), 2);
+
+        // Rematches replay the same sequence; swapped seeds trade sequences
+        // every other game.
+        let shared = Series { best_of: 1, seed: 7, policy: SeedPolicy::Shared, handicap: [0, 0] };
+        assert_eq!((shared.streams(0), shared.streams(3), shared.wins_needed()), ([0, 0], [0, 0], 1));
+        assert_eq!(shared.play(Weights::default(), Weights::default()), Ok(vec![versus::play_match(Weights::default(), Weights::default(), 7).unwrap()]));
+        let swapped = Series { best_of: 5, seed: 10, policy: SeedPolicy::Swapped, handicap: [0, 0] };
+        assert_eq!((0..4).map(|g| swapped.streams(g)).collect::<Vec<_>>(), [[0, 1], [1, 0], [2, 3], [3, 2]]);
+        assert_eq!(swapped.wins_needed(), 3);
+
+        // A board nearly full of garbage loses straight away.
+        let handicap = versus::play_seeded(Weights::default(), Weights::default(), 3, [0, 0], [0, 22]);
+        assert_eq!(handicap, Ok(Outcome::FirstWins));
+
+        // The handicapped side starts on its garbage, with the same pieces.
+        let generators = MatchGenerators::new(3, &[0, 0]);
+        let [even, handicapped] = versus::start_games(&generators, [0, 4], &mut StdRng::seed_from_u64(3));
+        let garbage_rows = |game: &Tetris| game.render_view().iter().filter(|row| row.contains(&GARBAGE_KIND)).count();
+        assert_eq!((garbage_rows(&even), garbage_rows(&handicapped)), (0, 4));
+        assert_eq!(even.current_tetromino.unwrap().kind, handicapped.current_tetromino.unwrap().kind);
+        let series = Series { best_of: 3, seed: 3, policy: SeedPolicy::Shared, handicap: [0, 22] };
+        assert_eq!(series.play(Weights::default(), Weights::default()), Ok(vec![Outcome::FirstWins; 2]));
+
+        let tally = Tally { wins: 7, losses: 2, draws: 1 };
+        assert_eq!(tally.score(), Some(0.75));
+        let (low, high) = tally.confidence_interval().unwrap();
+        assert!(low < 0.75 && 0.75 < high && low > 0.4 && high < 0.95);
+        assert_eq!(Tally::default().confidence_interval(), None);
+    }
+
+    #[test]
+    fn test_lobby_targeting_picks_the_expected_opponent() {
+        use std::collections::BTreeSet;
+        use tetris_core::lobby::{Lobby, Targeting};
+
+        // Four humans, so nothing moves unless the test says so.
+        let mut lobby = Lobby::new(&[None; 4], Targeting::Leader, 5);
+        for (i, score, badges) in [(1, 300, 3), (2, 500, 0), (3, 100, 3)] {
+            let p = lobby.player_mut(i).unwrap();
+            p.tetris.score = score;
+            p.badges = badges;
+        }
+        assert_eq!(lobby.target(0), Some(2));
+        // The most badges, with the higher score taking a tie.
+        lobby.set_targeting(0, Targeting::Badges);
+        assert_eq!(lobby.target(0), Some(1));
+        // The last attacker while they are alive, a random opponent after.
+        lobby.set_targeting(0, Targeting::Attacker);
+        lobby.player_mut(0).unwrap().last_attacker = Some(3);
+        assert_eq!(lobby.target(0), Some(3));
+        lobby.player_mut(3).unwrap().tetris.lost = true;
+        assert!(matches!(lobby.target(0), Some(1 | 2)));
+        // Random never picks the attacker or the eliminated.
+        lobby.set_targeting(0, Targeting::Random);
+        let picked: BTreeSet<_> = (0..50).filter_map(|_| lobby.target(0)).collect();
+        assert_eq!(picked, BTreeSet::from([1, 2]));
+        // Even skips the eliminated too.
+        lobby.set_targeting(0, Targeting::Even);
+        assert_eq!((0..3).map(|_| lobby.target(0)).collect::<Vec<_>>(), [Some(1), Some(2), Some(1)]);
+
+        // With nobody ahead, the leader is the first opponent; with nobody
+        // left, there is no target.
+        let mut lobby = Lobby::new(&[None; 3], Targeting::Leader, 5);
+        assert_eq!(lobby.target(0), Some(1));
+        for i in 1..3 {
+            lobby.player_mut(i).unwrap().tetris.lost = true;
+        }
+        assert_eq!(lobby.target(0), None);
+    }
+
+    #[test]
+    fn test_lobby_awards_badges_for_kos() {
+        use tetris_core::lobby::{Lobby, Targeting};
+
+        let mut lobby = Lobby::new(&[None; 4], Targeting::Leader, 5);
+        // Player 2, holding two badges, tops out to player 1's garbage;
+        // player 3 tops out without ever being attacked.
+        let victim = lobby.player_mut(2).unwrap();
+        victim.badges = 2;
+        victim.last_attacker = Some(1);
+        victim.tetris.lost = true;
+        lobby.player_mut(3).unwrap().tetris.lost = true;
+        lobby.step();
+
+        let kos_and_badges = lobby.players().iter().map(|p| (p.kos, p.badges)).collect::<Vec<_>>();
+        assert_eq!(kos_and_badges, [(0, 0), (1, 3), (0, 0), (0, 0)]);
+        assert_eq!(lobby.eliminated(), [2, 3]);
+        // A KO is only counted once.
+        lobby.step();
+        assert_eq!((lobby.players()[1].kos, lobby.players()[1].badges), (1, 3));
+    }
+
+    #[test]
+    fn test_lobby_plays_to_the_last_board_standing() {
+        use tetris_core::bot::Weights;
+        use tetris_core::lobby::{badge_bonus_percent, boosted_attack, Lobby, Targeting};
+        use tetris_core::render;
+        // Even targeting cycles through the opponents, skipping oneself.
+        let mut lobby = Lobby::new(&[Some(Weights::default()), None, None, None], Targeting::Even, 1);
+        assert_eq!((0..4).map(|_| lobby.target(0)).collect::<Vec<_>>(), [Some(1), Some(2), Some(3), Some(1)]);
+        assert_eq!(Lobby::new(&[None, None, None], Targeting::Leader, 1).target(2), Some(0));
+        // Strategies cycle per player, as the lobby's T key switches them.
+        assert_eq!(lobby.cycle_targeting(1), Some(Targeting::Leader));
+        assert_eq!(lobby.players()[1].targeting, Targeting::Leader);
+        assert_eq!(lobby.players()[0].targeting, Targeting::Even);
+        assert_eq!(Targeting::ALL.map(Targeting::next)[4], Targeting::Leader);
+        assert_eq!("ko".parse(), Ok(Targeting::Badges));
+        assert!(Targeting::ALL.iter().all(|t| t.id().parse() == Ok(*t)));
+
+        let mut lobby = Lobby::new(&[Some(Weights::default()); 3], Targeting::Even, 1);
+        lobby.set_targeting(2, Targeting::Attacker);
+        lobby.play();
+        assert!(lobby.is_over());
+        let kos: u32 = lobby.players().iter().map(|p| p.kos).sum();
+        assert!(kos as usize <= lobby.eliminated().len());
+        // Badges only change hands, plus one per KO.
+        assert!(lobby.players().iter().map(|p| p.badges).sum::<u32>() <= kos);
+
+        assert_eq!([0, 1, 2, 5, 6, 14, 30, 99].map(badge_bonus_percent), [0, 0, 25, 25, 50, 75, 100, 100]);
+        assert_eq!((boosted_attack(4, 0), boosted_attack(4, 6), boosted_attack(1, 2)), (4, 6, 1));
+        let mut standings = lobby.standings();
+        assert_eq!(&standings[3 - lobby.eliminated().len()..], lobby.eliminated().iter().rev().copied().collect::<Vec<_>>());
+        standings.sort();
+        assert_eq!(standings, [0, 1, 2]);
+
+        assert_eq!(render::side_by_side(&["ab\ncd", "x"], 1), "ab x\ncd");
+    }
+
+    #[test]
+    fn test_opening_book_places_early_pieces() {
+        use tetris_core::opening::{self, BookError, OpeningBook};
+        let book = opening::bundled();
+        assert_eq!(book.len(), 10);
+        let mut tetris = Tetris::new(10, 25);
+        assert_eq!(opening::key(&tetris.board), "-");
+        let i = book.lookup(&tetris.board, "I").unwrap();
+        assert!(i.cells().all(|c| c.1 == 24 && c.0 < 4));
+        assert!(book.lookup(&tetris.board, "S").is_none());
+
+        tetris.board.place(&i);
+        assert_eq!(opening::key(&tetris.board), "XXXX......");
+        for kind in ["O", "L", "J", "I"] {
+            let piece = book.lookup(&tetris.board, kind).unwrap();
+            assert!(piece.cells().all(|c| c.1 >= 23 && c.0 >= 4), "{kind}");
+        }
+        let mut o = Tetris::new(10, 25);
+        o.board.place(&book.lookup(&o.board, "O").unwrap());
+        assert!(book.lookup(&o.board, "I").is_some());
+
+        assert_eq!(OpeningBook::parse("# note\n- I 0,0 1,0\n"), Err(BookError::BadLine(2)));
+        let custom = OpeningBook::parse("- O 8,0 9,0 8,1 9,1").unwrap();
+        assert!(custom.lookup(&Tetris::new(10, 25).board, "O").unwrap().cells().all(|c| c.0 >= 8));
+    }
+
+    #[test]
+    fn test_strongest_policy_depends_on_features() {
+        use tetris_core::bot::Policy;
+        #[cfg(not(feature = "mcts"))]
+        assert_eq!(Policy::strongest(), Policy::Greedy);
+        #[cfg(feature = "mcts")]
+        {
+            use tetris_core::mcts::{self, Budget};
+            assert!(matches!(Policy::strongest(), Policy::Mcts(_)));
+            let mut tetris = Tetris::new(10, 25);
+            tetris.spawn_piece("T");
+            let budget = Budget { ms: 10_000.0, max_iterations: 300 };
+            let piece = mcts::best_placement(&tetris, &Default::default(), budget).unwrap();
+            assert_eq!(metrics::after_lock(&tetris.board, &piece).holes, 0);
+        }
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn test_onnx_value_net_picks_placements() {
+        use tetris_core::onnx::{self, ValueNet};
+        assert!(ValueNet::load("models/missing.onnx", 10, 25, Default::default()).is_err());
+        let net = ValueNet::load("models/fewest_cells.onnx", 10, 25, Default::default()).unwrap();
+        let mut tetris = Tetris::new(10, 25);
+        for x in 0..9 {
+            tetris.board.set(Position(x, 24), Some("X"));
+        }
+        assert_eq!(net.value(&tetris.board).unwrap(), -9.0);
+        // Fewest cells left means the vertical I that completes the row.
+        tetris.spawn_piece("I");
+        let piece = onnx::best_placement(&tetris, &net).unwrap();
+        assert!(piece.cells().all(|c| c.0 == 9));
+        assert!(onnx::best_placement(&Tetris::new(12, 25), &net).is_none());
     }
 
-    fn get_block_positions(tetris: &Tetris) -> HashSet<Position> { // Position is from tetris_core
-        tetris
-            .fixed_blocks
-            .iter()
-            .flat_map(|t| t.collect_positions())
-            .collect()
+    #[test]
+    fn test_mirroring_flips_boards_and_pieces() {
+        use tetris_core::observation::{ObservationEncoder, ObservationFormat};
+        let mut board = Board::new(10, 20);
+        board.set(Position(0, 19), Some("S"));
+        board.set(Position(3, 18), Some("J"));
+        board.set(Position(4, 19), Some(GARBAGE_KIND));
+        let mirrored = board.mirrored();
+        assert_eq!((mirrored.get(Position(9, 19)), mirrored.get(Position(6, 18))), (Some("Z"), Some("L")));
+        assert_eq!((mirrored.get(Position(5, 19)), mirrored.column_height(9), mirrored.column_height(0)), (Some(GARBAGE_KIND), 1, 0));
+        let cells = |b: &Board| b.rows().flatten().copied().collect::<Vec<_>>();
+        assert_eq!(cells(&mirrored.mirrored()), cells(&board));
+
+        // Every piece in every rotation lands on the flipped cells, as its
+        // mirror kind, and flips back to where it was.
+        let sorted = |piece: &Tetromino| {
+            let mut cells = piece.collect_positions();
+            cells.sort_by_key(|p| (p.1, p.0));
+            cells
+        };
+        for kind in pieces::KINDS {
+            for rotation in 0..4 {
+                let mut piece = Tetromino::spawn_rotated(kind, rotation, 10);
+                piece.data.position = piece.data.position + Position(1, 3);
+                let mirrored = piece.mirrored(10);
+                assert_eq!(mirrored.kind, pieces::mirror_kind(kind));
+                let mut flipped: Vec<Position> = piece.cells().map(|p| Position(9 - p.0, p.1)).collect();
+                flipped.sort_by_key(|p| (p.1, p.0));
+                assert_eq!(sorted(&mirrored), flipped, "{kind} {rotation}");
+                assert_eq!(sorted(&mirrored.mirrored(10)), sorted(&piece));
+            }
+        }
+        let t = Tetromino::spawn_rotated("T", 1, 10);
+        assert_eq!(t.mirrored(10).rotation, 3);
+
+        let mut tetris = Tetris::new(10, 20);
+        tetris.board = board;
+        tetris.spawn_piece("L");
+        let encoder = ObservationEncoder::new(ObservationFormat::Columns);
+        let (plain, flipped) = (encoder.encode(&tetris), encoder.encode_mirrored(&tetris));
+        assert_eq!(&flipped[..10], &plain[..10].iter().rev().copied().collect::<Vec<_>>()[..]);
+        assert_eq!(flipped[20 + pieces::kind_index("J").unwrap()], 1.0);
+    }
+
+    #[test]
+    fn test_observation_encoder_formats() {
+        use tetris_core::observation::{ObservationEncoder, ObservationFormat};
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("T");
+        tetris.board.set(Position(0, 19), Some("X"));
+        tetris.board.set(Position(1, 19), Some("I"));
+        tetris.board.set(Position(1, 17), Some("I"));
+
+        let flat = ObservationEncoder::new(ObservationFormat::Flat).encode(&tetris);
+        assert_eq!(flat.len(), 207);
+        assert_eq!(flat.iter().sum::<f32>(), 3.0 + 4.0 + 1.0);
+        assert_eq!(flat[200 + pieces::kind_index("T").unwrap()], 1.0);
+
+        let grid = ObservationEncoder::new(ObservationFormat::Grid);
+        assert_eq!(grid.shape(10, 20), [4, 20, 10]);
+        let channels: Vec<f32> = grid.encode(&tetris).chunks(200).map(|c| c.iter().sum()).collect();
+        assert_eq!(channels, [2.0, 4.0, 4.0, 1.0]);
+
+        let columns = ObservationEncoder::new(ObservationFormat::Columns).encode(&tetris);
+        assert_eq!(columns.len(), 27);
+        assert_eq!(&columns[..2], &[0.05, 0.15]);
+        assert_eq!(&columns[10..12], &[0.0, 0.05]);
+        assert!(columns.iter().all(|v| (0.0..=1.0).contains(v)));
+        assert_eq!(ObservationFormat::from_name("grid"), Some(ObservationFormat::Grid));
+        assert_eq!(tetris_observation_len(10, 20, 3), 0);
+    }
+
+    #[test]
+    fn test_reward_shaping_weights() {
+        use tetris_core::reward::RewardWeights;
+        let weights = RewardWeights { lines: 10.0, holes_delta: -2.0, height_delta: -1.0, top_out: -100.0, survival: 0.5 };
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clear_animation_ms(0.0);
+        assert_eq!(tetris.shape_reward(), 0.0);
+        tetris.set_reward_weights(Some(weights));
+        assert_eq!(tetris.shape_reward(), 0.5);
+
+        // One block over a gap: a hole and two rows of height.
+        tetris.board.set(Position(0, 18), Some("X"));
+        assert_eq!(tetris.shape_reward(), -2.0 - 2.0 + 0.5);
+
+        // An O dropped into a two-wide well clears both rows.
+        let mut well = Tetris::new(10, 20);
+        well.set_clear_animation_ms(0.0);
+        for x in 2..10 {
+            well.board.set(Position(x, 18), Some("X"));
+            well.board.set(Position(x, 19), Some("X"));
+        }
+        well.set_reward_weights(Some(weights));
+        well.spawn_piece("O");
+        for _ in 0..5 {
+            well.move_left();
+        }
+        well.hard_drop();
+        assert_eq!(well.lines_cleared(), 2);
+        assert_eq!(well.shape_reward(), 20.0 + 2.0 + 0.5);
+
+        // Topping out costs `top_out` once, and a reset measures afresh.
+        for x in 0..10 {
+            tetris.board.set(Position(x, 0), Some("X"));
+            tetris.board.set(Position(x, 1), Some("X"));
+        }
+        tetris.shape_reward();
+        tetris.spawn_piece("O");
+        assert!(tetris.lost);
+        assert_eq!(tetris.shape_reward(), -100.0);
+        assert_eq!(tetris.shape_reward(), 0.0);
+        tetris.reset();
+        assert_eq!(tetris.shape_reward(), 0.5);
+    }
+
+    #[test]
+    fn test_curriculum_shapes_episodes() {
+        use tetris_core::curriculum::Curriculum;
+        assert_eq!(Curriculum::mask_of("IO"), 0b101);
+        let mut tetris = Tetris::new(10, 8);
+        tetris.set_curriculum(Curriculum { piece_mask: Curriculum::mask_of("O"), garbage_rows: 3, max_steps: 4 });
+        assert_eq!(tetris.board.rows().flatten().filter(|c| c.is_some()).count(), 27);
+        for _ in 0..3 {
+            assert_eq!(tetris.current_tetromino.as_ref().unwrap().kind, "O");
+            assert!(!tetris.is_truncated());
+            tetris.apply(Action::HardDrop);
+        }
+        tetris.apply(Action::MoveLeft);
+        assert!(tetris.is_truncated());
+
+        // Resets keep the curriculum, and garbage never fills the board.
+        tetris.reset();
+        assert_eq!((tetris.steps(), tetris.is_truncated()), (0, false));
+        assert_eq!(tetris.board.max_height(), 3);
+        tetris.set_curriculum(Curriculum { garbage_rows: 100, ..Default::default() });
+        assert_eq!(tetris.board.max_height(), 8 - board::MIN_HEIGHT);
+        assert!(!tetris.lost);
+    }
+
+    #[test]
+    fn test_env_pool_steps_into_shared_buffers() {
+        use tetris_core::env_pool::{EnvPool, RUNNING, TERMINATED};
+        use tetris_core::observation::{ObservationEncoder, ObservationFormat};
+        use tetris_core::reward::RewardWeights;
+
+        assert!(EnvPool::new(2, 2, 20, ObservationFormat::Flat, RewardWeights::default()).is_err());
+        let mut pool = EnvPool::new(40, 10, 8, ObservationFormat::Columns, RewardWeights::default()).unwrap();
+        let len = pool.observation_len();
+        assert_eq!(len, ObservationEncoder::new(ObservationFormat::Columns).len(10, 8));
+        let mut obs = vec![f32::NAN; pool.len() * len];
+        pool.reset(&mut obs);
+        assert!(obs.iter().all(|v| v.is_finite()));
+
+        // Every env hard-drops until it tops out, and restarts on its own.
+        let (mut rewards, mut dones) = (vec![0.0; 40], vec![RUNNING; 40]);
+        let mut finished = [false; 40];
+        for _ in 0..40 {
+            pool.step(&[tetris_core::TETRIS_ACTION_DROP; 40], &mut obs, &mut rewards, &mut dones);
+            for (i, &done) in dones.iter().enumerate() {
+                if done == TERMINATED {
+                    assert!(rewards[i] <= RewardWeights::default().top_out + 1.0);
+                    finished[i] = true;
+                }
+            }
+            for (i, tetris) in pool.envs().iter().enumerate() {
+                assert!(!tetris.lost);
+                let expected = ObservationEncoder::new(ObservationFormat::Columns).encode(tetris);
+                assert_eq!(&obs[i * len..(i + 1) * len], &expected[..]);
+            }
+        }
+        assert!(finished.iter().all(|&f| f));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_rpc_engine_drives_games() {
+        use serde_json::{json, Value};
+        use tetris_core::rpc::{Engine, INVALID_PARAMS, METHOD_NOT_FOUND, UNKNOWN_GAME};
+        let mut engine = Engine::new();
+        let (client, notes) = std::sync::mpsc::channel();
+        let game = engine.call("create", &json!({ "width": 8, "height": 12 }), &client).unwrap()["game"].clone();
+        assert_eq!(engine.call("subscribe", &json!({ "game": game }), &client), Ok(Value::Bool(true)));
+        let state = engine.call("step", &json!({ "game": game, "action": "HardDrop" }), &client).unwrap();
+        assert!(state["score"].as_i64().unwrap() > 0);
+        let events: Vec<Value> = notes.try_iter().map(|n| serde_json::from_str::<Value>(&n).unwrap()).collect();
+        assert!(events.iter().any(|n| n["params"]["event"]["type"] == "PieceLocked"));
+        let board = engine.call("board", &json!({ "game": game }), &client).unwrap();
+        assert_eq!(board["rows"].as_array().unwrap().len(), 12);
+
+        let code = |r: Result<Value, tetris_core::rpc::RpcError>| r.unwrap_err().code;
+        assert_eq!(code(engine.call("step", &json!({ "game": game, "action": "Jump" }), &client)), INVALID_PARAMS);
+        assert_eq!(code(engine.call("fly", &json!({ "game": game }), &client)), METHOD_NOT_FOUND);
+        assert_eq!(code(engine.call("create", &json!({ "width": 2 }), &client)), INVALID_PARAMS);
+        engine.call("destroy", &json!({ "game": game }), &client).unwrap();
+        assert_eq!(code(engine.call("state", &json!({ "game": game }), &client)), UNKNOWN_GAME);
+
+        let reply = engine.handle(r#"{"jsonrpc": "2.0", "id": 7, "method": "create"}"#, &client).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&reply).unwrap()["result"]["game"], 2);
+        assert!(engine.handle(r#"{"jsonrpc": "2.0", "method": "create"}"#, &client).is_none());
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[test]
+    fn test_broadcaster_feeds_overlays() {
+        use tetris_core::broadcast::Broadcaster;
+        let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        let stream = std::net::TcpStream::connect(broadcaster.local_addr()).unwrap();
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/", broadcaster.local_addr()), stream).unwrap();
+        while broadcaster.client_count() == 0 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let mut tetris = Tetris::new(10, 20);
+        for x in 0..10 {
+            for y in 18..20 {
+                tetris.board.set(Position(x, y), Some("X"));
+            }
+        }
+        tetris.set_upcoming_pieces(&["S", "Z"]);
+        let events = [GameEvent::PieceLocked { kind: "I" }, GameEvent::LinesCleared { count: 2 }];
+        broadcaster.publish(1, &tetris, &events);
+        let frame: serde_json::Value = serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(frame["game"], 1);
+        assert_eq!(frame["attack"], 1);
+        assert_eq!(frame["next"], serde_json::json!(["S", "Z"]));
+        assert_eq!(frame["board"][19], "XXXXXXXXXX");
+        assert_eq!(frame["events"][1], serde_json::json!({ "type": "LinesCleared", "count": 2 }));
+
+        // Clients that hung up are dropped on the next publish.
+        drop(socket);
+        broadcaster.publish(1, &tetris, &[]);
+        broadcaster.publish(1, &tetris, &[]);
+        assert_eq!(broadcaster.client_count(), 0);
+    }
+
+    #[cfg(feature = "twitch")]
+    #[test]
+    fn test_chat_votes_pick_the_most_voted_action() {
+        use tetris_core::chat_vote::{self, ChatVotes};
+        let msg = |user: &str, text: &str| format!(":{user}!{user}@{user}.tmi.twitch.tv PRIVMSG #host :{text}\r\n");
+        assert_eq!(chat_vote::parse_privmsg(&msg("ann", "hi there")), Some(("ann", "hi there")));
+        assert_eq!(chat_vote::parse_privmsg(":tmi.twitch.tv 001 justinfan31415 :Welcome"), None);
+        assert_eq!(chat_vote::reply_to("PING :tmi.twitch.tv").as_deref(), Some("PONG :tmi.twitch.tv"));
+        assert_eq!(chat_vote::login_lines("#Host")[2], "JOIN #host");
+
+        let mut votes = ChatVotes::new(1000.0);
+        assert_eq!(votes.poll(0.0), None);
+        votes.observe_line(&msg("ann", "left"), 100.0);
+        votes.observe_line(&msg("bob", "Drop"), 200.0);
+        votes.observe_line(&msg("cat", "drop"), 300.0);
+        votes.observe_line(&msg("cat", "hello"), 400.0);
+        // A second vote replaces the viewer's first.
+        votes.observe_line(&msg("ann", "drop"), 500.0);
+        votes.observe_line(&msg("bob", "l"), 600.0);
+        assert_eq!(votes.tally(), vec![(Action::HardDrop, 2), (Action::MoveLeft, 1)]);
+        assert_eq!(votes.remaining_ms(600.0), Some(500.0));
+        assert_eq!(votes.poll(1099.0), None);
+        assert_eq!(votes.poll(1100.0), Some(Action::HardDrop));
+        assert!(votes.tally().is_empty() && votes.remaining_ms(1100.0).is_none());
+    }
+
+    #[test]
+    fn test_heavy_events_hint_board_effects() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("I");
+        tetris.receive_garbage(2, 3);
+        assert_eq!(tetris.board.get(Position(0, 19)), Some(GARBAGE_KIND));
+        assert_eq!(tetris.board.get(Position(3, 18)), None);
+        let events = tetris.drain_events();
+        assert_eq!(events, vec![GameEvent::GarbageReceived { rows: 2 }]);
+        assert_eq!(RenderHint::for_event(&events[0]), Some(RenderHint::Shake { strength: 0.5 }));
+
+        // Filling the holes clears both rows and empties the board.
+        tetris.board.set(Position(3, 18), Some("I"));
+        tetris.board.set(Position(3, 19), Some("I"));
+        tetris.clear_lines();
+        let hints: Vec<_> = tetris.drain_events().iter().filter_map(RenderHint::for_event).collect();
+        assert_eq!(hints, vec![RenderHint::Flash]);
+        assert!(!tetris.lost);
+    }
+
+    #[test]
+    fn test_rotations_report_plain_turns_and_spins() {
+        use tetris_core::events::Rotation;
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("T");
+        tetris.drain_events();
+        tetris.apply(Action::Rotate);
+        let events = tetris.drain_events();
+        assert!(events.contains(&GameEvent::Rotated(Rotation::Plain)));
+        assert!(events.iter().all(|e| RenderHint::for_event(e).is_none()));
+
+        // Wall in three corners of the T's box so the next turn wedges it.
+        let piece = tetris.current_tetromino.as_ref().unwrap();
+        let turned: Vec<Position> = piece.rotated().cells().collect();
+        let pos = piece.data.position;
+        for corner in [Position(0, 0), Position(2, 0), Position(0, 2), Position(2, 2)] {
+            if !turned.contains(&(pos + corner)) {
+                tetris.board.set(pos + corner, Some("Z"));
+            }
+        }
+        tetris.apply(Action::Rotate);
+        let hints: Vec<_> = tetris.drain_events().iter().filter_map(RenderHint::for_event).collect();
+        assert_eq!(hints, vec![RenderHint::SpinGlow]);
+        assert_eq!(RenderHint::for_event(&GameEvent::Rotated(Rotation::Kicked)), Some(RenderHint::Kick));
+    }
+
+    #[test]
+    fn test_srs_kicks_off_walls() {
+        use tetris_core::events::Rotation;
+        let t = pieces::kicks("T", 0);
+        assert_eq!(t, [Position(0, 0), Position(-1, 0), Position(-1, -1), Position(0, 2), Position(-1, 2)]);
+        assert_eq!(pieces::kicks("O", 1), [Position(0, 0)]);
+        // S keeps one shape for SRS states 0 and 2, so turning into 2 drops
+        // it a row to where SRS has it.
+        assert_eq!(pieces::kicks("S", 1)[0], Position(0, 1));
+
+        // An upright I against the left wall turns by stepping right.
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("I");
+        while tetris.current_tetromino.as_ref().unwrap().cells().all(|p| p.0 > 0) {
+            tetris.apply(Action::MoveLeft);
+        }
+        tetris.drain_events();
+        assert_eq!(tetris.rotate_with_kicks(), Some(1));
+        let cells = tetris.current_tetromino.as_ref().unwrap().collect_positions();
+        assert_eq!(cells.iter().map(|p| p.0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert!(tetris.drain_events().contains(&GameEvent::Rotated(Rotation::Kicked)));
+
+        // Walled into its own column, it has nowhere to turn.
+        let mut tetris = Tetris::new(4, 4);
+        tetris.spawn_piece("I");
+        for y in 0..4 {
+            for x in [0, 2, 3] {
+                tetris.board.set(Position(x, y), Some("Z"));
+            }
+        }
+        assert_eq!(tetris.rotate_with_kicks(), None);
     }
 
     #[test]
@@ -285,33 +3659,21 @@ mod tests {
         let width = 10;
         let height = 5;
         let mut tetris = Tetris::new(width, height); // tetris_core::Tetris
+        tetris.set_clock(Clock::Manual(0.0));
         tetris.current_tetromino = None;
-        tetris.fixed_blocks.clear();
+        tetris.ghost_tetromino = None;
+        tetris.board.clear();
         tetris.score = 0;
 
         let shifting_block_orig_pos = Position(0, height as i32 - 2);
-        tetris.fixed_blocks.push(Tetromino { // tetris_core::Tetromino
-            kind: "S",
-            data: TetrominoData { // tetris_core::TetrominoData
-                position: shifting_block_orig_pos,
-                data: [Position(0,0)].into(),
-            },
-            rotation: 0,
-        });
+        tetris.board.set(shifting_block_orig_pos, Some("S"));
 
         let line_to_clear_y = height as i32 - 1;
         for i in 0..width {
-            tetris.fixed_blocks.push(Tetromino {
-                kind: "I",
-                data: TetrominoData {
-                    position: Position(i as i32, line_to_clear_y),
-                    data: [Position(0,0)].into(),
-                },
-                rotation: 0,
-            });
+            tetris.board.set(Position(i as i32, line_to_clear_y), Some("I"));
         }
 
-        assert_eq!(tetris.fixed_blocks.len(), width as usize + 1);
+        assert_eq!(get_block_positions(&tetris).len(), width as usize + 1);
         assert!(get_block_positions(&tetris).contains(&shifting_block_orig_pos));
         assert_eq!(count_blocks_at_line(&tetris, line_to_clear_y), width as usize);
 
@@ -320,28 +3682,811 @@ mod tests {
         assert_eq!(tetris.lines_being_cleared, Some(vec![line_to_clear_y as usize]));
         assert!(tetris.animation_start_time.is_some());
         assert_eq!(tetris.score, 1);
-        assert_eq!(tetris.fixed_blocks.len(), width as usize + 1);
+        assert_eq!(get_block_positions(&tetris).len(), width as usize + 1);
         assert!(get_block_positions(&tetris).contains(&shifting_block_orig_pos));
         assert_eq!(count_blocks_at_line(&tetris, line_to_clear_y), width as usize);
 
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS);
         tetris.tick(); // tick() from tetris_core::Tetris
 
         assert_eq!(tetris.lines_being_cleared, None);
         assert!(tetris.animation_start_time.is_none());
-        assert_eq!(count_blocks_at_line(&tetris, line_to_clear_y), 0);
-        
+        // Only the shifted "S" cell occupies the cleared row now.
+        assert_eq!(count_blocks_at_line(&tetris, line_to_clear_y), 1);
+
         let shifted_block_new_pos = Position(shifting_block_orig_pos.0, shifting_block_orig_pos.1 + 1);
         let current_positions = get_block_positions(&tetris);
         assert!(current_positions.contains(&shifted_block_new_pos), "Shifting block should have moved down to {:?}, current positions: {:?}", shifted_block_new_pos, current_positions);
         assert!(!current_positions.contains(&shifting_block_orig_pos));
-        assert_eq!(tetris.fixed_blocks.len(), 1);
-        assert_eq!(tetris.fixed_blocks[0].kind, "S");
+        assert_eq!(current_positions.len(), 1);
+        // The shifted cell keeps the color of the piece it came from.
+        assert_eq!(tetris.board.get(shifted_block_new_pos), Some("S"));
 
         let old_score = tetris.score;
         tetris.clear_lines();
         assert_eq!(tetris.lines_being_cleared, None);
         assert_eq!(tetris.score, old_score);
     }
+
+    #[test]
+    fn test_clear_animation_progress_follows_the_game_clock() {
+        let mut tetris = Tetris::new(10, 5);
+        tetris.set_clock(Clock::Manual(0.0));
+        for x in 0..10 {
+            tetris.board.set(Position(x, 4), Some("I"));
+        }
+        tetris.clear_lines();
+
+        // Progress follows the game clock, not how often tick() runs.
+        assert_eq!(tetris.clear_animation_progress(), Some(0.0));
+        tetris.tick();
+        assert!(tetris.lines_being_cleared.is_some());
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS / 2.0);
+        assert_eq!(tetris.clear_animation_progress(), Some(0.5));
+        // A 30 Hz and a 144 Hz renderer agree at the same timestamp.
+        let start = tetris.animation_start_time.unwrap();
+        assert_eq!(
+            tetris.clear_animation_progress_at(start + animation::frames_to_ms(15, 30.0)),
+            Some(1.0)
+        );
+        assert_eq!(
+            tetris.clear_animation_progress_at(start + animation::frames_to_ms(36, 144.0)),
+            Some(0.5)
+        );
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS / 2.0);
+        tetris.tick();
+        assert_eq!((tetris.lines_being_cleared.clone(), tetris.clear_animation_progress()), (None, None));
+    }
+
+    #[test]
+    fn test_multi_line_clear_keeps_colors() {
+        let mut tetris = Tetris::new(4, 6);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.current_tetromino = None;
+        tetris.ghost_tetromino = None;
+
+        // Two full rows with a mixed-color row sandwiched between them.
+        for x in 0..4 {
+            tetris.board.set(Position(x, 5), Some("I"));
+            tetris.board.set(Position(x, 3), Some("O"));
+        }
+        tetris.board.set(Position(0, 4), Some("T"));
+        tetris.board.set(Position(3, 4), Some(GARBAGE_KIND));
+        tetris.board.set(Position(1, 2), Some("Z"));
+
+        tetris.clear_lines();
+        assert_eq!(tetris.lines_being_cleared, Some(vec![3, 5]));
+        tetris.advance_clock(DEFAULT_CLEAR_ANIMATION_MS);
+        tetris.tick();
+
+        let view = tetris.render_view();
+        assert_eq!(view[5], vec!["T", "B", "B", GARBAGE_KIND]);
+        assert_eq!(view[4], vec!["B", "Z", "B", "B"]);
+        assert!(view[..4].iter().all(|row| row.iter().all(|&c| c == "B")));
+    }
+
+    #[test]
+    fn test_soft_drop_speed_while_held() {
+        use tetris_core::input::SoftDropSpeed;
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("O");
+        assert_eq!(tetris.soft_drop_speed(), SoftDropSpeed::Times20);
+        assert_eq!(tetris.gravity_interval_ms(1000.0), 1000.0);
+        let y = |t: &Tetris| t.current_tetromino.as_ref().unwrap().data.position.1;
+        let top = y(&tetris);
+
+        // A press moves one row; holding speeds ticks up and scores them.
+        tetris.apply(Action::SoftDrop);
+        tetris.set_soft_drop_held(true);
+        assert_eq!(tetris.gravity_interval_ms(1000.0), 50.0);
+        tetris.tick();
+        assert_eq!((y(&tetris), tetris.score_breakdown.soft_drop), (top + 2, 2));
+        tetris.set_soft_drop_speed(SoftDropSpeed::Times5);
+        assert_eq!(tetris.gravity_interval_ms(1000.0), 200.0);
+        tetris.set_soft_drop_held(false);
+        tetris.tick();
+        assert_eq!((y(&tetris), tetris.score_breakdown.soft_drop), (top + 3, 2));
+
+        // Instant lands the piece without locking; the next press locks it.
+        tetris.set_soft_drop_speed(SoftDropSpeed::Instant);
+        let ghost = tetris.ghost_position().unwrap();
+        tetris.apply(Action::SoftDrop);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, ghost);
+        assert_eq!(tetris.score_breakdown.soft_drop, 2 + (ghost.1 - top - 3));
+        tetris.apply(Action::SoftDrop);
+        assert_eq!(tetris.pieces_placed(), 1);
+        tetris.reset();
+        assert_eq!(tetris.soft_drop_speed(), SoftDropSpeed::Instant);
+    }
+
+    #[test]
+    fn test_latency_probe_follows_presses_to_the_frame() {
+        use ui::latency::{LatencyProbe, WINDOW};
+        let mut probe = LatencyProbe::default();
+        assert!(!probe.frame(5.0));
+        probe.pressed(10.0);
+        assert!(!probe.frame(12.0)); // not applied yet
+        probe.applied(14.0);
+        probe.pressed(15.0);
+        assert!(probe.frame(30.0));
+        assert_eq!((probe.to_apply.mean(), probe.to_frame.mean(), probe.total.mean()), (Some(4.0), Some(16.0), Some(20.0)));
+        probe.applied(35.0);
+        assert!(probe.frame(45.0));
+        assert_eq!((probe.total.count(), probe.total.mean()), (2, Some(25.0)));
+
+        for i in 0..WINDOW {
+            probe.pressed(i as f64 * 100.0);
+            probe.applied(i as f64 * 100.0 + 1.0);
+            probe.frame(i as f64 * 100.0 + 3.0);
+        }
+        assert_eq!((probe.total.count(), probe.total.mean()), (WINDOW, Some(3.0)));
+    }
+
+    #[test]
+    fn test_drop_scoring_breakdown() {
+        let mut tetris = Tetris::new(10, 10);
+        let mut piece = tetris.current_tetromino.clone().unwrap();
+        piece.kind = "O";
+        piece.rotation = 0;
+        piece.data = TetrominoData::new(Position(3, 0), Tetromino::get_rotation_data("O", 0));
+        tetris.current_tetromino = Some(piece);
+
+        tetris.soft_drop();
+        tetris.soft_drop();
+        // The O piece now rests 2 rows lower and has 6 more rows to fall;
+        // soft drops move it without locking it.
+        assert_eq!(tetris.pieces_placed(), 0);
+        assert_eq!(tetris.current_tetromino.as_ref().unwrap().data.position, Position(3, 2));
+        tetris.hard_drop();
+        assert_eq!(tetris.pieces_placed(), 1);
+
+        assert_eq!(tetris.score_breakdown, ScoreBreakdown { lines: 0, soft_drop: 2, hard_drop: 12 });
+        assert_eq!(tetris.score, 14);
+        let events: Vec<_> = tetris
+            .drain_events()
+            .into_iter()
+            .filter(|e| matches!(e, GameEvent::Score { .. }))
+            .collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[2],
+            GameEvent::Score { source: ScoreSource::HardDrop, points: 12, breakdown: tetris.score_breakdown }
+        );
+        assert!(tetris.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_game_timer_skips_paused_time() {
+        let mut timer = tetris_core::clock::GameTimer::default();
+        assert_eq!(timer.elapsed_ms(500.0), 0.0); // not started yet, e.g. countdown
+        timer.start(1000.0);
+        assert_eq!(timer.elapsed_ms(1500.0), 500.0);
+        timer.stop(2000.0);
+        assert_eq!(timer.elapsed_ms(9000.0), 1000.0);
+        timer.start(10_000.0);
+        timer.start(10_500.0); // starting twice keeps the first start
+        assert_eq!(timer.elapsed_ms(11_000.0), 2000.0);
+    }
+
+    #[test]
+    fn test_ffi_reports_pause_and_phase() {
+        use tetris_core::{GamePhase, TETRIS_ACTION_DROP, TETRIS_ACTION_LEFT, TETRIS_ACTION_PAUSE, TETRIS_ACTION_RESUME};
+
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("I");
+        // A bottom row full but for the column the I drops into.
+        let column = tetris.current_tetromino.as_ref().unwrap().collect_positions()[0].0;
+        for x in (0..10).filter(|&x| x != column) {
+            tetris.board.set(Position(x, 19), Some("X"));
+        }
+        unsafe {
+            let ptr = Box::into_raw(Box::new(tetris));
+            assert_eq!(tetris_get_game_state(ptr).phase, GamePhase::Playing);
+
+            let state = tetris_step(ptr, TETRIS_ACTION_PAUSE);
+            assert!(state.paused);
+            assert_eq!(state.phase, GamePhase::Paused);
+            // Paused games ignore everything else.
+            let before = (*ptr).current_tetromino.as_ref().unwrap().collect_positions();
+            assert_eq!(tetris_step(ptr, TETRIS_ACTION_LEFT).phase, GamePhase::Paused);
+            assert_eq!((*ptr).current_tetromino.as_ref().unwrap().collect_positions(), before);
+
+            let state = tetris_step(ptr, TETRIS_ACTION_RESUME);
+            assert!(!state.paused);
+            assert_eq!(state.phase, GamePhase::Playing);
+            assert_eq!(tetris_step(ptr, TETRIS_ACTION_DROP).phase, GamePhase::Clearing);
+
+            (*ptr).lost = true;
+            assert_eq!(tetris_get_game_state(ptr).phase, GamePhase::GameOver);
+            assert_eq!(tetris_get_game_state(std::ptr::null()).phase, GamePhase::GameOver);
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_ffi_clearing_rows_fit_the_buffer() {
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_clear_animation_ms(500.0);
+        for y in [23, 24] {
+            for x in 0..10 {
+                tetris.board.set(Position(x, y), Some("I"));
+            }
+        }
+        tetris.clear_lines();
+        tetris.advance_clock(125.0);
+
+        // Room for every row: both written, the rest of the buffer untouched.
+        let (mut rows, mut progress) = ([u32::MAX; 4], 0.0);
+        assert_eq!(unsafe { tetris_get_clearing_rows(&tetris, rows.as_mut_ptr(), 4, &mut progress) }, 2);
+        assert_eq!((rows, progress), ([23, 24, u32::MAX, u32::MAX], 0.25));
+
+        // Too short a buffer gets the first rows only, and the count says how
+        // many there are.
+        let mut rows = [u32::MAX; 2];
+        assert_eq!(unsafe { tetris_get_clearing_rows(&tetris, rows.as_mut_ptr(), 1, std::ptr::null_mut()) }, 2);
+        assert_eq!(rows, [23, u32::MAX]);
+        assert_eq!(unsafe { tetris_get_clearing_rows(&tetris, std::ptr::null_mut(), 0, std::ptr::null_mut()) }, 2);
+
+        // Nothing once the animation is over.
+        tetris.advance_clock(375.0);
+        tetris.tick();
+        assert_eq!(unsafe { tetris_get_clearing_rows(&tetris, rows.as_mut_ptr(), 2, &mut progress) }, 0);
+    }
+
+    #[test]
+    fn test_ffi_steps_apply_like_the_frontend() {
+        use tetris_core::{TETRIS_ACTION_DROP, TETRIS_ACTION_LEFT};
+
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.spawn_piece("I");
+        unsafe {
+            let ptr = Box::into_raw(Box::new(tetris));
+            tetris_step(ptr, TETRIS_ACTION_LEFT);
+            // A bottom row full but for the column the I now drops into.
+            let column = (*ptr).current_tetromino.as_ref().unwrap().collect_positions()[0].0;
+            for x in (0..10).filter(|&x| x != column) {
+                (*ptr).board.set(Position(x, 19), Some("X"));
+            }
+            tetris_step(ptr, TETRIS_ACTION_DROP);
+            let inputs: Vec<_> = (*ptr)
+                .drain_events()
+                .into_iter()
+                .filter_map(|e| match e {
+                    GameEvent::Input(action) => Some(action),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(inputs, [Action::MoveLeft, Action::HardDrop]);
+            // During the clear the move waits in the input buffer.
+            assert!((*ptr).lines_being_cleared.is_some());
+            tetris_step(ptr, TETRIS_ACTION_LEFT);
+            assert!(!(*ptr).drain_events().iter().any(|e| matches!(e, GameEvent::Input(_))));
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_ffi_soft_drop_moves_one_row() {
+        use tetris_core::TETRIS_ACTION_SOFT_DROP;
+
+        let mut tetris = Tetris::new(10, 20);
+        tetris.spawn_piece("T");
+        unsafe {
+            let ptr = Box::into_raw(Box::new(tetris));
+            let before = (*ptr).current_tetromino.as_ref().unwrap().collect_positions();
+            tetris_step(ptr, TETRIS_ACTION_SOFT_DROP);
+            // One row down for a point, without locking.
+            let after = (*ptr).current_tetromino.as_ref().unwrap().collect_positions();
+            assert_eq!(after, before.iter().map(|&p| p + Position(0, 1)).collect::<Vec<_>>());
+            assert_eq!(((*ptr).score, (*ptr).pieces_placed()), (1, 0));
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_board_dimensions_are_validated() {
+        assert_eq!(Tetris::try_new(3, 3).unwrap_err(), BoardError::TooNarrow(3));
+        assert_eq!(Tetris::try_new(10, 2).unwrap_err(), BoardError::TooShort(2));
+        assert!(Tetris::try_new(4, 4).is_ok());
+
+        unsafe {
+            assert!(tetris_create(2, 20).is_null());
+            assert_eq!(tetris_last_error(), 1);
+            let ptr = tetris_create(10, 20);
+            assert!(!ptr.is_null());
+            assert_eq!(tetris_last_error(), 0);
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_board_queries_for_external_tools() {
+        let mut tetris = Tetris::new(6, 8);
+        for pos in [Position(0, 7), Position(1, 7), Position(1, 5), Position(5, 6)] {
+            tetris.board.set(pos, Some("T"));
+        }
+        assert_eq!(tetris.column_heights(), vec![1, 3, 0, 0, 0, 2]);
+        assert_eq!(tetris.row_fill_counts(), vec![0, 0, 0, 0, 0, 1, 1, 2]);
+        assert_eq!(tetris.holes_per_column(), vec![0, 1, 0, 0, 0, 1]);
+        assert!(tetris.is_cell_occupied(Position(1, 5)) && !tetris.is_cell_occupied(Position(1, 6)));
+        assert!(!tetris.is_cell_occupied(Position(-1, 7)) && !tetris.is_cell_occupied(Position(0, 8)));
+
+        unsafe {
+            let ptr = Box::into_raw(Box::new(tetris));
+            let mut out = [9u8; 8];
+            assert_eq!(tetris_get_column_heights(ptr, out.as_mut_ptr(), 4), 6);
+            assert_eq!(out, [1, 3, 0, 0, 9, 9, 9, 9]);
+            assert_eq!(tetris_get_row_fill_counts(ptr, out.as_mut_ptr(), 8), 8);
+            assert_eq!(out[5..], [1, 1, 2]);
+            assert_eq!(tetris_get_holes_per_column(ptr, std::ptr::null_mut(), 0), 6);
+            assert!(tetris_is_cell_occupied(ptr, 5, 6) && !tetris_is_cell_occupied(ptr, 5, 7));
+            assert_eq!(tetris_get_column_heights(std::ptr::null(), out.as_mut_ptr(), 8), 0);
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_board_delta_reports_changed_cells() {
+        use tetris_core::board_delta::CellChange;
+        let mut tetris = Tetris::new(6, 8);
+        tetris.spawn_piece("O");
+        let first = tetris.board_version();
+        assert_eq!(tetris.board_changes_since(0).len(), 48);
+        assert_eq!((tetris.board_version(), tetris.board_changes_since(first)), (first, vec![]));
+
+        // Shifting the O left moves its right column and its ghost's.
+        tetris.apply(Action::MoveLeft);
+        let moved = tetris.board_changes_since(first);
+        assert_eq!(moved.len(), 8);
+        assert!(moved.contains(&CellChange { x: 1, y: 0, kind: b'O' }));
+        assert!(moved.contains(&CellChange { x: 3, y: 7, kind: 0 }));
+        assert!(moved.contains(&CellChange { x: 1, y: 7, kind: b'G' }));
+        let second = tetris.board_version();
+        assert_eq!(second, first + 1);
+
+        // Versions keep counting across a reset.
+        tetris.reset();
+        assert!(tetris.board_version() > second);
+
+        unsafe {
+            let ptr = Box::into_raw(Box::new(tetris));
+            let version = tetris_board_version(ptr);
+            let mut out = [CellChange::default(); 4];
+            assert_eq!(tetris_get_board_delta(ptr, 0, out.as_mut_ptr(), 4), 48);
+            assert_eq!(out[0], CellChange { x: 0, y: 0, kind: 0 });
+            assert_eq!(tetris_get_board_delta(ptr, version, out.as_mut_ptr(), 4), 0);
+            assert_eq!(tetris_get_board_delta(std::ptr::null_mut(), 0, out.as_mut_ptr(), 4), 0);
+            tetris_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_hole_preview_marks_covered_cells() {
+        use tetris_core::metrics;
+        let mut tetris = Tetris::new(6, 6);
+        for x in 1..6 {
+            tetris.board.set(Position(x, 5), Some("T"));
+        }
+        tetris.board.set(Position(4, 4), Some("T"));
+        tetris.board.set(Position(5, 4), Some("T"));
+        tetris.spawn_piece("O");
+        while tetris.current_tetromino.as_ref().unwrap().cells().any(|p| p.0 > 1) {
+            tetris.apply(Action::MoveLeft);
+        }
+        // The O lands on column 1 and covers the gap in column 0.
+        assert_eq!(tetris.hole_preview(), vec![Position(0, 5)]);
+        tetris.apply(Action::MoveRight);
+        assert_eq!(tetris.hole_preview(), vec![]);
+
+        // A flat I fills row 4, which clears, so nothing is left covering
+        // the gap.
+        let flat_i = metrics::placements(&tetris.board, "I")
+            .into_iter()
+            .find(|p| p.cells().all(|c| c.1 == 4) && p.cells().any(|c| c.0 == 0))
+            .unwrap();
+        assert!(metrics::new_holes(&tetris.board, &flat_i).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_restores_game_in_progress() {
+        use tetris_core::session::{Snapshot, SnapshotError};
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.spawn_piece("T");
+        tetris.set_upcoming_pieces(&["I", "O", "S", "Z"]);
+        for action in [Action::MoveLeft, Action::HardDrop, Action::Rotate, Action::HardDrop] {
+            tetris.apply(action);
+        }
+        tetris.receive_garbage(1, 4);
+        tetris.advance_clock(1500.0);
+        let snapshot = tetris.snapshot().unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+
+        let mut resumed = Tetris::new(10, 20);
+        resumed.set_clock(Clock::Manual(0.0));
+        resumed.restore(&snapshot).unwrap();
+        assert!(resumed.is_paused());
+        assert_eq!(resumed.board.rows().collect::<Vec<_>>(), tetris.board.rows().collect::<Vec<_>>());
+        let cells = |t: &Tetris| t.current_tetromino.as_ref().map(|p| (p.kind, p.collect_positions()));
+        assert_eq!(cells(&resumed), cells(&tetris));
+        assert_eq!(resumed.upcoming_pieces().collect::<Vec<_>>(), ["S", "Z"]);
+        assert_eq!((resumed.get_score(), resumed.pieces_placed()), (tetris.get_score(), 2));
+        // JSON may round the last bit of a float.
+        assert!((resumed.elapsed_ms() - tetris.elapsed_ms()).abs() < 1e-6);
+
+        // From here on both games deal and play alike.
+        resumed.resume();
+        for game in [&mut tetris, &mut resumed] {
+            for _ in 0..3 {
+                game.apply(Action::HardDrop);
+            }
+        }
+        assert_eq!(resumed.board.rows().collect::<Vec<_>>(), tetris.board.rows().collect::<Vec<_>>());
+        assert_eq!(resumed.piece_rng(), tetris.piece_rng());
+
+        let mut small = Tetris::new(8, 20);
+        assert_eq!(small.restore(&snapshot), Err(SnapshotError::Size));
+    }
+
+    #[test]
+    fn test_hold_swaps_once_per_piece() {
+        let kind = |t: &Tetris| t.current_tetromino.as_ref().unwrap().kind;
+        let mut tetris = Tetris::new(10, 25);
+        tetris.spawn_piece("T");
+        tetris.set_upcoming_pieces(&["I", "O", "S"]);
+        tetris.move_left();
+        // Nothing held yet: the piece is set aside and the next one dealt.
+        assert!(tetris.hold());
+        assert_eq!((tetris.held_piece(), kind(&tetris)), (Some("T"), "I"));
+        assert!(!tetris.can_hold() && !tetris.hold());
+        assert_eq!((tetris.held_piece(), kind(&tetris)), (Some("T"), "I"));
+
+        // Locking allows the next hold, which swaps, and the held piece
+        // comes back fresh at the top.
+        tetris.apply(Action::HardDrop);
+        assert_eq!(kind(&tetris), "O");
+        tetris.apply(Action::Hold);
+        assert_eq!(tetris.held_piece(), Some("O"));
+        assert_eq!(tetris.current_tetromino.as_ref().map(|t| (t.kind, t.data.position)), Some(("T", tetris.spawned("T").data.position)));
+        assert!(tetris.drain_events().contains(&GameEvent::Input(Action::Hold)));
+
+        let snapshot = tetris.snapshot().unwrap();
+        let mut resumed = Tetris::new(10, 25);
+        resumed.restore(&snapshot).unwrap();
+        assert_eq!((resumed.held_piece(), resumed.can_hold()), (Some("O"), false));
+        resumed.reset();
+        assert_eq!(resumed.held_piece(), None);
+
+        let cells = ui::hold::preview_cells("I");
+        assert!(cells.iter().all(|p| p.1 == 0) && cells.iter().map(|p| p.0).max() == Some(3));
+        assert!(ui::hold::preview_cells("O").iter().all(|p| (0..2).contains(&p.0) && (0..2).contains(&p.1)));
+    }
+
+    #[test]
+    fn test_reset_keeps_settings() {
+        use tetris_core::{curriculum::Curriculum, generator::Randomizer, input::SoftDropSpeed, pieces::SpawnOrientations, reward::RewardWeights};
+
+        let mut tetris = Tetris::new(10, 25);
+        let curriculum = Curriculum { piece_mask: Curriculum::mask_of("IO"), garbage_rows: 2, max_steps: 50 };
+        let mut orientations = SpawnOrientations::default();
+        orientations.set("T", 2);
+        tetris.set_input_buffer_ms(40.0);
+        tetris.set_clear_animation_ms(0.0);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_slow_motion(false);
+        tetris.set_time_scale(2.0);
+        tetris.set_reward_weights(Some(RewardWeights { lines: 0.0, holes_delta: 0.0, height_delta: 0.0, top_out: 0.0, survival: 1.0 }));
+        tetris.set_curriculum(curriculum);
+        tetris.set_spawn_orientations(orientations);
+        tetris.set_zone(true);
+        tetris.set_randomizer(Randomizer::Classic);
+        tetris.set_soft_drop_speed(SoftDropSpeed::Instant);
+        let version = tetris.board_version();
+
+        tetris.reset();
+        assert_eq!(tetris.input_buffer_ms(), 40.0);
+        assert_eq!(tetris.clear_animation_ms(), 0.0);
+        assert!(!tetris.slow_motion_enabled());
+        assert_eq!(tetris.time_scale(), 2.0);
+        // Still the manual clock, at double speed.
+        let before = tetris.now_ms();
+        tetris.advance_clock(100.0);
+        assert_eq!(tetris.now_ms() - before, 200.0);
+        assert_eq!(tetris.shape_reward(), 1.0);
+        assert_eq!(tetris.curriculum(), curriculum);
+        assert_eq!(tetris.spawn_orientations(), orientations);
+        assert!(tetris.zone().is_some());
+        assert_eq!(tetris.randomizer(), Randomizer::Classic);
+        assert_eq!(tetris.soft_drop_speed(), SoftDropSpeed::Instant);
+        assert!(tetris.board_version() > version);
+    }
+
+    #[test]
+    fn test_seeded_curriculum_starts_alike() {
+        use tetris_core::{curriculum::Curriculum, generator::PieceRng};
+
+        let curriculum = Curriculum { piece_mask: 0, garbage_rows: 6, max_steps: 0 };
+        let start = |seed| {
+            let mut tetris = Tetris::new(10, 25);
+            tetris.set_curriculum(curriculum);
+            tetris.set_piece_rng(PieceRng::from_state(seed));
+            tetris.reset();
+            (tetris.render_view(), tetris.upcoming_pieces().take(5).collect::<Vec<_>>())
+        };
+        let (board, next) = start(42);
+        assert_eq!(board.iter().filter(|row| row.contains(&GARBAGE_KIND)).count(), 6);
+        assert_eq!(start(42), (board.clone(), next));
+        // Another seed almost surely puts some hole elsewhere.
+        assert_ne!(start(43).0, board);
+    }
+
+    #[test]
+    fn test_spawn_preview_spawns_and_lands_without_playing() {
+        use tetris_core::pieces::{self, SpawnOrientations};
+
+        let mut tetris = Tetris::new(10, 20);
+        let mut orientations = SpawnOrientations::default();
+        orientations.set("T", 2);
+        tetris.set_spawn_orientations(orientations);
+        for x in 0..10 {
+            tetris.board.set(Position(x, 19), Some("X"));
+        }
+        let before = tetris.render_view();
+
+        for (kind, rotation) in [("J", 0), ("T", 2)] {
+            let (spawned, landed) = tetris.spawn_preview(kind).unwrap();
+            assert_eq!((spawned.kind, spawned.rotation), (kind, rotation));
+            assert_eq!(spawned.data.position, pieces::spawn_position_rotated(kind, rotation, 10));
+            // Straight down onto the filled bottom row.
+            assert_eq!(landed.rotation, rotation);
+            assert_eq!(landed.data.position.0, spawned.data.position.0);
+            let bottom = landed.collect_positions().iter().map(|p| p.1).max().unwrap();
+            assert_eq!(bottom, 18);
+        }
+        assert_eq!(tetris.render_view(), before);
+
+        // A blocked spawn has no preview.
+        tetris.board.set(pieces::spawn_position("O", 10) + pieces::cells("O", 0)[0], Some("X"));
+        assert!(tetris.spawn_preview("O").is_none());
+    }
+
+    #[test]
+    fn test_hold_swap_preview_shows_the_piece_a_hold_brings_in() {
+        let mut tetris = Tetris::new(10, 20);
+        tetris.set_upcoming_pieces(&["T", "S", "Z"]);
+        tetris.spawn_next();
+
+        // Nothing held yet: the hold deals the next piece.
+        let (spawned, _) = tetris.hold_swap_preview().unwrap();
+        assert_eq!(spawned.kind, "S");
+        assert!(tetris.hold());
+        assert!(tetris.hold_swap_preview().is_none());
+
+        tetris.apply(Action::HardDrop);
+        let (spawned, landed) = tetris.hold_swap_preview().unwrap();
+        assert_eq!(spawned.kind, "T");
+        let (t_spawned, t_landed) = tetris.spawn_preview("T").unwrap();
+        assert_eq!(spawned.collect_positions(), t_spawned.collect_positions());
+        assert_eq!(landed.collect_positions(), t_landed.collect_positions());
+    }
+
+    #[test]
+    fn test_spawn_centers_occupied_columns() {
+        let columns = |kind, width| {
+            let xs: Vec<i32> = Tetromino::spawn(kind, width).cells().map(|p| p.0).collect();
+            (*xs.iter().min().unwrap(), *xs.iter().max().unwrap())
+        };
+        // I spawns in its vertical orientation.
+        assert_eq!(columns("I", 10), (4, 4));
+        assert_eq!(columns("O", 10), (4, 5));
+        assert_eq!(columns("T", 10), (3, 5));
+        assert_eq!(columns("O", 9), (3, 4));
+        assert_eq!(columns("I", 9), (4, 4));
+        assert!(Tetromino::spawn("J", 10).cells().all(|p| p.1 >= 0));
+    }
+
+    #[test]
+    fn test_cached_drop_distance_matches_simulation() {
+        use rand::Rng;
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let mut tetris = Tetris::new(8, 12);
+            for y in 4..12 {
+                for x in 0..8 {
+                    if rng.random_bool(0.35) {
+                        tetris.board.set(Position(x, y), Some("J"));
+                    }
+                }
+            }
+            // Clearing a few cells exercises the height recomputation.
+            for _ in 0..6 {
+                let p = Position(rng.random_range(0..8), rng.random_range(0..12));
+                tetris.board.set(p, None);
+            }
+            let kind = Tetromino::random_kind();
+            let mut piece = Tetromino::new(kind, Position(rng.random_range(-1..6), rng.random_range(0..9)));
+            piece.rotation = rng.random_range(0..4);
+            piece.data.cells = Tetromino::get_rotation_data(kind, piece.rotation);
+            if tetris.is_oob(&piece) || tetris.is_colliding(&piece) {
+                continue;
+            }
+
+            let mut expected = 0;
+            let mut probe = piece.clone();
+            loop {
+                probe.data.position = probe.data.position + Position(0, 1);
+                if tetris.is_oob(&probe) || tetris.is_colliding(&probe) {
+                    break;
+                }
+                expected += 1;
+            }
+            assert_eq!(tetris.board.drop_distance(&piece), expected, "{:?}\n{}", piece, tetris.render());
+        }
+    }
+
+    #[test]
+    fn test_locales_define_every_key() {
+        use std::collections::HashMap;
+        let en: HashMap<String, String> = serde_json::from_str(include_str!("../locales/en.json")).unwrap();
+        for (code, source) in [
+            ("zh-CN", include_str!("../locales/zh-CN.json")),
+            ("ar", include_str!("../locales/ar.json")),
+        ] {
+            let strings: HashMap<String, String> = serde_json::from_str(source).unwrap();
+            let mut missing: Vec<_> = en.keys().filter(|k| !strings.contains_key(*k)).collect();
+            missing.sort();
+            assert!(missing.is_empty(), "{} is missing {:?}", code, missing);
+        }
+    }
+
+    #[test]
+    fn test_skin_cell_styles() {
+        use ui::skin::{cell_style, sprite_offset, Skin};
+        assert_eq!(sprite_offset("I"), Some(("900% 100%".to_string(), "0% 0%".to_string())));
+        assert_eq!(sprite_offset(GARBAGE_KIND), Some(("900% 100%".to_string(), "100% 0%".to_string())));
+        assert_eq!(sprite_offset("B"), None);
+        assert_eq!(Skin::Custom.atlas(None), None);
+        let atlas = Skin::Bevel.atlas(None);
+        assert!(cell_style("G", atlas, false).contains("url(\"skins/bevel.svg\")"));
+        // Empty cells keep the flat background under every skin.
+        assert_eq!(cell_style("B", atlas, false), cell_style("B", None, false));
+        assert!(cell_style("B", atlas, true).contains("rgba"));
+    }
+
+    #[test]
+    fn test_profiles_keep_separate_keys() {
+        use ui::storage::{profile_key, SavedProfiles, SETTINGS_KEY};
+        let mut profiles = SavedProfiles::default();
+        assert_eq!(profiles.only(), Some(0));
+        let id = profiles.add("  Sam ");
+        assert_eq!(id, 1);
+        assert_eq!(profiles.profiles[1].name, "Sam");
+        assert_eq!(profiles.only(), None);
+        // The first profile reads the keys saved before profiles existed.
+        assert_eq!(profile_key(SETTINGS_KEY, 0), SETTINGS_KEY);
+        assert_ne!(profile_key(SETTINGS_KEY, id), SETTINGS_KEY);
+    }
+
+    #[test]
+    fn test_sync_merge_keeps_newer_records() {
+        use ui::sync::{merge, Record, Records};
+        let record = |value: Option<&str>, modified_at| Record { value: value.map(str::to_string), modified_at };
+        let ours: Records = [
+            ("a".to_string(), record(Some("1"), 10.0)),
+            ("b".to_string(), record(Some("old"), 5.0)),
+        ]
+        .into();
+        let theirs: Records = [
+            ("b".to_string(), record(None, 7.0)),
+            ("c".to_string(), record(Some("3"), 1.0)),
+            ("a".to_string(), record(Some("stale"), 10.0)),
+        ]
+        .into();
+        let merged = merge(&ours, &theirs);
+        assert_eq!(merged["a"], record(Some("1"), 10.0));
+        // A newer deletion wins over an older value.
+        assert_eq!(merged["b"], record(None, 7.0));
+        assert_eq!(merged["c"], record(Some("3"), 1.0));
+    }
+
+    #[test]
+    fn test_archive_rejects_foreign_and_newer_files() {
+        use ui::archive::{parse, ImportError, SCHEMA_VERSION};
+        let bundle = |format: &str, version: u32| {
+            format!(
+                r#"{{"format":"{}","schema_version":{},"exported_at":1.0,"records":{{"tetris_html.stats":{{"value":"{{}}","modified_at":2.0}}}}}}"#,
+                format, version
+            )
+        };
+        let parsed = parse(&bundle("tetris_html-export", 1)).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.records["tetris_html.stats"].value.as_deref(), Some("{}"));
+        assert_eq!(parse(&bundle("something-else", 1)), Err(ImportError::NotABundle));
+        assert_eq!(parse("not json"), Err(ImportError::NotABundle));
+        assert_eq!(parse(&bundle("tetris_html-export", SCHEMA_VERSION + 1)), Err(ImportError::TooNew(SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_stored_data_migrates_between_schema_versions() {
+        use serde::{Deserialize, Serialize};
+        use serde_json::{json, Value};
+        use ui::storage::{decode, encode, Migration, Persist};
+
+        // Version 0 kept a bare number of lines; version 1 wraps it in an
+        // object; version 2 renames the field.
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Totals {
+            lines: u32,
+        }
+        fn wrap(v: Value) -> Value {
+            json!({ "total": v })
+        }
+        fn rename(v: Value) -> Value {
+            json!({ "lines": v["total"] })
+        }
+        impl Persist for Totals {
+            const MIGRATIONS: &'static [Migration] = &[wrap, rename];
+        }
+
+        assert_eq!(decode::<Totals>("7"), Some((Totals { lines: 7 }, true)));
+        assert_eq!(
+            decode::<Totals>(r#"{"schema_version":1,"data":{"total":8}}"#),
+            Some((Totals { lines: 8 }, true))
+        );
+        let current = encode(&Totals { lines: 9 }).unwrap();
+        assert_eq!(decode::<Totals>(&current), Some((Totals { lines: 9 }, false)));
+        // Saves from a newer build are not guessed at.
+        assert_eq!(decode::<Totals>(r#"{"schema_version":3,"data":{"lines":1}}"#), None);
+        // Existing bare settings still load.
+        let (settings, outdated) = decode::<Settings>(r#"{"retry_key":"KeyT"}"#).unwrap();
+        assert_eq!((settings.retry_key.as_str(), outdated), ("KeyT", true));
+    }
+
+    #[test]
+    fn test_telemetry_reports_clear_types() {
+        use ui::telemetry::{append_log_line, MAX_LOG_LINES};
+        let mut clears = ClearCounts::default();
+        for event in [
+            GameEvent::LinesCleared { count: 1 },
+            GameEvent::TSpin { lines: 2 },
+            GameEvent::LinesCleared { count: 2 },
+            GameEvent::LinesCleared { count: 4 },
+            GameEvent::PerfectClear,
+            GameEvent::PieceLocked { kind: "I" },
+        ] {
+            clears.observe(&event);
+        }
+        assert_eq!((clears.single, clears.double, clears.triple, clears.tetris), (1, 1, 0, 1));
+        assert_eq!((clears.t_spin, clears.perfect_clear), (1, 1));
+
+        let report = GameReport::new("marathon", 61_500.7, 1200, 7, clears);
+        let line = serde_json::to_string(&report).unwrap();
+        assert!(line.contains(r#""mode":"marathon","duration_ms":61500,"score":1200,"lines":7"#));
+        assert_eq!(serde_json::from_str::<GameReport>(&line).unwrap(), report);
+
+        let log = append_log_line("", "a");
+        assert_eq!(append_log_line(&log, "b"), "a\nb\n");
+        let full = (0..MAX_LOG_LINES).fold(String::new(), |log, i| append_log_line(&log, &i.to_string()));
+        let trimmed = append_log_line(&full, "new");
+        assert_eq!(trimmed.lines().count(), MAX_LOG_LINES);
+        assert_eq!((trimmed.lines().next(), trimmed.lines().last()), (Some("1"), Some("new")));
+    }
+
+    #[test]
+    fn test_format_time() {
+        use ui::i18n::{format_number, format_time, Locale};
+        assert_eq!(format_time(Locale::En, 0.0), "00:00.00");
+        assert_eq!(format_time(Locale::En, 83_456.0), "01:23.45");
+        assert_eq!(format_time(Locale::En, 3_600_000.0), "60:00.00");
+        assert_eq!(format_number(Locale::En, 1_234_567), "1,234,567");
+        assert_eq!(format_number(Locale::ZhCn, -1000), "-1,000");
+        assert_eq!(format_number(Locale::En, 999), "999");
+        assert_eq!(format_number(Locale::Ar, 12_345), "12٬345");
+        assert_eq!(format_time(Locale::Ar, 83_456.0), "01:23٫45");
+    }
 }
 
 // FFI C-compatible API (GameState struct, tetris_create, tetris_destroy, etc.)
@@ -0,0 +1,148 @@
+//! Monte Carlo tree search over placements, the strongest `bot::Policy`
+//! (behind the `mcts` feature). Nodes are boards after a lock; below the
+//! root, the next piece is unknown, so each visit draws a random kind and
+//! descends among that kind's placements by UCT. New boards are scored with
+//! the greedy bot's `Weights::cost` instead of being played out, and the
+//! search runs until its time budget or iteration cap is spent. The root
+//! placement visited most wins.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::bot::Weights;
+use crate::clock::now_ms;
+use crate::metrics::{self, BoardMetrics};
+use crate::{pieces, Board, Tetris, Tetromino};
+
+/// Exploration constant of the UCT formula.
+const EXPLORATION: f64 = 0.7;
+/// Pieces looked ahead below the root.
+const MAX_DEPTH: u32 = 3;
+
+struct Node {
+    board: Board,
+    visits: u32,
+    total: f64,
+    /// Child nodes per next piece kind, once that kind was drawn here.
+    children: HashMap<&'static str, Vec<usize>>,
+}
+
+impl Node {
+    fn new(board: Board) -> Self {
+        Self { board, visits: 0, total: 0.0, children: HashMap::new() }
+    }
+
+    fn mean(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.total / self.visits as f64 }
+    }
+}
+
+/// Search limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub ms: f64,
+    pub max_iterations: u32,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self { ms: 50.0, max_iterations: 2000 }
+    }
+}
+
+/// 0–1, higher for boards the greedy cost likes better.
+fn reward(board: &Board, weights: &Weights) -> f64 {
+    1.0 / (1.0 + weights.cost(&BoardMetrics::of(board)) as f64 / 100.0)
+}
+
+/// Placements of `kind` on `board`, without the duplicates symmetric
+/// rotations give.
+fn distinct_placements(board: &Board, kind: &'static str) -> Vec<Tetromino> {
+    let mut seen = Vec::new();
+    metrics::placements(board, kind)
+        .into_iter()
+        .filter(|p| {
+            let mut cells: Vec<_> = p.cells().map(|c| (c.0, c.1)).collect();
+            cells.sort();
+            let new = !seen.contains(&cells);
+            seen.push(cells);
+            new
+        })
+        .collect()
+}
+
+struct Tree<'a> {
+    nodes: Vec<Node>,
+    weights: &'a Weights,
+}
+
+impl Tree<'_> {
+    /// Adds a child of `parent` per placement of `kind`.
+    fn expand(&mut self, parent: usize, kind: &'static str) -> Vec<usize> {
+        let board = &self.nodes[parent].board;
+        let boards: Vec<Board> = distinct_placements(board, kind).iter().map(|p| metrics::locked(board, p)).collect();
+        let start = self.nodes.len();
+        self.nodes.extend(boards.into_iter().map(Node::new));
+        let ids: Vec<usize> = (start..self.nodes.len()).collect();
+        self.nodes[parent].children.insert(kind, ids.clone());
+        ids
+    }
+
+    fn select(&self, parent: usize, children: &[usize]) -> usize {
+        let log_n = (self.nodes[parent].visits.max(1) as f64).ln();
+        let uct = |&id: &usize| {
+            let node = &self.nodes[id];
+            if node.visits == 0 {
+                return f64::INFINITY;
+            }
+            node.mean() + EXPLORATION * (log_n / node.visits as f64).sqrt()
+        };
+        children.iter().copied().max_by(|a, b| uct(a).total_cmp(&uct(b))).unwrap_or(children[0])
+    }
+
+    /// One iteration from `node`, drawing `kind` for it; returns the reward
+    /// backed up.
+    fn visit(&mut self, node: usize, kind: &'static str, depth: u32) -> f64 {
+        let children = match self.nodes[node].children.get(kind) {
+            Some(children) => children.clone(),
+            None => self.expand(node, kind),
+        };
+        let value = if children.is_empty() {
+            // Topped out.
+            0.0
+        } else {
+            let child = self.select(node, &children);
+            if self.nodes[child].visits == 0 || depth >= MAX_DEPTH {
+                let value = reward(&self.nodes[child].board, self.weights);
+                self.nodes[child].visits += 1;
+                self.nodes[child].total += value;
+                value
+            } else {
+                let next = pieces::KINDS[rand::rng().random_range(0..pieces::KINDS.len())];
+                self.visit(child, next, depth + 1)
+            }
+        };
+        let n = &mut self.nodes[node];
+        n.visits += 1;
+        n.total += value;
+        value
+    }
+}
+
+/// The placement for `tetris`'s falling piece the search prefers, if any.
+pub fn best_placement(tetris: &Tetris, weights: &Weights, budget: Budget) -> Option<Tetromino> {
+    let kind = tetris.current_tetromino.as_ref()?.kind;
+    let placements = distinct_placements(&tetris.board, kind);
+    let mut tree = Tree { nodes: vec![Node::new(tetris.board.clone())], weights };
+    let children = tree.expand(0, kind);
+    let deadline = now_ms() + budget.ms;
+    for _ in 0..budget.max_iterations {
+        tree.visit(0, kind, 0);
+        if now_ms() >= deadline {
+            break;
+        }
+    }
+    let best = children.iter().enumerate().max_by_key(|(_, &id)| tree.nodes[id].visits)?.0;
+    placements.into_iter().nth(best)
+}
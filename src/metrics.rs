@@ -0,0 +1,119 @@
+//! Measures of the stack's shape, and a single 0–100 "board health" score
+//! built from them for teaching aids: 100 is a flat, low, hole-free stack.
+//! `placements`, `locked`, `after_lock` and `new_holes` let callers weigh
+//! where a piece could go.
+
+use crate::{Board, Position, Tetromino, TetrominoData};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoardMetrics {
+    /// See `Board::holes`.
+    pub holes: u32,
+    /// Height of the tallest column.
+    pub max_height: u32,
+    /// Sum of height differences between neighbouring columns.
+    pub bumpiness: u32,
+    /// How unevenly blocks sit on the two colors of a checkerboard; an
+    /// imbalance leaves spots only some pieces can fill.
+    pub parity: u32,
+}
+
+impl BoardMetrics {
+    pub fn of(board: &Board) -> Self {
+        let heights: Vec<u32> = (0..board.width()).map(|x| board.column_height(x)).collect();
+        let (mut dark, mut light) = (0i64, 0i64);
+        for (y, row) in board.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.is_some() {
+                    if (x + y) % 2 == 0 { dark += 1 } else { light += 1 }
+                }
+            }
+        }
+        Self {
+            holes: board.holes(),
+            max_height: heights.iter().copied().max().unwrap_or(0),
+            bumpiness: heights.windows(2).map(|w| w[0].abs_diff(w[1])).sum(),
+            parity: (dark - light).unsigned_abs() as u32,
+        }
+    }
+
+    /// 0–100 for a board `height` rows tall. Holes cost up to 40 points,
+    /// height above half the board up to 40, and bumpiness and parity up to
+    /// 10 each.
+    pub fn health(&self, height: u32) -> u32 {
+        let half = (height / 2).max(1);
+        let holes = (self.holes * 6).min(40);
+        let tall = (self.max_height.saturating_sub(half) * 40 / half).min(40);
+        let bumps = self.bumpiness.min(10);
+        let parity = self.parity.min(10);
+        100u32.saturating_sub(holes + tall + bumps + parity)
+    }
+}
+
+/// Every spot a piece of `kind` can be hard-dropped into straight from the
+/// top of `board`: each rotation over each column it fits in.
+pub fn placements(board: &Board, kind: &'static str) -> Vec<Tetromino> {
+    (0..4)
+        .flat_map(|rotation| {
+            let (min, max) = Tetromino::bounding_box(kind, rotation);
+            (-min.0..board.width() as i32 - max.0).map(move |x| Tetromino {
+                kind,
+                data: TetrominoData::new(Position(x, -min.1), Tetromino::get_rotation_data(kind, rotation)),
+                rotation,
+            })
+        })
+        .filter(|piece| piece.cells().all(|p| !board.is_occupied(p)))
+        .map(|mut piece| {
+            piece.data.position.1 += board.drop_distance(&piece);
+            piece
+        })
+        .collect()
+}
+
+/// `board` once `piece` locks where it is and any rows it completes are
+/// cleared.
+pub fn locked(board: &Board, piece: &Tetromino) -> Board {
+    let mut board = board.clone();
+    board.place(piece);
+    let full = board.full_lines();
+    board.remove_rows(&full);
+    board
+}
+
+/// Empty cells of `board` that become holes once `piece` locks where it is:
+/// covered by a block in a row the lock doesn't clear, where nothing
+/// covered them before. In `board`'s rows, so they can be shown before the
+/// piece drops.
+pub fn new_holes(board: &Board, piece: &Tetromino) -> Vec<Position> {
+    let mut placed = board.clone();
+    placed.place(piece);
+    let full = placed.full_lines();
+    let before = covered_cells(board, &[]);
+    covered_cells(&placed, &full).into_iter().filter(|p| !before.contains(p)).collect()
+}
+
+/// Empty cells of `board` with a block above them, ignoring blocks in the
+/// `skipped` rows.
+fn covered_cells(board: &Board, skipped: &[usize]) -> Vec<Position> {
+    let mut cells = Vec::new();
+    for x in 0..board.width() as i32 {
+        let mut roofed = false;
+        for y in 0..board.height() as i32 {
+            if skipped.contains(&(y as usize)) {
+                continue;
+            }
+            let pos = Position(x, y);
+            if board.is_occupied(pos) {
+                roofed = true;
+            } else if roofed {
+                cells.push(pos);
+            }
+        }
+    }
+    cells
+}
+
+/// The metrics of the `locked` board.
+pub fn after_lock(board: &Board, piece: &Tetromino) -> BoardMetrics {
+    BoardMetrics::of(&locked(board, piece))
+}
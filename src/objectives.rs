@@ -0,0 +1,95 @@
+//! Long-term single-player goals, checked against `GameEvent`s and the game
+//! clock. Only ids are meant to be persisted, so the list can grow freely.
+
+use crate::GameEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    /// Clear at least this many rows with a single piece.
+    LinesAtOnce(u32),
+    /// Lock any T-spin.
+    TSpin,
+    /// Keep a game going for this long (paused time excluded).
+    SurviveMs(f64),
+}
+
+#[derive(Debug)]
+pub struct Objective {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub condition: Condition,
+}
+
+impl Objective {
+    pub fn is_met_by(&self, event: &GameEvent) -> bool {
+        match (self.condition, event) {
+            (Condition::LinesAtOnce(n), GameEvent::LinesCleared { count }) => *count >= n,
+            (Condition::TSpin, GameEvent::TSpin { .. }) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_met_after(&self, elapsed_ms: f64) -> bool {
+        matches!(self.condition, Condition::SurviveMs(ms) if elapsed_ms >= ms)
+    }
+}
+
+pub const OBJECTIVES: [Objective; 4] = [
+    Objective { id: "double", title: "Clear 2 lines with one piece", condition: Condition::LinesAtOnce(2) },
+    Objective { id: "tetris", title: "Clear 4 lines with one piece", condition: Condition::LinesAtOnce(4) },
+    Objective { id: "t-spin", title: "Perform a T-spin", condition: Condition::TSpin },
+    Objective { id: "survive-2m", title: "Survive 2 minutes", condition: Condition::SurviveMs(120_000.0) },
+];
+
+/// Which objectives have been completed, across games.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Objectives {
+    completed: Vec<&'static str>,
+}
+
+impl Objectives {
+    /// Rebuilds progress from persisted ids, skipping ones no longer defined.
+    pub fn from_ids<'a>(ids: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut objectives = Self::default();
+        for id in ids {
+            if let Some(o) = OBJECTIVES.iter().find(|o| o.id == id) {
+                objectives.complete(o);
+            }
+        }
+        objectives
+    }
+
+    pub fn completed_ids(&self) -> &[&'static str] {
+        &self.completed
+    }
+
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed.contains(&id)
+    }
+
+    fn complete(&mut self, objective: &'static Objective) -> bool {
+        if self.is_completed(objective.id) {
+            return false;
+        }
+        self.completed.push(objective.id);
+        true
+    }
+
+    /// Returns the objectives `event` newly completed.
+    pub fn observe(&mut self, event: &GameEvent) -> Vec<&'static Objective> {
+        OBJECTIVES
+            .iter()
+            .filter(|o| o.is_met_by(event))
+            .filter(|o| self.complete(o))
+            .collect()
+    }
+
+    /// Returns the objectives newly completed by a game lasting `elapsed_ms`.
+    pub fn observe_time(&mut self, elapsed_ms: f64) -> Vec<&'static Objective> {
+        OBJECTIVES
+            .iter()
+            .filter(|o| o.is_met_after(elapsed_ms))
+            .filter(|o| self.complete(o))
+            .collect()
+    }
+}
@@ -0,0 +1,131 @@
+//! What learning code sees of a game. An `ObservationEncoder` turns a game
+//! (or a bare board) into floats in 0.0–1.0 in one of three formats:
+//!
+//! * `Flat`: one vector, the `height × width` cells (stack and falling piece
+//!   as 1.0, top row first) followed by a one-hot of the falling piece's
+//!   kind in `pieces::KINDS` order.
+//! * `Grid`: binary `4 × height × width` channels: stack, falling piece,
+//!   ghost, garbage. Stack cells from garbage rows are only in the last.
+//! * `Columns`: per column its height and its holes, each divided by the
+//!   board height, then the falling piece's kind as in `Flat`.
+//!
+//! `encode_mirrored` gives the same position flipped left to right, for
+//! data augmentation.
+//!
+//! The RL env reads it over the FFI (`tetris_get_observation`) and
+//! `onnx::ValueNet` feeds it to networks.
+
+use crate::{pieces, Board, Tetris, Tetromino, GARBAGE_KIND};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObservationFormat {
+    #[default]
+    Flat,
+    Grid,
+    Columns,
+}
+
+impl ObservationFormat {
+    pub const ALL: [ObservationFormat; 3] = [ObservationFormat::Flat, ObservationFormat::Grid, ObservationFormat::Columns];
+
+    /// Name used on command lines and in the Python env.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObservationFormat::Flat => "flat",
+            ObservationFormat::Grid => "grid",
+            ObservationFormat::Columns => "columns",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+}
+
+/// Channels of the `Grid` format, in order.
+pub const GRID_CHANNELS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObservationEncoder {
+    pub format: ObservationFormat,
+}
+
+impl ObservationEncoder {
+    pub fn new(format: ObservationFormat) -> Self {
+        Self { format }
+    }
+
+    /// Tensor shape for a `width × height` board, without a batch axis.
+    pub fn shape(&self, width: u32, height: u32) -> Vec<usize> {
+        let (w, h) = (width as usize, height as usize);
+        match self.format {
+            ObservationFormat::Flat => vec![w * h + pieces::KINDS.len()],
+            ObservationFormat::Grid => vec![GRID_CHANNELS, h, w],
+            ObservationFormat::Columns => vec![2 * w + pieces::KINDS.len()],
+        }
+    }
+
+    pub fn len(&self, width: u32, height: u32) -> usize {
+        self.shape(width, height).iter().product()
+    }
+
+    /// `tetris` as it is now, falling piece and ghost included.
+    pub fn encode(&self, tetris: &Tetris) -> Vec<f32> {
+        self.encode_board(&tetris.board, tetris.current_tetromino.as_ref(), tetris.ghost_tetromino.as_ref())
+    }
+
+    /// `encode` of the game flipped left to right, for augmenting training
+    /// data: mirror positions are worth the same, so one sample is two.
+    pub fn encode_mirrored(&self, tetris: &Tetris) -> Vec<f32> {
+        let mirror = |piece: Option<&Tetromino>| piece.map(|p| p.mirrored(tetris.width));
+        let (active, ghost) = (mirror(tetris.current_tetromino.as_ref()), mirror(tetris.ghost_tetromino.as_ref()));
+        self.encode_board(&tetris.board.mirrored(), active.as_ref(), ghost.as_ref())
+    }
+
+    /// `board` with an optional falling piece and its ghost, e.g. to rate a
+    /// board after a placement, where neither is known yet.
+    pub fn encode_board(&self, board: &Board, active: Option<&Tetromino>, ghost: Option<&Tetromino>) -> Vec<f32> {
+        let (w, h) = (board.width() as usize, board.height() as usize);
+        let mut out = vec![0.0; self.len(board.width(), board.height())];
+        let mark = |out: &mut [f32], offset: usize, piece: Option<&Tetromino>| {
+            for p in piece.into_iter().flat_map(Tetromino::cells) {
+                if (0..w as i32).contains(&p.0) && (0..h as i32).contains(&p.1) {
+                    out[offset + p.1 as usize * w + p.0 as usize] = 1.0;
+                }
+            }
+        };
+        let kind_offset = match self.format {
+            ObservationFormat::Flat => {
+                for (i, cell) in board.rows().flatten().enumerate() {
+                    out[i] = cell.is_some() as u8 as f32;
+                }
+                mark(&mut out, 0, active);
+                w * h
+            }
+            ObservationFormat::Grid => {
+                for (i, cell) in board.rows().flatten().enumerate() {
+                    match cell {
+                        Some(GARBAGE_KIND) => out[3 * w * h + i] = 1.0,
+                        Some(_) => out[i] = 1.0,
+                        None => {}
+                    }
+                }
+                mark(&mut out, w * h, active);
+                mark(&mut out, 2 * w * h, ghost);
+                return out;
+            }
+            ObservationFormat::Columns => {
+                for x in 0..w {
+                    let (height, holes) = (board.column_height(x as u32), board.column_holes(x as u32));
+                    out[x] = height as f32 / h as f32;
+                    out[w + x] = holes as f32 / h as f32;
+                }
+                2 * w
+            }
+        };
+        if let Some(kind) = active.and_then(|p| pieces::kind_index(p.kind)) {
+            out[kind_offset + kind] = 1.0;
+        }
+        out
+    }
+}
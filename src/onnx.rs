@@ -0,0 +1,79 @@
+//! Learned bot policies (behind the `onnx` feature, desktop builds only). A
+//! `ValueNet` is an ONNX network that rates boards: it takes a board encoded
+//! by an `observation::ObservationEncoder` (with a leading batch axis of 1,
+//! and no falling piece) and returns one number, higher for better boards. `best_placement` locks each placement
+//! and lets the network pick. `models/fewest_cells.onnx` is a toy network
+//! (the negated sum of its input, so the cell count) to try it with.
+
+use std::fmt;
+use std::path::Path;
+
+use tract_onnx::prelude::*;
+use tract_onnx::tract_core::anyhow::Context;
+
+use crate::observation::ObservationEncoder;
+use crate::{metrics, Board, Tetris, Tetromino};
+
+pub struct ValueNet {
+    plan: TypedRunnableModel<TypedModel>,
+    encoder: ObservationEncoder,
+    width: u32,
+    height: u32,
+}
+
+impl fmt::Debug for ValueNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueNet")
+            .field("encoder", &self.encoder)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+/// Networks are compared by identity; see `bot::Policy`.
+impl PartialEq for ValueNet {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl ValueNet {
+    /// Loads the network at `path` for boards `width` by `height`, fed by
+    /// `encoder`.
+    pub fn load(path: impl AsRef<Path>, width: u32, height: u32, encoder: ObservationEncoder) -> TractResult<Self> {
+        let shape: Vec<usize> = std::iter::once(1).chain(encoder.shape(width, height)).collect();
+        let plan = tract_onnx::onnx()
+            .model_for_path(path)?
+            .with_input_fact(0, f32::fact(&shape).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Self { plan, encoder, width, height })
+    }
+
+    /// The network's rating of `board`.
+    pub fn value(&self, board: &Board) -> TractResult<f32> {
+        let shape: Vec<usize> = std::iter::once(1).chain(self.encoder.shape(self.width, self.height)).collect();
+        let input = Tensor::from_shape(&shape, &self.encoder.encode_board(board, None, None))?;
+        let output = self.plan.run(tvec!(input.into()))?;
+        output[0].to_array_view::<f32>()?.iter().next().copied().context("network returned no value")
+    }
+}
+
+/// The placement for `tetris`'s falling piece whose board `net` rates
+/// highest. `None` without a piece, for other board sizes, or if the
+/// network fails.
+pub fn best_placement(tetris: &Tetris, net: &ValueNet) -> Option<Tetromino> {
+    let kind = tetris.current_tetromino.as_ref()?.kind;
+    if (tetris.width, tetris.height) != (net.width, net.height) {
+        return None;
+    }
+    let mut best: Option<(f32, Tetromino)> = None;
+    for piece in metrics::placements(&tetris.board, kind) {
+        let value = net.value(&metrics::locked(&tetris.board, &piece)).ok()?;
+        if best.as_ref().is_none_or(|(v, _)| value > *v) {
+            best = Some((value, piece));
+        }
+    }
+    best.map(|(_, piece)| piece)
+}
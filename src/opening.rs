@@ -0,0 +1,102 @@
+//! Opening book for the bot. For early boards it names where each piece
+//! should go, e.g. to build a known setup, and `Bot` plays those placements
+//! before falling back to its search. `bundled` is the book shipped in
+//! `openings/default.txt`, whose header describes the format.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::{metrics, pieces, Board, Tetromino};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookError {
+    /// Line `n` (1-based) isn't `<board> <kind> <column,row> x4`.
+    BadLine(usize),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::BadLine(n) => write!(f, "line {} is not a book entry", n),
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+/// A placement as the cells the piece ends up on: columns from the left and
+/// rows from the bottom, sorted.
+type Cells = [(i32, i32); 4];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    entries: HashMap<(String, &'static str), Cells>,
+}
+
+impl OpeningBook {
+    pub fn parse(text: &str) -> Result<Self, BookError> {
+        let mut entries = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad = || BookError::BadLine(i + 1);
+            let mut words = line.split_whitespace();
+            let board = words.next().ok_or_else(bad)?;
+            let kind = words.next().and_then(|k| pieces::KINDS.iter().copied().find(|&c| c == k)).ok_or_else(bad)?;
+            let cells: Vec<(i32, i32)> = words
+                .map(|w| {
+                    let (x, y) = w.split_once(',')?;
+                    Some((x.parse().ok()?, y.parse().ok()?))
+                })
+                .collect::<Option<_>>()
+                .ok_or_else(bad)?;
+            let mut cells: Cells = cells.try_into().map_err(|_| bad())?;
+            cells.sort();
+            entries.insert((board.to_string(), kind), cells);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The book's placement of `kind` on `board`, if it has one that can be
+    /// hard-dropped there.
+    pub fn lookup(&self, board: &Board, kind: &'static str) -> Option<Tetromino> {
+        let cells = self.entries.get(&(key(board), kind))?;
+        metrics::placements(board, kind).into_iter().find(|p| {
+            let mut placed: Vec<(i32, i32)> = p.cells().map(|c| (c.0, board.height() as i32 - 1 - c.1)).collect();
+            placed.sort();
+            placed == cells
+        })
+    }
+}
+
+/// `board` as written in a book: filled rows from the bottom up.
+pub fn key(board: &Board) -> String {
+    let rows: Vec<String> = board
+        .rows()
+        .filter(|row| row.iter().any(Option::is_some))
+        .map(|row| row.iter().map(|c| if c.is_some() { 'X' } else { '.' }).collect())
+        .collect();
+    if rows.is_empty() {
+        return "-".to_string();
+    }
+    rows.into_iter().rev().collect::<Vec<_>>().join("/")
+}
+
+/// The book in `openings/default.txt`.
+pub fn bundled() -> &'static OpeningBook {
+    static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+    BOOK.get_or_init(|| {
+        OpeningBook::parse(include_str!("../openings/default.txt")).expect("bundled opening book is valid")
+    })
+}
@@ -0,0 +1,216 @@
+//! Piece definitions shared by the engine and external tools (renderers,
+//! editors, bots), so nobody has to hard-code shapes.
+//!
+//! Rotations are indexed 0..4, each one a clockwise quarter turn from the
+//! previous. Cell coordinates are relative to the piece position, with y
+//! growing downward.
+
+use crate::Position;
+
+/// Piece kinds in table order; `kind_index` maps a kind back to its slot.
+pub const KINDS: [&str; 7] = ["I", "T", "O", "J", "L", "S", "Z"];
+
+macro_rules! place_it {
+    ($($pos:expr),*) => {
+        [$(Position($pos.0, $pos.1)),*]
+    };
+}
+
+const S_OPTS: [[[Position; 4]; 4]; 7] = [
+    [
+        place_it!((1, 0), (1, 1), (1, 2), (1, 3)),
+        place_it!((0, 1), (1, 1), (2, 1), (3, 1)),
+        place_it!((2, 0), (2, 1), (2, 2), (2, 3)),
+        place_it!((0, 2), (1, 2), (2, 2), (3, 2)),
+    ],
+    [
+        place_it!((1, 0), (0, 1), (1, 1), (2, 1)),
+        place_it!((1, 0), (1, 1), (2, 1), (1, 2)),
+        place_it!((0, 1), (1, 1), (2, 1), (1, 2)),
+        place_it!((1, 0), (0, 1), (1, 1), (1, 2)),
+    ],
+    [
+        place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
+        place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
+        place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
+        place_it!((1, 0), (2, 0), (1, 1), (2, 1)),
+    ],
+    [
+        place_it!((1, 0), (1, 1), (1, 2), (0, 2)),
+        place_it!((0, 0), (0, 1), (1, 1), (2, 1)),
+        place_it!((1, 0), (2, 0), (1, 1), (1, 2)),
+        place_it!((0, 1), (1, 1), (2, 1), (2, 2)),
+    ],
+    [
+        place_it!((1, 0), (1, 1), (1, 2), (2, 2)),
+        place_it!((0, 1), (1, 1), (2, 1), (0, 2)),
+        place_it!((0, 0), (1, 0), (1, 1), (1, 2)),
+        place_it!((0, 1), (1, 1), (2, 1), (2, 0)),
+    ],
+    [
+        place_it!((1, 0), (2, 0), (1, 1), (0, 1)),
+        place_it!((1, 0), (1, 1), (2, 1), (2, 2)),
+        place_it!((1, 0), (2, 0), (1, 1), (0, 1)),
+        place_it!((1, 0), (1, 1), (2, 1), (2, 2)),
+    ],
+    [
+        place_it!((0, 0), (1, 0), (1, 1), (2, 1)),
+        place_it!((1, 0), (1, 1), (0, 1), (0, 2)),
+        place_it!((0, 0), (1, 0), (1, 1), (2, 1)),
+        place_it!((1, 0), (1, 1), (0, 1), (0, 2)),
+    ],
+];
+
+/// Inclusive bounding box `(min, max)` of each entry in `S_OPTS`.
+const S_BOUNDS: [[(Position, Position); 4]; 7] = {
+    let mut bounds = [[(Position(0, 0), Position(0, 0)); 4]; 7];
+    let mut piece = 0;
+    while piece < 7 {
+        let mut rotation = 0;
+        while rotation < 4 {
+            let cells = &S_OPTS[piece][rotation];
+            let (mut min, mut max) = (cells[0], cells[0]);
+            let mut i = 1;
+            while i < 4 {
+                let p = cells[i];
+                if p.0 < min.0 { min.0 = p.0; }
+                if p.1 < min.1 { min.1 = p.1; }
+                if p.0 > max.0 { max.0 = p.0; }
+                if p.1 > max.1 { max.1 = p.1; }
+                i += 1;
+            }
+            bounds[piece][rotation] = (min, max);
+            rotation += 1;
+        }
+        piece += 1;
+    }
+    bounds
+};
+
+
+/// Super Rotation System kick tests for a clockwise turn out of each SRS
+/// state (0, R, 2, L), in board coordinates. The published tables count y
+/// upward, so their y is negated here.
+const SRS_JLSTZ_KICKS: [[Position; 5]; 4] = [
+    place_it!((0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)),
+    place_it!((0, 0), (1, 0), (1, 1), (0, -2), (1, -2)),
+    place_it!((0, 0), (1, 0), (1, -1), (0, 2), (1, 2)),
+    place_it!((0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)),
+];
+const SRS_I_KICKS: [[Position; 5]; 4] = [
+    place_it!((0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)),
+    place_it!((0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)),
+    place_it!((0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)),
+    place_it!((0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)),
+];
+
+/// The SRS state each entry of `S_OPTS` is, and how far its cells sit from
+/// that state's. I and J start from L, L from R; S and Z only keep two
+/// shapes, so two of their rotations are shifted copies of SRS states.
+const SRS_STATES: [[(usize, Position); 4]; 7] = [
+    [(3, Position(0, 0)), (0, Position(0, 0)), (1, Position(0, 0)), (2, Position(0, 0))],
+    [(0, Position(0, 0)), (1, Position(0, 0)), (2, Position(0, 0)), (3, Position(0, 0))],
+    [(0, Position(0, 0)), (1, Position(0, 0)), (2, Position(0, 0)), (3, Position(0, 0))],
+    [(3, Position(0, 0)), (0, Position(0, 0)), (1, Position(0, 0)), (2, Position(0, 0))],
+    [(1, Position(0, 0)), (2, Position(0, 0)), (3, Position(0, 0)), (0, Position(0, 0))],
+    [(0, Position(0, 0)), (1, Position(0, 0)), (2, Position(0, -1)), (3, Position(1, 0))],
+    [(0, Position(0, 0)), (1, Position(-1, 0)), (2, Position(0, -1)), (3, Position(0, 0))],
+];
+
+/// `kicks` for every kind and rotation: the SRS tests of the states the
+/// turn goes between, corrected for where `S_OPTS` puts those states.
+const KICKS: [[[Position; 5]; 4]; 7] = {
+    let mut kicks = [[[Position(0, 0); 5]; 4]; 7];
+    let mut piece = 0;
+    while piece < 7 {
+        let mut rotation = 0;
+        while rotation < 4 {
+            let (from, from_shift) = SRS_STATES[piece][rotation];
+            let (_, to_shift) = SRS_STATES[piece][(rotation + 1) % 4];
+            let table = if piece == 0 { &SRS_I_KICKS } else { &SRS_JLSTZ_KICKS };
+            let mut i = 0;
+            while i < 5 {
+                let k = table[from][i];
+                kicks[piece][rotation][i] = Position(k.0 + from_shift.0 - to_shift.0, k.1 + from_shift.1 - to_shift.1);
+                i += 1;
+            }
+            rotation += 1;
+        }
+        piece += 1;
+    }
+    kicks
+};
+
+/// Offsets tried, in order, when rotating `kind` from `from` to `from + 1`:
+/// the Super Rotation System's five tests, with I's own table. O turns in
+/// place or not at all, as do kinds that aren't pieces.
+pub fn kicks(kind: &str, from: usize) -> &'static [Position] {
+    match kind_index(kind) {
+        Some(2) | None => &KICKS[2][0][..1],
+        Some(i) => &KICKS[i][from % 4],
+    }
+}
+
+/// The kind `kind` turns into when mirrored left to right: S and Z swap, as
+/// do J and L; the rest are symmetric. Other kinds, like garbage, stay.
+pub fn mirror_kind(kind: &'static str) -> &'static str {
+    match kind {
+        "S" => "Z",
+        "Z" => "S",
+        "J" => "L",
+        "L" => "J",
+        _ => kind,
+    }
+}
+
+pub fn kind_index(kind: &str) -> Option<usize> {
+    KINDS.iter().position(|&k| k == kind)
+}
+
+fn index(kind: &str) -> usize {
+    kind_index(kind).unwrap_or_else(|| unreachable!("unknown tetromino kind {}", kind))
+}
+
+/// Cells of `kind` in `rotation`.
+pub fn cells(kind: &str, rotation: usize) -> [Position; 4] {
+    S_OPTS[index(kind)][rotation]
+}
+
+/// Inclusive `(min, max)` corners of the cells of `kind` in `rotation`.
+pub fn bounding_box(kind: &str, rotation: usize) -> (Position, Position) {
+    S_BOUNDS[index(kind)][rotation]
+}
+
+/// Where `kind` spawns on a board `width` cells wide: its occupied columns are
+/// centered, rounding toward the left for odd-width pieces (guideline spawn),
+/// and its top row sits on row 0.
+pub fn spawn_position(kind: &str, width: u32) -> Position {
+    spawn_position_rotated(kind, 0, width)
+}
+
+/// `spawn_position` for `kind` spawning in `rotation`.
+pub fn spawn_position_rotated(kind: &str, rotation: usize, width: u32) -> Position {
+    let (min, max) = bounding_box(kind, rotation);
+    let box_width = max.0 - min.0 + 1;
+    let left = (width as i32 - box_width) / 2;
+    Position(left - min.0, -min.1)
+}
+
+/// The rotation each kind spawns in, indexed like `KINDS`; all 0 by default.
+/// Rotating from a spawn state continues in the usual order, so kicks and
+/// T-spin checks see it as any other rotation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpawnOrientations(pub [usize; 7]);
+
+impl SpawnOrientations {
+    pub fn of(&self, kind: &str) -> usize {
+        kind_index(kind).map_or(0, |i| self.0[i])
+    }
+
+    /// Makes `kind` spawn in `rotation`, taken modulo 4.
+    pub fn set(&mut self, kind: &str, rotation: usize) {
+        if let Some(i) = kind_index(kind) {
+            self.0[i] = rotation % 4;
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Every placement of a game, for analysis outside the game. `Tetris`
+//! reports each lock as a `GameEvent::Placed`, and `PlacementLog` collects
+//! them into CSV or JSON, e.g. for the post-game download.
+
+use serde::Serialize;
+
+use crate::GameEvent;
+
+/// One locked piece.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Placement {
+    /// 1 for the first piece of the game.
+    pub piece: u32,
+    pub kind: &'static str,
+    /// Final position and rotation, as in `Tetromino`.
+    pub x: i32,
+    pub y: i32,
+    pub rotation: usize,
+    /// Game time from the piece spawning to it locking.
+    pub time_ms: f64,
+    /// Actions other than gravity ticks applied to the piece, including ones
+    /// that could not move it.
+    pub inputs: u32,
+    /// Rows the piece completed.
+    pub lines: u32,
+    /// `BoardMetrics::health` before the lock and once its rows are cleared.
+    pub health_before: u32,
+    pub health_after: u32,
+}
+
+const CSV_HEADER: &str = "piece,kind,x,y,rotation,time_ms,inputs,lines,health_before,health_after";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlacementLog {
+    pub placements: Vec<Placement>,
+}
+
+impl PlacementLog {
+    pub fn observe(&mut self, event: &GameEvent) {
+        if let GameEvent::Placed(placement) = event {
+            self.placements.push(*placement);
+        }
+    }
+
+    /// One line per placement under a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        for p in &self.placements {
+            csv += &format!(
+                "{},{},{},{},{},{:.0},{},{},{},{}\n",
+                p.piece, p.kind, p.x, p.y, p.rotation, p.time_ms, p.inputs, p.lines, p.health_before, p.health_after
+            );
+        }
+        csv
+    }
+
+    /// An array of placement objects with the CSV's column names as keys.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.placements).unwrap_or_default()
+    }
+}
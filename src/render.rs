@@ -0,0 +1,111 @@
+//! Backend-agnostic drawing. `draw` walks a game once per frame and feeds a
+//! `Renderer`: every cell, then the overlays on top. Cells are the piece
+//! kinds `Tetris::render_view` uses, with `EMPTY` and `GHOST` for the rest.
+//! `TextRenderer` here draws to a string for terminals; the web frontend has
+//! DOM and canvas renderers, and the FFI forwards frames to C callbacks.
+
+use crate::{GamePhase, Tetris};
+
+pub const EMPTY: &str = "B";
+pub const GHOST: &str = "G";
+
+/// Something drawn over the cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overlay {
+    /// Row `y` is being cleared, `progress` of the way from 0.0 to 1.0.
+    ClearingRow { y: u32, progress: f64 },
+    Paused,
+    GameOver,
+}
+
+pub trait Renderer {
+    fn begin_frame(&mut self, width: u32, height: u32);
+    /// Draws the cell at column `x`, row `y` (0 at the top).
+    fn draw_cell(&mut self, x: u32, y: u32, kind: &'static str);
+    fn draw_overlay(&mut self, overlay: Overlay);
+    fn end_frame(&mut self);
+}
+
+/// Draws one frame of `tetris` with `renderer`.
+pub fn draw(tetris: &Tetris, renderer: &mut dyn Renderer) {
+    renderer.begin_frame(tetris.width, tetris.height);
+    for (y, row) in tetris.render_view().into_iter().enumerate() {
+        for (x, kind) in row.into_iter().enumerate() {
+            renderer.draw_cell(x as u32, y as u32, kind);
+        }
+    }
+    if let (Some(rows), Some(progress)) = (&tetris.lines_being_cleared, tetris.clear_animation_progress()) {
+        for &y in rows {
+            renderer.draw_overlay(Overlay::ClearingRow { y: y as u32, progress });
+        }
+    }
+    match tetris.phase() {
+        GamePhase::Paused => renderer.draw_overlay(Overlay::Paused),
+        GamePhase::GameOver => renderer.draw_overlay(Overlay::GameOver),
+        _ => {}
+    }
+    renderer.end_frame();
+}
+
+/// Several text frames next to each other, `gap` spaces apart, e.g. the
+/// boards of a `lobby`. Shorter frames are padded out.
+pub fn side_by_side(frames: &[&str], gap: usize) -> String {
+    let widths: Vec<usize> = frames.iter().map(|f| f.lines().map(|l| l.chars().count()).max().unwrap_or(0)).collect();
+    let height = frames.iter().map(|f| f.lines().count()).max().unwrap_or(0);
+    (0..height)
+        .map(|y| {
+            let cells: Vec<String> = frames
+                .iter()
+                .zip(&widths)
+                .map(|(frame, &width)| format!("{:<width$}", frame.lines().nth(y).unwrap_or("")))
+                .collect();
+            cells.join(&" ".repeat(gap)).trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Draws to text, one character per cell as in `Tetris::render`, followed by
+/// a status line while paused or over.
+#[derive(Debug, Clone, Default)]
+pub struct TextRenderer {
+    rows: Vec<String>,
+    status: Option<&'static str>,
+    text: String,
+}
+
+impl TextRenderer {
+    /// The last finished frame.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn begin_frame(&mut self, _width: u32, height: u32) {
+        self.rows = vec![String::new(); height as usize];
+        self.status = None;
+    }
+
+    fn draw_cell(&mut self, _x: u32, y: u32, kind: &'static str) {
+        if let Some(row) = self.rows.get_mut(y as usize) {
+            row.push_str(kind);
+        }
+    }
+
+    fn draw_overlay(&mut self, overlay: Overlay) {
+        match overlay {
+            Overlay::ClearingRow { .. } => {}
+            Overlay::Paused => self.status = Some("PAUSED"),
+            Overlay::GameOver => self.status = Some("GAME OVER"),
+        }
+    }
+
+    fn end_frame(&mut self) {
+        self.text = self.rows.join("\n");
+        if let Some(status) = self.status {
+            self.text.push('\n');
+            self.text.push_str(status);
+        }
+    }
+}
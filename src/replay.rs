@@ -0,0 +1,238 @@
+//! Recorded games. A `Replay` holds the pieces a game was dealt and every
+//! action applied to it with the game time it happened at, which is enough
+//! for `play` to rerun the game move for move. Replays are of 10×25 games on
+//! a manual clock with instant line clears, as `--simulate` plays them.
+//!
+//! The text form is a `pieces` line with the kinds in order, optionally an
+//! `rng` line with the generator state that deals on once they run out (see
+//! `generator::PieceRng`) and a `randomizer` line saying how it deals, then
+//! one `<ms> <action>` line per input. Replays from before bags have no
+//! `randomizer` line and deal classic random pieces.
+//!
+//! ```text
+//! pieces ITOJLSZ...
+//! rng 3f2a9c0d1e5b7784
+//! randomizer bag
+//! 0 Rotate
+//! 0 MoveLeft
+//! 16 Tick
+//! ```
+
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::generator::{PieceBag, PieceRng, Randomizer};
+use crate::input::ActionQueue;
+use crate::{pieces, Action, GameEvent, Tetris};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    MissingPieces,
+    UnknownPiece(char),
+    BadRng,
+    BadRandomizer,
+    /// Line `n` (1-based) isn't `<ms> <action>`.
+    BadInput(usize),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::MissingPieces => write!(f, "replay has no pieces line"),
+            ReplayError::UnknownPiece(c) => write!(f, "unknown piece kind {}", c),
+            ReplayError::BadRng => write!(f, "rng line is not a generator state"),
+            ReplayError::BadRandomizer => write!(f, "randomizer line is neither bag nor classic"),
+            ReplayError::BadInput(n) => write!(f, "line {} is not a timed action", n),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub pieces: Vec<&'static str>,
+    /// Generator state once `pieces` run out; without it the game draws
+    /// further pieces from a fresh generator.
+    pub rng: Option<PieceRng>,
+    pub randomizer: Randomizer,
+    pub inputs: Vec<(f64, Action)>,
+}
+
+impl Replay {
+    /// A replay about to record a game dealt `count` random pieces, and more
+    /// from the generator that dealt them. A bag dealt into is dealt out, so
+    /// there may be up to six pieces more.
+    pub fn deal(count: usize) -> Self {
+        Self::deal_from(count, PieceRng::from_entropy())
+    }
+
+    /// Like `deal`, with the pieces drawn from `rng`.
+    pub fn deal_from(count: usize, mut rng: PieceRng) -> Self {
+        let curriculum = crate::curriculum::Curriculum::default();
+        let mut bag = PieceBag::default();
+        let mut pieces: Vec<_> = (0..count).map(|_| bag.deal(&curriculum, &mut rng)).collect();
+        pieces.extend(bag.queued());
+        Self { pieces, rng: Some(rng), randomizer: bag.randomizer(), inputs: Vec::new() }
+    }
+
+    /// A new game set up as the replay's was, with its pieces dealt.
+    pub fn start(&self) -> Tetris {
+        let mut tetris = Tetris::new(10, 25);
+        tetris.set_clock(Clock::Manual(0.0));
+        tetris.set_clear_animation_ms(0.0);
+        tetris.set_randomizer(self.randomizer);
+        if !self.pieces.is_empty() {
+            tetris.set_upcoming_pieces(&self.pieces);
+            tetris.spawn_next();
+        }
+        if let Some(rng) = self.rng {
+            tetris.set_piece_rng(rng);
+        }
+        tetris.drain_events();
+        tetris
+    }
+
+    pub fn record(&mut self, at_ms: f64, action: Action) {
+        self.inputs.push((at_ms, action));
+    }
+
+    /// The inputs as a source for a live game loop, each due at its recorded
+    /// time; start the game with `start` to get the same pieces.
+    pub fn playback(&self) -> ActionQueue {
+        let mut queue = ActionQueue::default();
+        for &(at_ms, action) in &self.inputs {
+            queue.push(at_ms, action);
+        }
+        queue
+    }
+
+    /// Reruns the game, calling `observe` with the game and the events each
+    /// input caused. Returns the finished game.
+    pub fn play(&self, mut observe: impl FnMut(&Tetris, &[GameEvent])) -> Tetris {
+        let mut tetris = self.start();
+        for &(at_ms, action) in &self.inputs {
+            tetris.advance_clock(at_ms - tetris.now_ms());
+            tetris.apply(action);
+            let events = tetris.drain_events();
+            observe(&tetris, &events);
+        }
+        tetris
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ReplayError> {
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let kinds = lines
+            .next()
+            .and_then(|(_, l)| l.strip_prefix("pieces "))
+            .ok_or(ReplayError::MissingPieces)?;
+        let mut lines = lines.peekable();
+        let rng = match lines.next_if(|(_, l)| l.starts_with("rng ")) {
+            Some((_, l)) => Some(l["rng ".len()..].parse().map_err(|_| ReplayError::BadRng)?),
+            None => None,
+        };
+        let randomizer = match lines.next_if(|(_, l)| l.starts_with("randomizer ")) {
+            Some((_, l)) => match l["randomizer ".len()..].trim() {
+                "bag" => Randomizer::Bag,
+                "classic" => Randomizer::Classic,
+                _ => return Err(ReplayError::BadRandomizer),
+            },
+            None => Randomizer::Classic,
+        };
+        let pieces = kinds
+            .trim()
+            .chars()
+            .map(|c| pieces::KINDS.iter().copied().find(|k| k.starts_with(c)).ok_or(ReplayError::UnknownPiece(c)))
+            .collect::<Result<_, _>>()?;
+        let inputs = lines
+            .map(|(i, line)| {
+                let (ms, name) = line.trim().split_once(' ').ok_or(ReplayError::BadInput(i + 1))?;
+                let ms = ms.parse().map_err(|_| ReplayError::BadInput(i + 1))?;
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|a| format!("{:?}", a) == name)
+                    .ok_or(ReplayError::BadInput(i + 1))?;
+                Ok((ms, action))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { pieces, rng, randomizer, inputs })
+    }
+}
+
+/// A replay played up to some input, for viewers that step through it and
+/// seek. Seeking back replays from the start.
+#[derive(Debug)]
+pub struct ReplayCursor {
+    replay: Replay,
+    game: Tetris,
+    next: usize,
+}
+
+impl ReplayCursor {
+    pub fn new(replay: Replay) -> Self {
+        let game = replay.start();
+        Self { replay, game, next: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// The game after the inputs played so far.
+    pub fn game(&self) -> &Tetris {
+        &self.game
+    }
+
+    /// Inputs played so far.
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    /// Game time of the last input played.
+    pub fn at_ms(&self) -> f64 {
+        self.next.checked_sub(1).map_or(0.0, |i| self.replay.inputs[i].0)
+    }
+
+    /// Plays the first `position` inputs, or all of them if there are fewer.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.replay.inputs.len());
+        if position < self.next {
+            self.game = self.replay.start();
+            self.next = 0;
+        }
+        for &(at_ms, action) in &self.replay.inputs[self.next..position] {
+            self.game.advance_clock(at_ms - self.game.now_ms());
+            self.game.apply(action);
+        }
+        self.game.drain_events();
+        self.next = position;
+    }
+
+    /// Plays on through the inputs due by `at_ms`.
+    pub fn play_until(&mut self, at_ms: f64) {
+        let due = self.replay.inputs[self.next..].iter().take_while(|(ms, _)| *ms <= at_ms).count();
+        self.seek(self.next + due);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.replay.inputs.len()
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "pieces {}", self.pieces.concat())?;
+        if let Some(rng) = self.rng {
+            writeln!(f, "rng {}", rng)?;
+        }
+        let randomizer = match self.randomizer {
+            Randomizer::Bag => "bag",
+            Randomizer::Classic => "classic",
+        };
+        writeln!(f, "randomizer {}", randomizer)?;
+        for (ms, action) in &self.inputs {
+            writeln!(f, "{} {:?}", ms, action)?;
+        }
+        Ok(())
+    }
+}
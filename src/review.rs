@@ -0,0 +1,54 @@
+//! The moments of a game worth a second look once it is over: every Tetris,
+//! every misdrop (see `stats::judge`) and the top-out, each with the board as
+//! it was then. The post-game review steps through them.
+
+use crate::stats::Misdrop;
+use crate::{GameEvent, Tetris};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MomentKind {
+    Tetris,
+    Misdrop(Misdrop),
+    TopOut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moment {
+    pub kind: MomentKind,
+    /// Pieces placed so far, including the one the moment is about.
+    pub piece: u32,
+    pub elapsed_ms: f64,
+    /// Locked cells only, in `render_view` letters, top row first.
+    pub board: Vec<Vec<&'static str>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Review {
+    pub moments: Vec<Moment>,
+    /// Pieces placed over the whole game, for laying moments out on a
+    /// timeline.
+    pub pieces: u32,
+}
+
+impl Review {
+    /// Notes `event` if it marks a key moment, snapshotting `tetris`'s board.
+    pub fn observe(&mut self, event: &GameEvent, tetris: &Tetris) {
+        self.pieces = tetris.pieces_placed();
+        let kind = match *event {
+            GameEvent::LinesCleared { count: 4.. } => MomentKind::Tetris,
+            GameEvent::Misdrop(misdrop) => MomentKind::Misdrop(misdrop),
+            GameEvent::GameOver { .. } => MomentKind::TopOut,
+            _ => return,
+        };
+        self.moments.push(Moment {
+            kind,
+            piece: tetris.pieces_placed(),
+            elapsed_ms: tetris.elapsed_ms(),
+            board: tetris.board.rows().map(|row| row.iter().map(|c| c.unwrap_or("B")).collect()).collect(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moments.is_empty()
+    }
+}
@@ -0,0 +1,91 @@
+//! Reward shaping for reinforcement learning. A `RewardShaper` compares the
+//! game after each step with the game before it and adds up the
+//! `RewardWeights` terms, so environments can tune what is rewarded without
+//! recompiling. Over the FFI, `tetris_create_with_reward` sets the weights
+//! and `tetris_last_reward` reads each step's reward.
+
+use crate::Tetris;
+
+/// What each change is worth. Deltas are new minus old, so penalties are
+/// negative weights.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardWeights {
+    /// Per line cleared.
+    pub lines: f32,
+    /// Per hole added (see `Board::holes`).
+    pub holes_delta: f32,
+    /// Per row the stack's highest column grew.
+    pub height_delta: f32,
+    /// Once, when the game tops out.
+    pub top_out: f32,
+    /// Every step the game is still going after.
+    pub survival: f32,
+}
+
+impl Default for RewardWeights {
+    fn default() -> Self {
+        Self { lines: 1.0, holes_delta: -0.5, height_delta: -0.1, top_out: -10.0, survival: 0.01 }
+    }
+}
+
+/// The measures the reward looks at.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Snapshot {
+    lines: u32,
+    holes: u32,
+    height: u32,
+    lost: bool,
+}
+
+impl Snapshot {
+    fn of(tetris: &Tetris) -> Self {
+        Self {
+            lines: tetris.lines_cleared(),
+            holes: tetris.board.holes(),
+            height: tetris.board.max_height(),
+            lost: tetris.lost,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardShaper {
+    pub weights: RewardWeights,
+    last: Snapshot,
+    last_reward: f32,
+}
+
+impl RewardShaper {
+    /// Starts measuring from `tetris` as it is now.
+    pub fn new(weights: RewardWeights, tetris: &Tetris) -> Self {
+        Self { weights, last: Snapshot::of(tetris), last_reward: 0.0 }
+    }
+
+    /// Measures from `tetris` again, e.g. after a reset.
+    pub fn restart(&mut self, tetris: &Tetris) {
+        *self = Self::new(self.weights, tetris);
+    }
+
+    /// Reward for everything since the previous call (or the start).
+    pub fn step(&mut self, tetris: &Tetris) -> f32 {
+        let now = Snapshot::of(tetris);
+        let w = &self.weights;
+        let mut reward = w.lines * now.lines.saturating_sub(self.last.lines) as f32
+            + w.holes_delta * (now.holes as f32 - self.last.holes as f32)
+            + w.height_delta * (now.height as f32 - self.last.height as f32);
+        if now.lost && !self.last.lost {
+            reward += w.top_out;
+        } else if !now.lost {
+            reward += w.survival;
+        }
+        self.last = now;
+        self.last_reward = reward;
+        reward
+    }
+
+    /// What the last `step` returned.
+    pub fn last_reward(&self) -> f32 {
+        self.last_reward
+    }
+}
@@ -0,0 +1,198 @@
+//! JSON-RPC 2.0 access to the engine, for languages without a C FFI and for
+//! other processes or machines. `serve` listens on TCP and takes one request
+//! per line; each connection can drive any game and subscribe to the events
+//! of any game, which then arrive as `event` notifications.
+//!
+//! Methods (`game` is the id `create` returned):
+//!
+//! - `create {width?, height?}` → `{game}`; defaults to 10×20.
+//! - `step {game, action}` → the game's state, with `action` an
+//!   `input::Action` name such as `"HardDrop"`.
+//! - `state {game}`, `board {game}` → the state, or the cells as one string
+//!   per row, top first, in `render_view` letters.
+//! - `reset {game}`, `destroy {game}`.
+//! - `subscribe {game}` → `true`; afterwards `{"method": "event", "params":
+//!   {game, event}}` for every `GameEvent` (see `GameEvent::to_json`).
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use crate::clock::Clock;
+use crate::input::Action;
+use crate::{Tetris, TICK_MS};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The `game` id does not name a live game.
+pub const UNKNOWN_GAME: i64 = -32000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+struct Game {
+    tetris: Tetris,
+    /// Connections subscribed to this game's events, as their outgoing lines.
+    subscribers: Vec<Sender<String>>,
+}
+
+/// Every game the server runs, shared by all connections.
+#[derive(Default)]
+pub struct Engine {
+    games: HashMap<u64, Game>,
+    next_id: u64,
+    #[cfg(feature = "broadcast")]
+    broadcaster: Option<crate::broadcast::Broadcaster>,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also publishes every game to stream overlays after each call on it.
+    #[cfg(feature = "broadcast")]
+    pub fn broadcast_to(&mut self, broadcaster: crate::broadcast::Broadcaster) {
+        self.broadcaster = Some(broadcaster);
+    }
+
+    /// Handles one request line and returns the response line, or nothing
+    /// for a notification. `client` receives the caller's event notifications.
+    pub fn handle(&mut self, line: &str, client: &Sender<String>) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(response(&Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))),
+        };
+        let method = request["method"].as_str().unwrap_or_default();
+        let result = self.call(method, &request["params"], client);
+        request.get("id").map(|id| response(id, result))
+    }
+
+    pub fn call(&mut self, method: &str, params: &Value, client: &Sender<String>) -> Result<Value, RpcError> {
+        if method == "create" {
+            let dimension = |name, default| params[name].as_u64().map_or(default, |v| v as u32);
+            let mut tetris = Tetris::try_new(dimension("width", 10), dimension("height", 20))
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+            tetris.set_clock(Clock::Manual(0.0));
+            self.next_id += 1;
+            self.games.insert(self.next_id, Game { tetris, subscribers: Vec::new() });
+            return Ok(json!({ "game": self.next_id }));
+        }
+        let id = params["game"].as_u64().ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing game id"))?;
+        if method == "destroy" {
+            self.games.remove(&id);
+            return Ok(Value::Bool(true));
+        }
+        let game = self.games.get_mut(&id).ok_or_else(|| RpcError::new(UNKNOWN_GAME, format!("no game {}", id)))?;
+        let result = match method {
+            "step" => {
+                let name = params["action"].as_str().unwrap_or_default();
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|a| format!("{:?}", a) == name)
+                    .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("unknown action {:?}", name)))?;
+                if action == Action::Tick {
+                    let tetris = &mut game.tetris;
+                    tetris.advance_clock(tetris.gravity_interval_ms(TICK_MS));
+                }
+                game.tetris.apply(action);
+                state(&game.tetris)
+            }
+            "reset" => {
+                game.tetris.reset();
+                state(&game.tetris)
+            }
+            "state" => state(&game.tetris),
+            "board" => {
+                let rows: Vec<String> = game.tetris.render_view().iter().map(|row| row.concat()).collect();
+                json!({ "rows": rows })
+            }
+            "subscribe" => {
+                game.subscribers.push(client.clone());
+                Value::Bool(true)
+            }
+            _ => return Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method {:?}", method))),
+        };
+        let events = game.tetris.drain_events();
+        for event in &events {
+            let note = json!({ "jsonrpc": "2.0", "method": "event", "params": { "game": id, "event": event.to_json() } });
+            game.subscribers.retain(|s| s.send(note.to_string()).is_ok());
+        }
+        #[cfg(feature = "broadcast")]
+        if let Some(broadcaster) = &mut self.broadcaster {
+            broadcaster.publish(id, &game.tetris, &events);
+        }
+        Ok(result)
+    }
+}
+
+fn state(tetris: &Tetris) -> Value {
+    json!({
+        "score": tetris.get_score(),
+        "lines": tetris.lines_cleared(),
+        "lost": tetris.lost,
+        "paused": tetris.is_paused(),
+        "width": tetris.width,
+        "height": tetris.height,
+    })
+}
+
+fn response(id: &Value, result: Result<Value, RpcError>) -> String {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": e.code, "message": e.message } }),
+    }
+    .to_string()
+}
+
+/// Accepts connections to `engine` until the listener fails, each on its
+/// own thread.
+pub fn serve(listener: TcpListener, engine: Engine) -> io::Result<()> {
+    let engine = Arc::new(Mutex::new(engine));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let engine = Arc::clone(&engine);
+        std::thread::spawn(move || {
+            let _ = handle_connection(stream, &engine);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, engine: &Mutex<Engine>) -> io::Result<()> {
+    // Responses and notifications share one writer so lines never interleave.
+    let (client, outgoing) = mpsc::channel::<String>();
+    let mut writer = stream.try_clone()?;
+    std::thread::spawn(move || {
+        for line in outgoing {
+            if writeln!(writer, "{}", line).is_err() {
+                break;
+            }
+        }
+    });
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = engine.lock().unwrap_or_else(|e| e.into_inner()).handle(&line, &client);
+        if let Some(reply) = reply {
+            let _ = client.send(reply);
+        }
+    }
+    Ok(())
+}
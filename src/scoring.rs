@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Points awarded per cleared line.
+pub const LINE_POINTS: i32 = 1;
+/// Points awarded per cell a piece is soft-dropped.
+pub const SOFT_DROP_POINTS_PER_CELL: i32 = 1;
+/// Points awarded per cell a piece is hard-dropped.
+pub const HARD_DROP_POINTS_PER_CELL: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreSource {
+    Lines,
+    SoftDrop,
+    HardDrop,
+}
+
+impl ScoreSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreSource::Lines => "lines",
+            ScoreSource::SoftDrop => "soft drop",
+            ScoreSource::HardDrop => "hard drop",
+        }
+    }
+}
+
+/// Running totals of where the score came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub lines: i32,
+    pub soft_drop: i32,
+    pub hard_drop: i32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> i32 {
+        self.lines + self.soft_drop + self.hard_drop
+    }
+
+    /// Adds the points for `amount` lines or cells from `source` and returns them.
+    pub fn award(&mut self, source: ScoreSource, amount: u32) -> i32 {
+        let amount = amount as i32;
+        let points = match source {
+            ScoreSource::Lines => amount * LINE_POINTS,
+            ScoreSource::SoftDrop => amount * SOFT_DROP_POINTS_PER_CELL,
+            ScoreSource::HardDrop => amount * HARD_DROP_POINTS_PER_CELL,
+        };
+        match source {
+            ScoreSource::Lines => self.lines += points,
+            ScoreSource::SoftDrop => self.soft_drop += points,
+            ScoreSource::HardDrop => self.hard_drop += points,
+        }
+        points
+    }
+}
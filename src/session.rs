@@ -0,0 +1,83 @@
+//! Snapshots of a game in progress, small enough to autosave every few
+//! seconds and whole enough to pick the game back up after the page or app
+//! was killed: the board, the falling and held pieces, the queue and generator, and
+//! the running totals. Take one with `Tetris::snapshot`, put it back with
+//! `Tetris::restore`; both sides serialize with serde.
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::PieceRng;
+use crate::scoring::ScoreBreakdown;
+use crate::{pieces, GARBAGE_KIND};
+
+/// The falling piece of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceState {
+    pub kind: char,
+    pub rotation: usize,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub width: u32,
+    pub height: u32,
+    /// Board rows from the top, one character per cell: the initial of the
+    /// kind there (`X` for garbage), or `.` for empty.
+    pub rows: Vec<String>,
+    pub piece: Option<PieceState>,
+    /// Initials of the queued pieces, in order.
+    pub upcoming: String,
+    /// Initial of the held piece; see `Tetris::hold`.
+    #[serde(default)]
+    pub held: Option<char>,
+    /// Whether the falling piece was already swapped with the held one.
+    #[serde(default)]
+    pub hold_used: bool,
+    pub rng: PieceRng,
+    pub score: i32,
+    pub breakdown: ScoreBreakdown,
+    pub lines_cleared: u32,
+    pub garbage_lines_cleared: u32,
+    pub pieces_placed: u32,
+    pub elapsed_ms: f64,
+}
+
+/// Why a snapshot can't be restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// It is of a board of another size.
+    Size,
+    UnknownKind(char),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Size => write!(f, "snapshot is of a board of another size"),
+            SnapshotError::UnknownKind(c) => write!(f, "unknown piece kind {}", c),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// The character `kind` is saved as.
+pub(crate) fn kind_char(kind: &str) -> char {
+    kind.chars().next().unwrap_or('.')
+}
+
+/// The kind saved as `c`; `None` for an empty cell.
+pub(crate) fn parse_kind(c: char) -> Result<Option<&'static str>, SnapshotError> {
+    if c == '.' {
+        return Ok(None);
+    }
+    pieces::KINDS
+        .iter()
+        .copied()
+        .chain([GARBAGE_KIND])
+        .find(|k| k.starts_with(c))
+        .map(Some)
+        .ok_or(SnapshotError::UnknownKind(c))
+}
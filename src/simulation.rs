@@ -0,0 +1,196 @@
+//! Headless bot games for long unattended runs (`--simulate` in the native
+//! binary). Several games play side by side on manual clocks, each driven by
+//! its input sources (a `bot::Bot` unless `set_sources` says otherwise),
+//! until `total` have finished; `summary` reports on the ones
+//! that did, and with `keep_replays` their replays are kept too.
+
+use std::fmt;
+
+use crate::bot::{Bot, Policy};
+use crate::input::{self, InputSource};
+use crate::replay::Replay;
+use crate::{Action, Tetris, TICK_MS};
+
+/// Builds the input sources of each new game from its bot.
+pub type SourceFactory = Box<dyn Fn(Bot) -> Vec<Box<dyn InputSource>>>;
+
+/// Bot actions between gravity ticks.
+const ACTIONS_PER_TICK: u32 = 4;
+/// Pieces after which a game is ended, so a bot that never tops out still
+/// lets the run finish.
+pub const MAX_PIECES: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Summary {
+    pub games: u32,
+    pub pieces: u64,
+    pub lines: u64,
+    pub best_score: i32,
+    pub total_score: i64,
+}
+
+impl Summary {
+    pub fn average_score(&self) -> Option<f64> {
+        (self.games > 0).then(|| self.total_score as f64 / self.games as f64)
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Games: {}", self.games)?;
+        writeln!(f, "Pieces: {}", self.pieces)?;
+        writeln!(f, "Lines: {}", self.lines)?;
+        writeln!(f, "Best score: {}", self.best_score)?;
+        write!(f, "Average score: {:.0}", self.average_score().unwrap_or(0.0))
+    }
+}
+
+struct Slot {
+    tetris: Tetris,
+    sources: Vec<Box<dyn InputSource>>,
+    replay: Replay,
+    actions: u32,
+}
+
+impl Slot {
+    fn new(policy: Policy, sources: &SourceFactory) -> Self {
+        let replay = Replay::deal(MAX_PIECES as usize + 1);
+        let mut bot = Bot::default();
+        bot.policy = policy;
+        Self { tetris: replay.start(), sources: sources(bot), replay, actions: 0 }
+    }
+
+    fn apply(&mut self, action: Action) {
+        self.replay.record(self.tetris.now_ms(), action);
+        self.tetris.apply(action);
+    }
+
+    fn poll_sources(&mut self) {
+        let now = self.tetris.now_ms();
+        for (at_ms, action) in input::apply_sources(&mut self.tetris, &mut self.sources, now) {
+            self.replay.record(at_ms, action);
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.tetris.lost || self.tetris.pieces_placed() >= MAX_PIECES
+    }
+}
+
+pub struct Simulation {
+    slots: Vec<Slot>,
+    /// Games still to start once a slot frees up.
+    pending: u32,
+    summary: Summary,
+    replays: Option<Vec<Replay>>,
+    policy: Policy,
+    sources: SourceFactory,
+}
+
+fn bot_only(bot: Bot) -> Vec<Box<dyn InputSource>> {
+    vec![Box::new(bot)]
+}
+
+impl Simulation {
+    /// Plays `total` games, `parallel` at a time.
+    pub fn new(total: u32, parallel: u32) -> Self {
+        let parallel = parallel.clamp(1, total.max(1));
+        let sources: SourceFactory = Box::new(bot_only);
+        let slots = (0..parallel.min(total)).map(|_| Slot::new(Policy::Greedy, &sources)).collect();
+        Self {
+            slots,
+            pending: total.saturating_sub(parallel),
+            summary: Summary::default(),
+            replays: None,
+            policy: Policy::Greedy,
+            sources,
+        }
+    }
+
+    /// Has every bot, including those of games yet to start, play `policy`.
+    /// Games under way start over.
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+        self.restart_slots();
+    }
+
+    /// Drives every game with the sources `sources` builds from its bot
+    /// instead of the bot alone. Games under way start over.
+    pub fn set_sources(&mut self, sources: impl Fn(Bot) -> Vec<Box<dyn InputSource>> + 'static) {
+        self.sources = Box::new(sources);
+        self.restart_slots();
+    }
+
+    fn restart_slots(&mut self) {
+        for slot in &mut self.slots {
+            *slot = Slot::new(self.policy, &self.sources);
+        }
+    }
+
+    /// Keeps the replays of games finished from now on.
+    pub fn keep_replays(&mut self) {
+        self.replays.get_or_insert_with(Vec::new);
+    }
+
+    /// Replays kept since the last call.
+    pub fn take_replays(&mut self) -> Vec<Replay> {
+        self.replays.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn summary(&self) -> Summary {
+        self.summary
+    }
+
+    /// Games being played right now.
+    pub fn games(&self) -> impl Iterator<Item = &Tetris> {
+        self.slots.iter().map(|s| &s.tetris)
+    }
+
+    /// Gives every running game one bot action, ticking gravity every few,
+    /// and replaces finished games with pending ones. Returns how many pieces
+    /// were placed.
+    pub fn step(&mut self) -> u32 {
+        let mut placed = 0;
+        for slot in &mut self.slots {
+            let before = slot.tetris.pieces_placed();
+            slot.poll_sources();
+            slot.actions += 1;
+            if slot.actions % ACTIONS_PER_TICK == 0 {
+                slot.tetris.advance_clock(TICK_MS);
+                slot.apply(Action::Tick);
+            }
+            slot.tetris.drain_events();
+            placed += slot.tetris.pieces_placed() - before;
+        }
+        let summary = &mut self.summary;
+        let pending = &mut self.pending;
+        let replays = &mut self.replays;
+        let policy = self.policy;
+        let sources = &self.sources;
+        self.slots.retain_mut(|slot| {
+            if !slot.is_over() {
+                return true;
+            }
+            let score = slot.tetris.get_score();
+            summary.games += 1;
+            summary.pieces += slot.tetris.pieces_placed() as u64;
+            summary.lines += slot.tetris.lines_cleared() as u64;
+            summary.best_score = summary.best_score.max(score);
+            summary.total_score += score as i64;
+            let finished = std::mem::replace(slot, Slot::new(policy, sources));
+            if let Some(replays) = replays {
+                replays.push(finished.replay);
+            }
+            if *pending == 0 {
+                return false;
+            }
+            *pending -= 1;
+            true
+        });
+        placed
+    }
+}
@@ -0,0 +1,115 @@
+//! Live terminal view of a `--simulate` run (`--tui`, behind the `tui`
+//! feature): the first running board, the run's summary so far and its
+//! throughput. Press `q` to close the view; the run carries on without it.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use tetris_core::render::{self, Overlay, Renderer, EMPTY, GHOST};
+use tetris_core::simulation::Simulation;
+
+const FRAME: Duration = Duration::from_millis(33);
+
+/// Collects a board as colored two-column cells.
+#[derive(Default)]
+struct Cells {
+    lines: Vec<Line<'static>>,
+    status: Option<&'static str>,
+}
+
+fn color(kind: &str) -> Color {
+    match kind {
+        "I" => Color::Cyan,
+        "O" => Color::Yellow,
+        "T" => Color::Magenta,
+        "S" => Color::Green,
+        "Z" => Color::Red,
+        "J" => Color::Blue,
+        "L" => Color::LightRed,
+        _ => Color::DarkGray,
+    }
+}
+
+impl Renderer for Cells {
+    fn begin_frame(&mut self, _width: u32, height: u32) {
+        self.lines = vec![Line::default(); height as usize];
+        self.status = None;
+    }
+
+    fn draw_cell(&mut self, _x: u32, y: u32, kind: &'static str) {
+        let span = match kind {
+            EMPTY => Span::styled(" .", Style::new().fg(Color::DarkGray)),
+            GHOST => Span::styled("[]", Style::new().fg(Color::DarkGray)),
+            _ => Span::styled("  ", Style::new().bg(color(kind))),
+        };
+        if let Some(line) = self.lines.get_mut(y as usize) {
+            line.push_span(span);
+        }
+    }
+
+    fn draw_overlay(&mut self, overlay: Overlay) {
+        if overlay == Overlay::GameOver {
+            self.status = Some("GAME OVER");
+        }
+    }
+
+    fn end_frame(&mut self) {
+        if let Some(status) = self.status {
+            self.lines.push(Line::from(status));
+        }
+    }
+}
+
+/// Steps `simulation` until it is done or `q` is pressed, drawing about 30
+/// times a second and simulating in between.
+pub fn run(simulation: &mut Simulation) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run_in(&mut terminal, simulation);
+    ratatui::restore();
+    result
+}
+
+fn run_in(terminal: &mut DefaultTerminal, simulation: &mut Simulation) -> io::Result<()> {
+    let start = Instant::now();
+    let mut pieces = 0u64;
+    while !simulation.is_done() {
+        let frame_start = Instant::now();
+        while frame_start.elapsed() < FRAME && !simulation.is_done() {
+            pieces += simulation.step() as u64;
+        }
+        let rate = pieces as f64 / start.elapsed().as_secs_f64().max(0.001);
+        terminal.draw(|frame| draw(frame, simulation, rate))?;
+        if event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn draw(frame: &mut Frame, simulation: &Simulation, pieces_per_second: f64) {
+    let [board_area, stats_area] = Layout::horizontal([Constraint::Length(22), Constraint::Fill(1)]).areas(frame.area());
+    let mut cells = Cells::default();
+    if let Some(tetris) = simulation.games().next() {
+        render::draw(tetris, &mut cells);
+    }
+    frame.render_widget(Paragraph::new(cells.lines).block(Block::bordered().title("Board")), board_area);
+    let running = simulation.games().count();
+    let summary = simulation.summary().to_string();
+    let lines: Vec<Line> = summary
+        .lines()
+        .map(str::to_owned)
+        .chain([format!("Running: {running}"), format!("Pieces/s: {pieces_per_second:.0}"), String::new(), "q: close view".into()])
+        .map(Line::from)
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title("Simulation")), stats_area);
+}
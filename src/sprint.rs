@@ -0,0 +1,78 @@
+//! Sprint: clear `SPRINT_LINES` rows as fast as possible. A `SplitTimer` fed
+//! with the game's events records when each split's line count was reached,
+//! so runs can be compared split by split against a personal best. Only the
+//! splits are meant to be persisted; frontends store the best run's.
+
+use crate::GameEvent;
+
+pub const SPRINT_LINES: u32 = 40;
+pub const DEFAULT_SPLITS: [u32; 4] = [10, 20, 30, 40];
+
+/// A reached split: the line count and the game time it was reached at.
+pub type Split = (u32, f64);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitTimer {
+    targets: Vec<u32>,
+    lines: u32,
+    reached: Vec<Split>,
+}
+
+impl SplitTimer {
+    /// Timer splitting at the given line counts. `SPRINT_LINES` is always
+    /// the last split; counts beyond it are ignored.
+    pub fn new(targets: &[u32]) -> Self {
+        let mut targets: Vec<u32> = targets.iter().copied().filter(|&n| n > 0 && n < SPRINT_LINES).collect();
+        targets.push(SPRINT_LINES);
+        targets.sort_unstable();
+        targets.dedup();
+        Self { targets, lines: 0, reached: Vec::new() }
+    }
+
+    pub fn targets(&self) -> &[u32] {
+        &self.targets
+    }
+
+    pub fn reached(&self) -> &[Split] {
+        &self.reached
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.reached.len() == self.targets.len()
+    }
+
+    /// Counts the rows `event` cleared and stamps every split they complete
+    /// with `elapsed_ms`. Returns how many splits were completed.
+    pub fn observe(&mut self, event: &GameEvent, elapsed_ms: f64) -> usize {
+        let GameEvent::LinesCleared { count } = *event else { return 0 };
+        self.lines += count;
+        let before = self.reached.len();
+        while let Some(&target) = self.targets.get(self.reached.len()) {
+            if self.lines < target {
+                break;
+            }
+            self.reached.push((target, elapsed_ms));
+        }
+        self.reached.len() - before
+    }
+}
+
+/// How far `split` is ahead (negative) or behind (positive) the same line
+/// count in `best`, or `None` if `best` has no such split.
+pub fn delta(split: Split, best: &[Split]) -> Option<f64> {
+    let (lines, ms) = split;
+    best.iter().find(|&&(l, _)| l == lines).map(|&(_, best_ms)| ms - best_ms)
+}
+
+/// Time at which `splits` reached `SPRINT_LINES`, if they did.
+pub fn finish_time(splits: &[Split]) -> Option<f64> {
+    splits.iter().find(|&&(lines, _)| lines == SPRINT_LINES).map(|&(_, ms)| ms)
+}
+
+/// Whether the run with `splits` finished faster than `best`.
+pub fn is_new_best(splits: &[Split], best: &[Split]) -> bool {
+    match (finish_time(splits), finish_time(best)) {
+        (Some(run), Some(best)) => run < best,
+        (run, _) => run.is_some(),
+    }
+}
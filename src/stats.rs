@@ -0,0 +1,190 @@
+//! How well the player is placing pieces. `judge` decides whether a lock
+//! was a misdrop; `GameStats` counts them over a game, and `RollingStats`
+//! tracks placement speed and the misdrop rate over the most recent pieces.
+//! `Pace` projects where a run is headed from its pace so far.
+
+use std::collections::VecDeque;
+
+use crate::metrics::{self, BoardMetrics};
+use crate::{Board, GameEvent, Tetromino};
+
+/// Pieces the rolling measures look back over.
+pub const WINDOW: usize = 20;
+/// Health points below the best placement at which a lock is a misdrop.
+pub const EVAL_MARGIN: u32 = 15;
+
+/// Why a lock counted as a misdrop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misdrop {
+    /// It left new holes where some other placement left none.
+    Hole,
+    /// It left the board at least `EVAL_MARGIN` health points worse than the
+    /// best placement would have.
+    Evaluation,
+}
+
+/// Judges `piece` about to lock on `board` against every other placement of
+/// the same piece.
+pub fn judge(board: &Board, piece: &Tetromino) -> Option<Misdrop> {
+    let height = board.height();
+    let holes = board.holes();
+    let actual = metrics::after_lock(board, piece);
+    let others: Vec<BoardMetrics> =
+        metrics::placements(board, piece.kind).iter().map(|p| metrics::after_lock(board, p)).collect();
+    let fewest_holes = others.iter().map(|m| m.holes).min().unwrap_or(actual.holes);
+    let best_health = others.iter().map(|m| m.health(height)).max().unwrap_or(0);
+    if actual.holes > holes && fewest_holes <= holes {
+        Some(Misdrop::Hole)
+    } else if actual.health(height) + EVAL_MARGIN <= best_health {
+        Some(Misdrop::Evaluation)
+    } else {
+        None
+    }
+}
+
+/// Pieces and misdrops over one game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameStats {
+    pub pieces: u32,
+    pub hole_misdrops: u32,
+    pub eval_misdrops: u32,
+}
+
+impl GameStats {
+    pub fn observe(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PieceLocked { .. } => self.pieces += 1,
+            GameEvent::Misdrop(Misdrop::Hole) => self.hole_misdrops += 1,
+            GameEvent::Misdrop(Misdrop::Evaluation) => self.eval_misdrops += 1,
+            _ => {}
+        }
+    }
+
+    pub fn misdrops(&self) -> u32 {
+        self.hole_misdrops + self.eval_misdrops
+    }
+
+    /// Share of pieces that weren't misdrops, from 0.0 to 1.0.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.pieces > 0).then(|| 1.0 - self.misdrops() as f64 / self.pieces as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollingStats {
+    /// Game time of each recent lock, and whether it was a misdrop.
+    locks: VecDeque<(f64, bool)>,
+}
+
+impl RollingStats {
+    /// Notes a piece locked at `at_ms`.
+    pub fn observe_lock(&mut self, at_ms: f64) {
+        if self.locks.len() == WINDOW {
+            self.locks.pop_front();
+        }
+        self.locks.push_back((at_ms, false));
+    }
+
+    /// Marks the piece locked last as a misdrop.
+    pub fn mark_misdrop(&mut self) {
+        if let Some(lock) = self.locks.back_mut() {
+            lock.1 = true;
+        }
+    }
+
+    /// Locks in the window.
+    pub fn len(&self) -> usize {
+        self.locks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locks.is_empty()
+    }
+
+    /// Pieces per second across the window; `None` until two pieces locked.
+    pub fn pps(&self) -> Option<f64> {
+        let (&(first, _), &(last, _)) = (self.locks.front()?, self.locks.back()?);
+        (self.locks.len() >= 2 && last > first).then(|| (self.locks.len() - 1) as f64 * 1000.0 / (last - first))
+    }
+
+    /// Share of the window's pieces that were misdrops.
+    pub fn misdrop_rate(&self) -> Option<f64> {
+        let misdrops = self.locks.iter().filter(|&&(_, misdrop)| misdrop).count();
+        (!self.locks.is_empty()).then(|| misdrops as f64 / self.locks.len() as f64)
+    }
+}
+
+/// Lines a Marathon run is projected over.
+pub const MARATHON_LINES: u32 = 150;
+/// Length of an Ultra run.
+pub const ULTRA_MS: f64 = 180_000.0;
+
+/// Where a run ends, for projecting it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Horizon {
+    /// After this many lines, as in Marathon.
+    Lines(u32),
+    /// After this much game time, as in Ultra.
+    Time(f64),
+}
+
+/// Final lines and score a run is on pace for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Projection {
+    pub lines: u32,
+    pub score: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+    #[default]
+    Flat,
+}
+
+/// Projects a run to its `Horizon` at its average pace so far, once per
+/// clear, and whether the projection rose or fell since the last clear.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pace {
+    horizon: Horizon,
+    projection: Option<Projection>,
+    trend: Trend,
+}
+
+impl Pace {
+    pub fn new(horizon: Horizon) -> Self {
+        Self { horizon, projection: None, trend: Trend::Flat }
+    }
+
+    /// Recomputes the projection on a clear, from the run's `lines` and
+    /// `score` after `elapsed_ms`. Returns whether it was recomputed.
+    pub fn observe(&mut self, event: &GameEvent, elapsed_ms: f64, lines: u32, score: i32) -> bool {
+        if !matches!(event, GameEvent::LinesCleared { .. }) || lines == 0 || elapsed_ms <= 0.0 {
+            return false;
+        }
+        let (lines_f, score_f) = (lines as f64, score as f64);
+        let next = match self.horizon {
+            Horizon::Lines(goal) => Projection { lines: goal.max(lines), score: (score_f * goal.max(lines) as f64 / lines_f) as i64 },
+            Horizon::Time(ms) => {
+                let scale = ms.max(elapsed_ms) / elapsed_ms;
+                Projection { lines: (lines_f * scale) as u32, score: (score_f * scale) as i64 }
+            }
+        };
+        self.trend = match self.projection {
+            Some(last) if next.score > last.score => Trend::Up,
+            Some(last) if next.score < last.score => Trend::Down,
+            _ => Trend::Flat,
+        };
+        self.projection = Some(next);
+        true
+    }
+
+    pub fn projection(&self) -> Option<Projection> {
+        self.projection
+    }
+
+    pub fn trend(&self) -> Trend {
+        self.trend
+    }
+}
@@ -0,0 +1,135 @@
+//! Scripted onboarding: a fixed list of steps, each asking the player for one
+//! action and only advancing once the matching `GameEvent` shows up.
+
+use crate::{GameEvent, Position, Tetris, GARBAGE_KIND};
+use crate::input::Action;
+
+/// What the player has to do to finish a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Move,
+    Rotate,
+    SoftDrop,
+    HardDrop,
+    Hold,
+    ClearLine,
+}
+
+impl Goal {
+    pub fn is_met_by(&self, event: &GameEvent) -> bool {
+        matches!(
+            (self, event),
+            (Goal::Move, GameEvent::Input(Action::MoveLeft | Action::MoveRight))
+                | (Goal::Rotate, GameEvent::Input(Action::Rotate))
+                | (Goal::SoftDrop, GameEvent::Input(Action::SoftDrop))
+                | (Goal::HardDrop, GameEvent::Input(Action::HardDrop))
+                | (Goal::Hold, GameEvent::Input(Action::Hold))
+                | (Goal::ClearLine, GameEvent::LinesCleared { .. })
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct TutorialStep {
+    pub prompt: &'static str,
+    pub goal: Goal,
+    /// Pieces dealt while the step is active, starting with the falling one.
+    pub pieces: &'static [&'static str],
+    /// Fill the bottom row except where the first piece would land, so a hard
+    /// drop clears it.
+    pub prefill_row: bool,
+}
+
+pub const STEPS: [TutorialStep; 6] = [
+    TutorialStep {
+        prompt: "Move the piece with the Left and Right arrow keys.",
+        goal: Goal::Move,
+        pieces: &["T", "T"],
+        prefill_row: false,
+    },
+    TutorialStep {
+        prompt: "Rotate the piece with the Up arrow key.",
+        goal: Goal::Rotate,
+        pieces: &["L", "L"],
+        prefill_row: false,
+    },
+    TutorialStep {
+        prompt: "Press the Down arrow key to soft drop one row.",
+        goal: Goal::SoftDrop,
+        pieces: &["J", "J"],
+        prefill_row: false,
+    },
+    TutorialStep {
+        prompt: "Press Space to hard drop the piece straight to its ghost.",
+        goal: Goal::HardDrop,
+        pieces: &["O", "O"],
+        prefill_row: false,
+    },
+    TutorialStep {
+        prompt: "Press C or Shift to hold the piece for later.",
+        goal: Goal::Hold,
+        pieces: &["S", "Z"],
+        prefill_row: false,
+    },
+    TutorialStep {
+        prompt: "Hard drop the I piece into the gap to clear a line.",
+        goal: Goal::ClearLine,
+        pieces: &["I", "I", "I"],
+        prefill_row: true,
+    },
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn step_index(&self) -> usize {
+        self.step
+    }
+
+    pub fn current(&self) -> Option<&'static TutorialStep> {
+        STEPS.get(self.step)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= STEPS.len()
+    }
+
+    /// Sets the game up for the current step: deals its pieces and, if the
+    /// step needs one, lays out a nearly full row.
+    pub fn start_step(&self, tetris: &mut Tetris) {
+        let Some(step) = self.current() else {
+            return;
+        };
+        let Some((&first, rest)) = step.pieces.split_first() else {
+            return;
+        };
+        if step.prefill_row {
+            tetris.board.clear();
+            let bottom = tetris.height as i32 - 1;
+            let gap: Vec<i32> = tetris.spawned(first).cells().map(|p| p.0).collect();
+            for x in (0..tetris.width as i32).filter(|x| !gap.contains(x)) {
+                tetris.board.set(Position(x, bottom), Some(GARBAGE_KIND));
+            }
+        }
+        tetris.set_upcoming_pieces(rest);
+        tetris.spawn_piece(first);
+    }
+
+    /// Feeds one game event; returns true when it completed the current step.
+    pub fn observe(&mut self, event: &GameEvent) -> bool {
+        match self.current() {
+            Some(step) if step.goal.is_met_by(event) => {
+                self.step += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
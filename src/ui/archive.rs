@@ -0,0 +1,72 @@
+//! Export and import of everything the app stores, as one JSON file: the
+//! same records `sync` exchanges, wrapped with a format tag and schema
+//! version.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::sync::{self, Records};
+
+const FORMAT: &str = "tetris_html-export";
+/// Bumped whenever the bundle layout changes; `import` migrates older ones.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub schema_version: u32,
+    pub exported_at: f64,
+    pub records: Records,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// Not JSON, or not one of our bundles.
+    NotABundle,
+    /// Written by a newer version of the game.
+    TooNew(u32),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::NotABundle => write!(f, "not an exported data file"),
+            ImportError::TooNew(v) => write!(f, "made by a newer version (format {})", v),
+        }
+    }
+}
+
+/// Everything currently stored, as bundle JSON.
+pub fn export(now: f64) -> String {
+    let bundle = Bundle {
+        format: FORMAT.to_string(),
+        schema_version: SCHEMA_VERSION,
+        exported_at: now,
+        records: sync::local_records(),
+    };
+    serde_json::to_string_pretty(&bundle).unwrap_or_default()
+}
+
+/// Parses bundle JSON and brings it up to `SCHEMA_VERSION`.
+pub fn parse(raw: &str) -> Result<Bundle, ImportError> {
+    let bundle: Bundle = serde_json::from_str(raw).map_err(|_| ImportError::NotABundle)?;
+    if bundle.format != FORMAT {
+        return Err(ImportError::NotABundle);
+    }
+    if bundle.schema_version > SCHEMA_VERSION {
+        return Err(ImportError::TooNew(bundle.schema_version));
+    }
+    // Version 1 is the only layout so far; later versions add steps here.
+    Ok(Bundle { schema_version: SCHEMA_VERSION, ..bundle })
+}
+
+/// Writes an exported bundle over the stored data. Imported records are
+/// stamped `now`, so they win the next sync; keys the bundle lacks are left
+/// alone.
+pub fn import(raw: &str, now: f64) -> Result<usize, ImportError> {
+    let mut records = parse(raw)?.records;
+    records.values_mut().for_each(|r| r.modified_at = now);
+    sync::store_local(&records);
+    Ok(records.len())
+}
@@ -0,0 +1,49 @@
+//! Optional image or video behind the playfield, picked by the player and
+//! stored as a data URL. Dim and blur come from `Settings`.
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::settings::Settings;
+
+/// The player's background as a data URL, persisted under
+/// `storage::BACKGROUND_KEY` and shared through context. Very large videos
+/// may not fit in localStorage; they then only last for the session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomBackground(pub Option<String>);
+
+impl CustomBackground {
+    pub fn is_video(&self) -> bool {
+        self.0.as_deref().is_some_and(|url| url.starts_with("data:video/"))
+    }
+}
+
+/// Fills its parent, which must be positioned and `isolate`d, with the
+/// custom background, if one is set, behind the parent's other content.
+#[component]
+pub fn Background() -> impl IntoView {
+    let background = expect_context::<RwSignal<CustomBackground>>();
+    let settings = expect_context::<RwSignal<Settings>>();
+    let filter = move || settings.with(|s| format!("blur({}px)", s.background_blur));
+    let dim = move || settings.with(|s| (s.background_dim.min(100) as f64 / 100.0).to_string());
+    move || {
+        let bg = background.get();
+        let is_video = bg.is_video();
+        bg.0.map(|url| {
+            let media = if is_video {
+                view! {
+                    <video class="w-full h-full object-cover" src=url style:filter=filter autoplay loop muted playsinline></video>
+                }
+                .into_any()
+            } else {
+                view! { <img class="w-full h-full object-cover" src=url style:filter=filter alt="" /> }.into_any()
+            };
+            view! {
+                <div class="absolute inset-0 -z-10 overflow-hidden pointer-events-none">
+                    {media}
+                    <div class="absolute inset-0 bg-black" style:opacity=dim></div>
+                </div>
+            }
+        })
+    }
+}
@@ -0,0 +1,117 @@
+//! A quick render benchmark run on first launch: a bot game drawn for a few
+//! dozen frames with each renderer, off screen, to pick the renderer and
+//! effect strength this machine can afford. Kept in `Settings::benchmark`;
+//! changing either setting by hand turns `Settings::auto_quality` off.
+
+use leptos::prelude::document;
+use serde::{Deserialize, Serialize};
+use tetris_core::bot::Bot;
+use tetris_core::clock::now_ms;
+use tetris_core::{render, Tetris};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, HtmlElement};
+
+use super::renderers::{CanvasRenderer, DomRenderer, RendererKind};
+use super::skin;
+
+/// Frames drawn with each renderer.
+const FRAMES: u32 = 30;
+/// Milliseconds per DOM frame below which the DOM renderer, which supports
+/// skins and grid lines, is kept whatever the canvas manages.
+const DOM_FAST_MS: f64 = 2.0;
+/// Milliseconds per frame of the faster renderer from which board effects
+/// are halved, and turned off.
+const EFFECTS_HALF_MS: f64 = 4.0;
+const EFFECTS_OFF_MS: f64 = 8.0;
+
+/// Mean milliseconds per frame of each renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RenderBenchmark {
+    pub dom_ms: f64,
+    pub canvas_ms: f64,
+}
+
+impl RenderBenchmark {
+    /// The DOM renderer unless it is slow and the canvas clearly faster.
+    pub fn renderer(&self) -> RendererKind {
+        if self.dom_ms > DOM_FAST_MS && self.canvas_ms * 1.5 < self.dom_ms {
+            RendererKind::Canvas
+        } else {
+            RendererKind::Dom
+        }
+    }
+
+    /// Effect intensity for `Settings::effect_intensity`, from how long a
+    /// frame of the chosen renderer takes.
+    pub fn effect_intensity(&self) -> u32 {
+        let frame_ms = match self.renderer() {
+            RendererKind::Dom => self.dom_ms,
+            RendererKind::Canvas => self.canvas_ms,
+        };
+        match frame_ms {
+            ms if ms >= EFFECTS_OFF_MS => 0,
+            ms if ms >= EFFECTS_HALF_MS => 50,
+            _ => 100,
+        }
+    }
+}
+
+/// Runs the benchmark; `None` if the page has no body to draw in.
+pub fn run() -> Option<RenderBenchmark> {
+    let document = document();
+    let body = document.body()?;
+    let host: HtmlElement = document.create_element("div").ok()?.dyn_into().ok()?;
+    // Laid out and painted like the board, but out of sight.
+    let _ = host.set_attribute("style", "position: fixed; left: -10000px; top: 0; width: 320px;");
+    body.append_child(&host).ok()?;
+    let result = measure(&host);
+    host.remove();
+    result
+}
+
+fn measure(host: &HtmlElement) -> Option<RenderBenchmark> {
+    let document = document();
+    let mut game = Tetris::new(10, 25);
+    let mut bot = Bot::default();
+    let mut advance = move |game: &mut Tetris| {
+        if game.lost {
+            game.reset();
+        }
+        bot.act(game);
+        game.tick();
+        game.drain_events();
+    };
+
+    let cells: Vec<HtmlElement> = (0..game.width * game.height)
+        .filter_map(|_| {
+            let cell: HtmlElement = document.create_element("div").ok()?.dyn_into().ok()?;
+            let _ = cell.set_attribute("class", "cell aspect-square w-[10%] inline-block");
+            host.append_child(&cell).ok()?;
+            Some(cell)
+        })
+        .collect();
+    let start = now_ms();
+    for _ in 0..FRAMES {
+        advance(&mut game);
+        let mut dom = DomRenderer::default();
+        render::draw(&game, &mut dom);
+        for (cell, kind) in cells.iter().zip(dom.into_frame().cells.into_iter().flatten()) {
+            let _ = cell.set_attribute("style", &skin::cell_style(kind, None, false));
+        }
+        // Reading the layout makes the browser apply the styles now.
+        let _ = host.offset_height();
+    }
+    let dom_ms = (now_ms() - start) / FRAMES as f64;
+    cells.iter().for_each(|cell| cell.remove());
+
+    let canvas: HtmlCanvasElement = document.create_element("canvas").ok()?.dyn_into().ok()?;
+    host.append_child(&canvas).ok()?;
+    let mut canvas = CanvasRenderer::new(canvas)?;
+    let start = now_ms();
+    for _ in 0..FRAMES {
+        advance(&mut game);
+        render::draw(&game, &mut canvas);
+    }
+    let canvas_ms = (now_ms() - start) / FRAMES as f64;
+    Some(RenderBenchmark { dom_ms, canvas_ms })
+}
@@ -0,0 +1,52 @@
+use leptos::{html, prelude::*};
+use tetris_core::objectives::OBJECTIVES;
+
+use super::focus;
+use super::i18n::{tr, tr_or, use_locale};
+
+/// Lists every objective and whether it has been completed.
+#[component]
+pub fn ChallengesDialog(
+    open: ReadSignal<bool>,
+    completed: RwSignal<Vec<String>>,
+    on_close: impl Fn() + Clone + 'static,
+) -> impl IntoView {
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, &on_close_key);
+                    }
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "challenges.title")}</h3>
+                <ul class="py-4 flex flex-col gap-2">
+                    {OBJECTIVES.iter().map(|o| {
+                        let done = move || completed.with(|ids| ids.iter().any(|id| id == o.id));
+                        view! {
+                            <li class="flex flex-row gap-2 items-center">
+                                <input type="checkbox" class="checkbox checkbox-sm" disabled prop:checked=done />
+                                <span class:opacity-60=done>
+                                    {move || tr_or(locale.get(), &format!("objective.{}", o.id), o.title)}
+                                </span>
+                            </li>
+                        }
+                    }).collect::<Vec<_>>()}
+                </ul>
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
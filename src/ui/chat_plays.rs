@@ -0,0 +1,98 @@
+//! Twitch "chat plays", opened with `#twitch=<channel>` after the page URL:
+//! the page reads the channel's chat over Twitch's IRC WebSocket, counts
+//! votes with `tetris_core::chat_vote` as one more input source of the game,
+//! and shows the running tally in a banner over the board.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use leptos::prelude::*;
+use leptos_use::use_interval_fn;
+use tetris_core::chat_vote::{self, ChatVotes};
+use tetris_core::clock;
+use tetris_core::{Action, InputSource};
+use wasm_bindgen::prelude::*;
+use web_sys::{MessageEvent, WebSocket};
+
+use super::i18n::{tr, tr_args, use_locale};
+
+/// How often the banner is refreshed.
+const POLL_MS: u64 = 100;
+
+/// The channel named after `#twitch=` in the page URL.
+pub fn requested_channel() -> Option<String> {
+    let hash = window().location().hash().ok()?;
+    let channel = hash.strip_prefix("#twitch=")?;
+    (!channel.is_empty()).then(|| channel.to_string())
+}
+
+fn symbol(action: Action) -> &'static str {
+    match action {
+        Action::MoveLeft => "←",
+        Action::MoveRight => "→",
+        Action::Rotate => "⟳",
+        Action::SoftDrop => "↓",
+        Action::Hold => "⇄",
+        _ => "⤓",
+    }
+}
+
+/// Joins the requested channel, if any, and adds its votes to `sources`.
+/// Returns the banner to show over the board.
+pub fn use_chat_plays(sources: StoredValue<Vec<Box<dyn InputSource>>, LocalStorage>) -> impl IntoView {
+    let channel = requested_channel()?;
+    let votes = Rc::new(RefCell::new(ChatVotes::default()));
+    sources.update_value(|s| s.push(Box::new(votes.clone())));
+    let votes = StoredValue::new_local(votes);
+    let (tally, set_tally) = signal((Vec::<(Action, u32)>::new(), None::<f64>));
+    match WebSocket::new(chat_vote::TWITCH_IRC_WS) {
+        Ok(socket) => {
+            let sender = socket.clone();
+            let on_open = Closure::<dyn FnMut()>::new(move || {
+                for line in chat_vote::login_lines(&channel) {
+                    let _ = sender.send_with_str(&line);
+                }
+            });
+            let sender = socket.clone();
+            let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+                let Some(text) = e.data().as_string() else { return };
+                let now = clock::now_ms();
+                for line in text.lines() {
+                    match chat_vote::reply_to(line) {
+                        Some(reply) => drop(sender.send_with_str(&reply)),
+                        None => votes.with_value(|v| v.borrow_mut().observe_line(line, now)),
+                    }
+                }
+            });
+            socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            on_open.forget();
+            on_message.forget();
+        }
+        Err(e) => leptos::logging::error!("cannot reach Twitch chat: {:?}", e),
+    }
+    use_interval_fn(
+        move || {
+            let current = votes.with_value(|v| {
+                let v = v.borrow();
+                (v.tally(), v.remaining_ms(clock::now_ms()))
+            });
+            if tally.with_untracked(|t| *t != current) {
+                set_tally.set(current);
+            }
+        },
+        POLL_MS,
+    );
+    let locale = use_locale();
+    Some(view! {
+        <div class="absolute top-2 inset-x-2 z-20 rounded bg-black bg-opacity-70 px-3 py-1 text-white text-sm text-center">
+            {move || {
+                let (counts, remaining) = tally.get();
+                let Some(ms) = remaining else { return tr(locale.get(), "chat.waiting") };
+                let counts: Vec<String> = counts.iter().map(|&(a, n)| format!("{} {}", symbol(a), n)).collect();
+                let seconds = format!("{:.1}", ms / 1000.0);
+                format!("{} {}", tr_args(locale.get(), "chat.votes", &[("seconds", seconds)]), counts.join("  "))
+            }}
+        </div>
+    })
+}
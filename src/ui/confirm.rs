@@ -0,0 +1,41 @@
+use leptos::{html, prelude::*};
+
+use super::focus;
+use super::i18n::{tr, use_locale};
+
+/// Modal asking the player to confirm a destructive action.
+#[component]
+pub fn ConfirmDialog(
+    open: ReadSignal<bool>,
+    #[prop(into)] message: Signal<String>,
+    #[prop(into)] confirm_label: Signal<String>,
+    on_confirm: impl Fn() + 'static,
+    on_cancel: impl Fn() + Clone + 'static,
+) -> impl IntoView {
+    let locale = use_locale();
+    let on_cancel_backdrop = on_cancel.clone();
+    let on_cancel_key = on_cancel.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, &on_cancel_key);
+                    }
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <p class="py-4">{message}</p>
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_cancel()>{move || tr(locale.get(), "button.cancel")}</button>
+                    <button class="btn btn-error" on:click=move |_| on_confirm()>{confirm_label}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_cancel_backdrop()></div>
+        </div>
+    }
+}
@@ -0,0 +1,67 @@
+use leptos::{html, prelude::*};
+use tetris_core::drills::Template;
+
+use super::focus;
+use super::i18n::{format_time, tr, tr_args, tr_or, use_locale};
+use super::storage::SavedDrills;
+
+/// Lists the drill templates with their best times; picking one starts a
+/// fresh drill of it through `on_start`.
+#[component]
+pub fn DrillsDialog(
+    open: ReadSignal<bool>,
+    saved: RwSignal<SavedDrills>,
+    on_start: impl Fn(Template) + Copy + Send + Sync + 'static,
+    on_close: impl Fn() + Clone + 'static,
+) -> impl IntoView {
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, &on_close_key);
+                    }
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "drills.title")}</h3>
+                <ul class="py-4 flex flex-col gap-2">
+                    {Template::ALL.into_iter().map(|template| {
+                        let record = move || {
+                            let l = locale.get();
+                            saved.with(|s| match (s.best_ms.get(template.id()), s.completed.get(template.id())) {
+                                (Some(&best), Some(&count)) => tr_args(l, "drills.record", &[
+                                    ("best", format_time(l, best)),
+                                    ("count", count.to_string()),
+                                ]),
+                                _ => tr(l, "drills.untried"),
+                            })
+                        };
+                        view! {
+                            <li class="flex flex-row gap-2 items-center">
+                                <div class="grow flex flex-col">
+                                    <span>{move || tr_or(locale.get(), &format!("drill.{}", template.id()), template.title())}</span>
+                                    <span class="text-xs opacity-70">{record}</span>
+                                </div>
+                                <button class="btn btn-sm btn-primary" on:click=move |_| on_start(template)>
+                                    {move || tr(locale.get(), "drills.start")}
+                                </button>
+                            </li>
+                        }
+                    }).collect::<Vec<_>>()}
+                </ul>
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
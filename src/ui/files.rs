@@ -0,0 +1,58 @@
+//! Reading files the player picks with an `<input type="file">`, and offering
+//! files for download. The Tauri webview shows its native file dialogs for
+//! both, so the same code covers web and desktop builds.
+
+use leptos::prelude::document;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Event, File, FileReader, HtmlElement, HtmlInputElement};
+
+/// Reads the file picked in the input that fired `ev` and passes it to
+/// `on_load` as a `data:` URL. Does nothing if no file was picked.
+pub fn read_as_data_url(ev: &Event, on_load: impl FnOnce(String) + 'static) {
+    read_picked(ev, on_load, |reader, file| reader.read_as_data_url(file));
+}
+
+/// Like `read_as_data_url`, but passes the file's text.
+pub fn read_as_text(ev: &Event, on_load: impl FnOnce(String) + 'static) {
+    read_picked(ev, on_load, |reader, file| reader.read_as_text(file));
+}
+
+/// Offers `contents` as a download named `file_name`.
+pub fn download(file_name: &str, mime: &str, contents: &str) {
+    let url = format!("data:{};charset=utf-8,{}", mime, js_sys::encode_uri_component(contents));
+    let Ok(link) = document().create_element("a") else { return };
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", file_name);
+    if let Ok(link) = link.dyn_into::<HtmlElement>() {
+        link.click();
+    }
+}
+
+fn read_picked(
+    ev: &Event,
+    on_load: impl FnOnce(String) + 'static,
+    start: impl FnOnce(&FileReader, &File) -> Result<(), JsValue>,
+) {
+    let Some(file) = ev
+        .target()
+        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+        .and_then(|input| input.files())
+        .and_then(|files| files.get(0))
+    else {
+        return;
+    };
+    let Ok(reader) = FileReader::new() else { return };
+    let onload = Closure::once(move |ev: Event| {
+        let url = ev
+            .target()
+            .and_then(|t| t.dyn_into::<FileReader>().ok())
+            .and_then(|r| r.result().ok())
+            .and_then(|r| r.as_string());
+        if let Some(url) = url {
+            on_load(url);
+        }
+    });
+    reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+    onload.forget();
+    let _ = start(&reader, &file);
+}
@@ -0,0 +1,106 @@
+//! Keyboard focus helpers shared by menus and dialogs: arrow keys move between
+//! controls, Escape leaves, and the game only reads keys while no menu has
+//! focus.
+
+use leptos::{html, prelude::*};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+
+const FOCUSABLE: &str = "button:not([disabled]), select, input:not([disabled]), [tabindex]:not([tabindex='-1'])";
+
+/// Focusable controls inside `container`, in document order.
+pub fn focusable(container: &Element) -> Vec<HtmlElement> {
+    let Ok(nodes) = container.query_selector_all(FOCUSABLE) else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i))
+        .filter_map(|n| n.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+pub fn focus_first(container: &Element) {
+    if let Some(first) = focusable(container).first() {
+        let _ = first.focus();
+    }
+}
+
+/// Moves focus `delta` controls forward (negative: backward), wrapping around.
+pub fn move_focus(container: &Element, delta: i32) {
+    let items = focusable(container);
+    if items.is_empty() {
+        return;
+    }
+    let active = document().active_element();
+    let current = items
+        .iter()
+        .position(|el| active.as_ref().is_some_and(|a| a == el.unchecked_ref::<Element>()));
+    let next = match current {
+        Some(i) => (i as i32 + delta).rem_euclid(items.len() as i32) as usize,
+        None if delta < 0 => items.len() - 1,
+        None => 0,
+    };
+    let _ = items[next].focus();
+}
+
+pub fn blur_active() {
+    if let Some(active) = document().active_element().and_then(|a| a.dyn_into::<HtmlElement>().ok()) {
+        let _ = active.blur();
+    }
+}
+
+/// Arrow-key navigation for a menu: Up/Down (and Left/Right) move between its
+/// controls, Escape calls `on_escape`. Arrows are left alone inside `<select>`
+/// so it can still change its value.
+pub fn handle_menu_key(ev: &KeyboardEvent, container: &Element, on_escape: impl Fn()) {
+    let in_select = ev
+        .target()
+        .and_then(|t| t.dyn_into::<Element>().ok())
+        .is_some_and(|t| t.tag_name().eq_ignore_ascii_case("select"));
+    let delta = match ev.key().as_str() {
+        "ArrowDown" | "ArrowRight" if !in_select => 1,
+        "ArrowUp" | "ArrowLeft" if !in_select => -1,
+        "Escape" => {
+            ev.prevent_default();
+            on_escape();
+            return;
+        }
+        _ => return,
+    };
+    ev.prevent_default();
+    move_focus(container, delta);
+}
+
+/// Focuses the first control of `container` whenever `open` becomes true
+/// (deferred a frame so it is visible, and focusable, by then), and hands
+/// focus back to the game when it closes.
+pub fn focus_on_open(open: impl Fn() -> bool + 'static, container: NodeRef<html::Div>) {
+    Effect::new(move || {
+        if open() {
+            request_animation_frame(move || {
+                if let Some(el) = container.get_untracked() {
+                    focus_first(&el);
+                }
+            });
+        } else if let Some(el) = container.get_untracked() {
+            if el.contains(document().active_element().as_ref().map(|a| a.unchecked_ref())) {
+                blur_active();
+            }
+        }
+    });
+}
+
+pub fn is_menu_open() -> bool {
+    document().query_selector(".modal.modal-open").ok().flatten().is_some()
+}
+
+/// True when keyboard input should drive the game: no dialog is open and no
+/// menu control has focus.
+pub fn game_has_focus() -> bool {
+    if is_menu_open() {
+        return false;
+    }
+    document().active_element().is_none_or(|el| {
+        !matches!(el.tag_name().to_ascii_lowercase().as_str(), "button" | "select" | "input")
+    })
+}
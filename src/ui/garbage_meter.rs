@@ -0,0 +1,48 @@
+//! The garbage meter: a bar up the side of the well, as tall as the garbage
+//! queued for the board, with the seconds left until the oldest batch
+//! arrives. Queued garbage comes in on the next lock without a clear once it
+//! has arrived (see `tetris_core::garbage::GarbageQueue`).
+
+use leptos::prelude::*;
+use tetris_core::garbage::GarbageQueue;
+
+use super::i18n::{tr_args, use_locale};
+
+/// What the meter shows of a `GarbageQueue`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeterReading {
+    pub rows: u32,
+    /// See `GarbageQueue::countdown_ms`.
+    pub countdown_ms: Option<f64>,
+}
+
+impl MeterReading {
+    pub fn of(queue: &GarbageQueue, now_ms: f64) -> Self {
+        Self { rows: queue.pending(), countdown_ms: queue.countdown_ms(now_ms) }
+    }
+}
+
+/// The meter beside a well `height` rows tall; empty with nothing queued.
+#[component]
+pub fn GarbageMeter(reading: ReadSignal<MeterReading>, height: u32) -> impl IntoView {
+    let locale = use_locale();
+    move || {
+        let MeterReading { rows, countdown_ms } = reading.get();
+        countdown_ms.map(|ms| {
+            // Dimmed until it has arrived and can come in.
+            let waiting = ms > 0.0;
+            let seconds = format!("{:.1}", ms / 1000.0);
+            let title = tr_args(locale.get(), "garbage.incoming", &[("rows", rows.to_string()), ("seconds", seconds.clone())]);
+            view! {
+                <div class="absolute inset-y-0 end-full me-1 w-2 flex flex-col justify-end items-center pointer-events-none" title=title>
+                    <span class="text-[0.6rem] font-mono leading-none mb-1">{seconds}</span>
+                    <div
+                        class="w-full bg-error"
+                        class:opacity-60=waiting
+                        style:height=format!("{:.2}%", rows.min(height) as f64 * 100.0 / height as f64)>
+                    </div>
+                </div>
+            }
+        })
+    }
+}
@@ -0,0 +1,68 @@
+//! The hold slot in the sidebar: the held piece, lying flat, and a button
+//! to hold for touch screens. See `Tetris::hold`.
+
+use leptos::prelude::*;
+use tetris_core::{pieces, Position, Tetris};
+
+use super::skin;
+
+/// Side of a preview cell, in CSS pixels.
+const CELL_PX: i32 = 10;
+
+/// Cells of `kind` in its flattest rotation, so it fits a slot four cells
+/// wide and two high, moved so the leftmost and topmost ones are at 0.
+pub fn preview_cells(kind: &str) -> [Position; 4] {
+    let height = |cells: &[Position; 4]| {
+        cells.iter().map(|p| p.1).max().unwrap_or(0) - cells.iter().map(|p| p.1).min().unwrap_or(0)
+    };
+    let cells = (0..4).map(|r| pieces::cells(kind, r)).min_by_key(height).unwrap_or_else(|| pieces::cells(kind, 0));
+    let left = cells.iter().map(|p| p.0).min().unwrap_or(0);
+    let top = cells.iter().map(|p| p.1).min().unwrap_or(0);
+    cells.map(|p| Position(p.0 - left, p.1 - top))
+}
+
+/// Cells where a hold would spawn the piece coming in and where it would
+/// land, for the faint overlay on the board; empty if there is no hold to
+/// preview. See `Tetris::hold_swap_preview`.
+pub fn swap_preview_cells(st: &Tetris) -> Vec<Position> {
+    st.hold_swap_preview()
+        .map(|(spawned, landed)| spawned.cells().chain(landed.cells()).collect())
+        .unwrap_or_default()
+}
+
+/// `held` is the held kind and whether a hold is allowed right now; `on_hold`
+/// asks for one. `preview` is set while the slot is hovered or focused, so
+/// the board can show where a hold would put the piece coming in.
+#[component]
+pub fn HoldSlot(
+    held: ReadSignal<(Option<&'static str>, bool)>,
+    label: Signal<String>,
+    preview: RwSignal<bool>,
+    on_hold: impl Fn() + 'static,
+) -> impl IntoView {
+    view! {
+        <button
+            class="btn btn-sm btn-ghost flex flex-col h-auto gap-1"
+            class:opacity-50=move || !held.get().1
+            on:mouseenter=move |_| preview.set(true)
+            on:mouseleave=move |_| preview.set(false)
+            on:focus=move |_| preview.set(true)
+            on:blur=move |_| preview.set(false)
+            on:click=move |_| on_hold()>
+            <span>{label}</span>
+            <div class="relative" style=format!("width: {}px; height: {}px", 4 * CELL_PX, 2 * CELL_PX)>
+                {move || held.get().0.map(|kind| {
+                    preview_cells(kind).map(|p| view! {
+                        <div
+                            class="absolute"
+                            style=format!(
+                                "left: {}px; top: {}px; width: {}px; height: {}px; background-color: {};",
+                                p.0 * CELL_PX, p.1 * CELL_PX, CELL_PX, CELL_PX, skin::flat_color(kind),
+                            )>
+                        </div>
+                    })
+                })}
+            </div>
+        </button>
+    }
+}
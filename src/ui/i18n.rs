@@ -0,0 +1,134 @@
+//! UI strings and locale-aware formatting. Translations live in
+//! `locales/<code>.json` as flat key → template maps; `{name}` placeholders are
+//! filled by `tr_args`. Missing keys fall back to English, then to the key.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::settings::Settings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "ar")]
+    Ar,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::ZhCn, Locale::Ar];
+
+    /// BCP 47 tag, also the locale file name.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+            Locale::Ar => "ar",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Locale> {
+        Locale::ALL.into_iter().find(|l| l.code() == code)
+    }
+
+    /// The language's name in itself, for the language picker.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::ZhCn => "简体中文",
+            Locale::Ar => "العربية",
+        }
+    }
+
+    pub fn is_rtl(&self) -> bool {
+        matches!(self, Locale::Ar)
+    }
+
+    /// Value for the HTML `dir` attribute of translated UI. The board and the
+    /// on-screen arrow pad always stay left-to-right.
+    pub fn dir(&self) -> &'static str {
+        if self.is_rtl() { "rtl" } else { "ltr" }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../../locales/en.json"),
+            Locale::ZhCn => include_str!("../../locales/zh-CN.json"),
+            Locale::Ar => include_str!("../../locales/ar.json"),
+        }
+    }
+
+    fn strings(&self) -> &'static HashMap<String, String> {
+        static TABLES: [OnceLock<HashMap<String, String>>; 3] = [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        TABLES[*self as usize].get_or_init(|| serde_json::from_str(self.source()).unwrap_or_default())
+    }
+
+    /// Thousands separator and decimal mark.
+    fn separators(&self) -> (char, char) {
+        match self {
+            Locale::En | Locale::ZhCn => (',', '.'),
+            Locale::Ar => ('٬', '٫'),
+        }
+    }
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    locale
+        .strings()
+        .get(key)
+        .or_else(|| Locale::En.strings().get(key))
+        .map(String::as_str)
+}
+
+/// Translation of `key`, or the key itself when no locale defines it.
+pub fn tr(locale: Locale, key: &str) -> String {
+    lookup(locale, key).unwrap_or(key).to_string()
+}
+
+/// Like `tr`, but uses `fallback` when no locale defines `key`; for strings
+/// that originate in `tetris_core`.
+pub fn tr_or(locale: Locale, key: &str, fallback: &str) -> String {
+    lookup(locale, key).unwrap_or(fallback).to_string()
+}
+
+/// `tr` with `{name}` placeholders replaced by `args`.
+pub fn tr_args(locale: Locale, key: &str, args: &[(&str, String)]) -> String {
+    args.iter()
+        .fold(tr(locale, key), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Integer with the locale's digit grouping, e.g. `12,345`.
+pub fn format_number(locale: Locale, n: i64) -> String {
+    let (group, _) = locale.separators();
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(group);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Formats milliseconds as `mm:ss.cc`, the usual Sprint timer layout, with
+/// the locale's decimal mark.
+pub fn format_time(locale: Locale, ms: f64) -> String {
+    let (_, decimal) = locale.separators();
+    let centis = (ms.max(0.0) / 10.0) as u64;
+    format!("{:02}:{:02}{}{:02}", centis / 6000, centis / 100 % 60, decimal, centis % 100)
+}
+
+/// The selected locale, tracked from the settings in context.
+pub fn use_locale() -> Signal<Locale> {
+    let settings = expect_context::<RwSignal<Settings>>();
+    Signal::derive(move || settings.with(|s| s.locale))
+}
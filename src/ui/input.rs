@@ -0,0 +1,24 @@
+/// Tracks a key that triggers an action only after being held for a while,
+/// e.g. hold-R-to-retry. Auto-repeated keydowns do not restart the hold.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HoldKey {
+    pressed_at: Option<f64>,
+}
+
+impl HoldKey {
+    /// Records a press and returns its timestamp, which identifies the press
+    /// when the hold is checked later.
+    pub fn press(&mut self, now: f64) -> f64 {
+        *self.pressed_at.get_or_insert(now)
+    }
+
+    pub fn release(&mut self) {
+        self.pressed_at = None;
+    }
+
+    /// True when the press started at `pressed_at` is still held and has
+    /// lasted at least `hold_ms`.
+    pub fn is_held(&self, pressed_at: f64, now: f64, hold_ms: f64) -> bool {
+        self.pressed_at == Some(pressed_at) && now - pressed_at >= hold_ms
+    }
+}
@@ -0,0 +1,79 @@
+//! Frame stepping for debug builds: while stepping, each press of the step
+//! key runs one update of the paused game, and the inspector lists what that
+//! update changed.
+
+// Only the step key uses the snapshots, and it is compiled out of release builds.
+#![cfg_attr(not(debug_assertions), allow(dead_code))]
+
+use leptos::prelude::*;
+use tetris_core::generator::PieceRng;
+use tetris_core::{GamePhase, Position, Tetris};
+
+use super::i18n::{tr, use_locale};
+
+/// The parts of a game an update can change that are worth watching when
+/// chasing timing and rotation bugs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub now_ms: f64,
+    /// Kind, position and rotation of the falling piece.
+    pub piece: Option<(&'static str, Position, usize)>,
+    pub score: i32,
+    pub lines: u32,
+    pub phase: GamePhase,
+    /// Changes whenever a random piece is dealt.
+    pub rng: PieceRng,
+}
+
+impl Snapshot {
+    pub fn of(tetris: &Tetris) -> Self {
+        Self {
+            now_ms: tetris.now_ms(),
+            piece: tetris.current_tetromino.as_ref().map(|t| (t.kind, t.data.position, t.rotation)),
+            score: tetris.get_score(),
+            lines: tetris.lines_cleared(),
+            phase: tetris.phase(),
+            rng: tetris.piece_rng(),
+        }
+    }
+}
+
+fn describe_piece(piece: Option<(&'static str, Position, usize)>) -> String {
+    piece.map_or_else(|| "-".to_string(), |(kind, Position(x, y), r)| format!("{} ({}, {}) r{}", kind, x, y, r))
+}
+
+/// One line per thing that differs between `before` and `after`, starting
+/// with how much game time passed.
+pub fn deltas(before: &Snapshot, after: &Snapshot) -> Vec<String> {
+    let mut lines = vec![format!("Δt {:+.1} ms", after.now_ms - before.now_ms)];
+    if before.piece != after.piece {
+        lines.push(format!("piece {} → {}", describe_piece(before.piece), describe_piece(after.piece)));
+    }
+    if before.score != after.score {
+        lines.push(format!("score {:+}", after.score - before.score));
+    }
+    if before.lines != after.lines {
+        lines.push(format!("lines {:+}", after.lines as i64 - before.lines as i64));
+    }
+    if before.phase != after.phase {
+        lines.push(format!("phase {:?} → {:?}", before.phase, after.phase));
+    }
+    if before.rng != after.rng {
+        lines.push(format!("rng {} → {}", before.rng, after.rng));
+    }
+    lines
+}
+
+/// Overlay listing the last step's `deltas`, shown while stepping.
+#[component]
+pub fn Inspector(stepping: ReadSignal<bool>, deltas: ReadSignal<Vec<String>>) -> impl IntoView {
+    let locale = use_locale();
+    move || {
+        stepping.get().then(|| view! {
+            <div class="absolute top-1 start-1 z-20 pointer-events-none rounded bg-base-200/90 p-2 text-xs font-mono" dir="ltr">
+                <div class="font-bold">{move || tr(locale.get(), "inspector.title")}</div>
+                {move || deltas.get().into_iter().map(|line| view! { <div>{line}</div> }).collect::<Vec<_>>()}
+            </div>
+        })
+    }
+}
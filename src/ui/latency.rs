@@ -0,0 +1,95 @@
+//! Input latency diagnostics, shown when `Settings::show_latency` is on: for
+//! each key press, the time from the DOM `keydown` to the game applying the
+//! action, and from there to the next animation frame, which paints the
+//! result. The overlay shows rolling averages of both and of their sum, for
+//! tuning the frame loop and spotting regressions in the wasm build.
+
+use std::collections::VecDeque;
+
+use leptos::prelude::*;
+
+use super::i18n::{tr, tr_args, use_locale};
+
+/// Presses each average covers.
+pub const WINDOW: usize = 30;
+
+/// Mean of the last `WINDOW` samples.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollingMean {
+    samples: VecDeque<f64>,
+}
+
+impl RollingMean {
+    pub fn push(&mut self, sample: f64) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (!self.samples.is_empty()).then(|| self.samples.iter().sum::<f64>() / self.samples.len() as f64)
+    }
+
+    /// Samples in the window so far.
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+/// Follows presses through the pipeline. All times are `performance.now()`
+/// milliseconds, which `KeyboardEvent.timeStamp` also uses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyProbe {
+    /// Presses not painted yet: when they happened and, once applied, when
+    /// that was.
+    in_flight: VecDeque<(f64, Option<f64>)>,
+    pub to_apply: RollingMean,
+    pub to_frame: RollingMean,
+    pub total: RollingMean,
+}
+
+impl LatencyProbe {
+    pub fn pressed(&mut self, at_ms: f64) {
+        self.in_flight.push_back((at_ms, None));
+    }
+
+    /// The game applied what was pressed so far.
+    pub fn applied(&mut self, now_ms: f64) {
+        for (_, applied) in self.in_flight.iter_mut().filter(|(_, applied)| applied.is_none()) {
+            *applied = Some(now_ms);
+        }
+    }
+
+    /// An animation frame started; presses applied before it are painted in
+    /// it. Returns whether that recorded any samples.
+    pub fn frame(&mut self, now_ms: f64) -> bool {
+        let painted = self.in_flight.iter().take_while(|(_, applied)| applied.is_some()).count();
+        for (pressed, applied) in self.in_flight.drain(..painted) {
+            let applied = applied.unwrap_or(now_ms);
+            self.to_apply.push(applied - pressed);
+            self.to_frame.push(now_ms - applied);
+            self.total.push(now_ms - pressed);
+        }
+        painted > 0
+    }
+}
+
+/// Overlay with the averages, while `shown`.
+#[component]
+pub fn LatencyOverlay(shown: Signal<bool>, probe: ReadSignal<LatencyProbe>) -> impl IntoView {
+    let locale = use_locale();
+    let ms = |mean: &RollingMean| mean.mean().map_or_else(|| "-".to_string(), |ms| format!("{ms:.1}"));
+    move || {
+        shown.get().then(|| view! {
+            <div class="absolute top-1 end-1 z-20 pointer-events-none rounded bg-base-200/90 p-2 text-xs font-mono" dir="ltr">
+                <div class="font-bold">
+                    {move || tr_args(locale.get(), "latency.title", &[("count", probe.with(|p| p.total.count().to_string()))])}
+                </div>
+                <div>{move || format!("{} {} ms", tr(locale.get(), "latency.to_apply"), probe.with(|p| ms(&p.to_apply)))}</div>
+                <div>{move || format!("{} {} ms", tr(locale.get(), "latency.to_frame"), probe.with(|p| ms(&p.to_frame)))}</div>
+                <div>{move || format!("{} {} ms", tr(locale.get(), "latency.total"), probe.with(|p| ms(&p.total)))}</div>
+            </div>
+        })
+    }
+}
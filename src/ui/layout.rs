@@ -0,0 +1,64 @@
+//! Side by side or stacked: a landscape window puts the sidebar next to the
+//! board, a portrait one stacks it underneath with the arrow pad first. The
+//! board's cell size follows the window, so turning a phone recomputes both.
+//! In the desktop app's mini mode the board is all there is.
+
+use leptos::prelude::*;
+use leptos_use::use_window_size;
+
+use super::mini::MiniMode;
+
+/// Share of a portrait window's height the board may take; the controls and
+/// stats below get the rest.
+pub const STACKED_BOARD_HEIGHT: f64 = 0.65;
+/// Share of a portrait window's width the board may take.
+pub const STACKED_BOARD_WIDTH: f64 = 0.94;
+/// Margin around the board in mini mode, by which the borderless window is
+/// dragged, in CSS pixels.
+pub const MINI_FRAME_PX: f64 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    SideBySide,
+    Stacked,
+    /// Just the board, fitted inside `MINI_FRAME_PX` of the window's edges.
+    Mini,
+}
+
+impl Layout {
+    /// Stacked for windows taller than they are wide.
+    pub fn for_window(width: f64, height: f64) -> Self {
+        if height > width { Layout::Stacked } else { Layout::SideBySide }
+    }
+
+    /// Side of a board cell, in CSS pixels, that fits a `cols` × `rows` board
+    /// into a `width` × `height` window: the full height side by side, and
+    /// no more than its share of either side stacked.
+    pub fn cell_px(self, width: f64, height: f64, cols: u32, rows: u32) -> f64 {
+        match self {
+            Layout::SideBySide => height / rows as f64,
+            Layout::Mini => {
+                ((height - 2.0 * MINI_FRAME_PX) / rows as f64).min((width - 2.0 * MINI_FRAME_PX) / cols as f64)
+            }
+            Layout::Stacked => {
+                (height * STACKED_BOARD_HEIGHT / rows as f64).min(width * STACKED_BOARD_WIDTH / cols as f64)
+            }
+        }
+    }
+}
+
+/// The layout and cell size for the current window and a `cols` × `rows`
+/// board, updated on resize and orientation change and when mini mode, if
+/// provided, is turned on or off.
+pub fn use_layout(cols: u32, rows: u32) -> Signal<(Layout, f64)> {
+    let window = use_window_size();
+    let mini = use_context::<MiniMode>();
+    Signal::derive(move || {
+        let (width, height) = (window.width.get(), window.height.get());
+        let layout = match mini {
+            Some(mini) if mini.get() => Layout::Mini,
+            _ => Layout::for_window(width, height),
+        };
+        (layout, layout.cell_px(width, height, cols, rows))
+    })
+}
@@ -0,0 +1,245 @@
+//! King of the hill against the bots: a `tetris_core::lobby::Lobby` of three
+//! or four boards side by side, played in real time. Bots fill every seat
+//! until a human takes one; the human's keys then drive that board, with the
+//! same bindings as the main game, and T switches where their garbage goes.
+
+use leptos::{html, prelude::*};
+use leptos_use::use_interval_fn;
+use tetris_core::bot::Weights;
+use tetris_core::lobby::{Lobby, Targeting, MAX_PLAYERS};
+use tetris_core::versus::ACTIONS_PER_TICK;
+use tetris_core::{render, Action, TICK_MS};
+
+use super::focus;
+use super::garbage_meter::{GarbageMeter, MeterReading};
+use super::i18n::{tr, tr_args, use_locale};
+use super::renderers::{DomFrame, DomRenderer};
+use super::skin;
+
+/// Real time between `Lobby::step`s, so gravity runs at the normal speed.
+const STEP_MS: f64 = TICK_MS / ACTIONS_PER_TICK as f64;
+/// Rows of the lobby's boards, for the garbage meters.
+const BOARD_ROWS: u32 = 25;
+/// Switches the human's `Targeting` mid-game; see `Lobby::cycle_targeting`.
+const TARGETING_KEY: &str = "KeyT";
+
+/// What the lobby shows under one board.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SeatStats {
+    sent: u32,
+    kos: u32,
+    badges: u32,
+    out: bool,
+    targeting: Targeting,
+}
+
+/// A new lobby of `players` seats, bots everywhere but `seat`.
+fn new_lobby(players: usize, seat: Option<usize>) -> Lobby {
+    let bots: Vec<Option<Weights>> = (0..players).map(|i| (seat != Some(i)).then(Weights::default)).collect();
+    Lobby::new(&bots, Default::default(), rand::random::<u32>() as u64)
+}
+
+/// The action the main game binds to the key with `code`, if any.
+fn action_for(code: &str) -> Option<Action> {
+    Some(match code {
+        "ArrowUp" => Action::Rotate,
+        "ArrowLeft" => Action::MoveLeft,
+        "ArrowRight" => Action::MoveRight,
+        "ArrowDown" => Action::SoftDrop,
+        "Space" => Action::HardDrop,
+        "KeyS" => Action::SonicDrop,
+        "KeyC" | "ShiftLeft" | "ShiftRight" => Action::Hold,
+        _ => return None,
+    })
+}
+
+/// Name of the player in `seat`: "You" for the human, "Bot n" otherwise.
+fn seat_name(locale: super::i18n::Locale, seat: usize, human: Option<usize>) -> String {
+    if human == Some(seat) {
+        tr(locale, "lobby.you")
+    } else {
+        tr_args(locale, "lobby.bot", &[("number", (seat + 1).to_string())])
+    }
+}
+
+/// The lobby dialog. It plays while open; taking a seat, leaving it or
+/// changing the number of players starts a new round.
+#[component]
+pub fn LobbyDialog(open: ReadSignal<bool>, on_close: impl Fn() + Clone + 'static) -> impl IntoView {
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    let players = RwSignal::new(MAX_PLAYERS);
+    // The human's seat, if they have taken one.
+    let seat = RwSignal::new(None::<usize>);
+    let lobby = StoredValue::new_local(new_lobby(MAX_PLAYERS, None));
+    let frames: [RwSignal<DomFrame>; MAX_PLAYERS] = std::array::from_fn(|_| RwSignal::new(DomFrame::default()));
+    let meters: [RwSignal<MeterReading>; MAX_PLAYERS] = std::array::from_fn(|_| RwSignal::new(MeterReading::default()));
+    let stats = RwSignal::new(Vec::<SeatStats>::new());
+    // Final placing once the round is over.
+    let standings = RwSignal::new(None::<Vec<usize>>);
+    let show = move || {
+        lobby.with_value(|lobby| {
+            for (i, p) in lobby.players().iter().enumerate() {
+                let mut dom = DomRenderer::default();
+                render::draw(&p.tetris, &mut dom);
+                frames[i].set(dom.into_frame());
+                meters[i].set(MeterReading::of(&p.incoming, p.tetris.elapsed_ms()));
+            }
+            stats.set(
+                lobby
+                    .players()
+                    .iter()
+                    .map(|p| SeatStats {
+                        sent: p.sent,
+                        kos: p.kos,
+                        badges: p.badges,
+                        out: p.tetris.lost,
+                        targeting: p.targeting,
+                    })
+                    .collect(),
+            );
+            standings.set(lobby.is_over().then(|| lobby.standings()));
+        });
+    };
+    let restart = move || {
+        lobby.set_value(new_lobby(players.get_untracked(), seat.get_untracked()));
+        show();
+    };
+    show();
+    use_interval_fn(
+        move || {
+            if open.get_untracked() && standings.with_untracked(Option::is_none) {
+                lobby.update_value(|l| l.step());
+                show();
+            }
+        },
+        STEP_MS as u64,
+    );
+    let take_seat = move |i: Option<usize>| {
+        seat.set(i);
+        restart();
+        // Off the buttons, so Space drops instead of pressing one.
+        if let Some(el) = menu.get_untracked() {
+            let _ = el.focus();
+        }
+    };
+    let on_key = move |ev: web_sys::KeyboardEvent| {
+        let playing = standings.with_untracked(Option::is_none);
+        if let (Some(i), TARGETING_KEY, true) = (seat.get_untracked(), ev.code().as_str(), playing) {
+            ev.prevent_default();
+            lobby.update_value(|l| {
+                l.cycle_targeting(i);
+            });
+            show();
+            return;
+        }
+        if let (Some(i), Some(action), true) = (seat.get_untracked(), action_for(&ev.code()), playing) {
+            ev.prevent_default();
+            lobby.update_value(|l| l.apply(i, action));
+            show();
+            return;
+        }
+        if let Some(el) = menu.get_untracked() {
+            focus::handle_menu_key(&ev, &el, &on_close_key);
+        }
+    };
+    let board = move |i: usize| {
+        let frame = frames[i];
+        view! {
+            <div class="flex flex-col items-center gap-1">
+                <span class="text-sm font-bold">{move || seat_name(locale.get(), i, seat.get())}</span>
+                <div class="relative flex flex-col h-64">
+                    <GarbageMeter reading=meters[i].read_only() height=BOARD_ROWS />
+                    {move || frame.get().cells.into_iter().map(|row| view! {
+                        <div class="row flex flex-row h-[calc(100%/25)]">
+                            {row.into_iter().map(|c| view! {
+                                <div class="cell aspect-square" style=skin::cell_style(c, None, false)></div>
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    }).collect::<Vec<_>>()}
+                    {move || stats.with(|s| s.get(i).is_some_and(|s| s.out)).then(|| view! {
+                        <div class="absolute inset-0 bg-black bg-opacity-60 flex items-center justify-center text-white font-bold">
+                            {move || tr(locale.get(), "lobby.out")}
+                        </div>
+                    })}
+                </div>
+                <span class="text-xs opacity-70 text-center">
+                    {move || stats.with(|s| s.get(i).copied()).map(|s| tr_args(locale.get(), "lobby.stats", &[
+                        ("sent", s.sent.to_string()),
+                        ("kos", s.kos.to_string()),
+                        ("badges", s.badges.to_string()),
+                    ]))}
+                </span>
+                {move || (seat.get() == Some(i)).then(|| view! {
+                    <span class="badge badge-soft badge-accent text-xs h-auto" title=move || tr(locale.get(), "lobby.targeting.hint")>
+                        {move || stats.with(|s| s.get(i).map(|s| s.targeting)).map(|t| {
+                            let l = locale.get();
+                            tr_args(l, "lobby.targeting", &[("strategy", tr(l, &format!("lobby.target.{}", t.id())))])
+                        })}
+                    </span>
+                })}
+                {move || if seat.get() == Some(i) {
+                    view! {
+                        <button class="btn btn-xs" on:click=move |_| take_seat(None)>
+                            {move || tr(locale.get(), "lobby.leave_seat")}
+                        </button>
+                    }.into_any()
+                } else {
+                    view! {
+                        <button class="btn btn-xs btn-primary" on:click=move |_| take_seat(Some(i))>
+                            {move || tr(locale.get(), "lobby.take_seat")}
+                        </button>
+                    }.into_any()
+                }}
+            </div>
+        }
+    };
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box max-w-3xl"
+                tabindex="-1"
+                node_ref=menu
+                on:keydown=on_key
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "lobby.title")}</h3>
+                <p class="text-sm opacity-70 pt-2">{move || tr(locale.get(), "lobby.hint")}</p>
+                <div class="flex flex-row gap-2 items-center pt-4">
+                    {[3, MAX_PLAYERS].map(|n| view! {
+                        <button
+                            class="btn btn-sm"
+                            class:btn-active=move || players.get() == n
+                            on:click=move |_| {
+                                players.set(n);
+                                if seat.get_untracked().is_some_and(|s| s >= n) {
+                                    seat.set(None);
+                                }
+                                restart();
+                            }>
+                            {move || tr_args(locale.get(), "lobby.players", &[("count", n.to_string())])}
+                        </button>
+                    })}
+                    <button class="btn btn-sm btn-outline" on:click=move |_| restart()>
+                        {move || tr(locale.get(), "lobby.new_round")}
+                    </button>
+                </div>
+                <div class="grid grid-cols-2 sm:grid-cols-4 gap-4 pt-4 ps-3">
+                    {move || (0..players.get()).map(board).collect::<Vec<_>>()}
+                </div>
+                {move || standings.get().map(|order| {
+                    let l = locale.get();
+                    let names: Vec<String> = order.iter().map(|&i| seat_name(l, i, seat.get())).collect();
+                    view! { <p class="font-bold pt-4">{tr_args(l, "lobby.standings", &[("players", names.join(", "))])}</p> }
+                })}
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
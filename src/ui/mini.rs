@@ -0,0 +1,50 @@
+//! Mini mode, desktop app only: the window shrinks to a small borderless
+//! board kept above other windows, with no sidebar, for a game on the side.
+//! `KEY` toggles it, and the margin around the board drags the window (see
+//! `layout::MINI_FRAME_PX`).
+
+use leptos::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use super::views::is_desktop;
+
+/// Toggles mini mode.
+pub const KEY: &str = "KeyM";
+
+/// Whether the window is in mini mode; provided by `App`.
+#[derive(Debug, Clone, Copy)]
+pub struct MiniMode(pub RwSignal<bool>);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+impl MiniMode {
+    /// Provides mini mode, off. A window left in it by a reload is restored.
+    pub fn provide() -> Self {
+        let mini = MiniMode(RwSignal::new(false));
+        provide_context(mini);
+        if is_desktop() {
+            mini.set(false);
+        }
+        mini
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.get()
+    }
+
+    /// Asks the desktop app to enter or leave mini mode.
+    pub fn set(&self, on: bool) {
+        let args = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&args, &JsValue::from_str("on"), &JsValue::from_bool(on));
+        invoke("set_mini_mode", args.into());
+        self.0.set(on);
+    }
+
+    pub fn toggle(&self) {
+        self.set(!self.0.get_untracked());
+    }
+}
@@ -0,0 +1,37 @@
+//! Frontend-only pieces of the Leptos app that don't belong in `tetris_core`.
+
+pub mod challenges;
+#[cfg(feature = "twitch")]
+pub mod chat_plays;
+pub mod archive;
+pub mod background;
+pub mod benchmark;
+pub mod confirm;
+pub mod drills;
+pub mod files;
+pub mod focus;
+pub mod garbage_meter;
+pub mod hold;
+pub mod i18n;
+pub mod input;
+pub mod inspector;
+pub mod latency;
+pub mod layout;
+pub mod lobby;
+pub mod mini;
+pub mod motion;
+pub mod power;
+pub mod profiles;
+pub mod pwa;
+pub mod renderers;
+pub mod replays;
+pub mod review;
+pub mod settings;
+pub mod skin;
+pub mod splits;
+pub mod storage;
+pub mod stress;
+pub mod sync;
+pub mod telemetry;
+pub mod toast;
+pub mod views;
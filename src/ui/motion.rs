@@ -0,0 +1,81 @@
+//! Picks how much animation to show. Everything that animates asks the
+//! `AnimationStyle` here instead of checking settings or media queries itself.
+
+use leptos::prelude::*;
+use leptos_use::use_prefers_reduced_motion;
+use serde::{Deserialize, Serialize};
+use tetris_core::DEFAULT_CLEAR_ANIMATION_MS;
+
+use super::settings::Settings;
+
+/// The player's manual choice; `System` follows `prefers-reduced-motion`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MotionPreference {
+    #[default]
+    System,
+    Reduced,
+    Full,
+}
+
+impl MotionPreference {
+    pub const ALL: [MotionPreference; 3] = [MotionPreference::System, MotionPreference::Reduced, MotionPreference::Full];
+
+    /// Locale key of the option label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            MotionPreference::System => "settings.motion.system",
+            MotionPreference::Reduced => "settings.motion.reduced",
+            MotionPreference::Full => "settings.motion.full",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationStyle {
+    Full,
+    /// No flashes or pop-ups; state changes appear instantly.
+    Reduced,
+}
+
+impl AnimationStyle {
+    /// How long cleared rows linger before collapsing.
+    pub fn clear_animation_ms(&self) -> f64 {
+        match self {
+            AnimationStyle::Full => DEFAULT_CLEAR_ANIMATION_MS,
+            AnimationStyle::Reduced => 0.0,
+        }
+    }
+
+    /// CSS class for rows that are being cleared, if they animate at all.
+    pub fn line_clear_class(&self) -> Option<&'static str> {
+        match self {
+            AnimationStyle::Full => Some("line-clearing-animation"),
+            AnimationStyle::Reduced => None,
+        }
+    }
+
+    /// Whether board shakes and flashes are played at all.
+    pub fn shows_board_effects(&self) -> bool {
+        *self == AnimationStyle::Full
+    }
+
+    /// Whether transient "+N points" pop-ups are shown.
+    pub fn shows_score_popups(&self) -> bool {
+        *self == AnimationStyle::Full
+    }
+}
+
+/// The animation style from the motion setting and, for `System`, the
+/// `prefers-reduced-motion` media query. Energy saver always reduces it.
+pub fn use_animation_style() -> Signal<AnimationStyle> {
+    let settings = expect_context::<RwSignal<Settings>>();
+    let prefers_reduced = use_prefers_reduced_motion();
+    Signal::derive(move || {
+        let reduced = settings.with(|s| s.energy_saver) || match settings.with(|s| s.motion) {
+            MotionPreference::System => prefers_reduced.get(),
+            MotionPreference::Reduced => true,
+            MotionPreference::Full => false,
+        };
+        if reduced { AnimationStyle::Reduced } else { AnimationStyle::Full }
+    })
+}
@@ -0,0 +1,51 @@
+//! Energy saver, for laptops and phones on battery. With it on, the board is
+//! drawn only when the game changes (on gravity ticks and inputs) instead of
+//! every animation frame, animations and board effects are off (see
+//! `motion::use_animation_style`), and nothing is drawn while the page is
+//! hidden.
+
+use leptos::prelude::*;
+use leptos_use::use_document_visibility;
+use web_sys::VisibilityState;
+
+use super::settings::Settings;
+
+/// How often the board is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderPace {
+    EveryFrame,
+    /// When the game changes: at the logical tick rate, plus inputs.
+    OnChange,
+    /// Not at all, until the page is shown again.
+    Paused,
+}
+
+impl RenderPace {
+    pub fn of(energy_saver: bool, hidden: bool) -> Self {
+        match (energy_saver, hidden) {
+            (false, _) => RenderPace::EveryFrame,
+            (true, false) => RenderPace::OnChange,
+            (true, true) => RenderPace::Paused,
+        }
+    }
+
+    pub fn draws(self) -> bool {
+        self != RenderPace::Paused
+    }
+
+    /// Milliseconds between updates of the play clock shown next to the
+    /// board.
+    pub fn clock_interval_ms(self) -> u64 {
+        match self {
+            RenderPace::EveryFrame => 50,
+            _ => tetris_core::TICK_MS as u64,
+        }
+    }
+}
+
+/// The pace for the energy saver setting and the page's visibility.
+pub fn use_render_pace() -> Signal<RenderPace> {
+    let settings = expect_context::<RwSignal<Settings>>();
+    let visibility = use_document_visibility();
+    Signal::derive(move || RenderPace::of(settings.with(|s| s.energy_saver), visibility.get() == VisibilityState::Hidden))
+}
@@ -0,0 +1,91 @@
+use leptos::{html, prelude::*};
+
+use super::focus;
+use super::i18n::{format_number, tr, tr_args};
+use super::settings::Settings;
+use super::storage::{self, SavedProfiles, SavedStats};
+
+/// Startup screen listing the saved profiles. Picking one calls `on_pick`;
+/// profiles can also be created and deleted here.
+///
+/// Shown before any profile's settings are loaded, so the language comes
+/// from the profile played last.
+#[component]
+pub fn ProfilePicker(
+    profiles: RwSignal<SavedProfiles>,
+    on_pick: impl Fn(u32) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let locale = profiles
+        .with_untracked(|p| p.last_used)
+        .and_then(|id| storage::load::<Settings>(&storage::profile_key(storage::SETTINGS_KEY, id)))
+        .unwrap_or_default()
+        .locale;
+    let (new_name, set_new_name) = signal(String::new());
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(|| true, menu);
+    let create = move || {
+        let name = new_name.get_untracked();
+        if !name.trim().is_empty() {
+            let mut id = 0;
+            profiles.update(|p| id = p.add(&name));
+            set_new_name.set(String::new());
+            on_pick(id);
+        }
+    };
+    view! {
+        <div class="modal modal-open">
+            <div
+                class="modal-box flex flex-col gap-4"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, || {});
+                    }
+                }
+                dir=locale.dir()
+                lang=locale.code()>
+                <h3 class="text-lg font-bold">{tr(locale, "profiles.title")}</h3>
+                <ul class="flex flex-col gap-2">
+                    {move || profiles.get().profiles.into_iter().map(|p| {
+                        let stats = storage::load::<SavedStats>(&storage::profile_key(storage::STATS_KEY, p.id))
+                            .unwrap_or_default();
+                        let summary = tr_args(locale, "profiles.summary", &[
+                            ("games", format_number(locale, stats.games_played as i64)),
+                            ("best", format_number(locale, stats.best_score as i64)),
+                        ]);
+                        let can_delete = profiles.with(|all| all.profiles.len() > 1);
+                        view! {
+                            <li class="flex flex-row gap-2 items-center">
+                                <button class="btn btn-primary grow flex flex-col items-start h-auto py-2" on:click=move |_| on_pick(p.id)>
+                                    <span>{p.name.clone()}</span>
+                                    <span class="text-xs opacity-70 font-normal">{summary}</span>
+                                </button>
+                                <button
+                                    class="btn btn-ghost btn-sm"
+                                    disabled=!can_delete
+                                    on:click=move |_| profiles.update(|all| all.remove(p.id))>
+                                    {tr(locale, "button.delete")}
+                                </button>
+                            </li>
+                        }
+                    }).collect::<Vec<_>>()}
+                </ul>
+                <form
+                    class="flex flex-row gap-2"
+                    on:submit=move |ev| {
+                        ev.prevent_default();
+                        create();
+                    }>
+                    <input
+                        type="text"
+                        class="input input-sm grow"
+                        maxlength="24"
+                        placeholder=tr(locale, "profiles.new_name")
+                        prop:value=new_name
+                        on:input=move |ev| set_new_name.set(event_target_value(&ev)) />
+                    <button type="submit" class="btn btn-sm">{tr(locale, "profiles.create")}</button>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,44 @@
+//! Installing the web build as an app. `pwa/sw.js`, registered from
+//! `index.html`, keeps it playable offline; this catches the browser's
+//! install offer (`beforeinstallprompt`) so the sidebar can make it when the
+//! player asks instead of whenever the browser likes.
+
+use leptos::{ev, prelude::*};
+use leptos_use::use_event_listener;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::Event;
+
+/// The browser's held-back install offer, if it made one.
+#[derive(Clone, Copy)]
+pub struct InstallPrompt {
+    offer: RwSignal<Option<Event>, LocalStorage>,
+}
+
+impl InstallPrompt {
+    /// Starts listening for the offer, and for the app being installed some
+    /// other way, which withdraws it.
+    pub fn listen() -> Self {
+        let offer = RwSignal::new_local(None::<Event>);
+        let _ = use_event_listener(window(), ev::Custom::<Event>::new("beforeinstallprompt"), move |e| {
+            e.prevent_default();
+            offer.set(Some(e));
+        });
+        let _ = use_event_listener(window(), ev::Custom::<Event>::new("appinstalled"), move |_| offer.set(None));
+        Self { offer }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.offer.with(Option::is_some)
+    }
+
+    /// Shows the browser's install dialog. An offer can only be shown once.
+    pub fn prompt(&self) {
+        let Some(offer) = self.offer.try_update(Option::take).flatten() else { return };
+        let prompt = js_sys::Reflect::get(&offer, &JsValue::from_str("prompt"))
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        if let Some(prompt) = prompt {
+            let _ = prompt.call0(&offer);
+        }
+    }
+}
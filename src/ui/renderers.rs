@@ -0,0 +1,142 @@
+//! The web frontend's `tetris_core::render::Renderer` backends, chosen in
+//! settings and switchable while playing. `Dom` keeps one element per cell,
+//! which skins, grid lines and the CSS fade style; `Canvas` paints flat colors
+//! onto a single canvas every animation frame.
+
+use serde::{Deserialize, Serialize};
+use tetris_core::render::{Overlay, Renderer};
+use tetris_core::Position;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use super::skin;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RendererKind {
+    #[default]
+    Dom,
+    Canvas,
+}
+
+impl RendererKind {
+    pub const ALL: [RendererKind; 2] = [RendererKind::Dom, RendererKind::Canvas];
+
+    /// Locale key of the option label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            RendererKind::Dom => "settings.renderer.dom",
+            RendererKind::Canvas => "settings.renderer.canvas",
+        }
+    }
+}
+
+/// A frame for the DOM board: cell kinds by row, and the rows being cleared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DomFrame {
+    pub cells: Vec<Vec<&'static str>>,
+    pub clearing: Vec<u32>,
+}
+
+/// Collects a `DomFrame` for the board view to render.
+#[derive(Debug, Default)]
+pub struct DomRenderer {
+    frame: DomFrame,
+}
+
+impl DomRenderer {
+    pub fn into_frame(self) -> DomFrame {
+        self.frame
+    }
+}
+
+impl Renderer for DomRenderer {
+    fn begin_frame(&mut self, width: u32, height: u32) {
+        self.frame.cells = vec![Vec::with_capacity(width as usize); height as usize];
+        self.frame.clearing.clear();
+    }
+
+    fn draw_cell(&mut self, _x: u32, y: u32, kind: &'static str) {
+        self.frame.cells[y as usize].push(kind);
+    }
+
+    fn draw_overlay(&mut self, overlay: Overlay) {
+        // The pause menu and game over screen are regular views.
+        if let Overlay::ClearingRow { y, .. } = overlay {
+            self.frame.clearing.push(y);
+        }
+    }
+
+    fn end_frame(&mut self) {}
+}
+
+/// Paints onto a canvas, sized to `CANVAS_CELL_PX` per cell.
+pub struct CanvasRenderer {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+}
+
+pub const CANVAS_CELL_PX: u32 = 32;
+
+impl CanvasRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<Self> {
+        let ctx = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+        Some(Self { canvas, ctx })
+    }
+
+    /// Outlines `cells` over the last frame; see `Tetris::hole_preview`.
+    pub fn mark_holes(&mut self, cells: &[Position]) {
+        let size = CANVAS_CELL_PX as f64;
+        self.ctx.set_stroke_style_str("rgba(239, 68, 68, 0.85)");
+        self.ctx.set_line_width(3.0);
+        for &Position(x, y) in cells {
+            self.ctx.stroke_rect(x as f64 * size + 1.5, y as f64 * size + 1.5, size - 4.0, size - 4.0);
+        }
+    }
+
+    /// Shades `cells` faintly over the last frame, e.g. where a hold would
+    /// put the piece coming in; see `Tetris::hold_swap_preview`.
+    pub fn shade_cells(&mut self, cells: &[Position]) {
+        let size = CANVAS_CELL_PX as f64;
+        self.ctx.set_fill_style_str("rgba(255, 255, 255, 0.2)");
+        for &Position(x, y) in cells {
+            self.ctx.fill_rect(x as f64 * size, y as f64 * size, size, size);
+        }
+    }
+
+    fn fill_row(&self, y: u32, style: &str) {
+        self.ctx.set_fill_style_str(style);
+        self.ctx.fill_rect(0.0, (y * CANVAS_CELL_PX) as f64, self.canvas.width() as f64, CANVAS_CELL_PX as f64);
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn begin_frame(&mut self, width: u32, height: u32) {
+        let (w, h) = (width * CANVAS_CELL_PX, height * CANVAS_CELL_PX);
+        if self.canvas.width() != w || self.canvas.height() != h {
+            self.canvas.set_width(w);
+            self.canvas.set_height(h);
+        }
+    }
+
+    fn draw_cell(&mut self, x: u32, y: u32, kind: &'static str) {
+        let size = CANVAS_CELL_PX as f64;
+        self.ctx.set_fill_style_str(skin::flat_color(kind));
+        // A one-pixel gap stands in for the grid lines.
+        self.ctx.fill_rect(x as f64 * size, y as f64 * size, size - 1.0, size - 1.0);
+    }
+
+    fn draw_overlay(&mut self, overlay: Overlay) {
+        match overlay {
+            // Fades the row out over the same time as the CSS animation.
+            Overlay::ClearingRow { y, progress } => {
+                self.fill_row(y, &format!("rgba(119, 119, 119, {:.3})", progress.clamp(0.0, 1.0)));
+            }
+            Overlay::Paused | Overlay::GameOver => {
+                self.ctx.set_fill_style_str("rgba(0, 0, 0, 0.5)");
+                self.ctx.fill_rect(0.0, 0.0, self.canvas.width() as f64, self.canvas.height() as f64);
+            }
+        }
+    }
+
+    fn end_frame(&mut self) {}
+}
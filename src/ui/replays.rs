@@ -0,0 +1,341 @@
+//! The replay library: finished games kept in `SavedReplays`, listed with
+//! their mode, score, date, length and tags, searchable and sortable, and
+//! downloadable as `tetris_core::replay` text for `--simulate`. Picking one
+//! plays it back in a viewer with buttons that jump to its highlights (see
+//! `tetris_core::highlights`).
+
+use leptos::{html, prelude::*};
+use leptos_use::use_interval_fn;
+use tetris_core::highlights::{self, HighlightKind};
+use tetris_core::render;
+use tetris_core::replay::{Replay, ReplayCursor};
+use wasm_bindgen::JsValue;
+
+use super::files;
+use super::focus;
+use super::i18n::{format_number, format_time, tr, tr_args, tr_or, use_locale};
+use super::renderers::{DomFrame, DomRenderer};
+use super::skin;
+use super::storage::{SavedReplay, SavedReplays};
+
+/// Game time the viewer plays per step while playing.
+const PLAYBACK_STEP_MS: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplaySort {
+    #[default]
+    Newest,
+    Oldest,
+    Score,
+    Duration,
+}
+
+impl ReplaySort {
+    pub const ALL: [ReplaySort; 4] = [ReplaySort::Newest, ReplaySort::Oldest, ReplaySort::Score, ReplaySort::Duration];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            ReplaySort::Newest => "newest",
+            ReplaySort::Oldest => "oldest",
+            ReplaySort::Score => "score",
+            ReplaySort::Duration => "duration",
+        }
+    }
+}
+
+/// Whether every word of `query` is part of the replay's mode or one of
+/// its tags, ignoring case.
+pub fn matches(replay: &SavedReplay, query: &str) -> bool {
+    query.split_whitespace().all(|word| {
+        let word = word.to_lowercase();
+        replay.mode.to_lowercase().contains(&word) || replay.tags.iter().any(|t| t.to_lowercase().contains(&word))
+    })
+}
+
+/// The replays matching `query`, in `sort` order; highest score and longest
+/// game first for those.
+pub fn search(saved: &SavedReplays, query: &str, sort: ReplaySort) -> Vec<SavedReplay> {
+    let mut found: Vec<SavedReplay> = saved.replays.iter().filter(|r| matches(r, query)).cloned().collect();
+    match sort {
+        ReplaySort::Newest => found.sort_by(|a, b| b.date_ms.total_cmp(&a.date_ms)),
+        ReplaySort::Oldest => found.sort_by(|a, b| a.date_ms.total_cmp(&b.date_ms)),
+        ReplaySort::Score => found.sort_by_key(|r| std::cmp::Reverse(r.score)),
+        ReplaySort::Duration => found.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms)),
+    }
+    found
+}
+
+/// Plays `replay` back: a board, a scrubber over its inputs, play and pause,
+/// and a button per highlight that jumps to just before it.
+#[component]
+fn ReplayViewer(replay: Replay) -> impl IntoView {
+    let locale = use_locale();
+    let found = highlights::find(&replay);
+    let total = replay.inputs.len();
+    let cursor = StoredValue::new_local(ReplayCursor::new(replay));
+    // Game time playback has reached; inputs up to it have been played.
+    let clock = StoredValue::new(0.0);
+    let (frame, set_frame) = signal(DomFrame::default());
+    let (position, set_position) = signal(0usize);
+    let (at_ms, set_at_ms) = signal(0.0);
+    let playing = RwSignal::new(false);
+    let show = move || {
+        cursor.with_value(|c| {
+            let mut dom = DomRenderer::default();
+            render::draw(c.game(), &mut dom);
+            set_frame.set(dom.into_frame());
+            set_position.set(c.position());
+            set_at_ms.set(c.at_ms());
+            if c.is_finished() {
+                playing.set(false);
+            }
+        });
+    };
+    let seek = move |n: usize| {
+        cursor.update_value(|c| c.seek(n));
+        clock.set_value(cursor.with_value(|c| c.at_ms()));
+        show();
+    };
+    show();
+    use_interval_fn(
+        move || {
+            if playing.get_untracked() {
+                clock.update_value(|ms| *ms += PLAYBACK_STEP_MS);
+                cursor.update_value(|c| c.play_until(clock.get_value()));
+                show();
+            }
+        },
+        PLAYBACK_STEP_MS as u64,
+    );
+    let label = move |kind: HighlightKind| {
+        let l = locale.get();
+        match kind {
+            HighlightKind::Tetris => tr(l, "highlights.tetris"),
+            HighlightKind::Combo { pieces } => tr_args(l, "highlights.combo", &[("count", pieces.to_string())]),
+            HighlightKind::Recovery => tr(l, "highlights.recovery"),
+        }
+    };
+    view! {
+        <div class="flex flex-row gap-4 pt-4">
+            <div class="flex flex-col h-80 shrink-0">
+                {move || frame.get().cells.into_iter().map(|row| view! {
+                    <div class="row flex flex-row h-[calc(100%/25)]">
+                        {row.into_iter().map(|c| view! {
+                            <div class="cell aspect-square" style=skin::cell_style(c, None, false)></div>
+                        }).collect::<Vec<_>>()}
+                    </div>
+                }).collect::<Vec<_>>()}
+            </div>
+            <div class="grow flex flex-col gap-2">
+                <div class="flex flex-row gap-2 items-center">
+                    <button class="btn btn-sm btn-primary" on:click=move |_| playing.update(|p| *p = !*p)>
+                        {move || tr(locale.get(), if playing.get() { "replays.pause" } else { "replays.play" })}
+                    </button>
+                    <span class="font-mono text-sm">{move || format_time(locale.get(), at_ms.get())}</span>
+                </div>
+                <input
+                    type="range"
+                    class="range range-xs"
+                    min="0"
+                    max=total.to_string()
+                    prop:value=move || position.get().to_string()
+                    on:input=move |ev| {
+                        if let Ok(n) = event_target_value(&ev).parse() {
+                            seek(n);
+                        }
+                    } />
+                <h4 class="font-bold">{move || tr(locale.get(), "highlights.title")}</h4>
+                <div class="flex flex-col gap-1 max-h-56 overflow-y-auto">
+                    {if found.is_empty() {
+                        view! { <span class="text-sm opacity-70">{move || tr(locale.get(), "highlights.none")}</span> }.into_any()
+                    } else {
+                        found.into_iter().map(|h| view! {
+                            <button
+                                class="btn btn-xs btn-outline justify-between"
+                                on:click=move |_| {
+                                    seek(h.start);
+                                    playing.set(true);
+                                }>
+                                <span>{move || label(h.kind)}</span>
+                                <span class="font-mono">{move || format_time(locale.get(), h.at_ms)}</span>
+                            </button>
+                        }).collect::<Vec<_>>().into_any()
+                    }}
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Lists the saved replays, with a search box and sort order, and lets each
+/// be tagged, downloaded or deleted.
+#[component]
+pub fn ReplayLibrary(
+    open: ReadSignal<bool>,
+    saved: RwSignal<SavedReplays>,
+    on_close: impl Fn() + Clone + 'static,
+) -> impl IntoView {
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    let query = RwSignal::new(String::new());
+    let sort = RwSignal::new(ReplaySort::default());
+    // Id of the replay in the viewer, if one is.
+    let watching = RwSignal::new(None::<u32>);
+    Effect::new(move || {
+        if !open.get() {
+            watching.set(None);
+        }
+    });
+    // Text fields keep their arrow keys; Escape still closes the dialog.
+    let keep_keys = |ev: web_sys::KeyboardEvent| {
+        if ev.key() != "Escape" {
+            ev.stop_propagation();
+        }
+    };
+    let row = move |replay: SavedReplay| {
+        let id = replay.id;
+        let date_ms = replay.date_ms;
+        let date = move || {
+            let date = js_sys::Date::new(&JsValue::from_f64(date_ms));
+            String::from(date.to_locale_string(locale.get().code(), &JsValue::UNDEFINED))
+        };
+        let summary = move || {
+            let l = locale.get();
+            tr_args(l, "replays.summary", &[
+                ("score", format_number(l, replay.score as i64)),
+                ("lines", replay.lines.to_string()),
+                ("time", format_time(l, replay.duration_ms)),
+            ])
+        };
+        let file_name = format!("{}-{}.replay", replay.mode, id);
+        let text = replay.replay.clone();
+        let new_tag = RwSignal::new(String::new());
+        let add_tag = move || {
+            saved.update(|s| s.add_tag(id, &new_tag.get_untracked()));
+            new_tag.set(String::new());
+        };
+        view! {
+            <li class="flex flex-col gap-1 border-b border-base-300 pb-2">
+                <div class="flex flex-row gap-2 items-center">
+                    <div class="grow flex flex-col">
+                        <span>
+                            {move || tr_or(locale.get(), &format!("mode.{}", replay.mode), &replay.mode)}
+                            " · "
+                            {date}
+                        </span>
+                        <span class="text-xs opacity-70">{summary}</span>
+                    </div>
+                    <button class="btn btn-sm btn-primary" on:click=move |_| watching.set(Some(id))>
+                        {move || tr(locale.get(), "replays.watch")}
+                    </button>
+                    <button class="btn btn-sm" on:click=move |_| files::download(&file_name, "text/plain", &text)>
+                        {move || tr(locale.get(), "replays.download")}
+                    </button>
+                    <button class="btn btn-sm btn-error" on:click=move |_| saved.update(|s| s.remove(id))>
+                        {move || tr(locale.get(), "button.delete")}
+                    </button>
+                </div>
+                <div class="flex flex-row flex-wrap gap-1 items-center">
+                    {replay.tags.into_iter().map(|tag| {
+                        let label = tag.clone();
+                        view! {
+                            <button
+                                class="badge badge-outline gap-1"
+                                title=move || tr(locale.get(), "replays.remove_tag")
+                                on:click=move |_| saved.update(|s| s.remove_tag(id, &tag))>
+                                {label}" ×"
+                            </button>
+                        }
+                    }).collect::<Vec<_>>()}
+                    <input
+                        type="text"
+                        class="input input-xs w-24"
+                        maxlength="24"
+                        placeholder=move || tr(locale.get(), "replays.add_tag")
+                        prop:value=new_tag
+                        on:input=move |ev| new_tag.set(event_target_value(&ev))
+                        on:keydown=move |ev| {
+                            if ev.key() == "Enter" {
+                                add_tag();
+                            }
+                            keep_keys(ev);
+                        } />
+                </div>
+            </li>
+        }
+    };
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, &on_close_key);
+                    }
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "replays.title")}</h3>
+                {move || match watching.get() {
+                    Some(id) => {
+                        let replay = saved.with_untracked(|s| {
+                            s.replays.iter().find(|r| r.id == id).and_then(|r| Replay::parse(&r.replay).ok())
+                        });
+                        view! {
+                            {match replay {
+                                Some(replay) => view! { <ReplayViewer replay=replay /> }.into_any(),
+                                None => view! { <p class="py-4">{move || tr(locale.get(), "replays.unreadable")}</p> }.into_any(),
+                            }}
+                            <button class="btn btn-sm mt-2" on:click=move |_| watching.set(None)>
+                                {move || tr(locale.get(), "replays.back")}
+                            </button>
+                        }.into_any()
+                    }
+                    None => view! {
+                        <div class="flex flex-row gap-2 pt-4">
+                            <input
+                                type="search"
+                                class="input input-sm grow"
+                                placeholder=move || tr(locale.get(), "replays.search")
+                                prop:value=query
+                                on:input=move |ev| query.set(event_target_value(&ev))
+                                on:keydown=keep_keys />
+                            <select
+                                class="select select-sm"
+                                on:change=move |ev| {
+                                    let picked = event_target_value(&ev);
+                                    if let Some(s) = ReplaySort::ALL.into_iter().find(|s| s.id() == picked) {
+                                        sort.set(s);
+                                    }
+                                }>
+                                {ReplaySort::ALL.into_iter().map(|s| view! {
+                                    <option value=s.id() selected=move || sort.get() == s>
+                                        {move || tr(locale.get(), &format!("replays.sort.{}", s.id()))}
+                                    </option>
+                                }).collect::<Vec<_>>()}
+                            </select>
+                        </div>
+                        <ul class="py-4 flex flex-col gap-2 max-h-96 overflow-y-auto">
+                            {move || {
+                                let found = saved.with(|s| search(s, &query.get(), sort.get()));
+                                if found.is_empty() {
+                                    view! { <li class="opacity-70">{move || tr(locale.get(), "replays.empty")}</li> }.into_any()
+                                } else {
+                                    found.into_iter().map(row).collect::<Vec<_>>().into_any()
+                                }
+                            }}
+                        </ul>
+                    }.into_any(),
+                }}
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
@@ -0,0 +1,129 @@
+use leptos::{html, prelude::*};
+use tetris_core::review::{MomentKind, Review};
+use tetris_core::stats::Misdrop;
+
+use super::focus;
+use super::i18n::{self, tr, tr_args, use_locale};
+use super::skin;
+
+fn moment_key(kind: MomentKind) -> &'static str {
+    match kind {
+        MomentKind::Tetris => "review.tetris",
+        MomentKind::Misdrop(Misdrop::Hole) => "review.misdrop_hole",
+        MomentKind::Misdrop(Misdrop::Evaluation) => "review.misdrop_eval",
+        MomentKind::TopOut => "review.top_out",
+    }
+}
+
+fn marker_class(kind: MomentKind) -> &'static str {
+    match kind {
+        MomentKind::Tetris => "bg-success",
+        MomentKind::Misdrop(_) => "bg-warning",
+        MomentKind::TopOut => "bg-error",
+    }
+}
+
+/// Post-game review: the board at each of the game's key moments, stepped
+/// through with the arrow keys or picked on a timeline of the game's pieces.
+#[component]
+pub fn ReviewDialog(
+    open: ReadSignal<bool>,
+    #[prop(into)] review: Signal<Review>,
+    on_close: impl Fn() + Clone + 'static,
+) -> impl IntoView {
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    let (index, set_index) = signal(0usize);
+    Effect::new(move || {
+        if open.get() {
+            set_index.set(0);
+        }
+    });
+    focus::focus_on_open(move || open.get(), menu);
+    let count = move || review.with(|r| r.moments.len());
+    let step = move |delta: isize| {
+        let last = count().saturating_sub(1);
+        set_index.update(|i| *i = i.saturating_add_signed(delta).min(last));
+    };
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box"
+                node_ref=menu
+                on:keydown=move |ev| match ev.key().as_str() {
+                    "ArrowLeft" | "ArrowUp" => {
+                        ev.prevent_default();
+                        step(-1);
+                    }
+                    "ArrowRight" | "ArrowDown" => {
+                        ev.prevent_default();
+                        step(1);
+                    }
+                    "Escape" => {
+                        ev.prevent_default();
+                        on_close_key();
+                    }
+                    _ => {}
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "review.title")}</h3>
+                {move || {
+                    let l = locale.get();
+                    review.with(|r| {
+                        let Some(moment) = r.moments.get(index.get()) else {
+                            return view! { <p class="py-4">{tr(l, "review.empty")}</p> }.into_any();
+                        };
+                        let pieces = r.pieces.max(1) as f64;
+                        view! {
+                            <p class="py-2">
+                                {tr_args(l, "review.moment", &[
+                                    ("moment", tr(l, moment_key(moment.kind))),
+                                    ("piece", i18n::format_number(l, moment.piece as i64)),
+                                    ("time", i18n::format_time(l, moment.elapsed_ms)),
+                                ])}
+                            </p>
+                            <div class="flex flex-col items-center py-2">
+                                {moment.board.iter().map(|row| view! {
+                                    <div class="flex flex-row">
+                                        {row.iter().map(|&c| view! {
+                                            <div class="w-3 h-3" style:background-color=skin::flat_color(c)></div>
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                }).collect::<Vec<_>>()}
+                            </div>
+                            // Timeline of the game's pieces with a marker per moment.
+                            <div class="relative h-4 my-2 rounded bg-base-300">
+                                {r.moments.iter().enumerate().map(|(i, m)| {
+                                    let left = format!("calc({:.2}% - 0.375rem)", m.piece as f64 * 100.0 / pieces);
+                                    view! {
+                                        <button
+                                            class=format!("absolute top-0.5 w-3 h-3 rounded-full {}", marker_class(m.kind))
+                                            class:ring-2=move || index.get() == i
+                                            style:left=left
+                                            title=tr(l, moment_key(m.kind))
+                                            on:click=move |_| set_index.set(i)>
+                                        </button>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        }.into_any()
+                    })
+                }}
+                <div class="modal-action">
+                    <button class="btn" disabled=move || index.get() == 0 on:click=move |_| step(-1)>
+                        {move || tr(locale.get(), "review.previous")}
+                    </button>
+                    <span class="self-center text-sm">{move || format!("{} / {}", (index.get() + 1).min(count()), count())}</span>
+                    <button class="btn" disabled=move || index.get() + 1 >= count() on:click=move |_| step(1)>
+                        {move || tr(locale.get(), "review.next")}
+                    </button>
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
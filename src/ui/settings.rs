@@ -0,0 +1,645 @@
+use leptos::{html, prelude::*};
+use serde::{Deserialize, Serialize};
+use tetris_core::garbage::{GarbagePattern, GarbageStyle, MAX_HOLES};
+use tetris_core::generator::Randomizer;
+use tetris_core::input::SoftDropSpeed;
+use tetris_core::pieces;
+use tetris_core::sprint::{DEFAULT_SPLITS, SPRINT_LINES};
+
+use super::background::CustomBackground;
+use super::benchmark::RenderBenchmark;
+use super::toast::Toaster;
+use super::{archive, files, focus};
+use super::i18n::{tr, tr_args, use_locale, Locale};
+use super::motion::MotionPreference;
+use super::renderers::RendererKind;
+use super::skin::{CustomAtlas, GridLines, Skin};
+use super::sync::SyncPanel;
+use super::telemetry::TelemetryPanel;
+
+/// Choices for `Settings::game_speed`, in percent.
+pub const GAME_SPEEDS: [u32; 6] = [25, 50, 75, 100, 150, 200];
+
+/// User preferences for the web/desktop frontend, shared through Leptos context
+/// as an `RwSignal<Settings>` and persisted under `storage::SETTINGS_KEY`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Resume automatically when the window regains focus or the tab becomes
+    /// visible again. When off (the default), the player has to press P to continue.
+    pub auto_resume_on_focus: bool,
+    /// `KeyboardEvent.code` that restarts the game when held.
+    pub retry_key: String,
+    /// How long the retry key has to be held, in milliseconds.
+    pub retry_hold_ms: u32,
+    /// UI language.
+    pub locale: Locale,
+    /// Whether to tone down animations; see `motion::use_animation_style`.
+    pub motion: MotionPreference,
+    /// Draw less and animate nothing to save battery; see `power`.
+    pub energy_saver: bool,
+    /// Game speed in percent, one of `GAME_SPEEDS`; see
+    /// `Tetris::set_time_scale`.
+    pub game_speed: u32,
+    /// Strength of board shakes and flashes in percent; 0 turns them off.
+    pub effect_intensity: u32,
+    /// Pick `renderer` and `effect_intensity` from `benchmark`; changing
+    /// either by hand turns this off. Settings saved before it existed stay
+    /// as they were.
+    #[serde(default)]
+    pub auto_quality: bool,
+    /// The startup render benchmark's result; `None` until it has run.
+    pub benchmark: Option<RenderBenchmark>,
+    /// Play close calls in slow motion; see `Tetris::set_slow_motion`.
+    pub close_call_slow_motion: bool,
+    /// How the board is drawn; see `renderers`.
+    pub renderer: RendererKind,
+    /// How blocks are drawn; see `skin`.
+    pub skin: Skin,
+    pub grid_lines: GridLines,
+    /// Outline the playfield so its edges stand out from the page.
+    pub playfield_border: bool,
+    /// Tint the columns the falling piece spawned in.
+    pub highlight_spawn: bool,
+    /// Mark the cells a hard drop would leave as holes; see
+    /// `Tetris::hole_preview`.
+    pub hole_preview: bool,
+    /// Rotation each piece spawns in, indexed like `pieces::KINDS`; see
+    /// `Tetris::set_spawn_orientations`.
+    pub spawn_rotations: [usize; 7],
+    /// How fast holding the soft drop key drops; see
+    /// `Tetris::set_soft_drop_speed`.
+    pub soft_drop: SoftDropSpeed,
+    /// Shuffled bags of seven, or classic random pieces, from the next game
+    /// on; see `Tetris::set_randomizer`.
+    pub randomizer: Randomizer,
+    /// Put the sidebar on the left of the board.
+    pub left_handed: bool,
+    /// Mirror the on-screen arrow pad left to right.
+    pub mirror_touch_controls: bool,
+    /// How much the custom background is darkened, in percent.
+    pub background_dim: u32,
+    /// Blur radius of the custom background, in pixels.
+    pub background_blur: u32,
+    /// Line counts at which Sprint runs record a split.
+    pub sprint_splits: Vec<u32>,
+    /// Show how the current run compares with the mode's best one.
+    pub race_personal_best: bool,
+    /// Seconds between garbage rows at the start of a Downstack run.
+    pub downstack_interval_s: u32,
+    /// Whether the Downstack feed quickens with every row.
+    pub downstack_speed_up: bool,
+    /// Where the holes go in Downstack and Adaptive garbage.
+    pub garbage: GarbageStyle,
+    /// Fill a zone meter with cleared lines, spent with Z; see
+    /// `tetris_core::zone`.
+    pub zone_meter: bool,
+    /// Show the board health gauge; see `tetris_core::metrics`.
+    pub show_board_health: bool,
+    /// Draw each column's height as a bar under the board; toggled with H.
+    pub show_column_heights: bool,
+    /// Measure key press latency and show it over the board; see `latency`.
+    pub show_latency: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_resume_on_focus: false,
+            retry_key: "KeyR".to_string(),
+            retry_hold_ms: 500,
+            locale: Locale::default(),
+            motion: MotionPreference::default(),
+            energy_saver: false,
+            game_speed: 100,
+            effect_intensity: 100,
+            auto_quality: true,
+            benchmark: None,
+            close_call_slow_motion: true,
+            renderer: RendererKind::default(),
+            skin: Skin::default(),
+            grid_lines: GridLines::default(),
+            playfield_border: true,
+            highlight_spawn: false,
+            hole_preview: false,
+            spawn_rotations: [0; 7],
+            soft_drop: SoftDropSpeed::default(),
+            randomizer: Randomizer::default(),
+            left_handed: false,
+            mirror_touch_controls: false,
+            background_dim: 40,
+            background_blur: 0,
+            sprint_splits: DEFAULT_SPLITS.to_vec(),
+            race_personal_best: true,
+            downstack_interval_s: 5,
+            downstack_speed_up: true,
+            garbage: GarbageStyle::default(),
+            zone_meter: false,
+            show_board_health: true,
+            show_column_heights: false,
+            show_latency: false,
+        }
+    }
+}
+
+/// Modal editing the `Settings` in context.
+#[component]
+pub fn SettingsDialog(open: ReadSignal<bool>, on_close: impl Fn() + Clone + 'static) -> impl IntoView {
+    let settings = expect_context::<RwSignal<Settings>>();
+    let custom_atlas = expect_context::<RwSignal<CustomAtlas>>();
+    let background = expect_context::<RwSignal<CustomBackground>>();
+    let toaster = expect_context::<Toaster>();
+    let has_background = move || background.with(|b| b.0.is_some());
+    let locale = use_locale();
+    let on_close_backdrop = on_close.clone();
+    let on_close_key = on_close.clone();
+    let menu = NodeRef::<html::Div>::new();
+    focus::focus_on_open(move || open.get(), menu);
+    view! {
+        <div class="modal" class:modal-open=move || open.get()>
+            <div
+                class="modal-box flex flex-col gap-4"
+                node_ref=menu
+                on:keydown=move |ev| {
+                    if let Some(el) = menu.get_untracked() {
+                        focus::handle_menu_key(&ev, &el, &on_close_key);
+                    }
+                }
+                dir=move || locale.get().dir()
+                lang=move || locale.get().code()>
+                <h3 class="text-lg font-bold">{move || tr(locale.get(), "settings.title")}</h3>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.language")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            if let Some(l) = Locale::from_code(&event_target_value(&ev)) {
+                                settings.update(|s| s.locale = l);
+                            }
+                        }>
+                        {Locale::ALL.into_iter().map(|l| view! {
+                            <option value=l.code() selected=move || locale.get() == l>{l.native_name()}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.motion")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            let picked = event_target_value(&ev);
+                            if let Some(m) = MotionPreference::ALL.into_iter().find(|m| m.label_key() == picked) {
+                                settings.update(|s| s.motion = m);
+                            }
+                        }>
+                        {MotionPreference::ALL.into_iter().map(|m| view! {
+                            <option value=m.label_key() selected=move || settings.with(|s| s.motion == m)>
+                                {move || tr(locale.get(), m.label_key())}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.energy_saver)
+                        on:change=move |ev| settings.update(|s| s.energy_saver = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.energy_saver")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.game_speed")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| s.game_speed = v);
+                            }
+                        }>
+                        {GAME_SPEEDS.into_iter().map(|n| view! {
+                            <option value=n.to_string() selected=move || settings.with(|s| s.game_speed == n)>
+                                {format!("{}%", n)}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                // Turning it back on runs the benchmark again.
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.auto_quality)
+                        on:change=move |ev| settings.update(|s| {
+                            s.auto_quality = event_target_checked(&ev);
+                            if s.auto_quality {
+                                s.benchmark = None;
+                            }
+                        }) />
+                    <span>{move || tr(locale.get(), "settings.auto_quality")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.renderer")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            let picked = event_target_value(&ev);
+                            if let Some(r) = RendererKind::ALL.into_iter().find(|r| r.label_key() == picked) {
+                                settings.update(|s| {
+                                    s.renderer = r;
+                                    s.auto_quality = false;
+                                });
+                            }
+                        }>
+                        {RendererKind::ALL.into_iter().map(|r| view! {
+                            <option value=r.label_key() selected=move || settings.with(|s| s.renderer == r)>
+                                {move || tr(locale.get(), r.label_key())}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.skin")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            let picked = event_target_value(&ev);
+                            if let Some(s) = Skin::ALL.into_iter().find(|s| s.label_key() == picked) {
+                                settings.update(|st| st.skin = s);
+                            }
+                        }>
+                        {Skin::ALL.into_iter().map(|s| view! {
+                            <option value=s.label_key() selected=move || settings.with(|st| st.skin == s)>
+                                {move || tr(locale.get(), s.label_key())}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label
+                    class="flex flex-col gap-1"
+                    style:display=move || if settings.with(|s| s.skin == Skin::Custom) { "flex" } else { "none" }>
+                    <span class="text-sm opacity-80">{move || tr(locale.get(), "settings.skin.custom_hint")}</span>
+                    <input
+                        type="file"
+                        accept="image/*"
+                        class="file-input file-input-sm"
+                        on:change=move |ev| files::read_as_data_url(&ev, move |url| custom_atlas.set(CustomAtlas(Some(url)))) />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.grid")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            let picked = event_target_value(&ev);
+                            if let Some(g) = GridLines::ALL.into_iter().find(|g| g.label_key() == picked) {
+                                settings.update(|s| s.grid_lines = g);
+                            }
+                        }>
+                        {GridLines::ALL.into_iter().map(|g| view! {
+                            <option value=g.label_key() selected=move || settings.with(|s| s.grid_lines == g)>
+                                {move || tr(locale.get(), g.label_key())}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.playfield_border)
+                        on:change=move |ev| settings.update(|s| s.playfield_border = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.playfield_border")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.highlight_spawn)
+                        on:change=move |ev| settings.update(|s| s.highlight_spawn = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.highlight_spawn")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.hole_preview)
+                        on:change=move |ev| settings.update(|s| s.hole_preview = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.hole_preview")}</span>
+                </label>
+                <div class="flex flex-col gap-1">
+                    <span>{move || tr(locale.get(), "settings.spawn_orientation")}</span>
+                    <div class="flex flex-row flex-wrap gap-2">
+                        {pieces::KINDS.into_iter().enumerate().map(|(i, kind)| view! {
+                            <label class="flex flex-row gap-1 items-center">
+                                <span class="font-mono">{kind}</span>
+                                <select
+                                    class="select select-xs"
+                                    on:change=move |ev| {
+                                        if let Ok(rotation) = event_target_value(&ev).parse::<usize>() {
+                                            settings.update(|s| s.spawn_rotations[i] = rotation % 4);
+                                        }
+                                    }>
+                                    {(0..4).map(|r| view! {
+                                        <option value=r.to_string() selected=move || settings.with(|s| s.spawn_rotations[i] == r)>
+                                            {format!("{}°", r * 90)}
+                                        </option>
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </label>
+                        }).collect::<Vec<_>>()}
+                    </div>
+                </div>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.soft_drop")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            if let Some(&speed) = SoftDropSpeed::ALL.get(event_target_value(&ev).parse::<usize>().unwrap_or(1)) {
+                                settings.update(|s| s.soft_drop = speed);
+                            }
+                        }>
+                        {SoftDropSpeed::ALL.iter().enumerate().map(|(i, &speed)| {
+                            let key = match speed {
+                                SoftDropSpeed::Times5 => "settings.soft_drop_x5",
+                                SoftDropSpeed::Times20 => "settings.soft_drop_x20",
+                                SoftDropSpeed::Instant => "settings.soft_drop_instant",
+                            };
+                            view! {
+                                <option value=i.to_string() selected=move || settings.with(|s| s.soft_drop == speed)>
+                                    {move || tr(locale.get(), key)}
+                                </option>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.randomizer == Randomizer::Classic)
+                        on:change=move |ev| settings.update(|s| {
+                            s.randomizer = if event_target_checked(&ev) { Randomizer::Classic } else { Randomizer::Bag };
+                        }) />
+                    <span>{move || tr(locale.get(), "settings.classic_randomizer")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.left_handed)
+                        on:change=move |ev| settings.update(|s| s.left_handed = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.left_handed")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.mirror_touch_controls)
+                        on:change=move |ev| settings.update(|s| s.mirror_touch_controls = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.mirror_touch")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.zone_meter)
+                        on:change=move |ev| settings.update(|s| s.zone_meter = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.zone_meter")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.show_board_health)
+                        on:change=move |ev| settings.update(|s| s.show_board_health = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.board_health")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.show_column_heights)
+                        on:change=move |ev| settings.update(|s| s.show_column_heights = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.column_heights")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.show_latency)
+                        on:change=move |ev| settings.update(|s| s.show_latency = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.show_latency")}</span>
+                </label>
+                <div class="flex flex-col gap-1">
+                    <span>{move || tr(locale.get(), "settings.background")}</span>
+                    <div class="flex flex-row gap-2 items-center">
+                        <input
+                            type="file"
+                            accept="image/*,video/*"
+                            class="file-input file-input-sm"
+                            on:change=move |ev| files::read_as_data_url(&ev, move |url| background.set(CustomBackground(Some(url)))) />
+                        <button
+                            class="btn btn-sm"
+                            disabled=move || !has_background()
+                            on:click=move |_| background.set(CustomBackground(None))>
+                            {move || tr(locale.get(), "button.remove")}
+                        </button>
+                    </div>
+                </div>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.background.dim")}</span>
+                    <input
+                        type="range"
+                        class="range range-sm"
+                        min="0"
+                        max="90"
+                        step="10"
+                        disabled=move || !has_background()
+                        prop:value=move || settings.with(|s| s.background_dim.to_string())
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| s.background_dim = v.min(90));
+                            }
+                        } />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.background.blur")}</span>
+                    <input
+                        type="range"
+                        class="range range-sm"
+                        min="0"
+                        max="20"
+                        step="2"
+                        disabled=move || !has_background()
+                        prop:value=move || settings.with(|s| s.background_blur.to_string())
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| s.background_blur = v.min(20));
+                            }
+                        } />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.effects")}</span>
+                    <input
+                        type="range"
+                        class="range range-sm"
+                        min="0"
+                        max="100"
+                        step="25"
+                        prop:value=move || settings.with(|s| s.effect_intensity.to_string())
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| {
+                                    s.effect_intensity = v.min(100);
+                                    s.auto_quality = false;
+                                });
+                            }
+                        } />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.close_call_slow_motion)
+                        on:change=move |ev| settings.update(|s| s.close_call_slow_motion = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.slow_motion")}</span>
+                </label>
+                <div class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.sprint_splits")}</span>
+                    // The finish is always a split.
+                    {DEFAULT_SPLITS.into_iter().filter(|&n| n < SPRINT_LINES).map(|n| view! {
+                        <label class="flex flex-row gap-1 items-center">
+                            <input
+                                type="checkbox"
+                                class="checkbox checkbox-sm"
+                                prop:checked=move || settings.with(|s| s.sprint_splits.contains(&n))
+                                on:change=move |ev| settings.update(|s| {
+                                    s.sprint_splits.retain(|&m| m != n);
+                                    if event_target_checked(&ev) {
+                                        s.sprint_splits.push(n);
+                                        s.sprint_splits.sort_unstable();
+                                    }
+                                }) />
+                            <span>{n}</span>
+                        </label>
+                    }).collect::<Vec<_>>()}
+                </div>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>
+                        {move || tr_args(locale.get(), "settings.downstack_interval", &[
+                            ("seconds", settings.with(|s| s.downstack_interval_s.to_string())),
+                        ])}
+                    </span>
+                    <input
+                        type="range"
+                        class="range range-sm"
+                        min="2"
+                        max="10"
+                        step="1"
+                        prop:value=move || settings.with(|s| s.downstack_interval_s.to_string())
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| s.downstack_interval_s = v.clamp(2, 10));
+                            }
+                        } />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.downstack_speed_up)
+                        on:change=move |ev| settings.update(|s| s.downstack_speed_up = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.downstack_speed_up")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>{move || tr(locale.get(), "settings.garbage_pattern")}</span>
+                    <select
+                        class="select select-sm"
+                        on:change=move |ev| {
+                            if let Some(&p) = GarbagePattern::ALL.get(event_target_value(&ev).parse::<usize>().unwrap_or(0)) {
+                                settings.update(|s| s.garbage.pattern = p);
+                            }
+                        }>
+                        {GarbagePattern::ALL.iter().enumerate().map(|(i, &p)| {
+                            let key = match p {
+                                GarbagePattern::Clean => "settings.garbage_clean",
+                                GarbagePattern::Cheese => "settings.garbage_cheese",
+                            };
+                            view! {
+                                <option value=i.to_string() selected=move || settings.with(|s| s.garbage.pattern == p)>
+                                    {move || tr(locale.get(), key)}
+                                </option>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <span>
+                        {move || tr_args(locale.get(), "settings.garbage_holes", &[
+                            ("holes", settings.with(|s| s.garbage.holes.to_string())),
+                        ])}
+                    </span>
+                    <input
+                        type="range"
+                        class="range range-sm"
+                        min="1"
+                        max=MAX_HOLES.to_string()
+                        step="1"
+                        prop:value=move || settings.with(|s| s.garbage.holes.to_string())
+                        on:change=move |ev| {
+                            if let Ok(v) = event_target_value(&ev).parse::<u32>() {
+                                settings.update(|s| s.garbage.holes = v.clamp(1, MAX_HOLES));
+                            }
+                        } />
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.race_personal_best)
+                        on:change=move |ev| settings.update(|s| s.race_personal_best = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.race_pb")}</span>
+                </label>
+                <label class="flex flex-row gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        class="checkbox checkbox-sm"
+                        prop:checked=move || settings.with(|s| s.auto_resume_on_focus)
+                        on:change=move |ev| settings.update(|s| s.auto_resume_on_focus = event_target_checked(&ev)) />
+                    <span>{move || tr(locale.get(), "settings.auto_resume")}</span>
+                </label>
+                <SyncPanel />
+                <TelemetryPanel />
+                <div class="flex flex-row gap-2 items-center">
+                    <button
+                        class="btn btn-sm"
+                        on:click=move |_| files::download("tetris-data.json", "application/json", &archive::export(js_sys::Date::now()))>
+                        {move || tr(locale.get(), "settings.export")}
+                    </button>
+                    <label class="btn btn-sm">
+                        {move || tr(locale.get(), "settings.import")}
+                        <input
+                            type="file"
+                            accept="application/json,.json"
+                            class="hidden"
+                            on:change=move |ev| files::read_as_text(&ev, move |raw| {
+                                match archive::import(&raw, js_sys::Date::now()) {
+                                    // Reload so every signal reads the imported data.
+                                    Ok(_) => {
+                                        let _ = window().location().reload();
+                                    }
+                                    Err(err) => {
+                                        let args = [("error", err.to_string())];
+                                        toaster.push(tr_args(locale.get_untracked(), "settings.import_failed", &args));
+                                    }
+                                }
+                            }) />
+                    </label>
+                </div>
+                <div class="modal-action">
+                    <button class="btn" on:click=move |_| on_close()>{move || tr(locale.get(), "button.close")}</button>
+                </div>
+            </div>
+            <div class="modal-backdrop" on:click=move |_| on_close_backdrop()></div>
+        </div>
+    }
+}
@@ -0,0 +1,117 @@
+//! Block skins and board styling. A skin either paints cells with flat colors
+//! or cuts them out of a sprite atlas: a single row of square tiles in
+//! `ATLAS_ORDER`. Empty cells always use the flat background so the grid
+//! stays readable.
+
+use serde::{Deserialize, Serialize};
+use tetris_core::GARBAGE_KIND;
+
+/// Tile order in an atlas: the seven pieces, then ghost and garbage.
+pub const ATLAS_ORDER: [&str; 9] = ["I", "T", "O", "J", "L", "S", "Z", "G", GARBAGE_KIND];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Skin {
+    /// Flat colors.
+    #[default]
+    Classic,
+    /// Beveled sprites bundled as `skins/bevel.svg`.
+    Bevel,
+    /// An atlas the player loaded; see `CustomAtlas`.
+    Custom,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 3] = [Skin::Classic, Skin::Bevel, Skin::Custom];
+
+    /// Locale key of the option label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            Skin::Classic => "settings.skin.classic",
+            Skin::Bevel => "settings.skin.bevel",
+            Skin::Custom => "settings.skin.custom",
+        }
+    }
+
+    /// Atlas URL, or `None` for flat colors (including `Custom` before an
+    /// image was loaded).
+    pub fn atlas<'a>(&self, custom: Option<&'a str>) -> Option<&'a str> {
+        match self {
+            Skin::Classic => None,
+            Skin::Bevel => Some("skins/bevel.svg"),
+            Skin::Custom => custom,
+        }
+    }
+}
+
+/// How strongly the lines between cells are drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridLines {
+    None,
+    Faint,
+    #[default]
+    Full,
+}
+
+impl GridLines {
+    pub const ALL: [GridLines; 3] = [GridLines::None, GridLines::Faint, GridLines::Full];
+
+    /// Locale key of the option label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            GridLines::None => "settings.grid.none",
+            GridLines::Faint => "settings.grid.faint",
+            GridLines::Full => "settings.grid.full",
+        }
+    }
+
+    /// Class for the playfield; cell borders are styled in `tailwind.css`.
+    pub fn class(&self) -> &'static str {
+        match self {
+            GridLines::None => "grid-lines-none",
+            GridLines::Faint => "grid-lines-faint",
+            GridLines::Full => "grid-lines-full",
+        }
+    }
+}
+
+/// The player's atlas as a data URL, persisted under
+/// `storage::CUSTOM_SKIN_KEY` and shared through context.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomAtlas(pub Option<String>);
+
+pub fn flat_color(kind: &str) -> &'static str {
+    match kind {
+        "I" => "blue", "T" => "purple", "O" => "yellow", "J" => "green",
+        "L" => "orange", "S" => "red", "Z" => "cyan",
+        "B" => "rgb(119, 119, 119)", "G" => "rgba(121, 119, 119, 0.76)",
+        GARBAGE_KIND => "rgb(64, 64, 64)",
+        _ => unreachable!(),
+    }
+}
+
+/// `background-size` and `background-position` that show `kind`'s tile of an
+/// atlas, or `None` for kinds without a tile.
+pub fn sprite_offset(kind: &str) -> Option<(String, String)> {
+    let index = ATLAS_ORDER.iter().position(|&k| k == kind)?;
+    let last = ATLAS_ORDER.len() - 1;
+    Some((
+        format!("{}% 100%", ATLAS_ORDER.len() * 100),
+        format!("{}% 0%", index * 100 / last),
+    ))
+}
+
+/// Empty cells over a custom background, so it shows through the grid.
+const SEE_THROUGH_EMPTY: &str = "rgba(119, 119, 119, 0.35)";
+
+/// Inline style for a cell of `kind` under `atlas`. With `see_through`,
+/// empty cells are translucent.
+pub fn cell_style(kind: &str, atlas: Option<&str>, see_through: bool) -> String {
+    match (atlas, sprite_offset(kind)) {
+        _ if see_through && kind == "B" => format!("background-color: {};", SEE_THROUGH_EMPTY),
+        (Some(url), Some((size, position))) => format!(
+            "background-image: url(\"{}\"); background-size: {}; background-position: {};",
+            url, size, position
+        ),
+        _ => format!("background-color: {};", flat_color(kind)),
+    }
+}
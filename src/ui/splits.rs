@@ -0,0 +1,53 @@
+//! The Sprint split table shown in the sidebar during a run.
+
+use leptos::prelude::*;
+use tetris_core::sprint::{self, SplitTimer};
+
+use super::i18n::{format_time, tr, use_locale, Locale};
+use super::storage::SavedSprintBest;
+
+/// `ms` as a signed time difference, e.g. `-00:01.20` when ahead.
+pub fn format_delta(locale: Locale, ms: f64) -> String {
+    let sign = if ms < 0.0 { '-' } else { '+' };
+    format!("{}{}", sign, format_time(locale, ms.abs()))
+}
+
+/// Each split of `timer`: its time once reached, and how that compares to
+/// the personal best, green when ahead.
+#[component]
+pub fn SplitTable(timer: RwSignal<Option<SplitTimer>>, best: RwSignal<SavedSprintBest>) -> impl IntoView {
+    let locale = use_locale();
+    move || {
+        let timer = timer.get()?;
+        let l = locale.get();
+        let best = best.get().splits;
+        let rows = timer.targets().iter().enumerate().map(|(i, &lines)| {
+            let reached = timer.reached().get(i).copied();
+            let delta = reached.and_then(|split| sprint::delta(split, &best));
+            view! {
+                <tr>
+                    <td>{lines}</td>
+                    <td class="font-mono">{reached.map(|(_, ms)| format_time(l, ms))}</td>
+                    <td
+                        class="font-mono"
+                        class:text-success=delta.is_some_and(|d| d < 0.0)
+                        class:text-error=delta.is_some_and(|d| d > 0.0)>
+                        {delta.map(|d| format_delta(l, d))}
+                    </td>
+                </tr>
+            }
+        }).collect::<Vec<_>>();
+        Some(view! {
+            <table class="table table-xs w-full">
+                <thead>
+                    <tr>
+                        <th>{tr(l, "sprint.lines")}</th>
+                        <th>{tr(l, "sprint.time")}</th>
+                        <th>{tr(l, "sprint.delta")}</th>
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        })
+    }
+}
@@ -0,0 +1,416 @@
+//! JSON-in-localStorage helpers for data that should outlive the page.
+//!
+//! Everything except the profile list is stored per profile; see
+//! `profile_key`. Each write is timestamped under `MODIFIED_KEY` so `sync`
+//! can merge with other devices.
+//!
+//! Values are stored as `{"schema_version": n, "data": …}`. When a stored
+//! type changes shape, bump its version by appending a `Migration` to its
+//! `Persist::MIGRATIONS`; older saves are upgraded step by step on load.
+//! Bare JSON from before versioning counts as version 0.
+
+use std::collections::BTreeMap;
+
+use leptos::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tetris_core::ghost::{Sample, Trace};
+use tetris_core::session::Snapshot;
+use tetris_core::sprint::Split;
+use web_sys::Storage;
+
+use super::background::CustomBackground;
+use super::settings::Settings;
+use super::skin::CustomAtlas;
+use super::sync::SyncTarget;
+use super::telemetry::{GameReport, TelemetryMode};
+
+/// Prefix of every key the app writes.
+pub const KEY_PREFIX: &str = "tetris_html.";
+/// When each key last changed, in milliseconds since the epoch.
+pub const MODIFIED_KEY: &str = "tetris_html.modified";
+/// Where this device syncs to; device-specific, so never synced itself.
+pub const SYNC_KEY: &str = "tetris_html.sync";
+/// Telemetry choice, queued reports and local log; see `telemetry`. Kept
+/// on this device like `SYNC_KEY`.
+pub const TELEMETRY_KEY: &str = "tetris_html.telemetry";
+pub const TELEMETRY_QUEUE_KEY: &str = "tetris_html.telemetry_queue";
+pub const TELEMETRY_LOG_KEY: &str = "tetris_html.telemetry_log";
+
+/// Keys that never leave this device.
+const DEVICE_KEYS: [&str; 5] = [MODIFIED_KEY, SYNC_KEY, TELEMETRY_KEY, TELEMETRY_QUEUE_KEY, TELEMETRY_LOG_KEY];
+
+pub const SETTINGS_KEY: &str = "tetris_html.settings";
+pub const OBJECTIVES_KEY: &str = "tetris_html.objectives";
+pub const ACHIEVEMENTS_KEY: &str = "tetris_html.achievements";
+pub const CUSTOM_SKIN_KEY: &str = "tetris_html.custom_skin";
+pub const BACKGROUND_KEY: &str = "tetris_html.background";
+pub const STATS_KEY: &str = "tetris_html.stats";
+pub const SPRINT_BEST_KEY: &str = "tetris_html.sprint_best";
+pub const GHOSTS_KEY: &str = "tetris_html.ghosts";
+pub const DRILLS_KEY: &str = "tetris_html.drills";
+pub const REPLAYS_KEY: &str = "tetris_html.replays";
+pub const SESSION_KEY: &str = "tetris_html.session";
+pub const PROFILES_KEY: &str = "tetris_html.profiles";
+
+/// Upgrades stored JSON from one schema version to the next.
+pub type Migration = fn(Value) -> Value;
+
+/// A type kept in storage.
+pub trait Persist: Serialize + DeserializeOwned {
+    /// `MIGRATIONS[i]` turns version `i` data into version `i + 1`, so the
+    /// current version is `MIGRATIONS.len()`. Only ever append.
+    const MIGRATIONS: &'static [Migration] = &[];
+
+    fn schema_version() -> u32 {
+        Self::MIGRATIONS.len() as u32
+    }
+}
+
+impl Persist for Settings {}
+impl Persist for Vec<String> {}
+impl Persist for SavedAchievements {}
+impl Persist for CustomAtlas {}
+impl Persist for CustomBackground {}
+impl Persist for SavedStats {}
+impl Persist for SavedSprintBest {}
+impl Persist for SavedGhosts {}
+impl Persist for SavedDrills {}
+impl Persist for SavedReplays {}
+impl Persist for SavedSession {}
+impl Persist for SavedProfiles {}
+impl Persist for SyncTarget {}
+impl Persist for TelemetryMode {}
+impl Persist for Vec<GameReport> {}
+
+/// Runs the migrations from `version` on. `None` for data newer than
+/// `migrations` know about.
+pub fn migrate(mut data: Value, version: u32, migrations: &[Migration]) -> Option<Value> {
+    for step in migrations.get(version as usize..)? {
+        data = step(data);
+    }
+    Some(data)
+}
+
+/// Splits stored JSON into its schema version and data; `None` as the
+/// version for bare JSON from before versioning.
+fn unwrap_versioned(raw: &str) -> Option<(Option<u32>, Value)> {
+    let value: Value = serde_json::from_str(raw).ok()?;
+    match &value {
+        Value::Object(map) if map.len() == 2 && map.contains_key("data") => {
+            let version = map.get("schema_version")?.as_u64()? as u32;
+            Some((Some(version), map["data"].clone()))
+        }
+        _ => Some((None, value)),
+    }
+}
+
+/// Parses stored JSON into the current `T`, migrating if needed. Also says
+/// whether it was stored in an older layout.
+pub fn decode<T: Persist>(raw: &str) -> Option<(T, bool)> {
+    let (version, data) = unwrap_versioned(raw)?;
+    let data = migrate(data, version.unwrap_or(0), T::MIGRATIONS)?;
+    let outdated = version.is_none_or(|v| v < T::schema_version());
+    Some((serde_json::from_value(data).ok()?, outdated))
+}
+
+pub fn encode<T: Persist>(value: &T) -> Option<String> {
+    serde_json::to_string(&json!({ "schema_version": T::schema_version(), "data": value })).ok()
+}
+
+/// Keys that hold per-profile data, removed along with a profile.
+pub const PROFILE_KEYS: [&str; 11] = [
+    SETTINGS_KEY,
+    OBJECTIVES_KEY,
+    ACHIEVEMENTS_KEY,
+    CUSTOM_SKIN_KEY,
+    BACKGROUND_KEY,
+    STATS_KEY,
+    SPRINT_BEST_KEY,
+    GHOSTS_KEY,
+    DRILLS_KEY,
+    REPLAYS_KEY,
+    SESSION_KEY,
+];
+
+/// Storage key of `base` for profile `id`. Profile 0 uses the bare keys from
+/// before profiles existed, so earlier progress becomes the first profile's.
+pub fn profile_key(base: &str, id: u32) -> String {
+    if id == 0 { base.to_string() } else { format!("{}.{}", base, id) }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Every profile, plus which one was played last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedProfiles {
+    pub profiles: Vec<Profile>,
+    pub last_used: Option<u32>,
+}
+
+impl Default for SavedProfiles {
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile { id: 0, name: "Player 1".to_string() }],
+            last_used: None,
+        }
+    }
+}
+
+impl SavedProfiles {
+    /// Adds a profile named `name` and returns its id.
+    pub fn add(&mut self, name: &str) -> u32 {
+        let id = self.profiles.iter().map(|p| p.id + 1).max().unwrap_or(0);
+        self.profiles.push(Profile { id, name: name.trim().to_string() });
+        id
+    }
+
+    /// Forgets profile `id` and deletes its data. The last profile stays.
+    pub fn remove(&mut self, id: u32) {
+        if self.profiles.len() <= 1 {
+            return;
+        }
+        self.profiles.retain(|p| p.id != id);
+        if self.last_used == Some(id) {
+            self.last_used = None;
+        }
+        for key in PROFILE_KEYS {
+            remove(&profile_key(key, id));
+        }
+    }
+
+    /// The profile to start with without asking: the only one there is.
+    pub fn only(&self) -> Option<u32> {
+        match self.profiles.as_slice() {
+            [only] => Some(only.id),
+            _ => None,
+        }
+    }
+}
+
+/// Per-profile totals across games.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedStats {
+    pub games_played: u32,
+    pub best_score: i32,
+    /// Most garbage rows cleared in one Downstack run.
+    pub best_downstack: u32,
+}
+
+/// Splits of the fastest finished Sprint; see `tetris_core::sprint`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSprintBest {
+    pub splits: Vec<Split>,
+}
+
+/// The best run of each mode, by mode id, as `tetris_core::ghost` samples.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedGhosts {
+    pub traces: BTreeMap<String, Vec<Sample>>,
+}
+
+impl SavedGhosts {
+    pub fn trace(&self, mode: &str) -> Option<Trace> {
+        self.traces.get(mode).map(|samples| Trace::from_samples(samples.clone()))
+    }
+}
+
+/// Results per drill template id: fastest completion and how many times
+/// it was completed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedDrills {
+    pub best_ms: BTreeMap<String, f64>,
+    pub completed: BTreeMap<String, u32>,
+}
+
+impl SavedDrills {
+    /// Records a completion of `id` in `ms`; true if it is the fastest yet.
+    pub fn record(&mut self, id: &str, ms: f64) -> bool {
+        *self.completed.entry(id.to_string()).or_default() += 1;
+        let best = self.best_ms.entry(id.to_string()).or_insert(f64::INFINITY);
+        let faster = ms < *best;
+        *best = best.min(ms);
+        faster
+    }
+}
+
+/// Replays kept in the library; older untagged ones make way for new ones
+/// beyond this.
+pub const MAX_REPLAYS: usize = 30;
+
+/// The game in progress, autosaved every few seconds so it can be picked
+/// back up after the page or app was killed. Empty between games.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSession {
+    /// `GameMode` id.
+    pub mode: String,
+    /// When it was saved, in milliseconds since the epoch.
+    pub saved_ms: f64,
+    pub snapshot: Option<Snapshot>,
+    /// The `tetris_core::replay` text so far, and the replay time the
+    /// snapshot was taken at.
+    pub replay: String,
+    pub replay_ms: f64,
+}
+
+/// A finished game in the replay library: what the list shows about it and
+/// its `tetris_core::replay` text.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedReplay {
+    pub id: u32,
+    /// `GameMode` id.
+    pub mode: String,
+    pub score: i32,
+    pub lines: u32,
+    /// When the game ended, in milliseconds since the epoch.
+    pub date_ms: f64,
+    pub duration_ms: f64,
+    pub tags: Vec<String>,
+    pub replay: String,
+}
+
+/// The replay library, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedReplays {
+    pub replays: Vec<SavedReplay>,
+}
+
+impl SavedReplays {
+    /// Adds `replay` under a fresh id, which it returns. Past `MAX_REPLAYS`
+    /// the oldest untagged replay goes, or the oldest one if all are tagged.
+    pub fn add(&mut self, mut replay: SavedReplay) -> u32 {
+        replay.id = self.replays.iter().map(|r| r.id + 1).max().unwrap_or(0);
+        let id = replay.id;
+        self.replays.push(replay);
+        while self.replays.len() > MAX_REPLAYS {
+            let oldest = self.replays.iter().position(|r| r.tags.is_empty()).unwrap_or(0);
+            self.replays.remove(oldest);
+        }
+        id
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.replays.retain(|r| r.id != id);
+    }
+
+    /// Tags replay `id` with `tag`, trimmed; blank and repeated tags are
+    /// ignored.
+    pub fn add_tag(&mut self, id: u32, tag: &str) {
+        let tag = tag.trim();
+        if let Some(replay) = self.replays.iter_mut().find(|r| r.id == id) {
+            if !tag.is_empty() && !replay.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                replay.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    pub fn remove_tag(&mut self, id: u32, tag: &str) {
+        if let Some(replay) = self.replays.iter_mut().find(|r| r.id == id) {
+            replay.tags.retain(|t| t != tag);
+        }
+    }
+}
+
+/// Achievement progress as stored; `tetris_core::achievements` rebuilds its
+/// tracker from this.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedAchievements {
+    pub unlocked: Vec<String>,
+    pub total_lines: u32,
+}
+
+fn local_storage() -> Option<Storage> {
+    window().local_storage().ok()?
+}
+
+/// Whether `key` travels with `sync`.
+pub fn is_synced(key: &str) -> bool {
+    key.starts_with(KEY_PREFIX) && !DEVICE_KEYS.contains(&key)
+}
+
+pub fn read_raw(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+/// Sets (`Some`) or removes (`None`) the stored JSON of `key` and records
+/// `modified_at` for it. Writes that change nothing keep the old timestamp.
+pub fn write_raw(key: &str, raw: Option<&str>, modified_at: f64) {
+    let Some(storage) = local_storage() else { return };
+    if storage.get_item(key).ok().flatten().as_deref() == raw {
+        return;
+    }
+    let _ = match raw {
+        Some(raw) => storage.set_item(key, raw),
+        None => storage.remove_item(key),
+    };
+    if is_synced(key) {
+        let mut times = modified_times();
+        times.insert(key.to_string(), modified_at);
+        if let Ok(raw) = serde_json::to_string(&times) {
+            let _ = storage.set_item(MODIFIED_KEY, &raw);
+        }
+    }
+}
+
+/// Last-change times of synced keys, including removed ones.
+pub fn modified_times() -> BTreeMap<String, f64> {
+    read_raw(MODIFIED_KEY)
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Every synced key currently stored.
+pub fn synced_keys() -> Vec<String> {
+    let Some(storage) = local_storage() else { return Vec::new() };
+    (0..storage.length().unwrap_or(0))
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter(|k| is_synced(k))
+        .collect()
+}
+
+pub fn load<T: Persist>(key: &str) -> Option<T> {
+    decode(&read_raw(key)?).map(|(value, _)| value)
+}
+
+pub fn save<T: Persist>(key: &str, value: &T) {
+    if let Some(raw) = encode(value) {
+        write_raw(key, Some(&raw), js_sys::Date::now());
+    }
+}
+
+pub fn remove(key: &str) {
+    write_raw(key, None, js_sys::Date::now());
+}
+
+/// Signal initialised from `key` that writes itself back whenever it changes.
+pub fn persisted<T>(key: impl Into<String>, default: impl FnOnce() -> T) -> RwSignal<T>
+where
+    T: Persist + Send + Sync + 'static,
+{
+    let key = key.into();
+    let loaded = read_raw(&key).and_then(|raw| decode::<T>(&raw));
+    if let Some((value, true)) = &loaded {
+        // Rewrite upgraded data in the new layout without making it look
+        // newer than other devices' copies.
+        let modified_at = modified_times().get(&key).copied().unwrap_or(0.0);
+        if let Some(raw) = encode(value) {
+            write_raw(&key, Some(&raw), modified_at);
+        }
+    }
+    let signal = RwSignal::new(loaded.map(|(value, _)| value).unwrap_or_else(default));
+    Effect::new(move || signal.with(|value| save(&key, value)));
+    signal
+}
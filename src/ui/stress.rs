@@ -0,0 +1,81 @@
+//! Hidden renderer stress test, opened with `#stress` after the page URL:
+//! `BOARDS` games played at high speed by `tetris_core::bot`, each redrawn in
+//! full on every update, with the update rate the page keeps up.
+
+use leptos::prelude::*;
+use leptos_use::use_interval_fn;
+use tetris_core::bot::Bot;
+use tetris_core::Tetris;
+
+use super::i18n::{tr, tr_args, use_locale};
+use super::settings::Settings;
+use super::skin;
+
+pub const BOARDS: usize = 16;
+/// Milliseconds between updates, and updates per gravity tick.
+const STEP_MS: u64 = 16;
+const STEPS_PER_TICK: u32 = 4;
+
+/// Whether the page was opened as the stress test.
+pub fn is_requested() -> bool {
+    window().location().hash().is_ok_and(|hash| hash == "#stress")
+}
+
+#[component]
+pub fn StressDemo() -> impl IntoView {
+    // Outside any profile: default settings, for the locale and skin.
+    provide_context(RwSignal::new(Settings::default()));
+    let locale = use_locale();
+    let games = StoredValue::new_local((0..BOARDS).map(|_| (Tetris::new(10, 25), Bot::default())).collect::<Vec<_>>());
+    let frames: Vec<RwSignal<Vec<Vec<&'static str>>>> = (0..BOARDS).map(|_| RwSignal::new(Vec::new())).collect();
+    let (rate, set_rate) = signal(0u32);
+    let counter = StoredValue::new((0u32, 0u32, tetris_core::clock::now_ms()));
+    let boards = frames.clone();
+    use_interval_fn(
+        move || {
+            let (steps, updates, since) = counter.get_value();
+            games.update_value(|games| {
+                for ((tetris, bot), frame) in games.iter_mut().zip(&boards) {
+                    if tetris.lost {
+                        tetris.reset();
+                    }
+                    bot.act(tetris);
+                    if steps % STEPS_PER_TICK == 0 {
+                        tetris.tick();
+                    }
+                    tetris.drain_events();
+                    frame.set(tetris.render_view());
+                }
+            });
+            let (updates, now) = (updates + BOARDS as u32, tetris_core::clock::now_ms());
+            if now - since >= 1000.0 {
+                set_rate.set((updates as f64 * 1000.0 / (now - since)) as u32);
+                counter.set_value((steps + 1, 0, now));
+            } else {
+                counter.set_value((steps + 1, updates, since));
+            }
+        },
+        STEP_MS,
+    );
+    view! {
+        <div class="flex flex-col gap-2 p-4">
+            <h1 class="text-lg font-bold">{move || tr(locale.get(), "stress.title")}</h1>
+            <span class="font-mono">
+                {move || tr_args(locale.get(), "stress.rate", &[("count", rate.get().to_string())])}
+            </span>
+            <div class="grid grid-cols-8 gap-2">
+                {frames.into_iter().map(|frame| view! {
+                    <div class="flex flex-col w-20">
+                        {move || frame.get().into_iter().map(|row| view! {
+                            <div class="flex flex-row">
+                                {row.into_iter().map(|c| view! {
+                                    <div class="cell aspect-square w-[10%]" style=skin::cell_style(c, None, false)></div>
+                                }).collect::<Vec<_>>()}
+                            </div>
+                        }).collect::<Vec<_>>()}
+                    </div>
+                }).collect::<Vec<_>>()}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,348 @@
+//! Syncing saved data (profiles and everything stored per profile) with
+//! another device. Each side is a `StorageBackend` holding the same records:
+//! stored JSON per key plus when it last changed. `sync` merges them, the
+//! newer change of each key winning, and writes the result back to both.
+
+use std::{collections::BTreeMap, fmt};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, Response};
+
+use super::i18n::{tr, tr_args, use_locale};
+use super::storage;
+
+/// One key's stored JSON, or `None` once deleted, and when that happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub value: Option<String>,
+    pub modified_at: f64,
+}
+
+pub type Records = BTreeMap<String, Record>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncError {
+    /// The backend cannot be reached from this build, e.g. Tauri on the web.
+    Unavailable(String),
+    /// The remote answered with this HTTP status.
+    Http(u16),
+    /// The remote data is not a records document.
+    Format(String),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Unavailable(why) => write!(f, "storage unavailable: {}", why),
+            SyncError::Http(status) => write!(f, "server answered {}", status),
+            SyncError::Format(why) => write!(f, "unreadable sync data: {}", why),
+        }
+    }
+}
+
+fn js_error(err: JsValue) -> SyncError {
+    SyncError::Unavailable(err.as_string().unwrap_or_else(|| format!("{:?}", err)))
+}
+
+fn parse_records(raw: &str) -> Result<Records, SyncError> {
+    serde_json::from_str(raw).map_err(|e| SyncError::Format(e.to_string()))
+}
+
+fn records_json(records: &Records) -> String {
+    serde_json::to_string(records).unwrap_or_default()
+}
+
+/// A place records can be read from and written to as a whole.
+#[allow(async_fn_in_trait)]
+pub trait StorageBackend {
+    async fn load(&self) -> Result<Records, SyncError>;
+    async fn store(&self, records: &Records) -> Result<(), SyncError>;
+}
+
+/// Every synced key in localStorage, deletions included.
+pub fn local_records() -> Records {
+    let times = storage::modified_times();
+    let mut records: Records = times
+        .iter()
+        .map(|(key, &at)| (key.clone(), Record { value: storage::read_raw(key), modified_at: at }))
+        .collect();
+    // Keys written before timestamps existed lose to any remote change.
+    for key in storage::synced_keys() {
+        records
+            .entry(key.clone())
+            .or_insert_with(|| Record { value: storage::read_raw(&key), modified_at: 0.0 });
+    }
+    records
+}
+
+/// Writes `records` to localStorage, keeping their timestamps.
+pub fn store_local(records: &Records) {
+    for (key, record) in records.iter().filter(|(key, _)| storage::is_synced(key)) {
+        storage::write_raw(key, record.value.as_deref(), record.modified_at);
+    }
+}
+
+/// This browser's (or webview's) localStorage.
+pub struct LocalStorageBackend;
+
+impl StorageBackend for LocalStorageBackend {
+    async fn load(&self) -> Result<Records, SyncError> {
+        Ok(local_records())
+    }
+
+    async fn store(&self, records: &Records) -> Result<(), SyncError> {
+        store_local(records);
+        Ok(())
+    }
+}
+
+/// A JSON document at `url`, read with GET and replaced with PUT; a plain
+/// HTTP endpoint or a file on a WebDAV share. A missing document (404) is
+/// treated as empty.
+pub struct HttpBackend {
+    pub url: String,
+    /// Sent as the `Authorization` header, e.g. `Basic …` for WebDAV.
+    pub authorization: Option<String>,
+}
+
+impl HttpBackend {
+    async fn request(&self, method: &str, body: Option<String>) -> Result<Response, SyncError> {
+        let init = RequestInit::new();
+        init.set_method(method);
+        let headers = Headers::new().map_err(js_error)?;
+        headers.set("Content-Type", "application/json").map_err(js_error)?;
+        if let Some(auth) = &self.authorization {
+            headers.set("Authorization", auth).map_err(js_error)?;
+        }
+        init.set_headers(&headers);
+        if let Some(body) = body {
+            init.set_body(&JsValue::from_str(&body));
+        }
+        let response = JsFuture::from(window().fetch_with_str_and_init(&self.url, &init))
+            .await
+            .map_err(js_error)?;
+        Ok(response.unchecked_into())
+    }
+}
+
+impl StorageBackend for HttpBackend {
+    async fn load(&self) -> Result<Records, SyncError> {
+        let response = self.request("GET", None).await?;
+        match response.status() {
+            404 => return Ok(Records::new()),
+            status if !response.ok() => return Err(SyncError::Http(status)),
+            _ => {}
+        }
+        let text = JsFuture::from(response.text().map_err(js_error)?).await.map_err(js_error)?;
+        parse_records(&text.as_string().unwrap_or_default())
+    }
+
+    async fn store(&self, records: &Records) -> Result<(), SyncError> {
+        let response = self.request("PUT", Some(records_json(records))).await?;
+        if response.ok() { Ok(()) } else { Err(SyncError::Http(response.status())) }
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// A file in the desktop app's data folder, at `path` relative to it. Only
+/// available in the Tauri build.
+pub struct TauriFileBackend {
+    pub path: String,
+}
+
+impl TauriFileBackend {
+    async fn call(&self, cmd: &str, args: serde_json::Value) -> Result<JsValue, SyncError> {
+        if !js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("__TAURI__")).unwrap_or(false) {
+            return Err(SyncError::Unavailable("not running in the desktop app".to_string()));
+        }
+        let args = js_sys::JSON::parse(&args.to_string()).map_err(js_error)?;
+        invoke(cmd, args).await.map_err(js_error)
+    }
+}
+
+impl StorageBackend for TauriFileBackend {
+    async fn load(&self) -> Result<Records, SyncError> {
+        let raw = self.call("read_sync_file", serde_json::json!({ "path": self.path })).await?;
+        match raw.as_string() {
+            Some(raw) => parse_records(&raw),
+            None => Ok(Records::new()),
+        }
+    }
+
+    async fn store(&self, records: &Records) -> Result<(), SyncError> {
+        let args = serde_json::json!({ "path": self.path, "contents": records_json(records) });
+        self.call("write_sync_file", args).await.map(|_| ())
+    }
+}
+
+/// Combines two record sets key by key, keeping the more recent change;
+/// ties go to `ours`.
+pub fn merge(ours: &Records, theirs: &Records) -> Records {
+    let mut merged = ours.clone();
+    for (key, record) in theirs {
+        match merged.get(key) {
+            Some(mine) if mine.modified_at >= record.modified_at => {}
+            _ => {
+                merged.insert(key.clone(), record.clone());
+            }
+        }
+    }
+    merged
+}
+
+/// How many keys a sync changed on each side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub pulled: usize,
+    pub pushed: usize,
+}
+
+/// Keys whose value differs; timestamps alone don't count, so unchanged
+/// data never triggers a reload.
+fn changed(before: &Records, after: &Records) -> usize {
+    after
+        .iter()
+        .filter(|(k, r)| before.get(*k).map_or(r.value.is_some(), |b| b.value != r.value))
+        .count()
+}
+
+/// Merges `local` and `remote` and writes the result to whichever side it
+/// changes.
+pub async fn sync(local: &impl StorageBackend, remote: &impl StorageBackend) -> Result<SyncReport, SyncError> {
+    let ours = local.load().await?;
+    let theirs = remote.load().await?;
+    let merged = merge(&ours, &theirs);
+    let report = SyncReport { pulled: changed(&ours, &merged), pushed: changed(&theirs, &merged) };
+    if report.pushed > 0 {
+        remote.store(&merged).await?;
+    }
+    if report.pulled > 0 {
+        local.store(&merged).await?;
+    }
+    Ok(report)
+}
+
+/// Where this device syncs to, stored under `storage::SYNC_KEY`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SyncTarget {
+    #[default]
+    Off,
+    Http { url: String, authorization: Option<String> },
+    TauriFile { path: String },
+}
+
+impl SyncTarget {
+    /// Syncs this device's localStorage with the target.
+    pub async fn sync(&self) -> Result<SyncReport, SyncError> {
+        match self {
+            SyncTarget::Off => Ok(SyncReport::default()),
+            SyncTarget::Http { url, authorization } => {
+                let remote = HttpBackend { url: url.clone(), authorization: authorization.clone() };
+                sync(&LocalStorageBackend, &remote).await
+            }
+            SyncTarget::TauriFile { path } => sync(&LocalStorageBackend, &TauriFileBackend { path: path.clone() }).await,
+        }
+    }
+}
+
+/// Runs `target.sync()` in the background and reloads the page when it
+/// brought in changes, so every signal picks them up.
+pub fn sync_in_background(target: SyncTarget, on_done: impl FnOnce(Result<SyncReport, SyncError>) + 'static) {
+    leptos::task::spawn_local(async move {
+        let result = target.sync().await;
+        if matches!(result, Ok(SyncReport { pulled, .. }) if pulled > 0) {
+            let _ = window().location().reload();
+        }
+        on_done(result);
+    });
+}
+
+/// Settings section choosing where to sync, with a button to sync now.
+#[component]
+pub fn SyncPanel() -> impl IntoView {
+    let locale = use_locale();
+    let target = storage::persisted(storage::SYNC_KEY, SyncTarget::default);
+    let (status, set_status) = signal(None::<String>);
+    let kind = move || match target.get() {
+        SyncTarget::Off => "off",
+        SyncTarget::Http { .. } => "http",
+        SyncTarget::TauriFile { .. } => "file",
+    };
+    let text = move |field: &'static str| {
+        target.with(|t| match (t, field) {
+            (SyncTarget::Http { url, .. }, "url") => url.clone(),
+            (SyncTarget::Http { authorization, .. }, "auth") => authorization.clone().unwrap_or_default(),
+            (SyncTarget::TauriFile { path }, "path") => path.clone(),
+            _ => String::new(),
+        })
+    };
+    let set_text = move |field: &'static str, value: String| {
+        target.update(|t| match (t, field) {
+            (SyncTarget::Http { url, .. }, "url") => *url = value,
+            (SyncTarget::Http { authorization, .. }, "auth") => {
+                *authorization = Some(value).filter(|v| !v.is_empty())
+            }
+            (SyncTarget::TauriFile { path }, "path") => *path = value,
+            _ => {}
+        })
+    };
+    let sync_now = move || {
+        set_status.set(Some(tr(locale.get_untracked(), "sync.running")));
+        sync_in_background(target.get_untracked(), move |result| {
+            let l = locale.get_untracked();
+            set_status.set(Some(match result {
+                Ok(report) => tr_args(l, "sync.done", &[("pushed", report.pushed.to_string())]),
+                Err(err) => tr_args(l, "sync.failed", &[("error", err.to_string())]),
+            }));
+        });
+    };
+    view! {
+        <div class="flex flex-col gap-2">
+            <label class="flex flex-row gap-2 items-center">
+                <span>{move || tr(locale.get(), "sync.title")}</span>
+                <select
+                    class="select select-sm"
+                    on:change=move |ev| target.set(match event_target_value(&ev).as_str() {
+                        "http" => SyncTarget::Http { url: String::new(), authorization: None },
+                        "file" => SyncTarget::TauriFile { path: String::new() },
+                        _ => SyncTarget::Off,
+                    })>
+                    {[("off", "sync.off"), ("http", "sync.http"), ("file", "sync.file")].into_iter().map(|(value, key)| view! {
+                        <option value=value selected=move || kind() == value>{move || tr(locale.get(), key)}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+            {move || {
+                let l = locale.get();
+                let fields: &[(&'static str, &'static str)] = match kind() {
+                    "http" => &[("url", "sync.url"), ("auth", "sync.authorization")],
+                    "file" => &[("path", "sync.path")],
+                    _ => &[],
+                };
+                fields.iter().map(|&(field, key)| view! {
+                    <input
+                        type="text"
+                        class="input input-sm"
+                        dir="ltr"
+                        placeholder=tr(l, key)
+                        prop:value=move || text(field)
+                        on:change=move |ev| set_text(field, event_target_value(&ev)) />
+                }).collect::<Vec<_>>()
+            }}
+            <div class="flex flex-row gap-2 items-center">
+                <button class="btn btn-sm" disabled=move || kind() == "off" on:click=move |_| sync_now()>
+                    {move || tr(locale.get(), "sync.now")}
+                </button>
+                <span class="text-sm opacity-80">{status}</span>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,216 @@
+//! Opt-in gameplay telemetry, off until the player picks a mode in settings.
+//! Each finished game becomes an anonymous `GameReport` (mode, duration,
+//! score and how lines were cleared; nothing about the player or device).
+//! `LocalOnly` appends reports to a JSON-lines log that can be downloaded;
+//! `Remote` queues them and posts them in batches of `BATCH_SIZE`.
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use tetris_core::GameEvent;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, Response};
+
+use super::files;
+use super::i18n::{tr, use_locale};
+use super::storage;
+
+/// Reports sent per request in `Remote` mode.
+pub const BATCH_SIZE: usize = 5;
+/// Lines kept in the local log; older ones are dropped first.
+pub const MAX_LOG_LINES: usize = 1000;
+
+/// What happens to reports, stored under `storage::TELEMETRY_KEY`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum TelemetryMode {
+    #[default]
+    Off,
+    /// Append to the local log only.
+    LocalOnly,
+    /// POST batches as `{"reports": […]}` to `endpoint`.
+    Remote { endpoint: String },
+}
+
+/// How the game's lines were cleared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClearCounts {
+    pub single: u32,
+    pub double: u32,
+    pub triple: u32,
+    pub tetris: u32,
+    pub t_spin: u32,
+    pub perfect_clear: u32,
+}
+
+impl ClearCounts {
+    pub fn observe(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::LinesCleared { count: 1 } => self.single += 1,
+            GameEvent::LinesCleared { count: 2 } => self.double += 1,
+            GameEvent::LinesCleared { count: 3 } => self.triple += 1,
+            GameEvent::LinesCleared { count } if count >= 4 => self.tetris += 1,
+            GameEvent::TSpin { .. } => self.t_spin += 1,
+            GameEvent::PerfectClear => self.perfect_clear += 1,
+            _ => {}
+        }
+    }
+}
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameReport {
+    /// Version of the game that played it.
+    pub version: String,
+    /// `"marathon"` or `"tutorial"`.
+    pub mode: String,
+    pub duration_ms: u64,
+    pub score: i32,
+    pub lines: u32,
+    pub clears: ClearCounts,
+}
+
+impl GameReport {
+    pub fn new(mode: &str, duration_ms: f64, score: i32, lines: u32, clears: ClearCounts) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            mode: mode.to_string(),
+            duration_ms: duration_ms.max(0.0) as u64,
+            score,
+            lines,
+            clears,
+        }
+    }
+}
+
+/// `log` with `line` appended, trimmed to the last `MAX_LOG_LINES` lines.
+pub fn append_log_line(log: &str, line: &str) -> String {
+    let lines: Vec<&str> = log.lines().chain(std::iter::once(line)).collect();
+    let keep = &lines[lines.len().saturating_sub(MAX_LOG_LINES)..];
+    keep.iter().map(|l| format!("{}\n", l)).collect()
+}
+
+pub fn mode() -> TelemetryMode {
+    storage::load(storage::TELEMETRY_KEY).unwrap_or_default()
+}
+
+fn queued() -> Vec<GameReport> {
+    storage::load(storage::TELEMETRY_QUEUE_KEY).unwrap_or_default()
+}
+
+/// Handles a finished game according to the player's `mode()`.
+pub fn record(report: GameReport) {
+    match mode() {
+        TelemetryMode::Off => {}
+        TelemetryMode::LocalOnly => {
+            let Ok(line) = serde_json::to_string(&report) else { return };
+            let log = storage::read_raw(storage::TELEMETRY_LOG_KEY).unwrap_or_default();
+            storage::write_raw(storage::TELEMETRY_LOG_KEY, Some(&append_log_line(&log, &line)), js_sys::Date::now());
+        }
+        TelemetryMode::Remote { endpoint } => {
+            let mut queue = queued();
+            queue.push(report);
+            storage::save(storage::TELEMETRY_QUEUE_KEY, &queue);
+            if queue.len() >= BATCH_SIZE {
+                flush(endpoint);
+            }
+        }
+    }
+}
+
+/// Posts the queued reports in the background, dropping them once the
+/// endpoint accepted them. Failed batches stay queued for the next game.
+fn flush(endpoint: String) {
+    let batch = queued();
+    leptos::task::spawn_local(async move {
+        if post(&endpoint, &batch).await.is_ok() {
+            let mut queue = queued();
+            queue.drain(..batch.len().min(queue.len()));
+            storage::save(storage::TELEMETRY_QUEUE_KEY, &queue);
+        }
+    });
+}
+
+async fn post(endpoint: &str, reports: &[GameReport]) -> Result<(), JsValue> {
+    let body = serde_json::json!({ "reports": reports }).to_string();
+    let init = RequestInit::new();
+    init.set_method("POST");
+    let headers = Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+    let response: Response = JsFuture::from(window().fetch_with_str_and_init(endpoint, &init))
+        .await?
+        .unchecked_into();
+    if response.ok() { Ok(()) } else { Err(JsValue::from(response.status())) }
+}
+
+/// Changes the mode. Reports queued under the old one are discarded, so
+/// opting out never sends anything afterwards.
+fn set_mode(mode: &TelemetryMode) {
+    storage::save(storage::TELEMETRY_KEY, mode);
+    storage::remove(storage::TELEMETRY_QUEUE_KEY);
+}
+
+/// Settings section choosing the telemetry mode.
+#[component]
+pub fn TelemetryPanel() -> impl IntoView {
+    let locale = use_locale();
+    let (current, set_current) = signal(mode());
+    let change = move |mode: TelemetryMode| {
+        set_mode(&mode);
+        set_current.set(mode);
+    };
+    let kind = move || match current.get() {
+        TelemetryMode::Off => "off",
+        TelemetryMode::LocalOnly => "local",
+        TelemetryMode::Remote { .. } => "remote",
+    };
+    let endpoint = move || match current.get() {
+        TelemetryMode::Remote { endpoint } => endpoint,
+        _ => String::new(),
+    };
+    view! {
+        <div class="flex flex-col gap-2">
+            <label class="flex flex-row gap-2 items-center">
+                <span>{move || tr(locale.get(), "telemetry.title")}</span>
+                <select
+                    class="select select-sm"
+                    on:change=move |ev| change(match event_target_value(&ev).as_str() {
+                        "local" => TelemetryMode::LocalOnly,
+                        "remote" => TelemetryMode::Remote { endpoint: String::new() },
+                        _ => TelemetryMode::Off,
+                    })>
+                    {[("off", "telemetry.off"), ("local", "telemetry.local"), ("remote", "telemetry.remote")].into_iter().map(|(value, key)| view! {
+                        <option value=value selected=move || kind() == value>{move || tr(locale.get(), key)}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+            <span class="text-sm opacity-80">{move || tr(locale.get(), "telemetry.hint")}</span>
+            <input
+                type="text"
+                class="input input-sm"
+                dir="ltr"
+                style:display=move || if kind() == "remote" { "block" } else { "none" }
+                placeholder=move || tr(locale.get(), "telemetry.endpoint")
+                prop:value=endpoint
+                on:change=move |ev| change(TelemetryMode::Remote { endpoint: event_target_value(&ev) }) />
+            <div
+                class="flex flex-row gap-2"
+                style:display=move || if kind() == "local" { "flex" } else { "none" }>
+                <button
+                    class="btn btn-sm"
+                    on:click=move |_| {
+                        let log = storage::read_raw(storage::TELEMETRY_LOG_KEY).unwrap_or_default();
+                        files::download("tetris-telemetry.jsonl", "application/jsonl", &log);
+                    }>
+                    {move || tr(locale.get(), "telemetry.download")}
+                </button>
+                <button
+                    class="btn btn-sm"
+                    on:click=move |_| storage::remove(storage::TELEMETRY_LOG_KEY)>
+                    {move || tr(locale.get(), "telemetry.clear")}
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use leptos::prelude::*;
+
+use super::i18n::use_locale;
+
+const TOAST_MS: u64 = 3000;
+
+/// Queue of short-lived notifications, shared through Leptos context.
+#[derive(Debug, Clone, Copy)]
+pub struct Toaster {
+    items: RwSignal<Vec<(u32, String)>>,
+    next_id: StoredValue<u32>,
+}
+
+impl Default for Toaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Toaster {
+    pub fn new() -> Self {
+        Self {
+            items: RwSignal::new(Vec::new()),
+            next_id: StoredValue::new(0),
+        }
+    }
+
+    /// Shows `text` for a few seconds.
+    pub fn push(&self, text: impl Into<String>) {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id.wrapping_add(1));
+        self.items.update(|items| items.push((id, text.into())));
+        let items = self.items;
+        set_timeout(
+            move || items.update(|items| items.retain(|(i, _)| *i != id)),
+            Duration::from_millis(TOAST_MS),
+        );
+    }
+}
+
+#[component]
+pub fn ToastStack(toaster: Toaster) -> impl IntoView {
+    let locale = use_locale();
+    view! {
+        <div class="toast toast-top toast-center" dir=move || locale.get().dir() lang=move || locale.get().code()>
+            <For each=move || toaster.items.get() key=|(id, _)| *id let:item>
+                <div class="alert alert-success">
+                    <span>{item.1}</span>
+                </div>
+            </For>
+        </div>
+    }
+}
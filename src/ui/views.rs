@@ -0,0 +1,194 @@
+//! Parts of the game in windows of their own, for streaming setups and
+//! second monitors; desktop build only. The sidebar asks the Tauri side to
+//! open a window on `index.html#view=<id>`, and the main window emits a
+//! `ViewFrame` as `FRAME_EVENT` every time the game changes, which those
+//! windows listen for and draw.
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use tetris_core::{pieces, Tetris, GARBAGE_KIND};
+use wasm_bindgen::prelude::*;
+
+use super::i18n::{self, tr, tr_args, use_locale};
+use super::settings::Settings;
+use super::skin;
+
+/// Event the main window's game is published as.
+pub const FRAME_EVENT: &str = "game-frame";
+/// Queued pieces a frame carries.
+pub const NEXT_PIECES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Stats,
+    Next,
+    Spectator,
+}
+
+impl View {
+    pub const ALL: [View; 3] = [View::Stats, View::Next, View::Spectator];
+
+    /// Name in the window's URL and in the `open_view` command.
+    pub fn id(&self) -> &'static str {
+        match self {
+            View::Stats => "stats",
+            View::Next => "next",
+            View::Spectator => "spectator",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.id() == id)
+    }
+
+    /// Locale key of the sidebar button that opens it.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            View::Stats => "views.stats",
+            View::Next => "views.next",
+            View::Spectator => "views.spectator",
+        }
+    }
+}
+
+/// What a view window is sent of the game.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewFrame {
+    pub score: i32,
+    pub lines: u32,
+    pub pieces: u32,
+    pub elapsed_ms: f64,
+    pub held: Option<String>,
+    /// The next `NEXT_PIECES` pieces known in advance.
+    pub next: Vec<String>,
+    /// Rows from the top, one `render_view` letter per cell.
+    pub board: Vec<String>,
+}
+
+impl ViewFrame {
+    pub fn of(tetris: &Tetris) -> Self {
+        Self {
+            score: tetris.get_score(),
+            lines: tetris.lines_cleared(),
+            pieces: tetris.pieces_placed(),
+            elapsed_ms: tetris.elapsed_ms(),
+            held: tetris.held_piece().map(str::to_string),
+            next: tetris.upcoming_pieces().take(NEXT_PIECES).map(str::to_string).collect(),
+            board: tetris.render_view().into_iter().map(|row| row.concat()).collect(),
+        }
+    }
+}
+
+/// The kind a board letter of a `ViewFrame` stands for.
+fn kind_of(c: char) -> &'static str {
+    pieces::KINDS
+        .iter()
+        .copied()
+        .chain(["G", GARBAGE_KIND])
+        .find(|k| k.starts_with(c))
+        .unwrap_or("B")
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = emit)]
+    fn tauri_emit(event: &str, payload: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = listen)]
+    fn tauri_listen(event: &str, handler: &JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke)]
+    fn tauri_invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+pub fn is_desktop() -> bool {
+    js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("__TAURI__")).unwrap_or(false)
+}
+
+/// The view this window was opened as, from `#view=<id>` after the URL.
+pub fn requested() -> Option<View> {
+    let hash = window().location().hash().ok()?;
+    View::from_id(hash.strip_prefix("#view=")?)
+}
+
+/// Opens `view` in a window of its own, or brings it forward if it is open.
+pub fn open(view: View) {
+    let args = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&args, &JsValue::from_str("view"), &JsValue::from_str(view.id()));
+    tauri_invoke("open_view", args.into());
+}
+
+/// Sends `frame` to every open view window.
+pub fn publish(frame: &ViewFrame) {
+    if let Ok(json) = serde_json::to_string(frame) {
+        tauri_emit(FRAME_EVENT, JsValue::from_str(&json));
+    }
+}
+
+/// A view window: `view` of the frames the main window publishes.
+#[component]
+pub fn ViewWindow(view: View) -> impl IntoView {
+    // Outside any profile: default settings, for the locale.
+    provide_context(RwSignal::new(Settings::default()));
+    let locale = use_locale();
+    let frame = RwSignal::new(ViewFrame::default());
+    let on_frame = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let payload = js_sys::Reflect::get(&event, &JsValue::from_str("payload")).ok().and_then(|p| p.as_string());
+        if let Some(f) = payload.and_then(|p| serde_json::from_str(&p).ok()) {
+            frame.set(f);
+        }
+    });
+    tauri_listen(FRAME_EVENT, on_frame.as_ref());
+    on_frame.forget();
+
+    let piece = |kind: String| {
+        let kind = kind_of(kind.chars().next().unwrap_or('B'));
+        let (cells, color) = (super::hold::preview_cells(kind), skin::flat_color(kind));
+        view! {
+            <div class="relative w-10 h-5">
+                {cells.map(|p| view! {
+                    <div
+                        class="absolute w-2.5 h-2.5"
+                        style=format!("left: {}px; top: {}px; background-color: {};", p.0 * 10, p.1 * 10, color)>
+                    </div>
+                })}
+            </div>
+        }
+    };
+    let content = match view {
+        View::Stats => view! {
+            <div class="flex flex-col gap-1 font-mono">
+                {move || {
+                    let (l, f) = (locale.get(), frame.get());
+                    [
+                        tr_args(l, "score", &[("value", i18n::format_number(l, f.score as i64))]),
+                        tr_args(l, "lines", &[("value", i18n::format_number(l, f.lines as i64))]),
+                        tr_args(l, "time", &[("value", i18n::format_time(l, f.elapsed_ms))]),
+                    ]
+                    .map(|text| view! { <span>{text}</span> })
+                }}
+            </div>
+        }
+        .into_any(),
+        View::Next => view! {
+            <div class="flex flex-col gap-3 items-center">
+                <span>{move || tr(locale.get(), "hold")}</span>
+                {move || frame.get().held.map(piece)}
+                <span>{move || tr(locale.get(), "views.next")}</span>
+                {move || frame.get().next.into_iter().map(piece).collect::<Vec<_>>()}
+            </div>
+        }
+        .into_any(),
+        View::Spectator => view! {
+            <div class="flex flex-col w-full">
+                {move || frame.get().board.into_iter().map(|row| view! {
+                    <div class="flex flex-row">
+                        {row.chars().map(|c| view! {
+                            <div class="cell aspect-square w-[10%]" style=skin::cell_style(kind_of(c), None, false)></div>
+                        }).collect::<Vec<_>>()}
+                    </div>
+                }).collect::<Vec<_>>()}
+            </div>
+        }
+        .into_any(),
+    };
+    view! { <div class="p-2 h-screen w-screen">{content}</div> }
+}
@@ -0,0 +1,235 @@
+//! Bot-versus-bot matches for checking AI changes. Two bots with their own
+//! `Weights` play side by side on seeded pieces, each dealt by their own
+//! generator from the match's `MatchGenerators`, sending garbage
+//! for the lines they clear, until one tops out. Garbage waits in a
+//! `GarbageQueue` until it has arrived and the receiver next locks without
+//! a clear, and their clears counter it first. `evaluate` plays each seed
+//! twice with the sides swapped and tallies the results, and `Series`
+//! plays a best-of series whose rematches deal the same sequences again.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::bot::{Bot, Weights};
+use crate::garbage::{GarbageQueue, DEFAULT_CANCEL_PERCENT};
+use crate::generator::{MatchGenerators, PieceRng, StreamMismatch};
+use crate::replay::Replay;
+use crate::{Action, GameEvent, Tetris, TICK_MS};
+
+/// Pieces per side after which a match is decided on lines sent.
+pub const MAX_PIECES: u32 = 500;
+/// Bot actions between gravity ticks, as in `simulation`.
+pub const ACTIONS_PER_TICK: u32 = 4;
+
+/// Garbage rows sent for clearing `lines` rows at once.
+pub fn attack(lines: u32) -> u32 {
+    match lines {
+        0 | 1 => 0,
+        2 => 1,
+        3 => 2,
+        n => n,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    FirstWins,
+    SecondWins,
+    Draw,
+}
+
+/// `MAX_PIECES` pieces and one spare, from a generator seeded by `rng`.
+pub(crate) fn deal(rng: &mut StdRng) -> Replay {
+    Replay::deal_from(MAX_PIECES as usize + 1, PieceRng::from_state(rng.random()))
+}
+
+/// Plays one match of `first` against `second` on pieces and garbage holes
+/// drawn from `seed`, both sides dealt the same pieces.
+pub fn play_match(first: Weights, second: Weights, seed: u64) -> Result<Outcome, StreamMismatch> {
+    play_seeded(first, second, seed, [0, 0], [0, 0])
+}
+
+/// Like `play_match`, but each side deals from its own stream of the match
+/// seed (the same pieces for equal streams), and starts with `handicap`
+/// garbage rows under its first piece. Fails if a game deals other pieces
+/// than its stream, which would make the match unfair.
+pub fn play_seeded(first: Weights, second: Weights, seed: u64, streams: [u64; 2], handicap: [u32; 2]) -> Result<Outcome, StreamMismatch> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut generators = MatchGenerators::new(seed, &streams);
+    let mut games = start_games(&generators, handicap, &mut rng);
+    let mut bots = [Bot::with_weights(first), Bot::with_weights(second)];
+    let mut sent = [0u32; 2];
+    let mut queues = [GarbageQueue::default(), GarbageQueue::default()];
+    let mut actions = 0;
+    while !games.iter().any(|g| g.lost) && games.iter().any(|g| g.pieces_placed() < MAX_PIECES) {
+        actions += 1;
+        for side in 0..2 {
+            let game = &mut games[side];
+            if game.pieces_placed() < MAX_PIECES {
+                bots[side].act(game);
+            }
+            if actions % ACTIONS_PER_TICK == 0 {
+                game.advance_clock(TICK_MS);
+                game.apply(Action::Tick);
+            }
+            let events = game.drain_events();
+            generators.check(side, game.dealt_digest())?;
+            let rows: u32 = events
+                .iter()
+                .map(|e| match e {
+                    GameEvent::LinesCleared { count } => attack(*count),
+                    _ => 0,
+                })
+                .sum();
+            let rows = queues[side].counter(rows, DEFAULT_CANCEL_PERCENT);
+            if rows > 0 {
+                sent[side] += rows;
+                // Both clocks advance together, so the sender's time will do.
+                queues[1 - side].push(rows, game.elapsed_ms());
+            }
+            let locked = events.iter().any(|e| matches!(e, GameEvent::PieceLocked { .. }));
+            let cleared = events.iter().any(|e| matches!(e, GameEvent::LinesCleared { .. }));
+            if locked && !cleared {
+                for batch in queues[side].take_arrived(game.elapsed_ms()) {
+                    let hole = rng.random_range(0..game.width);
+                    game.receive_garbage(batch, hole);
+                }
+            }
+        }
+    }
+    Ok(match (games[0].lost, games[1].lost) {
+        (false, true) => Outcome::FirstWins,
+        (true, false) => Outcome::SecondWins,
+        _ if sent[0] > sent[1] => Outcome::FirstWins,
+        _ if sent[1] > sent[0] => Outcome::SecondWins,
+        _ => Outcome::Draw,
+    })
+}
+
+/// The two games of a match as they start: dealt by their `generators`,
+/// each with its `handicap` garbage rows, holes drawn from `rng`, under the
+/// first piece.
+pub fn start_games(generators: &MatchGenerators, handicap: [u32; 2], rng: &mut impl Rng) -> [Tetris; 2] {
+    let mut games = [0, 1].map(|side| Replay::deal_from(1, generators.rng(side)).start());
+    for (game, rows) in games.iter_mut().zip(handicap) {
+        for _ in 0..rows {
+            game.receive_garbage(1, rng.random_range(0..game.width));
+        }
+    }
+    games
+}
+
+/// Which pieces the games of a `Series` deal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedPolicy {
+    /// Both sides get the same sequence, and every rematch replays it.
+    Shared,
+    /// Each side gets its own sequence, and the next game swaps them, so
+    /// over each pair of games both sides play both sequences.
+    Swapped,
+}
+
+/// A best-of-`best_of` series between two sides, every game seeded by
+/// `seed` and dealt from the streams `streams` picks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Series {
+    pub best_of: u32,
+    pub seed: u64,
+    pub policy: SeedPolicy,
+    /// Garbage rows the first and second side start every game with, to
+    /// even out a gap in strength.
+    pub handicap: [u32; 2],
+}
+
+impl Series {
+    /// Piece streams (see `MatchGenerators`) of the first and second side
+    /// in game `game`, from 0.
+    pub fn streams(&self, game: u32) -> [u64; 2] {
+        match self.policy {
+            SeedPolicy::Shared => [0; 2],
+            SeedPolicy::Swapped => {
+                let a = game as u64 / 2 * 2;
+                let b = a + 1;
+                if game.is_multiple_of(2) { [a, b] } else { [b, a] }
+            }
+        }
+    }
+
+    /// Wins needed to take the series.
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    /// Plays games until a side wins the series, or drawn games have run it
+    /// to twice its length. Returns each game's outcome, or the first game
+    /// that left its piece stream.
+    pub fn play(&self, first: Weights, second: Weights) -> Result<Vec<Outcome>, StreamMismatch> {
+        let mut outcomes = Vec::new();
+        let mut wins = [0; 2];
+        while wins.iter().all(|&w| w < self.wins_needed()) && outcomes.len() < 2 * self.best_of as usize {
+            let outcome = play_seeded(first, second, self.seed, self.streams(outcomes.len() as u32), self.handicap)?;
+            match outcome {
+                Outcome::FirstWins => wins[0] += 1,
+                Outcome::SecondWins => wins[1] += 1,
+                Outcome::Draw => {}
+            }
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Results from one configuration's side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Tally {
+    pub fn matches(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Share of points won, counting a draw as half a win.
+    pub fn score(&self) -> Option<f64> {
+        let n = self.matches();
+        (n > 0).then(|| (self.wins as f64 + self.draws as f64 / 2.0) / n as f64)
+    }
+
+    /// 95% Wilson score interval around `score`.
+    pub fn confidence_interval(&self) -> Option<(f64, f64)> {
+        const Z: f64 = 1.96;
+        let p = self.score()?;
+        let n = self.matches() as f64;
+        let denominator = 1.0 + Z * Z / n;
+        let center = (p + Z * Z / (2.0 * n)) / denominator;
+        let half = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / denominator;
+        Some(((center - half).max(0.0), (center + half).min(1.0)))
+    }
+}
+
+/// Plays `matches` matches of `a` against `b` starting from `seed`. Each
+/// seed is used twice, with `a` first and then second, so neither side
+/// profits from a lucky sequence. The tally is from `a`'s side.
+pub fn evaluate(a: Weights, b: Weights, matches: u32, seed: u64) -> Result<Tally, StreamMismatch> {
+    let mut tally = Tally::default();
+    for i in 0..matches {
+        let seed = seed.wrapping_add(i as u64 / 2);
+        let outcome = if i % 2 == 0 {
+            play_match(a, b, seed)?
+        } else {
+            match play_match(b, a, seed)? {
+                Outcome::FirstWins => Outcome::SecondWins,
+                Outcome::SecondWins => Outcome::FirstWins,
+                Outcome::Draw => Outcome::Draw,
+            }
+        };
+        match outcome {
+            Outcome::FirstWins => tally.wins += 1,
+            Outcome::SecondWins => tally.losses += 1,
+            Outcome::Draw => tally.draws += 1,
+        }
+    }
+    Ok(tally)
+}
@@ -0,0 +1,81 @@
+//! The zone: an optional meter that fills with every cleared line and, once
+//! at least a quarter full, can be spent on a stretch where gravity stops.
+//! Lines cleared in the zone don't score as they go; they are tallied and
+//! score all at once, `MULTIPLIER` times over, when it ends. Enable it with
+//! `Tetris::set_zone` and activate it with `Action::Zone`.
+
+/// Lines that fill the meter.
+pub const METER_LINES: u32 = 16;
+/// Least meter, in lines, that can be activated.
+pub const MIN_ACTIVATION_LINES: u32 = METER_LINES / 4;
+/// Zone time bought by each line in the meter.
+pub const MS_PER_LINE: f64 = 500.0;
+/// Lines cleared in the zone score this many times over.
+pub const MULTIPLIER: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Zone {
+    /// Lines banked towards the next zone, up to `METER_LINES`.
+    meter: u32,
+    /// While active: when the zone ends, in `Tetris::elapsed_ms`.
+    ends_ms: Option<f64>,
+    /// Lines cleared in the current zone so far.
+    lines: u32,
+}
+
+impl Zone {
+    /// How full the meter is, from 0.0 to 1.0.
+    pub fn meter(&self) -> f64 {
+        self.meter as f64 / METER_LINES as f64
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.ends_ms.is_some()
+    }
+
+    pub fn can_activate(&self) -> bool {
+        !self.is_active() && self.meter >= MIN_ACTIVATION_LINES
+    }
+
+    /// Lines cleared in the current zone so far.
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    /// Time left in the zone at `now_ms`, if it is active.
+    pub fn remaining_ms(&self, now_ms: f64) -> Option<f64> {
+        self.ends_ms.map(|end| (end - now_ms).max(0.0))
+    }
+
+    /// Spends the meter on a zone starting at `now_ms` and returns how long
+    /// it lasts, or `None` if the meter is too low.
+    pub fn activate(&mut self, now_ms: f64) -> Option<f64> {
+        if !self.can_activate() {
+            return None;
+        }
+        let duration_ms = std::mem::take(&mut self.meter) as f64 * MS_PER_LINE;
+        self.ends_ms = Some(now_ms + duration_ms);
+        self.lines = 0;
+        Some(duration_ms)
+    }
+
+    /// Notes `count` cleared lines: tallied in an active zone, banked in the
+    /// meter otherwise.
+    pub fn record(&mut self, count: u32) {
+        if self.is_active() {
+            self.lines += count;
+        } else {
+            self.meter = (self.meter + count).min(METER_LINES);
+        }
+    }
+
+    /// Ends the zone if it has run out by `now_ms`, returning the lines
+    /// cleared in it.
+    pub fn expire(&mut self, now_ms: f64) -> Option<u32> {
+        if self.ends_ms? > now_ms {
+            return None;
+        }
+        self.ends_ms = None;
+        Some(std::mem::take(&mut self.lines))
+    }
+}
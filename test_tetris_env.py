@@ -2,7 +2,7 @@ import unittest
 import numpy as np
 import gymnasium as gym
 from gymnasium.utils.env_checker import check_env
-from tetris_env import TetrisEnv # Assuming tetris_env.py is in the same directory
+from tetris_env import TetrisEnv, TetrisVecEnv # Assuming tetris_env.py is in the same directory
 import os
 import platform
 
@@ -78,6 +78,48 @@ class TestTetrisEnv(unittest.TestCase):
         self.assertEqual(info["score"], 0) # Assuming score resets to 0
         self.assertEqual(info["lost"], False) # Assuming not lost on reset
 
+    def test_seeded_reset_deals_the_same_pieces(self):
+        """Reset with the same seed, or a restored generator state, deals the same pieces."""
+        first, _ = self.env.reset(seed=42)
+        state = self.env.get_rng_state()
+        for _ in range(20):
+            self.env.step(3)
+        again, _ = self.env.reset(seed=42)
+        np.testing.assert_array_equal(first, again)
+        self.env.set_rng_state(state)
+        self.assertEqual(self.env.get_rng_state(), state)
+
+    def test_board_queries(self):
+        """Column, row and cell queries agree with each other after some drops."""
+        self.env.reset(seed=7)
+        for _ in range(3):
+            self.env.step(3)
+        heights = self.env.column_heights()
+        self.assertEqual(heights.shape, (self.env.width,))
+        self.assertEqual(self.env.row_fill_counts().shape, (self.env.height,))
+        self.assertEqual(self.env.holes_per_column().shape, (self.env.width,))
+        self.assertGreater(heights.max(), 0)
+        for x, h in enumerate(heights):
+            if h:
+                self.assertTrue(self.env.is_cell_occupied(x, self.env.height - int(h)))
+        self.assertFalse(self.env.is_cell_occupied(-1, 0))
+
+    def test_clearing_rows(self):
+        """Nothing is clearing at the start of a game."""
+        self.env.reset(seed=7)
+        self.assertEqual(self.env.clearing_rows(), ([], 0.0))
+
+    def test_board_delta(self):
+        """A delta from version 0 covers the board; later ones only what moved."""
+        self.env.reset(seed=7)
+        version, cells = self.env.board_delta()
+        self.assertEqual(len(cells), self.env.width * self.env.height)
+        self.assertEqual(self.env.board_delta(version), (version, []))
+        self.env.step(0)
+        newer, moved = self.env.board_delta(version)
+        self.assertGreater(newer, version)
+        self.assertTrue(0 < len(moved) < self.env.width * self.env.height)
+
     def test_step(self):
         """Test the step method with a few actions."""
         self.env.reset()
@@ -135,6 +177,70 @@ class TestTetrisEnv(unittest.TestCase):
         except Exception as e:
             self.fail(f"check_env failed: {e}")
 
+    def test_observation_formats(self):
+        """Test that every encoded observation format matches its space."""
+        for name, shape in [("flat", (207,)), ("grid", (4, 20, 10)), ("columns", (27,))]:
+            env = TetrisEnv(lib_path=self.lib_path, width=10, height=20, observation=name)
+            try:
+                obs, _ = env.reset()
+                self.assertEqual(obs.shape, shape)
+                self.assertEqual(obs.dtype, np.float32)
+                self.assertTrue(env.observation_space.contains(obs))
+                self.assertEqual(env.mirrored_obs().shape, shape)
+                check_env(env.unwrapped, skip_render_check=True)
+            finally:
+                env.close()
+        with self.assertRaises(ValueError):
+            TetrisEnv(lib_path=self.lib_path, observation="pixels")
+
+    def test_reward_weights(self):
+        """Test that shaped rewards follow the weights given at creation."""
+        env = TetrisEnv(lib_path=self.lib_path, width=10, height=20, reward_weights={"top_out": -50.0})
+        try:
+            self.assertEqual(env.reward_weights["top_out"], -50.0)
+            self.assertAlmostEqual(env.reward_weights["lines"], 1.0)
+            env.reset()
+            for i in range(300):
+                _, reward, terminated, _, _ = env.step(3 if i % 2 else 4)
+                if terminated:
+                    break
+            self.assertTrue(terminated)
+            self.assertLess(reward, -49.0)
+        finally:
+            env.close()
+        with self.assertRaises(ValueError):
+            TetrisEnv(lib_path=self.lib_path, reward_weights={"speed": 1.0})
+
+    def test_curriculum_options(self):
+        """Test pre-filled garbage and the episode step limit."""
+        env = TetrisEnv(lib_path=self.lib_path, width=10, height=8, pieces="O", garbage_rows=2, max_steps=5)
+        try:
+            obs, _ = env.reset()
+            self.assertEqual(obs.shape, (8, 10))
+            self.assertEqual(int(obs[-2:].sum()), 18)
+            truncated = False
+            for _ in range(5):
+                _, _, terminated, truncated, _ = env.step(1)
+            self.assertTrue(truncated and not terminated)
+        finally:
+            env.close()
+        with self.assertRaises(ValueError):
+            TetrisEnv(lib_path=self.lib_path, pieces="Q")
+
+    def test_vector_env(self):
+        """Test that the env pool steps every env with one call."""
+        env = TetrisVecEnv(16, lib_path=self.lib_path, width=10, height=8, observation="columns", max_steps=3)
+        try:
+            obs, _ = env.reset()
+            self.assertEqual(obs.shape, (16, 27))
+            truncated = np.zeros(16, dtype=bool)
+            for _ in range(3):
+                obs, rewards, terminated, truncated, _ = env.step(np.zeros(16, dtype=np.int64))
+                self.assertEqual(rewards.shape, (16,))
+            self.assertTrue(truncated.all() and not terminated.any())
+        finally:
+            env.close()
+
     def test_game_over_reward_check(self):
         """Test that reward is significantly negative upon game over."""
         self.env.reset()
@@ -144,10 +250,10 @@ class TestTetrisEnv(unittest.TestCase):
 
         # Try to force a game over by repeatedly dropping pieces.
         # This is heuristic and depends on the game logic.
-        # Action 3 is speed_up (drop), Action 4 is tick (move down)
-        # We'll primarily use speed_up and occasional ticks.
+        # Action 3 is hard drop, Action 4 is tick (move down)
+        # We'll primarily use hard drops and occasional ticks.
         for i in range(100): # Max steps to try to trigger game over
-            action = 3 # speed_up (drop piece to bottom)
+            action = 3 # hard drop (drop piece to bottom)
             if i % 5 == 0 and i > 0: # Occasionally just tick to let things settle or new piece appear
                 action = 4 # tick
 
@@ -12,35 +12,104 @@ class GameState(ctypes.Structure):
         ("lost", ctypes.c_bool),
         ("width", ctypes.c_uint32),
         ("height", ctypes.c_uint32),
+        ("paused", ctypes.c_bool),
+        ("phase", ctypes.c_uint32),
     ]
 
+# Reward shaping weights (see src/reward.rs); deltas are new minus old, so
+# penalties are negative
+class RewardWeights(ctypes.Structure):
+    _fields_ = [
+        ("lines", ctypes.c_float),
+        ("holes_delta", ctypes.c_float),
+        ("height_delta", ctypes.c_float),
+        ("top_out", ctypes.c_float),
+        ("survival", ctypes.c_float),
+    ]
+
+# Curriculum options (see src/curriculum.rs)
+class Curriculum(ctypes.Structure):
+    _fields_ = [
+        ("piece_mask", ctypes.c_uint32),
+        ("garbage_rows", ctypes.c_uint32),
+        ("max_steps", ctypes.c_uint32),
+    ]
+
+class CellChange(ctypes.Structure):
+    _fields_ = [
+        ("x", ctypes.c_uint16),
+        ("y", ctypes.c_uint16),
+        ("kind", ctypes.c_uint8),
+    ]
+
+# Piece kinds in tetris_piece_kind order, for Curriculum.piece_mask bits
+PIECE_KINDS = "ITOJLSZ"
+
+# Game phases reported in GameState.phase
+PHASE_PLAYING = 0
+PHASE_CLEARING = 1
+PHASE_PAUSED = 2
+PHASE_GAME_OVER = 3
+
+# tetris_last_error() codes for a failed tetris_create
+BOARD_ERRORS = {
+    1: "width below the minimum of 4",
+    2: "height below the minimum of 4",
+    3: "dimension above the maximum of 1024",
+}
+
+# Extra tetris_step action codes outside the RL action space
+ACTION_PAUSE = 5
+ACTION_RESUME = 6
+ACTION_SONIC_DROP = 7
+ACTION_ZONE = 8
+ACTION_HOLD = 9
+ACTION_SOFT_DROP = 10
+
+# Observation formats besides the default "board" grid (see src/observation.rs),
+# by tetris_get_observation code
+OBSERVATION_FORMATS = {"flat": 0, "grid": 1, "columns": 2}
+
+# RL actions of a mirrored game: left and right swap. The only rotation
+# turns clockwise, so it has no exact mirror and stays as it is.
+MIRRORED_ACTIONS = [1, 0, 2, 3, 4]
+
+# Done flags written by tetris_env_pool_step
+DONE_TERMINATED = 1
+DONE_TRUNCATED = 2
+
+def default_lib_path() -> str:
+    """The debug build of the Rust library next to this script."""
+    base_path = os.path.join(os.path.dirname(__file__), "target", "debug")
+    system = platform.system()
+    if system == "Linux":
+        lib_name = "libtetris_core.so"
+    elif system == "Windows":
+        lib_name = "tetris_core.dll"
+    elif system == "Darwin": # macOS
+        lib_name = "libtetris_core.dylib"
+    else:
+        raise OSError(f"Unsupported OS: {system}. Please provide lib_path manually.")
+    return os.path.join(base_path, lib_name)
+
 class TetrisEnv(gym.Env):
     metadata = {'render_modes': ['human', 'ansi'], 'render_fps': 4}
 
-    def __init__(self, lib_path: str = None, width: int = 10, height: int = 20, render_mode: str = None):
+    def __init__(self, lib_path: str = None, width: int = 10, height: int = 20, render_mode: str = None,
+                 observation: str = "board", reward_weights: dict = None,
+                 pieces: str = None, garbage_rows: int = 0, max_steps: int = 0):
         super().__init__()
 
+        if observation != "board" and observation not in OBSERVATION_FORMATS:
+            raise ValueError(f"Unknown observation format {observation!r}; expected 'board' or one of "
+                             f"{sorted(OBSERVATION_FORMATS)}")
         self.width = width
         self.height = height
+        self.observation = observation
         self.game_ptr = None # Pointer to the Rust Tetris object
         self.render_mode = render_mode
 
-        if lib_path is None:
-            # Determine default library path based on OS and architecture
-            base_path = os.path.join(os.path.dirname(__file__), "target", "debug")
-            lib_name = ""
-            system = platform.system()
-            if system == "Linux":
-                lib_name = "libtetris_core.so"
-            elif system == "Windows":
-                lib_name = "tetris_core.dll"
-            elif system == "Darwin": # macOS
-                lib_name = "libtetris_core.dylib"
-            else:
-                raise OSError(f"Unsupported OS: {system}. Please provide lib_path manually.")
-            self.lib_path = os.path.join(base_path, lib_name)
-        else:
-            self.lib_path = lib_path
+        self.lib_path = lib_path if lib_path is not None else default_lib_path()
 
         if not os.path.exists(self.lib_path):
             raise OSError(f"Tetris library not found at {self.lib_path}. "
@@ -49,16 +118,50 @@ class TetrisEnv(gym.Env):
         self.rust_lib = ctypes.CDLL(self.lib_path)
         self._define_ffi_argtypes()
 
-        self.game_ptr = self.rust_lib.tetris_create(ctypes.c_uint32(self.width), ctypes.c_uint32(self.height))
+        # With reward_weights, the reward is shaped in Rust from these weights
+        # (any left out keep their defaults) instead of the score difference.
+        self.reward_weights = None
+        if reward_weights is not None:
+            weights = self.rust_lib.tetris_default_reward_weights()
+            for name, value in reward_weights.items():
+                if name not in dict(RewardWeights._fields_):
+                    raise ValueError(f"Unknown reward weight {name!r}")
+                setattr(weights, name, value)
+            self.reward_weights = {name: getattr(weights, name) for name, _ in RewardWeights._fields_}
+            self.game_ptr = self.rust_lib.tetris_create_with_reward(
+                ctypes.c_uint32(self.width), ctypes.c_uint32(self.height), ctypes.byref(weights)
+            )
+        else:
+            self.game_ptr = self.rust_lib.tetris_create(ctypes.c_uint32(self.width), ctypes.c_uint32(self.height))
         if not self.game_ptr:
+            code = self.rust_lib.tetris_last_error()
+            if code in BOARD_ERRORS:
+                raise ValueError(f"Invalid board {self.width}x{self.height}: {BOARD_ERRORS[code]}")
             raise MemoryError("Failed to create Tetris game instance from Rust library.")
 
+        # Curriculum learning: a smaller board is just a smaller height; the
+        # rest applies to every episode from here on.
+        if pieces is not None and (not pieces or set(pieces) - set(PIECE_KINDS)):
+            raise ValueError(f"pieces must be a non-empty subset of {PIECE_KINDS!r}, got {pieces!r}")
+        self.pieces = pieces
+        self.max_steps = max_steps
+        if pieces or garbage_rows or max_steps:
+            mask = sum(1 << PIECE_KINDS.index(kind) for kind in set(pieces or ""))
+            curriculum = Curriculum(piece_mask=mask, garbage_rows=garbage_rows, max_steps=max_steps)
+            self.rust_lib.tetris_set_curriculum(self.game_ptr, ctypes.byref(curriculum))
+
         # Define action and observation spaces
         # 0:left, 1:right, 2:rotate, 3:drop, 4:tick (move down)
         self.action_space = spaces.Discrete(5)
-        self.observation_space = spaces.Box(
-            low=0, high=1, shape=(self.height, self.width), dtype=np.uint8
-        )
+        if self.observation == "board":
+            self.observation_space = spaces.Box(
+                low=0, high=1, shape=(self.height, self.width), dtype=np.uint8
+            )
+        else:
+            code = OBSERVATION_FORMATS[self.observation]
+            self._observation_len = self.rust_lib.tetris_observation_len(self.width, self.height, code)
+            shape = (4, self.height, self.width) if self.observation == "grid" else (self._observation_len,)
+            self.observation_space = spaces.Box(low=0.0, high=1.0, shape=shape, dtype=np.float32)
 
         # Initial reset to set up state
         # obs, info = self.reset()
@@ -70,6 +173,71 @@ class TetrisEnv(gym.Env):
         self.rust_lib.tetris_create.restype = ctypes.c_void_p # Represents *mut Tetris
         self.rust_lib.tetris_create.argtypes = [ctypes.c_uint32, ctypes.c_uint32]
 
+        # tetris_create_with_reward(width: u32, height: u32, weights: *const RewardWeights) -> *mut Tetris
+        self.rust_lib.tetris_create_with_reward.restype = ctypes.c_void_p
+        self.rust_lib.tetris_create_with_reward.argtypes = [
+            ctypes.c_uint32, ctypes.c_uint32, ctypes.POINTER(RewardWeights)
+        ]
+
+        # tetris_default_reward_weights() -> RewardWeights
+        self.rust_lib.tetris_default_reward_weights.restype = RewardWeights
+        self.rust_lib.tetris_default_reward_weights.argtypes = []
+
+        # tetris_last_reward(ptr: *const Tetris) -> f32
+        self.rust_lib.tetris_last_reward.restype = ctypes.c_float
+        self.rust_lib.tetris_last_reward.argtypes = [ctypes.c_void_p]
+
+        # tetris_set_curriculum(ptr: *mut Tetris, curriculum: *const Curriculum)
+        self.rust_lib.tetris_set_curriculum.restype = None
+        self.rust_lib.tetris_set_curriculum.argtypes = [ctypes.c_void_p, ctypes.POINTER(Curriculum)]
+
+        # tetris_is_truncated(ptr: *const Tetris) -> bool
+        self.rust_lib.tetris_is_truncated.restype = ctypes.c_bool
+        self.rust_lib.tetris_is_truncated.argtypes = [ctypes.c_void_p]
+
+        # tetris_get_rng_state(ptr: *const Tetris) -> u64
+        self.rust_lib.tetris_get_rng_state.restype = ctypes.c_uint64
+        self.rust_lib.tetris_get_rng_state.argtypes = [ctypes.c_void_p]
+
+        # tetris_set_rng_state(ptr: *mut Tetris, state: u64)
+        self.rust_lib.tetris_set_rng_state.restype = None
+        self.rust_lib.tetris_set_rng_state.argtypes = [ctypes.c_void_p, ctypes.c_uint64]
+
+        # tetris_get_held_kind(ptr: *const Tetris) -> u8
+        self.rust_lib.tetris_get_held_kind.restype = ctypes.c_uint8
+        self.rust_lib.tetris_get_held_kind.argtypes = [ctypes.c_void_p]
+
+        # tetris_set_classic_randomizer(ptr: *mut Tetris, classic: bool)
+        self.rust_lib.tetris_set_classic_randomizer.restype = None
+        self.rust_lib.tetris_set_classic_randomizer.argtypes = [ctypes.c_void_p, ctypes.c_bool]
+
+        # tetris_get_column_heights / _row_fill_counts / _holes_per_column(ptr: *const Tetris, out: *mut u8, cap: u32) -> u32
+        for name in ("tetris_get_column_heights", "tetris_get_row_fill_counts", "tetris_get_holes_per_column"):
+            getattr(self.rust_lib, name).restype = ctypes.c_uint32
+            getattr(self.rust_lib, name).argtypes = [ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint8), ctypes.c_uint32]
+
+        # tetris_is_cell_occupied(ptr: *const Tetris, x: i32, y: i32) -> bool
+        self.rust_lib.tetris_is_cell_occupied.restype = ctypes.c_bool
+        self.rust_lib.tetris_is_cell_occupied.argtypes = [ctypes.c_void_p, ctypes.c_int32, ctypes.c_int32]
+
+        # tetris_board_version(ptr: *mut Tetris) -> u32
+        self.rust_lib.tetris_board_version.restype = ctypes.c_uint32
+        self.rust_lib.tetris_board_version.argtypes = [ctypes.c_void_p]
+
+        # tetris_get_board_delta(ptr: *mut Tetris, last_version: u32, out_buf: *mut CellChange, cap: u32) -> u32
+        self.rust_lib.tetris_get_board_delta.restype = ctypes.c_uint32
+        self.rust_lib.tetris_get_board_delta.argtypes = [ctypes.c_void_p, ctypes.c_uint32, ctypes.POINTER(CellChange), ctypes.c_uint32]
+
+        # tetris_get_clearing_rows(ptr: *const Tetris, out_rows: *mut u32, cap: u32, out_progress: *mut f64) -> u32
+        self.rust_lib.tetris_get_clearing_rows.restype = ctypes.c_uint32
+        self.rust_lib.tetris_get_clearing_rows.argtypes = [
+            ctypes.c_void_p, ctypes.POINTER(ctypes.c_uint32), ctypes.c_uint32, ctypes.POINTER(ctypes.c_double)
+        ]
+
+        # tetris_last_error() -> i32
+        self.rust_lib.tetris_last_error.restype = ctypes.c_int32
+        self.rust_lib.tetris_last_error.argtypes = []
+
         # tetris_destroy(ptr: *mut Tetris)
         self.rust_lib.tetris_destroy.restype = None
         self.rust_lib.tetris_destroy.argtypes = [ctypes.c_void_p]
@@ -93,7 +261,46 @@ class TetrisEnv(gym.Env):
         self.rust_lib.tetris_get_game_state.restype = GameState
         self.rust_lib.tetris_get_game_state.argtypes = [ctypes.c_void_p]
 
+        # tetris_observation_len(width: u32, height: u32, format: u32) -> u32
+        self.rust_lib.tetris_observation_len.restype = ctypes.c_uint32
+        self.rust_lib.tetris_observation_len.argtypes = [ctypes.c_uint32, ctypes.c_uint32, ctypes.c_uint32]
+
+        # tetris_get_observation(ptr: *const Tetris, format: u32, out: *mut f32, cap: u32) -> u32
+        self.rust_lib.tetris_get_observation.restype = ctypes.c_uint32
+        self.rust_lib.tetris_get_observation.argtypes = [
+            ctypes.c_void_p,
+            ctypes.c_uint32,
+            ctypes.POINTER(ctypes.c_float),
+            ctypes.c_uint32,
+        ]
+        self.rust_lib.tetris_get_mirrored_observation.restype = ctypes.c_uint32
+        self.rust_lib.tetris_get_mirrored_observation.argtypes = self.rust_lib.tetris_get_observation.argtypes
+
+    def mirrored_obs(self) -> np.ndarray:
+        """The current observation flipped left to right, for augmentation.
+        Pair it with MIRRORED_ACTIONS."""
+        if self.observation == "board":
+            return np.fliplr(self._get_board()).copy()
+        if not self.game_ptr:
+            return np.zeros(self.observation_space.shape, dtype=np.float32)
+        buffer = (ctypes.c_float * self._observation_len)()
+        self.rust_lib.tetris_get_mirrored_observation(
+            self.game_ptr, OBSERVATION_FORMATS[self.observation], buffer, self._observation_len
+        )
+        return np.ctypeslib.as_array(buffer).reshape(self.observation_space.shape).copy()
+
     def _get_obs(self) -> np.ndarray:
+        if self.observation == "board":
+            return self._get_board()
+        if not self.game_ptr:
+            return np.zeros(self.observation_space.shape, dtype=np.float32)
+        buffer = (ctypes.c_float * self._observation_len)()
+        self.rust_lib.tetris_get_observation(
+            self.game_ptr, OBSERVATION_FORMATS[self.observation], buffer, self._observation_len
+        )
+        return np.ctypeslib.as_array(buffer).reshape(self.observation_space.shape).copy()
+
+    def _get_board(self) -> np.ndarray:
         if not self.game_ptr:
             # Return a zeroed observation if game_ptr is None, e.g. after close()
             return np.zeros(shape=(self.height, self.width), dtype=np.uint8)
@@ -113,14 +320,21 @@ class TetrisEnv(gym.Env):
 
     def _get_info(self) -> dict:
         if not self.game_ptr:
-            return {"score": 0, "lost": True, "width": self.width, "height": self.height}
+            return {"score": 0, "lost": True, "width": self.width, "height": self.height,
+                    "paused": False, "phase": PHASE_GAME_OVER}
 
         state_struct = self.rust_lib.tetris_get_game_state(self.game_ptr)
+        return self._state_to_info(state_struct)
+
+    @staticmethod
+    def _state_to_info(state_struct) -> dict:
         return {
             "score": state_struct.score,
             "lost": state_struct.lost,
             "width": state_struct.width,
             "height": state_struct.height,
+            "paused": state_struct.paused,
+            "phase": state_struct.phase,
         }
 
     def reset(self, seed=None, options=None) -> tuple[np.ndarray, dict]:
@@ -131,6 +345,10 @@ class TetrisEnv(gym.Env):
              # but as a safeguard if reset is called after close.
             raise ConnectionError("Rust game instance not available. Cannot reset.")
 
+        if seed is not None:
+            # Same seed, same pieces
+            self.rust_lib.tetris_set_rng_state(self.game_ptr, ctypes.c_uint64(seed % 2**64))
+
         self.rust_lib.tetris_reset(self.game_ptr)
 
         observation = self._get_obs()
@@ -153,6 +371,15 @@ class TetrisEnv(gym.Env):
 
         observation = self._get_obs()
         terminated = new_game_state_struct.lost
+        # Only the curriculum's step limit, if any, truncates an episode
+        truncated = not terminated and self.rust_lib.tetris_is_truncated(self.game_ptr)
+
+        if self.reward_weights is not None:
+            reward = float(self.rust_lib.tetris_last_reward(self.game_ptr))
+            info = self._state_to_info(new_game_state_struct)
+            if self.render_mode == "human":
+                self.render()
+            return observation, reward, terminated, truncated, info
 
         # Reward calculation
         # Basic reward: score difference. Penalty for losing. Small penalty per step.
@@ -175,26 +402,74 @@ class TetrisEnv(gym.Env):
         if terminated:
             reward -= 100.0 # Large penalty for losing
 
-        truncated = False # Tetris typically doesn't truncate early unless a step limit is imposed
-
-        info = {
-            "score": new_game_state_struct.score,
-            "lost": new_game_state_struct.lost,
-            "width": new_game_state_struct.width,
-            "height": new_game_state_struct.height,
-        }
+        info = self._state_to_info(new_game_state_struct)
 
         if self.render_mode == "human":
             self.render()
 
         return observation, reward, terminated, truncated, info
 
+    def get_rng_state(self) -> int:
+        """The piece generator's state, to save alongside a rollout."""
+        return self.rust_lib.tetris_get_rng_state(self.game_ptr)
+
+    def set_rng_state(self, state: int):
+        """Restores a state from get_rng_state; the next pieces dealt follow from it."""
+        self.rust_lib.tetris_set_rng_state(self.game_ptr, ctypes.c_uint64(state))
+
+    def held_piece(self):
+        """Letter of the piece in the hold slot (ACTION_HOLD), or None."""
+        kind = self.rust_lib.tetris_get_held_kind(self.game_ptr)
+        return chr(kind) if kind else None
+
+    def set_classic_randomizer(self, classic: bool = True):
+        """Deals each piece on its own instead of in shuffled bags of seven."""
+        self.rust_lib.tetris_set_classic_randomizer(self.game_ptr, classic)
+
+    def _read_bytes(self, name: str, count: int) -> np.ndarray:
+        buffer = (ctypes.c_uint8 * count)()
+        getattr(self.rust_lib, name)(self.game_ptr, buffer, count)
+        return np.ctypeslib.as_array(buffer).copy()
+
+    def column_heights(self) -> np.ndarray:
+        """Height of each column of the settled stack, left to right."""
+        return self._read_bytes("tetris_get_column_heights", self.width)
+
+    def row_fill_counts(self) -> np.ndarray:
+        """Filled cells in each row of the settled stack, top row first."""
+        return self._read_bytes("tetris_get_row_fill_counts", self.height)
+
+    def holes_per_column(self) -> np.ndarray:
+        """Covered empty cells in each column, left to right."""
+        return self._read_bytes("tetris_get_holes_per_column", self.width)
+
+    def is_cell_occupied(self, x: int, y: int) -> bool:
+        """Whether the settled stack fills (x, y), y counting from the top row."""
+        return self.rust_lib.tetris_is_cell_occupied(self.game_ptr, x, y)
+
+    def board_delta(self, since: int = 0):
+        """Cells whose shown kind changed after board version `since`, as
+        (x, y, kind byte) with 0 for empty, and the version to pass next time."""
+        version = self.rust_lib.tetris_board_version(self.game_ptr)
+        cap = self.width * self.height
+        buffer = (CellChange * cap)()
+        count = self.rust_lib.tetris_get_board_delta(self.game_ptr, since, buffer, cap)
+        return version, [(c.x, c.y, c.kind) for c in buffer[:min(count, cap)]]
+
+    def clearing_rows(self):
+        """Rows being cleared, top row 0, and their animation progress from
+        0.0 to 1.0; ([], 0.0) while nothing is clearing."""
+        rows = (ctypes.c_uint32 * self.height)()
+        progress = ctypes.c_double(0.0)
+        count = self.rust_lib.tetris_get_clearing_rows(self.game_ptr, rows, self.height, ctypes.byref(progress))
+        return list(rows[:count]), progress.value
+
     def render(self):
         if not self.game_ptr and self.render_mode in ["human", "ansi"]:
              print("No game instance to render.")
              return
 
-        obs = self._get_obs()
+        obs = self._get_board()
         if self.render_mode == 'human':
             # Simple console print, replace # with block, . with empty
             print("\033[H\033[J", end="") # Clear screen
@@ -220,6 +495,133 @@ class TetrisEnv(gym.Env):
     def __del__(self):
         self.close()
 
+class TetrisVecEnv:
+    """`num_envs` games stepped together in Rust.
+
+    Observations, rewards and done flags land in buffers shared with the
+    library, so each step is a single native call however many envs there
+    are. Follows the gymnasium vector API: `step` takes one action per env
+    and returns batched arrays. Envs that finish restart on their own, so
+    their observation is already the next episode's first. The returned
+    arrays are reused by the next step; copy them to keep them.
+    """
+
+    def __init__(self, num_envs: int, lib_path: str = None, width: int = 10, height: int = 20,
+                 observation: str = "flat", reward_weights: dict = None,
+                 pieces: str = None, garbage_rows: int = 0, max_steps: int = 0):
+        if observation not in OBSERVATION_FORMATS:
+            raise ValueError(f"Unknown observation format {observation!r}; expected one of "
+                             f"{sorted(OBSERVATION_FORMATS)}")
+        if pieces is not None and (not pieces or set(pieces) - set(PIECE_KINDS)):
+            raise ValueError(f"pieces must be a non-empty subset of {PIECE_KINDS!r}, got {pieces!r}")
+        self.num_envs = num_envs
+        self.pool_ptr = None
+        self.lib_path = lib_path if lib_path is not None else default_lib_path()
+        if not os.path.exists(self.lib_path):
+            raise OSError(f"Tetris library not found at {self.lib_path}. "
+                          "Ensure the Rust code is compiled and the path is correct.")
+        self.rust_lib = ctypes.CDLL(self.lib_path)
+        self._define_ffi_argtypes()
+
+        weights = self.rust_lib.tetris_default_reward_weights()
+        for name, value in (reward_weights or {}).items():
+            if name not in dict(RewardWeights._fields_):
+                raise ValueError(f"Unknown reward weight {name!r}")
+            setattr(weights, name, value)
+        code = OBSERVATION_FORMATS[observation]
+        self.pool_ptr = self.rust_lib.tetris_env_pool_create(num_envs, width, height, code, ctypes.byref(weights))
+        if not self.pool_ptr:
+            code = self.rust_lib.tetris_last_error()
+            if code in BOARD_ERRORS:
+                raise ValueError(f"Invalid board {width}x{height}: {BOARD_ERRORS[code]}")
+            raise MemoryError("Failed to create Tetris env pool from Rust library.")
+        if pieces or garbage_rows or max_steps:
+            mask = sum(1 << PIECE_KINDS.index(kind) for kind in set(pieces or ""))
+            curriculum = Curriculum(piece_mask=mask, garbage_rows=garbage_rows, max_steps=max_steps)
+            self.rust_lib.tetris_env_pool_set_curriculum(self.pool_ptr, ctypes.byref(curriculum))
+
+        obs_len = self.rust_lib.tetris_observation_len(width, height, code)
+        shape = (4, height, width) if observation == "grid" else (obs_len,)
+        self.single_observation_space = spaces.Box(low=0.0, high=1.0, shape=shape, dtype=np.float32)
+        self.single_action_space = spaces.Discrete(5)
+        self.observation_space = spaces.Box(low=0.0, high=1.0, shape=(num_envs, *shape), dtype=np.float32)
+        self.action_space = spaces.MultiDiscrete([5] * num_envs)
+
+        # The buffers Rust writes into on every step
+        self._obs = np.zeros((num_envs, *shape), dtype=np.float32)
+        self._rewards = np.zeros(num_envs, dtype=np.float32)
+        self._dones = np.zeros(num_envs, dtype=np.uint8)
+        self._actions = np.zeros(num_envs, dtype=np.uint32)
+
+    def _define_ffi_argtypes(self):
+        self.rust_lib.tetris_default_reward_weights.restype = RewardWeights
+        self.rust_lib.tetris_default_reward_weights.argtypes = []
+        self.rust_lib.tetris_last_error.restype = ctypes.c_int32
+        self.rust_lib.tetris_last_error.argtypes = []
+        self.rust_lib.tetris_observation_len.restype = ctypes.c_uint32
+        self.rust_lib.tetris_observation_len.argtypes = [ctypes.c_uint32, ctypes.c_uint32, ctypes.c_uint32]
+
+        # tetris_env_pool_create(count, width, height, format: u32, weights: *const RewardWeights) -> *mut EnvPool
+        self.rust_lib.tetris_env_pool_create.restype = ctypes.c_void_p
+        self.rust_lib.tetris_env_pool_create.argtypes = [
+            ctypes.c_uint32, ctypes.c_uint32, ctypes.c_uint32, ctypes.c_uint32, ctypes.POINTER(RewardWeights)
+        ]
+
+        # tetris_env_pool_destroy(pool: *mut EnvPool)
+        self.rust_lib.tetris_env_pool_destroy.restype = None
+        self.rust_lib.tetris_env_pool_destroy.argtypes = [ctypes.c_void_p]
+
+        # tetris_env_pool_set_curriculum(pool: *mut EnvPool, curriculum: *const Curriculum)
+        self.rust_lib.tetris_env_pool_set_curriculum.restype = None
+        self.rust_lib.tetris_env_pool_set_curriculum.argtypes = [ctypes.c_void_p, ctypes.POINTER(Curriculum)]
+
+        # tetris_env_pool_reset(pool: *mut EnvPool, obs: *mut f32)
+        self.rust_lib.tetris_env_pool_reset.restype = None
+        self.rust_lib.tetris_env_pool_reset.argtypes = [ctypes.c_void_p, ctypes.POINTER(ctypes.c_float)]
+
+        # tetris_env_pool_step(pool, actions: *const u32, obs: *mut f32, rewards: *mut f32, dones: *mut u8)
+        self.rust_lib.tetris_env_pool_step.restype = None
+        self.rust_lib.tetris_env_pool_step.argtypes = [
+            ctypes.c_void_p,
+            ctypes.POINTER(ctypes.c_uint32),
+            ctypes.POINTER(ctypes.c_float),
+            ctypes.POINTER(ctypes.c_float),
+            ctypes.POINTER(ctypes.c_uint8),
+        ]
+
+    @staticmethod
+    def _ptr(array: np.ndarray, ctype):
+        return array.ctypes.data_as(ctypes.POINTER(ctype))
+
+    def reset(self, seed=None, options=None) -> tuple[np.ndarray, dict]:
+        if not self.pool_ptr:
+            raise ConnectionError("Rust env pool not available. Cannot reset.")
+        self.rust_lib.tetris_env_pool_reset(self.pool_ptr, self._ptr(self._obs, ctypes.c_float))
+        return self._obs, {}
+
+    def step(self, actions) -> tuple[np.ndarray, np.ndarray, np.ndarray, np.ndarray, dict]:
+        if not self.pool_ptr:
+            raise ConnectionError("Rust env pool not available. Cannot step.")
+        self._actions[:] = actions
+        self.rust_lib.tetris_env_pool_step(
+            self.pool_ptr,
+            self._ptr(self._actions, ctypes.c_uint32),
+            self._ptr(self._obs, ctypes.c_float),
+            self._ptr(self._rewards, ctypes.c_float),
+            self._ptr(self._dones, ctypes.c_uint8),
+        )
+        terminated = self._dones == DONE_TERMINATED
+        truncated = self._dones == DONE_TRUNCATED
+        return self._obs, self._rewards, terminated, truncated, {}
+
+    def close(self):
+        if self.pool_ptr:
+            self.rust_lib.tetris_env_pool_destroy(self.pool_ptr)
+            self.pool_ptr = None
+
+    def __del__(self):
+        self.close()
+
 if __name__ == '__main__':
     # Example usage:
     # Ensure the .so/.dll/.dylib is in target/debug/ relative to this script, or provide full path.